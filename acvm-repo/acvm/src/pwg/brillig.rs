@@ -26,6 +26,12 @@ pub struct BrilligSolver<'b, B: BlackBoxFunctionSolver> {
     acir_index: usize,
 }
 
+impl<'b, B: BlackBoxFunctionSolver> Clone for BrilligSolver<'b, B> {
+    fn clone(&self) -> Self {
+        Self { vm: self.vm.clone(), acir_index: self.acir_index }
+    }
+}
+
 impl<'b, B: BlackBoxFunctionSolver> BrilligSolver<'b, B> {
     /// Evaluates if the Brillig block should be skipped entirely
     pub(super) fn should_skip(
@@ -142,6 +148,10 @@ impl<'b, B: BlackBoxFunctionSolver> BrilligSolver<'b, B> {
         self.vm.program_counter()
     }
 
+    pub fn get_call_stack(&self) -> Vec<usize> {
+        self.vm.get_call_stack()
+    }
+
     fn handle_vm_status(
         &self,
         vm_status: VMStatus,
@@ -12,7 +12,7 @@ use super::{ErrorLocation, OpcodeResolutionError};
 type MemoryIndex = u32;
 
 /// Maintains the state for solving [`MemoryInit`][`acir::circuit::Opcode::MemoryInit`] and [`MemoryOp`][`acir::circuit::Opcode::MemoryOp`] opcodes.
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub(super) struct MemoryOpSolver {
     block_value: HashMap<MemoryIndex, FieldElement>,
     block_len: u32,
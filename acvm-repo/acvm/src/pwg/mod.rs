@@ -142,6 +142,22 @@ pub struct ACVM<'a, B: BlackBoxFunctionSolver> {
     brillig_solver: Option<BrilligSolver<'a, B>>,
 }
 
+// Implemented by hand as the derived implementation would require the backend to be `Clone`,
+// even though the ACVM only holds a reference to it.
+impl<'a, B: BlackBoxFunctionSolver> Clone for ACVM<'a, B> {
+    fn clone(&self) -> Self {
+        Self {
+            status: self.status.clone(),
+            backend: self.backend,
+            block_solvers: self.block_solvers.clone(),
+            opcodes: self.opcodes,
+            instruction_pointer: self.instruction_pointer,
+            witness_map: self.witness_map.clone(),
+            brillig_solver: self.brillig_solver.clone(),
+        }
+    }
+}
+
 impl<'a, B: BlackBoxFunctionSolver> ACVM<'a, B> {
     pub fn new(backend: &'a B, opcodes: &'a [Opcode], initial_witness: WitnessMap) -> Self {
         let status = if opcodes.is_empty() { ACVMStatus::Solved } else { ACVMStatus::InProgress };
@@ -58,7 +58,7 @@ pub enum VMStatus {
     },
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, PartialEq, Eq)]
 /// VM encapsulates the state of the Brillig VM during execution.
 pub struct VM<'a, B: BlackBoxFunctionSolver> {
     /// Register storage
@@ -83,6 +83,24 @@ pub struct VM<'a, B: BlackBoxFunctionSolver> {
     black_box_solver: &'a B,
 }
 
+// Implemented by hand as the derived implementation would require the solver to be `Clone`,
+// even though the VM only holds a reference to it.
+impl<'a, B: BlackBoxFunctionSolver> Clone for VM<'a, B> {
+    fn clone(&self) -> Self {
+        Self {
+            registers: self.registers.clone(),
+            program_counter: self.program_counter,
+            foreign_call_counter: self.foreign_call_counter,
+            foreign_call_results: self.foreign_call_results.clone(),
+            bytecode: self.bytecode,
+            status: self.status.clone(),
+            memory: self.memory.clone(),
+            call_stack: self.call_stack.clone(),
+            black_box_solver: self.black_box_solver,
+        }
+    }
+}
+
 impl<'a, B: BlackBoxFunctionSolver> VM<'a, B> {
     /// Constructs a new VM instance
     pub fn new(
@@ -341,6 +359,12 @@ impl<'a, B: BlackBoxFunctionSolver> VM<'a, B> {
         self.program_counter
     }
 
+    /// Returns the program counters of the `Call` opcodes which haven't returned yet,
+    /// outermost first.
+    pub fn get_call_stack(&self) -> Vec<usize> {
+        self.call_stack.iter().map(|value| value.to_usize()).collect()
+    }
+
     /// Increments the program counter by 1.
     fn increment_program_counter(&mut self) -> VMStatus {
         self.set_program_counter(self.program_counter + 1)
@@ -10,11 +10,13 @@ use noirc_abi::{AbiParameter, AbiType, ContractEvent};
 use noirc_errors::{CustomDiagnostic, FileDiagnostic};
 use noirc_evaluator::create_circuit;
 use noirc_evaluator::errors::RuntimeError;
+use noirc_frontend::debug::{build_debug_crate_file, DEBUG_CRATE_NAME};
 use noirc_frontend::graph::{CrateId, CrateName};
+use noirc_frontend::hir::def_collector::dc_crate::CompilationError;
 use noirc_frontend::hir::def_map::{Contract, CrateDefMap};
 use noirc_frontend::hir::Context;
 use noirc_frontend::macros_api::MacroProcessor;
-use noirc_frontend::monomorphization::monomorphize;
+use noirc_frontend::monomorphization::{monomorphize, monomorphize_debug};
 use noirc_frontend::node_interner::FuncId;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -71,6 +73,10 @@ pub struct CompileOptions {
     /// Disables the builtin macros being used in the compiler
     #[arg(long, hide = true)]
     pub disable_macros: bool,
+
+    /// Instrument the program's variables so that their values can be inspected in the debugger
+    #[arg(long, hide = true)]
+    pub instrument_debug: bool,
 }
 
 /// Helper type used to signify where only warnings are expected in file diagnostics
@@ -107,6 +113,15 @@ fn add_stdlib_source_to_file_manager(file_manager: &mut FileManager) {
     }
 }
 
+/// Adds the source code of the crate which debug instrumentation calls into to the file manager
+///
+/// This is only needed by file managers used to compile programs for the debugger.
+pub fn add_debug_source_to_file_manager(file_manager: &mut FileManager) {
+    let path_to_debug_lib_file = Path::new(DEBUG_CRATE_NAME).join("lib.nr");
+    file_manager
+        .add_file_with_source_canonical_path(&path_to_debug_lib_file, build_debug_crate_file());
+}
+
 /// Adds the file from the file system at `Path` to the crate graph as a root file
 ///
 /// Note: This methods adds the stdlib as a dependency to the crate.
@@ -144,6 +159,25 @@ pub fn prepare_dependency(context: &mut Context, file_name: &Path) -> CrateId {
     crate_id
 }
 
+/// Links the crate called by debug instrumentation to the root crate, and enables the
+/// instrumentation of the root crate's variables when it is checked.
+///
+/// The debug crate is added to the context's file manager if it hasn't already been added with
+/// [add_debug_source_to_file_manager], which avoids copying a borrowed file manager.
+pub fn link_to_debug_crate(context: &mut Context, root_crate_id: CrateId) {
+    let path_to_debug_lib_file = Path::new(DEBUG_CRATE_NAME).join("lib.nr");
+    if context.file_manager.name_to_id(path_to_debug_lib_file.clone()).is_none() {
+        add_debug_source_to_file_manager(context.file_manager.to_mut());
+    }
+    let debug_file_id = context
+        .file_manager
+        .name_to_id(path_to_debug_lib_file)
+        .expect("debug crate file id is expected to be present");
+    let debug_crate_id = context.crate_graph.add_crate(debug_file_id);
+    add_dep(context, root_crate_id, debug_crate_id, DEBUG_CRATE_NAME.parse().unwrap());
+    context.instrument_debug = true;
+}
+
 /// Adds a edge in the crate graph for two crates
 pub fn add_dep(
     context: &mut Context,
@@ -168,14 +202,8 @@ pub fn check_crate(
     deny_warnings: bool,
     disable_macros: bool,
 ) -> CompilationResult<()> {
-    let macros: Vec<&dyn MacroProcessor> = if disable_macros {
-        vec![]
-    } else {
-        vec![&aztec_macros::AztecMacro as &dyn MacroProcessor]
-    };
-
     let mut errors = vec![];
-    let diagnostics = CrateDefMap::collect_defs(crate_id, context, macros);
+    let diagnostics = collect_crate_errors(context, crate_id, disable_macros);
     errors.extend(diagnostics.into_iter().map(|(error, file_id)| {
         let diagnostic: CustomDiagnostic = error.into();
         diagnostic.in_file(file_id)
@@ -188,6 +216,22 @@ pub fn check_crate(
     }
 }
 
+/// Run the same passes as [check_crate] but return the errors and warnings found as
+/// [CompilationError]s, for tooling which needs to act on the specific error rather than report it.
+pub fn collect_crate_errors(
+    context: &mut Context,
+    crate_id: CrateId,
+    disable_macros: bool,
+) -> Vec<(CompilationError, FileId)> {
+    let macros: Vec<&dyn MacroProcessor> = if disable_macros {
+        vec![]
+    } else {
+        vec![&aztec_macros::AztecMacro as &dyn MacroProcessor]
+    };
+
+    CrateDefMap::collect_defs(crate_id, context, macros)
+}
+
 pub fn compute_function_abi(
     context: &Context,
     crate_id: &CrateId,
@@ -208,6 +252,10 @@ pub fn compile_main(
     cached_program: Option<CompiledProgram>,
     force_compile: bool,
 ) -> CompilationResult<CompiledProgram> {
+    if options.instrument_debug && !context.instrument_debug {
+        link_to_debug_crate(context, crate_id);
+    }
+
     let (_, mut warnings) =
         check_crate(context, crate_id, options.deny_warnings, options.disable_macros)?;
 
@@ -383,7 +431,11 @@ pub fn compile_no_check(
     cached_program: Option<CompiledProgram>,
     force_compile: bool,
 ) -> Result<CompiledProgram, RuntimeError> {
-    let program = monomorphize(main_function, &context.def_interner);
+    let program = if context.instrument_debug {
+        monomorphize_debug(main_function, &context.def_interner, &context.debug_instrumenter)
+    } else {
+        monomorphize(main_function, &context.def_interner)
+    };
 
     let hash = fxhash::hash64(&program);
     let hashes_match = cached_program.as_ref().map_or(false, |program| program.hash == hash);
@@ -8,8 +8,61 @@ use std::collections::HashMap;
 use std::mem;
 
 use crate::Location;
+use noirc_printable_type::PrintableType;
 use serde::{Deserialize, Serialize};
 
+/// Identifies a source variable tracked by the debugger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct DebugVarId(pub u32);
+
+impl std::fmt::Display for DebugVarId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for DebugVarId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(DebugVarId)
+    }
+}
+
+/// Identifies the type of a source variable tracked by the debugger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct DebugTypeId(pub u32);
+
+impl std::fmt::Display for DebugTypeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for DebugTypeId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(DebugTypeId)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct DebugVariable {
+    pub name: String,
+    pub debug_type_id: DebugTypeId,
+}
+
+pub type DebugVariables = BTreeMap<DebugVarId, DebugVariable>;
+pub type DebugTypes = BTreeMap<DebugTypeId, PrintableType>;
+
+/// A Noir function of the program, whose location spans from its name to the end of its body.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct DebugFunction {
+    pub name: String,
+    pub location: Location,
+}
+
 #[serde_as]
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
 pub struct DebugInfo {
@@ -18,10 +71,27 @@ pub struct DebugInfo {
     /// that they should be serialized to/from strings.
     #[serde_as(as = "BTreeMap<DisplayFromStr, _>")]
     pub locations: BTreeMap<OpcodeLocation, Vec<Location>>,
+    /// The source variables whose values are reported to the debugger while the program runs
+    #[serde_as(as = "BTreeMap<DisplayFromStr, _>")]
+    #[serde(default)]
+    pub variables: DebugVariables,
+    /// The types of the tracked variables, used to decode their values
+    #[serde_as(as = "BTreeMap<DisplayFromStr, _>")]
+    #[serde(default)]
+    pub types: DebugTypes,
+    /// The functions the program is made of, starting with its entry point
+    #[serde(default)]
+    pub functions: Vec<DebugFunction>,
+    /// The function called at each call site of the program, by its index in `functions`.
+    /// Most calls are inlined away when compiling, so these mark where each function of the
+    /// source call stack of an opcode begins.
+    #[serde_as(as = "Vec<(_, _)>")]
+    #[serde(default)]
+    pub calls: HashMap<Location, usize>,
 }
 
 /// Holds OpCodes Counts for Acir and Brillig Opcodes
-/// To be printed with `nargo info --profile-info`
+/// Reported by the language server's `nargo/profile/run` request
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 pub struct OpCodesCount {
     pub acir_size: usize,
@@ -29,8 +99,14 @@ pub struct OpCodesCount {
 }
 
 impl DebugInfo {
-    pub fn new(locations: BTreeMap<OpcodeLocation, Vec<Location>>) -> Self {
-        DebugInfo { locations }
+    pub fn new(
+        locations: BTreeMap<OpcodeLocation, Vec<Location>>,
+        variables: DebugVariables,
+        types: DebugTypes,
+        functions: Vec<DebugFunction>,
+        calls: HashMap<Location, usize>,
+    ) -> Self {
+        DebugInfo { locations, variables, types, functions, calls }
     }
 
     /// Updates the locations map when the [`Circuit`][acvm::acir::circuit::Circuit] is modified.
@@ -53,6 +129,11 @@ impl DebugInfo {
         self.locations.get(loc).cloned()
     }
 
+    /// Returns the function called at the given call site, if it's known.
+    pub fn called_function(&self, call_site: &Location) -> Option<&DebugFunction> {
+        self.calls.get(call_site).and_then(|index| self.functions.get(*index))
+    }
+
     pub fn count_span_opcodes(&self) -> HashMap<Location, OpCodesCount> {
         let mut accumulator: HashMap<Location, Vec<&OpcodeLocation>> = HashMap::new();
 
@@ -0,0 +1,373 @@
+//! Instrumentation of the root crate for source-level debugging.
+//!
+//! When a program is compiled for the debugger, the functions of its root crate are rewritten so
+//! that every source variable reports its value whenever it is bound or assigned, and reports
+//! that it went out of scope at the end of the enclosing block. The reports are calls to the
+//! oracles of the `__debug` crate, which the debugger intercepts while executing the program.
+//! The monomorphizer replaces the source variable ids passed to these oracles with the
+//! [DebugVarId][noirc_errors::debug_info::DebugVarId]s it records in the program's debug info.
+use std::collections::HashMap;
+
+use acvm::FieldElement;
+use noirc_errors::{Span, Spanned};
+
+use crate::{
+    parser::{ItemKind, ParsedModule},
+    ArrayLiteral, BlockExpression, CallExpression, Expression, ExpressionKind, ForRange,
+    FunctionKind, Ident, LValue, LetStatement, Literal, NoirFunction, Path, PathKind, Pattern,
+    Statement, StatementKind, TraitImplItem, UnresolvedType,
+};
+
+/// The name under which the debug crate is linked to the instrumented crate
+pub const DEBUG_CRATE_NAME: &str = "__debug";
+
+/// The function called with a variable's id and value whenever the variable is assigned
+pub const DEBUG_VAR_ASSIGN: &str = "__debug_var_assign";
+
+/// The function called with a variable's id when the variable goes out of scope
+pub const DEBUG_VAR_DROP: &str = "__debug_var_drop";
+
+/// The name of the variable holding the value of a block while its variables are dropped
+const DEBUG_BLOCK_VALUE: &str = "__debug_expr";
+
+/// Identifies a variable in the source of the instrumented crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SourceVarId(pub u32);
+
+#[derive(Debug, Default)]
+pub struct DebugInstrumenter {
+    /// The name of each variable of the instrumented crate
+    pub variables: HashMap<SourceVarId, String>,
+
+    next_var_id: u32,
+
+    /// The variables declared in each enclosing block, innermost last
+    scopes: Vec<HashMap<String, SourceVarId>>,
+}
+
+impl DebugInstrumenter {
+    /// Instruments the functions and methods of a parsed module, including its inline submodules.
+    pub fn instrument_module(&mut self, module: &mut ParsedModule) {
+        for item in &mut module.items {
+            match &mut item.kind {
+                ItemKind::Function(func) => self.instrument_function(func),
+                ItemKind::Impl(type_impl) => {
+                    for method in &mut type_impl.methods {
+                        self.instrument_function(method);
+                    }
+                }
+                ItemKind::TraitImpl(trait_impl) => {
+                    for item in &mut trait_impl.items {
+                        if let TraitImplItem::Function(method) = item {
+                            self.instrument_function(method);
+                        }
+                    }
+                }
+                ItemKind::Submodules(submodule) => self.instrument_module(&mut submodule.contents),
+                _ => (),
+            }
+        }
+    }
+
+    fn instrument_function(&mut self, func: &mut NoirFunction) {
+        if func.kind != FunctionKind::Normal {
+            return;
+        }
+        let parameters =
+            func.def.parameters.iter().flat_map(|param| pattern_identifiers(&param.pattern));
+        let parameters = parameters.cloned().collect();
+        self.walk_block(&mut func.def.body, parameters, func.def.span);
+    }
+
+    /// Instruments a block whose scope starts with the given variables, such as the parameters
+    /// of a function or the index of a `for` loop.
+    fn walk_block(&mut self, block: &mut BlockExpression, declared: Vec<Ident>, span: Span) {
+        self.scopes.push(HashMap::new());
+
+        let mut statements = Vec::with_capacity(block.len() + declared.len());
+        for ident in declared {
+            statements.extend(self.declare(ident, span));
+        }
+
+        let original_statements = std::mem::take(&mut block.0);
+        let statement_count = original_statements.len();
+        let mut tail = None;
+        for (index, mut statement) in original_statements.into_iter().enumerate() {
+            self.walk_statement(&mut statement);
+            if index + 1 == statement_count
+                && matches!(statement.kind, StatementKind::Expression(_))
+            {
+                tail = Some(statement);
+                break;
+            }
+            let assignments = self.statement_assignments(&statement);
+            statements.push(statement);
+            statements.extend(assignments);
+        }
+
+        let scope = self.scopes.pop().expect("Expected a scope for the block");
+        let mut dropped: Vec<_> = scope.into_values().collect();
+        dropped.sort();
+        let drop_span = tail.as_ref().map_or(span, |tail| tail.span);
+        let drops: Vec<_> =
+            dropped.into_iter().map(|var_id| self.drop_var(var_id, drop_span)).collect();
+
+        match tail {
+            // The value of the block is computed before its variables are dropped
+            Some(tail) if !drops.is_empty() => {
+                let StatementKind::Expression(value) = tail.kind else { unreachable!() };
+                let value_ident = Ident(Spanned::from(tail.span, DEBUG_BLOCK_VALUE.to_string()));
+                statements.push(Statement {
+                    kind: StatementKind::Let(LetStatement {
+                        pattern: Pattern::Identifier(value_ident.clone()),
+                        r#type: UnresolvedType::unspecified(),
+                        expression: value,
+                    }),
+                    span: tail.span,
+                });
+                statements.extend(drops);
+                statements.push(Statement {
+                    kind: StatementKind::Expression(variable(&value_ident)),
+                    span: tail.span,
+                });
+            }
+            tail => {
+                statements.extend(drops);
+                statements.extend(tail);
+            }
+        }
+        block.0 = statements;
+    }
+
+    fn walk_statement(&mut self, statement: &mut Statement) {
+        match &mut statement.kind {
+            StatementKind::Let(let_statement) => {
+                self.walk_expression(&mut let_statement.expression);
+            }
+            StatementKind::Constrain(constrain) => self.walk_expression(&mut constrain.0),
+            StatementKind::Expression(expression) | StatementKind::Semi(expression) => {
+                self.walk_expression(expression);
+            }
+            StatementKind::Assign(assign) => {
+                self.walk_lvalue(&mut assign.lvalue);
+                self.walk_expression(&mut assign.expression);
+            }
+            StatementKind::For(for_loop) => {
+                match &mut for_loop.range {
+                    ForRange::Range(start, end) => {
+                        self.walk_expression(start);
+                        self.walk_expression(end);
+                    }
+                    ForRange::Array(array) => self.walk_expression(array),
+                }
+                let index = vec![for_loop.identifier.clone()];
+                match &mut for_loop.block.kind {
+                    ExpressionKind::Block(block) => self.walk_block(block, index, for_loop.span),
+                    _ => self.walk_expression(&mut for_loop.block),
+                }
+            }
+            StatementKind::Error => (),
+        }
+    }
+
+    fn walk_lvalue(&mut self, lvalue: &mut LValue) {
+        match lvalue {
+            LValue::Ident(_) => (),
+            LValue::MemberAccess { object, .. } => self.walk_lvalue(object),
+            LValue::Index { array, index } => {
+                self.walk_lvalue(array);
+                self.walk_expression(index);
+            }
+            LValue::Dereference(reference) => self.walk_lvalue(reference),
+        }
+    }
+
+    fn walk_expression(&mut self, expression: &mut Expression) {
+        match &mut expression.kind {
+            ExpressionKind::Block(block) => self.walk_block(block, Vec::new(), expression.span),
+            ExpressionKind::Literal(Literal::Array(ArrayLiteral::Standard(elements)))
+            | ExpressionKind::Tuple(elements) => {
+                for element in elements {
+                    self.walk_expression(element);
+                }
+            }
+            ExpressionKind::Literal(Literal::Array(ArrayLiteral::Repeated {
+                repeated_element,
+                length,
+            })) => {
+                self.walk_expression(repeated_element);
+                self.walk_expression(length);
+            }
+            ExpressionKind::Literal(_) | ExpressionKind::Variable(_) | ExpressionKind::Error => (),
+            ExpressionKind::Prefix(prefix) => self.walk_expression(&mut prefix.rhs),
+            ExpressionKind::Index(index) => {
+                self.walk_expression(&mut index.collection);
+                self.walk_expression(&mut index.index);
+            }
+            ExpressionKind::Call(call) => {
+                self.walk_expression(&mut call.func);
+                for argument in &mut call.arguments {
+                    self.walk_expression(argument);
+                }
+            }
+            ExpressionKind::MethodCall(call) => {
+                self.walk_expression(&mut call.object);
+                for argument in &mut call.arguments {
+                    self.walk_expression(argument);
+                }
+            }
+            ExpressionKind::Constructor(constructor) => {
+                for (_, field) in &mut constructor.fields {
+                    self.walk_expression(field);
+                }
+            }
+            ExpressionKind::MemberAccess(access) => self.walk_expression(&mut access.lhs),
+            ExpressionKind::Cast(cast) => self.walk_expression(&mut cast.lhs),
+            ExpressionKind::Infix(infix) => {
+                self.walk_expression(&mut infix.lhs);
+                self.walk_expression(&mut infix.rhs);
+            }
+            ExpressionKind::If(if_expression) => {
+                self.walk_expression(&mut if_expression.condition);
+                self.walk_expression(&mut if_expression.consequence);
+                if let Some(alternative) = &mut if_expression.alternative {
+                    self.walk_expression(alternative);
+                }
+            }
+            ExpressionKind::Lambda(lambda) => {
+                let parameters = lambda
+                    .parameters
+                    .iter()
+                    .flat_map(|(pattern, _)| pattern_identifiers(pattern).into_iter().cloned());
+                let parameters = parameters.collect();
+                let span = lambda.body.span;
+                match &mut lambda.body.kind {
+                    ExpressionKind::Block(block) => self.walk_block(block, parameters, span),
+                    _ => self.walk_expression(&mut lambda.body),
+                }
+            }
+            ExpressionKind::Parenthesized(expression) => self.walk_expression(expression),
+        }
+    }
+
+    /// The statements reporting the variables bound or modified by an instrumented statement.
+    fn statement_assignments(&mut self, statement: &Statement) -> Vec<Statement> {
+        match &statement.kind {
+            StatementKind::Let(let_statement) => pattern_identifiers(&let_statement.pattern)
+                .into_iter()
+                .flat_map(|ident| self.declare(ident.clone(), statement.span))
+                .collect(),
+            StatementKind::Assign(assign) => {
+                let Some(ident) = assigned_variable(&assign.lvalue) else {
+                    return Vec::new();
+                };
+                match self.lookup(&ident.0.contents) {
+                    Some(var_id) => vec![self.assign_var(var_id, ident, statement.span)],
+                    None => Vec::new(),
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Adds a variable to the innermost scope, returning the statements reporting its value.
+    /// A variable it shadows within the same scope is dropped first.
+    fn declare(&mut self, ident: Ident, span: Span) -> Vec<Statement> {
+        let name = ident.0.contents.clone();
+        if name == "_" {
+            return Vec::new();
+        }
+
+        let var_id = SourceVarId(self.next_var_id);
+        self.next_var_id += 1;
+        self.variables.insert(var_id, name.clone());
+
+        let scope = self.scopes.last_mut().expect("Expected a scope for the variable");
+        let shadowed = scope.insert(name, var_id);
+        let mut statements = Vec::new();
+        if let Some(shadowed) = shadowed {
+            statements.push(self.drop_var(shadowed, span));
+        }
+        statements.push(self.assign_var(var_id, &ident, span));
+        statements
+    }
+
+    fn lookup(&self, name: &str) -> Option<SourceVarId> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name).copied())
+    }
+
+    fn assign_var(&self, var_id: SourceVarId, ident: &Ident, span: Span) -> Statement {
+        debug_call(DEBUG_VAR_ASSIGN, vec![var_id_literal(var_id, span), variable(ident)], span)
+    }
+
+    fn drop_var(&self, var_id: SourceVarId, span: Span) -> Statement {
+        debug_call(DEBUG_VAR_DROP, vec![var_id_literal(var_id, span)], span)
+    }
+}
+
+/// The source of the crate providing the oracles called by instrumented code.
+pub fn build_debug_crate_file() -> String {
+    format!(
+        r#"
+#[oracle({DEBUG_VAR_ASSIGN})]
+unconstrained fn {DEBUG_VAR_ASSIGN}_oracle<T>(_var_id: u32, _value: T) {{}}
+
+unconstrained pub fn {DEBUG_VAR_ASSIGN}<T>(var_id: u32, value: T) {{
+    {DEBUG_VAR_ASSIGN}_oracle(var_id, value);
+}}
+
+#[oracle({DEBUG_VAR_DROP})]
+unconstrained fn {DEBUG_VAR_DROP}_oracle(_var_id: u32) {{}}
+
+unconstrained pub fn {DEBUG_VAR_DROP}(var_id: u32) {{
+    {DEBUG_VAR_DROP}_oracle(var_id);
+}}
+"#
+    )
+}
+
+fn debug_call(function: &str, arguments: Vec<Expression>, span: Span) -> Statement {
+    let segments = vec![
+        Ident(Spanned::from(span, DEBUG_CRATE_NAME.to_string())),
+        Ident(Spanned::from(span, function.to_string())),
+    ];
+    let func = Expression::new(
+        ExpressionKind::Variable(Path { segments, kind: PathKind::Dep, span }),
+        span,
+    );
+    let call = CallExpression { func: Box::new(func), arguments };
+    Statement {
+        kind: StatementKind::Semi(Expression::new(ExpressionKind::Call(Box::new(call)), span)),
+        span,
+    }
+}
+
+fn var_id_literal(var_id: SourceVarId, span: Span) -> Expression {
+    let value = FieldElement::from(var_id.0 as u128);
+    Expression::new(ExpressionKind::Literal(Literal::Integer(value, false)), span)
+}
+
+fn variable(ident: &Ident) -> Expression {
+    Expression::new(ExpressionKind::Variable(Path::from_ident(ident.clone())), ident.span())
+}
+
+/// The variable whose value changes when assigning to an lvalue, unless it's behind a reference.
+fn assigned_variable(lvalue: &LValue) -> Option<&Ident> {
+    match lvalue {
+        LValue::Ident(ident) => Some(ident),
+        LValue::MemberAccess { object, .. } => assigned_variable(object),
+        LValue::Index { array, .. } => assigned_variable(array),
+        LValue::Dereference(_) => None,
+    }
+}
+
+fn pattern_identifiers(pattern: &Pattern) -> Vec<&Ident> {
+    match pattern {
+        Pattern::Identifier(ident) => vec![ident],
+        Pattern::Mutable(pattern, _) => pattern_identifiers(pattern),
+        Pattern::Tuple(patterns, _) => patterns.iter().flat_map(pattern_identifiers).collect(),
+        Pattern::Struct(_, fields, _) => {
+            fields.iter().flat_map(|(_, pattern)| pattern_identifiers(pattern)).collect()
+        }
+    }
+}
@@ -259,6 +259,15 @@ impl DefCollector {
             );
         }
 
+        // Uses of items in imports are only recorded once all imports are resolved, as an import may
+        // refer to an item made visible by another import.
+        let user_imports: Vec<_> = def_collector
+            .collected_imports
+            .iter()
+            .filter(|import| !import.is_prelude)
+            .cloned()
+            .collect();
+
         // Resolve unresolved imports collected from the crate, one by one.
         for collected_import in def_collector.collected_imports {
             match resolve_import(crate_id, collected_import, &context.def_maps) {
@@ -290,6 +299,18 @@ impl DefCollector {
             }
         }
 
+        for import in user_imports {
+            let module_id = ModuleId { krate: crate_id, local_id: import.module_id };
+            let file_id = context.def_maps[&crate_id].file_id(import.module_id);
+            path_resolver::add_path_references(
+                &path_resolver::StandardPathResolver::new(module_id),
+                &context.def_maps,
+                &mut context.def_interner,
+                &import.path,
+                file_id,
+            );
+        }
+
         // We must first resolve and intern the globals before we can resolve any stmts inside each function.
         // Each function uses its own resolver with a newly created ScopeForest, and must be resolved again to be within a function's scope
         //
@@ -20,7 +20,7 @@ use super::{
     },
     errors::{DefCollectorErrorKind, DuplicateType},
 };
-use crate::hir::def_map::{parse_file, LocalModuleId, ModuleData, ModuleId};
+use crate::hir::def_map::{LocalModuleId, ModuleData, ModuleId};
 use crate::hir::resolution::import::ImportDirective;
 use crate::hir::Context;
 
@@ -496,6 +496,10 @@ impl<'a> ModCollector<'a> {
         for submodule in submodules {
             match self.push_child_module(&submodule.name, file_id, true, submodule.is_contract) {
                 Ok(child) => {
+                    let module_id = ModuleId { krate: crate_id, local_id: child };
+                    let location = Location::new(submodule.name.span(), file_id);
+                    context.def_interner.add_module_location(module_id, location);
+
                     errors.extend(collect_defs(
                         self.def_collector,
                         submodule.contents,
@@ -555,7 +559,10 @@ impl<'a> ModCollector<'a> {
         context.visited_files.insert(child_file_id, location);
 
         // Parse the AST for the module we just found and then recursively look for it's defs
-        let (ast, parsing_errors) = parse_file(&context.file_manager, child_file_id);
+        let (mut ast, parsing_errors) = context.parsed_file_results(child_file_id);
+        if context.instrument_debug && crate_id.is_root() {
+            context.debug_instrumenter.instrument_module(&mut ast);
+        }
         let ast = ast.into_sorted();
 
         errors.extend(
@@ -565,6 +572,9 @@ impl<'a> ModCollector<'a> {
         // Add module into def collector and get a ModuleId
         match self.push_child_module(mod_name, child_file_id, true, false) {
             Ok(child_mod_id) => {
+                let module_id = ModuleId { krate: crate_id, local_id: child_mod_id };
+                context.def_interner.add_module_location(module_id, location);
+
                 errors.extend(collect_defs(
                     self.def_collector,
                     ast,
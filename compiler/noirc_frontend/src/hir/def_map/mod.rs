@@ -86,7 +86,10 @@ impl CrateDefMap {
 
         // First parse the root file.
         let root_file_id = context.crate_graph[crate_id].root_file_id;
-        let (ast, parsing_errors) = parse_file(&context.file_manager, root_file_id);
+        let (mut ast, parsing_errors) = context.parsed_file_results(root_file_id);
+        if context.instrument_debug && crate_id.is_root() {
+            context.debug_instrumenter.instrument_module(&mut ast);
+        }
         let mut ast = ast.into_sorted();
 
         for macro_processor in &macro_processors {
@@ -136,6 +139,10 @@ impl CrateDefMap {
     pub fn krate(&self) -> CrateId {
         self.krate
     }
+    /// The root module of each dependency of this crate, by the name it is imported with.
+    pub fn extern_prelude(&self) -> &BTreeMap<String, ModuleId> {
+        &self.extern_prelude
+    }
 
     /// Find the main function for this crate
     pub fn main_function(&self) -> Option<FuncId> {
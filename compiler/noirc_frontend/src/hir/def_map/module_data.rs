@@ -41,7 +41,7 @@ impl ModuleData {
         }
     }
 
-    pub(crate) fn scope(&self) -> &ItemScope {
+    pub fn scope(&self) -> &ItemScope {
         &self.scope
     }
 
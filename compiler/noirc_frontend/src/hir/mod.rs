@@ -4,17 +4,25 @@ pub mod resolution;
 pub mod scope;
 pub mod type_check;
 
+use crate::debug::DebugInstrumenter;
 use crate::graph::{CrateGraph, CrateId};
+use crate::hir::resolution::path_resolver::{PathResolver, StandardPathResolver};
 use crate::hir_def::function::FuncMeta;
 use crate::node_interner::{FuncId, NodeInterner, StructId};
-use def_map::{Contract, CrateDefMap};
-use fm::FileManager;
+use crate::parser::{ParsedModule, ParserError};
+use crate::Path;
+use def_map::{parse_file, Contract, CrateDefMap, ModuleDefId, ModuleId};
+use fm::{FileId, FileManager};
 use noirc_errors::Location;
 use std::borrow::Cow;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
 use self::def_map::TestFunction;
 
+/// The results of parsing files, so that files which haven't changed don't need to be parsed
+/// again when a crate is checked several times.
+pub type ParsedFiles = HashMap<FileId, (ParsedModule, Vec<ParserError>)>;
+
 /// Helper object which groups together several useful context objects used
 /// during name resolution. Once name resolution is finished, only the
 /// def_interner is required for type inference and monomorphization.
@@ -27,9 +35,19 @@ pub struct Context<'file_manager> {
     // is read-only however, once it has been passed to the Context.
     pub file_manager: Cow<'file_manager, FileManager>,
 
+    /// Files which have already been parsed. Any other file is parsed when it is first used.
+    pub parsed_files: Cow<'file_manager, ParsedFiles>,
+
     /// A map of each file that already has been visited from a prior `mod foo;` declaration.
     /// This is used to issue an error if a second `mod foo;` is declared to the same file.
     pub visited_files: BTreeMap<fm::FileId, Location>,
+
+    /// Whether the root crate should be instrumented to report the values of its variables
+    /// to the debugger
+    pub instrument_debug: bool,
+
+    /// The names of the variables instrumented in the root crate
+    pub debug_instrumenter: DebugInstrumenter,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -47,6 +65,9 @@ impl Context<'_> {
             visited_files: BTreeMap::new(),
             crate_graph: CrateGraph::default(),
             file_manager: Cow::Owned(file_manager),
+            parsed_files: Cow::Owned(ParsedFiles::default()),
+            instrument_debug: false,
+            debug_instrumenter: DebugInstrumenter::default(),
         }
     }
 
@@ -57,6 +78,28 @@ impl Context<'_> {
             visited_files: BTreeMap::new(),
             crate_graph: CrateGraph::default(),
             file_manager: Cow::Borrowed(file_manager),
+            parsed_files: Cow::Owned(ParsedFiles::default()),
+            instrument_debug: false,
+            debug_instrumenter: DebugInstrumenter::default(),
+        }
+    }
+
+    pub fn from_ref_file_manager_and_parsed_files<'a>(
+        file_manager: &'a FileManager,
+        parsed_files: &'a ParsedFiles,
+    ) -> Context<'a> {
+        Context {
+            parsed_files: Cow::Borrowed(parsed_files),
+            ..Context::from_ref_file_manager(file_manager)
+        }
+    }
+
+    /// Returns the AST of a file and its parsing errors, reusing the result of a previous parse
+    /// of the file if there is one.
+    pub(crate) fn parsed_file_results(&self, file_id: FileId) -> (ParsedModule, Vec<ParserError>) {
+        match self.parsed_files.get(&file_id) {
+            Some(parsed_file) => parsed_file.clone(),
+            None => parse_file(&self.file_manager, file_id),
         }
     }
 
@@ -68,6 +111,11 @@ impl Context<'_> {
         self.def_maps.get(crate_id)
     }
 
+    /// Resolves `path` as if it were written in the given module.
+    pub fn resolve_path(&self, module: ModuleId, path: Path) -> Option<ModuleDefId> {
+        StandardPathResolver::new(module).resolve(&self.def_maps, path).ok()
+    }
+
     /// Return the CrateId for each crate that has been compiled
     /// successfully
     pub fn crates(&self) -> impl Iterator<Item = CrateId> + '_ {
@@ -52,8 +52,6 @@ pub enum ResolverError {
     NoSuchNumericTypeVariable { path: crate::Path },
     #[error("Closures cannot capture mutable variables")]
     CapturedMutableVariable { span: Span },
-    #[error("Test functions are not allowed to have any parameters")]
-    TestFunctionHasParameters { span: Span },
     #[error("Only struct types can be used in constructor expressions")]
     NonStructUsedInConstructor { typ: Type, span: Span },
     #[error("Only struct types can have generics")]
@@ -239,11 +237,6 @@ impl From<ResolverError> for Diagnostic {
                 "Mutable variable".into(),
                 span,
             ),
-            ResolverError::TestFunctionHasParameters { span } => Diagnostic::simple_error(
-                "Test functions cannot have any parameters".into(),
-                "Try removing the parameters or moving the test into a wrapper function".into(),
-                span,
-            ),
             ResolverError::NonStructUsedInConstructor { typ, span } => Diagnostic::simple_error(
                 "Only struct types can be used in constructor expressions".into(),
                 format!("{typ} has no fields to construct it with"),
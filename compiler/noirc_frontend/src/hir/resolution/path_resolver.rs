@@ -1,7 +1,10 @@
 use super::import::{
     allow_referencing_contracts, resolve_path_to_ns, ImportDirective, PathResolutionError,
 };
+use crate::node_interner::NodeInterner;
 use crate::Path;
+use fm::FileId;
+use noirc_errors::Location;
 use std::collections::BTreeMap;
 
 use crate::graph::CrateId;
@@ -69,3 +72,24 @@ pub fn resolve_path(
     let id = function.or_else(|| ns.types.map(|(id, _, _)| id));
     Ok(id.expect("Found empty namespace"))
 }
+
+/// Records a reference to the module or item named by each prefix of the given path (`foo`, `foo::bar`
+/// and `foo::bar::baz` for `foo::bar::baz`) at the location of the last segment of that prefix.
+pub fn add_path_references(
+    path_resolver: &dyn PathResolver,
+    def_maps: &BTreeMap<CrateId, CrateDefMap>,
+    interner: &mut NodeInterner,
+    path: &Path,
+    file: FileId,
+) {
+    if !interner.is_tracking_references() {
+        return;
+    }
+    for (index, segment) in path.segments.iter().enumerate() {
+        let segments = path.segments[..=index].to_vec();
+        let prefix = Path { segments, kind: path.kind, span: path.span };
+        if let Ok(module_def_id) = path_resolver.resolve(def_maps, prefix) {
+            interner.add_module_def_reference(module_def_id, Location::new(segment.span(), file));
+        }
+    }
+}
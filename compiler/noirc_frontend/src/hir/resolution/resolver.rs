@@ -28,11 +28,14 @@ use crate::graph::CrateId;
 use crate::hir::def_map::{LocalModuleId, ModuleDefId, TryFromModuleDefId, MAIN_FUNCTION};
 use crate::hir_def::stmt::{HirAssignStatement, HirForStatement, HirLValue, HirPattern};
 use crate::node_interner::{
-    DefinitionId, DefinitionKind, ExprId, FuncId, NodeInterner, StmtId, StructId, TraitId,
-    TraitImplId, TraitImplKind,
+    DefinitionId, DefinitionKind, ExprId, FuncId, NodeInterner, ReferenceId, StmtId, StructId,
+    TraitId, TraitImplId, TraitImplKind,
 };
 use crate::{
-    hir::{def_map::CrateDefMap, resolution::path_resolver::PathResolver},
+    hir::{
+        def_map::CrateDefMap,
+        resolution::path_resolver::{add_path_references, PathResolver},
+    },
     BlockExpression, Expression, ExpressionKind, FunctionKind, Ident, Literal, NoirFunction,
     StatementKind,
 };
@@ -389,6 +392,7 @@ impl<'a> Resolver<'a> {
         if let Some((variable_found, scope)) = variable {
             variable_found.num_times_used += 1;
             let id = variable_found.ident.id;
+            self.interner.add_definition_reference(id, location);
             Ok((HirIdent { location, id }, scope))
         } else {
             Err(ResolverError::VariableNotDeclared {
@@ -605,6 +609,13 @@ impl<'a> Resolver<'a> {
         // If we cannot find a local generic of the same name, try to look up a global
         match self.path_resolver.resolve(self.def_maps, path.clone()) {
             Ok(ModuleDefId::GlobalId(id)) => {
+                add_path_references(
+                    self.path_resolver,
+                    self.def_maps,
+                    self.interner,
+                    path,
+                    self.file,
+                );
                 Some(Type::Constant(self.eval_global_as_array_length(id)))
             }
             _ => None,
@@ -780,9 +791,19 @@ impl<'a> Resolver<'a> {
         &mut self,
         where_clause: &Vec<UnresolvedTraitConstraint>,
     ) -> Vec<TraitConstraint> {
-        vecmap(where_clause, |constraint| TraitConstraint {
-            typ: self.resolve_type(constraint.typ.clone()),
-            trait_id: constraint.trait_bound.trait_id.unwrap_or_else(TraitId::dummy_id),
+        vecmap(where_clause, |constraint| {
+            // The trait was already resolved during collection, we only record its use here
+            add_path_references(
+                self.path_resolver,
+                self.def_maps,
+                self.interner,
+                &constraint.trait_bound.trait_path,
+                self.file,
+            );
+            TraitConstraint {
+                typ: self.resolve_type(constraint.typ.clone()),
+                trait_id: constraint.trait_bound.trait_id.unwrap_or_else(TraitId::dummy_id),
+            }
         })
     }
 
@@ -807,6 +828,7 @@ impl<'a> Resolver<'a> {
 
         let mut parameters = vec![];
         let mut parameter_types = vec![];
+        let is_test = matches!(attributes.function, Some(FunctionAttribute::Test { .. }));
 
         for Param { visibility, pattern, typ, span: _ } in func.parameters().iter().cloned() {
             if visibility == Visibility::Public && !self.pub_allowed(func) {
@@ -816,7 +838,8 @@ impl<'a> Resolver<'a> {
                 });
             }
 
-            if self.is_entry_point_function(func) {
+            // The arguments of test functions are generated from, or parsed into, their ABI types
+            if self.is_entry_point_function(func) || is_test {
                 self.verify_type_valid_for_program_input(&typ);
             }
 
@@ -852,14 +875,6 @@ impl<'a> Resolver<'a> {
             self.push_err(ResolverError::DistinctNotAllowed { ident: func.name_ident().clone() });
         }
 
-        if matches!(attributes.function, Some(FunctionAttribute::Test { .. }))
-            && !parameters.is_empty()
-        {
-            self.push_err(ResolverError::TestFunctionHasParameters {
-                span: func.name_ident().span(),
-            });
-        }
-
         let mut typ = Type::Function(parameter_types, return_type, Box::new(Type::Unit));
 
         if !generics.is_empty() {
@@ -1515,6 +1530,11 @@ impl<'a> Resolver<'a> {
         for (field, expr) in fields {
             let resolved = resolve_function(self, expr);
 
+            if let Some(index) = struct_type.borrow().field_index(&field.0.contents) {
+                let referenced = ReferenceId::StructMember(struct_type.borrow().id, index);
+                self.interner.add_reference(referenced, Location::new(field.span(), self.file));
+            }
+
             if unseen_fields.contains(&field) {
                 unseen_fields.remove(&field);
                 seen_fields.insert(field.clone());
@@ -1701,12 +1721,24 @@ impl<'a> Resolver<'a> {
     }
 
     fn resolve_trait_generic_path(&mut self, path: &Path) -> Option<(HirExpression, Type)> {
-        self.resolve_trait_static_method_by_self(path)
+        let resolved = self
+            .resolve_trait_static_method_by_self(path)
             .or_else(|| self.resolve_trait_static_method(path))
-            .or_else(|| self.resolve_trait_method_by_named_generic(path))
+            .or_else(|| self.resolve_trait_method_by_named_generic(path));
+
+        if let Some((HirExpression::TraitMethodReference(method), _)) = &resolved {
+            let the_trait = self.interner.get_trait(method.trait_id);
+            let method_name = &the_trait.methods[method.method_index].name.0.contents;
+            if let Some(func_id) = the_trait.method_ids.get(method_name).copied() {
+                let location = Location::new(path.last_segment().span(), self.file);
+                self.interner.add_reference(ReferenceId::Function(func_id), location);
+            }
+        }
+        resolved
     }
 
     fn resolve_path(&mut self, path: Path) -> Result<ModuleDefId, ResolverError> {
+        add_path_references(self.path_resolver, self.def_maps, self.interner, &path, self.file);
         self.path_resolver.resolve(self.def_maps, path).map_err(ResolverError::PathResolutionError)
     }
 
@@ -23,7 +23,7 @@ use crate::{
 
 use super::{
     functions, get_module_mut, get_struct_type,
-    path_resolver::{PathResolver, StandardPathResolver},
+    path_resolver::{add_path_references, PathResolver, StandardPathResolver},
     resolver::Resolver,
     take_errors,
 };
@@ -380,6 +380,14 @@ pub(crate) fn resolve_trait_impls(
 
         let self_type_span = unresolved_type.span;
 
+        add_path_references(
+            &path_resolver,
+            &context.def_maps,
+            interner,
+            &trait_impl.trait_path,
+            trait_impl.file_id,
+        );
+
         let mut resolver =
             Resolver::new(interner, &path_resolver, &context.def_maps, trait_impl.file_id);
         resolver.add_generics(&trait_impl.generics);
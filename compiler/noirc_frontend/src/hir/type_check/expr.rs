@@ -1,5 +1,6 @@
+use fm::FileId;
 use iter_extended::vecmap;
-use noirc_errors::Span;
+use noirc_errors::{Location, Span};
 
 use crate::{
     hir::{resolution::resolver::verify_mutable_reference, type_check::errors::Source},
@@ -10,8 +11,10 @@ use crate::{
         },
         types::Type,
     },
-    node_interner::{DefinitionKind, ExprId, FuncId, TraitId, TraitImplKind, TraitMethodId},
-    BinaryOpKind, TypeBinding, TypeBindings, TypeVariableKind, UnaryOp,
+    node_interner::{
+        DefinitionKind, ExprId, FuncId, ReferenceId, TraitId, TraitImplKind, TraitMethodId,
+    },
+    BinaryOpKind, Ident, TypeBinding, TypeBindings, TypeVariableKind, UnaryOp,
 };
 
 use super::{errors::TypeCheckError, TypeChecker};
@@ -170,6 +173,21 @@ impl<'interner> TypeChecker<'interner> {
                 let method_name = method_call.method.0.contents.as_str();
                 match self.lookup_method(&object_type, method_name, expr_id) {
                     Some(method_ref) => {
+                        let method_id = match &method_ref {
+                            HirMethodReference::FuncId(func_id) => Some(*func_id),
+                            HirMethodReference::TraitMethodId(method) => self
+                                .interner
+                                .get_trait(method.trait_id)
+                                .method_ids
+                                .get(method_name)
+                                .copied(),
+                        };
+                        if let Some(method_id) = method_id.filter(|id| *id != FuncId::dummy_id()) {
+                            let location =
+                                Location::new(method_call.method.span(), method_call.location.file);
+                            self.interner.add_reference(ReferenceId::Function(method_id), location);
+                        }
+
                         let mut args = vec![(
                             object_type.clone(),
                             method_call.object,
@@ -686,6 +704,9 @@ impl<'interner> TypeChecker<'interner> {
             this.interner.push_expr_location(*access_lhs, span, old_location.file);
         };
 
+        let file = self.interner.expr_location(&expr_id).file;
+        self.add_field_reference(&lhs_type, &access.rhs, file);
+
         match self.check_field_access(&lhs_type, &access.rhs.0.contents, span, dereference_lhs) {
             Some((element_type, index)) => {
                 self.interner.set_field_index(expr_id, index);
@@ -697,6 +718,28 @@ impl<'interner> TypeChecker<'interner> {
         }
     }
 
+    /// Records a use of the struct field `field_name` on an object of type `object_type`, which is
+    /// automatically dereferenced the same way as in `check_field_access`.
+    pub(super) fn add_field_reference(
+        &mut self,
+        object_type: &Type,
+        field_name: &Ident,
+        file: FileId,
+    ) {
+        let mut object_type = object_type.follow_bindings();
+        while let Type::MutableReference(element) = &object_type {
+            object_type = element.follow_bindings();
+        }
+
+        if let Type::Struct(struct_type, _) = &object_type {
+            let struct_type = struct_type.borrow();
+            if let Some(index) = struct_type.field_index(&field_name.0.contents) {
+                let referenced = ReferenceId::StructMember(struct_type.id, index);
+                self.interner.add_reference(referenced, Location::new(field_name.span(), file));
+            }
+        }
+    }
+
     /// This will verify that an expression in the form `lhs.rhs_name` has the given field and will push
     /// a type error if it does not. If there is no error, the type of the struct/tuple field is returned
     /// along with the index of the field in question.
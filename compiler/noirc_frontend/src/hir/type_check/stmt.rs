@@ -203,6 +203,11 @@ impl<'interner> TypeChecker<'interner> {
                 let span = field_name.span();
                 let field_name = field_name.clone();
 
+                if let Some(func_id) = self.current_function {
+                    let file = self.interner.function_meta(&func_id).location.file;
+                    self.add_field_reference(&lhs_type, &field_name, file);
+                }
+
                 let object_ref = &mut object;
                 let mutable_ref = &mut mutable;
 
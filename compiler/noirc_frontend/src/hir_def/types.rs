@@ -252,6 +252,16 @@ impl StructType {
         self.fields.iter().map(|(name, _)| name.clone()).collect()
     }
 
+    /// Returns the index of the field with the given name, if any.
+    pub fn field_index(&self, field_name: &str) -> Option<usize> {
+        self.fields.iter().position(|(name, _)| name.0.contents == field_name)
+    }
+
+    /// Returns the name and the declared (unsubstituted) type of the field at the given index.
+    pub fn field_at(&self, index: usize) -> &(Ident, Type) {
+        &self.fields[index]
+    }
+
     /// True if the given index is the same index as a generic type of this struct
     /// which is expected to be a numeric generic.
     /// This is needed because we infer type kinds in Noir and don't have extensive kind checking.
@@ -1653,7 +1663,7 @@ impl From<&Type> for PrintableType {
                 PrintableType::Struct { fields, name: struct_type.name.to_string() }
             }
             Type::TraitAsType(..) => unreachable!(),
-            Type::Tuple(_) => todo!("printing tuple types is not yet implemented"),
+            Type::Tuple(types) => PrintableType::Tuple { types: vecmap(types, |typ| typ.into()) },
             Type::TypeVariable(_, _) => unreachable!(),
             Type::NamedGeneric(..) => unreachable!(),
             Type::Forall(..) => unreachable!(),
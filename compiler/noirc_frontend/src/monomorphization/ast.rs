@@ -1,6 +1,11 @@
+use std::collections::HashMap;
+
 use acvm::FieldElement;
 use iter_extended::vecmap;
-use noirc_errors::Location;
+use noirc_errors::{
+    debug_info::{DebugFunction, DebugTypes, DebugVariables},
+    Location,
+};
 
 use crate::{
     hir_def::function::FunctionSignature, BinaryOpKind, Distinctness, Signedness, Visibility,
@@ -246,6 +251,13 @@ pub struct Program {
     pub return_distinctness: Distinctness,
     pub return_location: Option<Location>,
     pub return_visibility: Visibility,
+    /// The source variables tracked by the debugger, when the program has been instrumented
+    pub debug_variables: DebugVariables,
+    pub debug_types: DebugTypes,
+    /// The source functions the program was monomorphized from, used to rebuild call stacks
+    pub debug_functions: Vec<DebugFunction>,
+    /// The source function called at each call site, by its index in `debug_functions`
+    pub debug_calls: HashMap<Location, usize>,
 }
 
 impl Program {
@@ -255,6 +267,10 @@ impl Program {
         return_distinctness: Distinctness,
         return_location: Option<Location>,
         return_visibility: Visibility,
+        debug_variables: DebugVariables,
+        debug_types: DebugTypes,
+        debug_functions: Vec<DebugFunction>,
+        debug_calls: HashMap<Location, usize>,
     ) -> Program {
         Program {
             functions,
@@ -262,6 +278,10 @@ impl Program {
             return_distinctness,
             return_location,
             return_visibility,
+            debug_variables,
+            debug_types,
+            debug_functions,
+            debug_calls,
         }
     }
 
@@ -10,7 +10,12 @@
 //! function, will monomorphize the entire reachable program.
 use acvm::FieldElement;
 use iter_extended::{btree_map, vecmap};
-use noirc_errors::Location;
+use noirc_errors::{
+    debug_info::{
+        DebugFunction, DebugTypeId, DebugTypes, DebugVarId, DebugVariable, DebugVariables,
+    },
+    Location,
+};
 use noirc_printable_type::PrintableType;
 use std::{
     collections::{BTreeMap, HashMap, VecDeque},
@@ -18,6 +23,7 @@ use std::{
 };
 
 use crate::{
+    debug::{DebugInstrumenter, SourceVarId, DEBUG_VAR_ASSIGN, DEBUG_VAR_DROP},
     hir_def::{
         expr::*,
         function::{FunctionSignature, Parameters},
@@ -77,6 +83,26 @@ struct Monomorphizer<'interner> {
     is_range_loop: bool,
 
     return_location: Option<Location>,
+
+    /// The instrumentation of the program's variables, when compiling for the debugger
+    debug_instrumenter: Option<&'interner DebugInstrumenter>,
+
+    /// The debug ids given to the variables of the function being monomorphized. A variable
+    /// gets a new id in each instance of a generic function, as its type may differ.
+    debug_var_ids: HashMap<SourceVarId, DebugVarId>,
+
+    debug_variables: DebugVariables,
+    debug_types: DebugTypes,
+    debug_type_ids: HashMap<PrintableType, DebugTypeId>,
+
+    /// The source functions monomorphized so far, each recorded once however many instances
+    /// of it there are
+    debug_functions: Vec<DebugFunction>,
+    debug_function_indices: HashMap<Location, usize>,
+    /// The index in `debug_functions` of the source function of each monomorphized function
+    debug_function_ids: HashMap<FuncId, usize>,
+    /// The monomorphized function called at each call site, where it's known statically
+    debug_calls: Vec<(Location, FuncId)>,
 }
 
 type HirType = crate::Type;
@@ -94,12 +120,32 @@ type HirType = crate::Type;
 /// but it can also be, for example, an arbitrary test function for running `nargo test`.
 #[tracing::instrument(level = "trace", skip(main, interner))]
 pub fn monomorphize(main: node_interner::FuncId, interner: &NodeInterner) -> Program {
-    let mut monomorphizer = Monomorphizer::new(interner);
+    monomorphize_program(main, interner, None)
+}
+
+/// Monomorphizes a program whose variables were instrumented for the debugger, recording
+/// the variables and their types in the resulting [Program].
+#[tracing::instrument(level = "trace", skip(main, interner, debug_instrumenter))]
+pub fn monomorphize_debug(
+    main: node_interner::FuncId,
+    interner: &NodeInterner,
+    debug_instrumenter: &DebugInstrumenter,
+) -> Program {
+    monomorphize_program(main, interner, Some(debug_instrumenter))
+}
+
+fn monomorphize_program<'interner>(
+    main: node_interner::FuncId,
+    interner: &'interner NodeInterner,
+    debug_instrumenter: Option<&'interner DebugInstrumenter>,
+) -> Program {
+    let mut monomorphizer = Monomorphizer::new(interner, debug_instrumenter);
     let function_sig = monomorphizer.compile_main(main);
 
     while !monomorphizer.queue.is_empty() {
         let (next_fn_id, new_id, bindings, trait_method) = monomorphizer.queue.pop_front().unwrap();
         monomorphizer.locals.clear();
+        monomorphizer.debug_var_ids.clear();
 
         perform_instantiation_bindings(&bindings);
         let impl_bindings = monomorphizer.perform_impl_bindings(trait_method, next_fn_id);
@@ -108,6 +154,12 @@ pub fn monomorphize(main: node_interner::FuncId, interner: &NodeInterner) -> Pro
         undo_instantiation_bindings(bindings);
     }
 
+    let debug_function_ids = &monomorphizer.debug_function_ids;
+    let debug_calls = monomorphizer
+        .debug_calls
+        .iter()
+        .filter_map(|(location, id)| Some((*location, *debug_function_ids.get(id)?)))
+        .collect();
     let functions = vecmap(monomorphizer.finished_functions, |(_, f)| f);
     let meta = interner.function_meta(&main);
 
@@ -117,11 +169,18 @@ pub fn monomorphize(main: node_interner::FuncId, interner: &NodeInterner) -> Pro
         meta.return_distinctness,
         monomorphizer.return_location,
         meta.return_visibility,
+        monomorphizer.debug_variables,
+        monomorphizer.debug_types,
+        monomorphizer.debug_functions,
+        debug_calls,
     )
 }
 
 impl<'interner> Monomorphizer<'interner> {
-    fn new(interner: &'interner NodeInterner) -> Self {
+    fn new(
+        interner: &'interner NodeInterner,
+        debug_instrumenter: Option<&'interner DebugInstrumenter>,
+    ) -> Self {
         Monomorphizer {
             globals: HashMap::new(),
             locals: HashMap::new(),
@@ -133,6 +192,15 @@ impl<'interner> Monomorphizer<'interner> {
             lambda_envs_stack: Vec::new(),
             is_range_loop: false,
             return_location: None,
+            debug_instrumenter,
+            debug_var_ids: HashMap::new(),
+            debug_variables: DebugVariables::new(),
+            debug_types: DebugTypes::new(),
+            debug_type_ids: HashMap::new(),
+            debug_functions: Vec::new(),
+            debug_function_indices: HashMap::new(),
+            debug_function_ids: HashMap::new(),
+            debug_calls: Vec::new(),
         }
     }
 
@@ -235,6 +303,12 @@ impl<'interner> Monomorphizer<'interner> {
         let name = self.interner.function_name(&f).to_owned();
 
         let body_expr_id = *self.interner.function(&f).as_expr();
+        let body_location = self.interner.expr_location(&body_expr_id);
+        let function_span = meta.name.location.span.merge(body_location.span);
+        let debug_function = self
+            .push_debug_function(name.clone(), Location::new(function_span, body_location.file));
+        self.debug_function_ids.insert(id, debug_function);
+
         let body_return_type = self.interner.id_type(body_expr_id);
         let return_type = self.convert_type(match meta.return_type() {
             Type::TraitAsType(..) => &body_return_type,
@@ -251,6 +325,15 @@ impl<'interner> Monomorphizer<'interner> {
         self.push_function(id, function);
     }
 
+    /// Records the source function at `location`, unless it already was, and returns its index.
+    fn push_debug_function(&mut self, name: String, location: Location) -> usize {
+        let debug_functions = &mut self.debug_functions;
+        *self.debug_function_indices.entry(location).or_insert_with(|| {
+            debug_functions.push(DebugFunction { name, location });
+            debug_functions.len() - 1
+        })
+    }
+
     fn push_function(&mut self, id: FuncId, function: ast::Function) {
         let existing = self.finished_functions.insert(id, function);
         assert!(existing.is_none());
@@ -911,8 +994,24 @@ impl<'interner> Monomorphizer<'interner> {
         call: HirCallExpression,
         id: node_interner::ExprId,
     ) -> ast::Expression {
+        let debug_var_id = match self.debug_call_var_id(&call) {
+            Some(Some(var_id)) => Some(var_id),
+            // Variables which can't be decoded by the debugger aren't reported to it
+            Some(None) => return ast::Expression::Block(vec![]),
+            None => None,
+        };
+
         let original_func = Box::new(self.expr(call.func));
         let mut arguments = vecmap(&call.arguments, |id| self.expr(*id));
+        if let Some(DebugVarId(var_id)) = debug_var_id {
+            let location = self.interner.expr_location(&call.arguments[0]);
+            let typ = ast::Type::Integer(crate::Signedness::Unsigned, 32);
+            arguments[0] = ast::Expression::Literal(ast::Literal::Integer(
+                (var_id as u128).into(),
+                typ,
+                location,
+            ));
+        }
         let hir_arguments = vecmap(&call.arguments, |id| self.interner.expression(id));
         let return_type = self.interner.id_type(id);
         let return_type = self.convert_type(&return_type);
@@ -966,6 +1065,14 @@ impl<'interner> Monomorphizer<'interner> {
             original_func.clone()
         };
 
+        if let ast::Expression::Ident(ast::Ident {
+            definition: Definition::Function(func_id),
+            ..
+        }) = func.as_ref()
+        {
+            self.debug_calls.push((location, *func_id));
+        }
+
         let call = self
             .try_evaluate_call(&func, &id, &return_type)
             .unwrap_or(ast::Expression::Call(ast::Call { func, arguments, return_type, location }));
@@ -978,6 +1085,54 @@ impl<'interner> Monomorphizer<'interner> {
         }
     }
 
+    /// For calls made by the debug instrumentation, returns the id under which the variable it
+    /// reports on is known to the debugger, or `None` if the debugger can't display it.
+    fn debug_call_var_id(&mut self, call: &HirCallExpression) -> Option<Option<DebugVarId>> {
+        let debug_instrumenter = self.debug_instrumenter?;
+        let HirExpression::Ident(ident) = self.interner.expression(&call.func) else {
+            return None;
+        };
+        let DefinitionKind::Function(func_id) = self.interner.definition(ident.id).kind else {
+            return None;
+        };
+        let is_assign = match self.interner.function_name(&func_id) {
+            DEBUG_VAR_ASSIGN => true,
+            DEBUG_VAR_DROP => false,
+            _ => return None,
+        };
+        let HirExpression::Literal(HirLiteral::Integer(source_var_id, _)) =
+            self.interner.expression(call.arguments.first()?)
+        else {
+            return None;
+        };
+        let source_var_id = SourceVarId(source_var_id.to_u128() as u32);
+
+        if !is_assign {
+            return Some(self.debug_var_ids.get(&source_var_id).copied());
+        }
+        if let Some(var_id) = self.debug_var_ids.get(&source_var_id) {
+            return Some(Some(*var_id));
+        }
+
+        let name = debug_instrumenter.variables.get(&source_var_id)?.clone();
+        let typ = self.interner.id_type(call.arguments.get(1)?).follow_bindings();
+        if !is_printable(&typ) {
+            return Some(None);
+        }
+        let printable_type = PrintableType::from(&typ);
+        let next_type_id = DebugTypeId(self.debug_type_ids.len() as u32);
+        let debug_type_id =
+            *self.debug_type_ids.entry(printable_type.clone()).or_insert_with(|| {
+                self.debug_types.insert(next_type_id, printable_type);
+                next_type_id
+            });
+
+        let var_id = DebugVarId(self.debug_variables.len() as u32);
+        self.debug_variables.insert(var_id, DebugVariable { name, debug_type_id });
+        self.debug_var_ids.insert(source_var_id, var_id);
+        Some(Some(var_id))
+    }
+
     /// Adds a function argument that contains type metadata that is required to tell
     /// `println` how to convert values passed to an foreign call back to a human-readable string.
     /// The values passed to an foreign call will be a simple list of field elements,
@@ -1171,6 +1326,7 @@ impl<'interner> Monomorphizer<'interner> {
     }
 
     fn lambda(&mut self, lambda: HirLambda, expr: node_interner::ExprId) -> ast::Expression {
+        self.push_debug_function("lambda".to_string(), self.interner.expr_location(&expr));
         if lambda.captures.is_empty() {
             self.lambda_no_capture(lambda)
         } else {
@@ -1571,6 +1727,28 @@ fn unwrap_struct_type(typ: &HirType) -> Vec<(String, HirType)> {
     }
 }
 
+/// Whether values of the given type can be decoded into a [PrintableValue][noirc_printable_type::PrintableValue].
+fn is_printable(typ: &HirType) -> bool {
+    match typ {
+        HirType::FieldElement | HirType::Integer(..) | HirType::Bool => true,
+        HirType::Array(length, element) => {
+            length.evaluate_to_u64().is_some() && is_printable(element)
+        }
+        HirType::String(length) => length.evaluate_to_u64().is_some(),
+        HirType::Tuple(elements) => elements.iter().all(is_printable),
+        HirType::Struct(def, args) => {
+            def.borrow().get_fields(args).iter().all(|(_, field)| is_printable(field))
+        }
+        HirType::TypeVariable(binding, TypeVariableKind::IntegerOrField) => {
+            match &*binding.borrow() {
+                TypeBinding::Bound(typ) => is_printable(typ),
+                TypeBinding::Unbound(_) => true,
+            }
+        }
+        _ => false,
+    }
+}
+
 fn perform_instantiation_bindings(bindings: &TypeBindings) {
     for (var, binding) in bindings.values() {
         var.force_bind(binding.clone());
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use arena::{Arena, Index};
 use fm::FileId;
@@ -8,7 +8,7 @@ use noirc_errors::{Location, Span, Spanned};
 use crate::ast::Ident;
 use crate::graph::CrateId;
 use crate::hir::def_collector::dc_crate::{UnresolvedStruct, UnresolvedTrait, UnresolvedTypeAlias};
-use crate::hir::def_map::{LocalModuleId, ModuleId};
+use crate::hir::def_map::{LocalModuleId, ModuleDefId, ModuleId};
 
 use crate::hir_def::stmt::HirLetStatement;
 use crate::hir_def::traits::TraitImpl;
@@ -142,6 +142,21 @@ pub struct NodeInterner {
 
     // For trait implementation functions, this is their self type and trait they belong to
     func_id_to_trait: HashMap<FuncId, (Type, TraitId)>,
+
+    /// Whether references are recorded at all, which only tooling such as the LSP server needs.
+    track_references: bool,
+
+    /// Every location where an item is referred to by name, recorded during name resolution
+    /// and type checking so that tooling can find all the uses of an item.
+    references: BTreeMap<ReferenceId, HashSet<Location>>,
+
+    /// References to globals made before the global was resolved, such as in `use` statements,
+    /// which are recorded once the global has a definition.
+    unresolved_global_references: HashMap<StmtId, Vec<Location>>,
+
+    /// The location of the name of each module in the `mod` statement declaring it.
+    /// Crate roots are not declared by any `mod` statement so they have no entry.
+    module_locations: HashMap<ModuleId, Location>,
 }
 
 /// A trait implementation is either a normal implementation that is present in the source
@@ -210,7 +225,7 @@ impl FunctionModifiers {
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct DefinitionId(usize);
 
 impl DefinitionId {
@@ -246,7 +261,7 @@ impl ExprId {
         ExprId(Index::from_raw_parts(0, 0))
     }
 }
-#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone, PartialOrd, Ord)]
 pub struct FuncId(Index);
 
 impl FuncId {
@@ -312,6 +327,22 @@ pub struct TraitMethodId {
     pub method_index: usize, // index in Trait::methods
 }
 
+/// An item which can be referred to by name in the source code.
+///
+/// A [ReferenceId] is what tooling (such as the LSP server) gets back when looking up what
+/// is found at a given [Location], whether that location is the item's declaration or one of its uses.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ReferenceId {
+    Function(FuncId),
+    Struct(StructId),
+    /// A struct field, identified by its index in the struct's fields
+    StructMember(StructId, usize),
+    Trait(TraitId),
+    Global(DefinitionId),
+    Local(DefinitionId),
+    Module(ModuleId),
+}
+
 macro_rules! into_index {
     ($id_type:ty) => {
         impl From<$id_type> for Index {
@@ -437,6 +468,10 @@ impl Default for NodeInterner {
             globals: HashMap::new(),
             struct_methods: HashMap::new(),
             primitive_methods: HashMap::new(),
+            track_references: false,
+            references: BTreeMap::new(),
+            unresolved_global_references: HashMap::new(),
+            module_locations: HashMap::new(),
         };
 
         // An empty block expression is used often, we add this into the `node` on startup
@@ -634,6 +669,12 @@ impl NodeInterner {
             }
         };
         *stmt = hir_stmt;
+
+        if let Some(locations) = self.unresolved_global_references.remove(&stmt_id) {
+            for location in locations {
+                self.add_module_def_reference(ModuleDefId::GlobalId(stmt_id), location);
+            }
+        }
     }
 
     /// Intern an empty function.
@@ -871,6 +912,12 @@ impl NodeInterner {
         self.id_location(expr_id)
     }
 
+    /// Returns the [Location] of the given expression, or `None` for expressions
+    /// which were never given one such as the empty block of a function without a body.
+    pub fn try_expr_location(&self, expr_id: &ExprId) -> Option<Location> {
+        self.id_to_location.get(&(*expr_id).into()).copied()
+    }
+
     pub fn get_struct(&self, id: StructId) -> Shared<StructType> {
         self.structs[&id].clone()
     }
@@ -1251,6 +1298,34 @@ impl NodeInterner {
         self.lookup_primitive_method(&typ, method_name)
     }
 
+    /// Returns each method, including trait methods, which may be called on a value of the given type.
+    ///
+    /// Unlike [Self::lookup_method] this doesn't check that the method's `self` type unifies with `typ`.
+    pub fn methods_for_type(&self, typ: &Type) -> Vec<FuncId> {
+        let key = get_type_method_key(typ);
+        let struct_id = match typ.follow_bindings() {
+            Type::Struct(struct_type, _) => Some(struct_type.borrow().id),
+            Type::MutableReference(element) => return self.methods_for_type(&element),
+            _ => None,
+        };
+
+        let struct_methods = self
+            .struct_methods
+            .iter()
+            .filter(|((id, _), _)| Some(*id) == struct_id)
+            .map(|(_, methods)| methods);
+        // Methods from impls for all types `T`, e.g. `impl<T> Foo for T`, apply to every type
+        let primitive_methods = self
+            .primitive_methods
+            .iter()
+            .filter(|((method_key, _), _)| {
+                Some(*method_key) == key || *method_key == TypeMethodKey::Generic
+            })
+            .map(|(_, methods)| methods);
+
+        struct_methods.chain(primitive_methods).flat_map(|methods| methods.iter()).collect()
+    }
+
     /// Returns what the next trait impl id is expected to be.
     /// Note that this does not actually reserve the slot so care should
     /// be taken that the next trait impl added matches this ID.
@@ -1352,6 +1427,430 @@ impl NodeInterner {
         })
     }
 
+    /// Returns the [ReferenceId] of the item found at the given [Location].
+    ///
+    /// The location may point either to the name in the item's declaration or to one of its uses.
+    pub fn find_referenced(&self, location: Location) -> Option<ReferenceId> {
+        self.find_declaration(location)
+            .or_else(|| self.find_recorded_reference(location))
+            .or_else(|| self.find_usage(location))
+    }
+
+    /// Returns the [ReferenceId] of the item found at each of the given byte offsets into `file`,
+    /// as [NodeInterner::find_referenced] would for a single character location at that offset.
+    ///
+    /// This goes over the interner once for all offsets rather than once per offset, so that
+    /// every identifier of a file can be looked up at once.
+    pub fn find_all_referenced(&self, file: FileId, offsets: &[u32]) -> HashMap<u32, ReferenceId> {
+        let mut offsets = offsets.to_vec();
+        offsets.sort_unstable();
+        offsets.dedup();
+        let offsets_within = |span: Span| {
+            let start = offsets.partition_point(|offset| *offset < span.start());
+            let end = offsets.partition_point(|offset| *offset < span.end());
+            &offsets[start..end]
+        };
+
+        // Usages are found through the innermost node at each offset, so larger nodes are visited
+        // first to be overwritten by the nodes nested within them.
+        let mut nodes: Vec<_> =
+            self.id_to_location.iter().filter(|(_, location)| location.file == file).collect();
+        nodes.sort_by_key(|(_, location)| {
+            std::cmp::Reverse(location.span.end() - location.span.start())
+        });
+        let mut usages = HashMap::new();
+        for (index, location) in nodes {
+            let expression = match self.nodes.get(*index) {
+                Some(Node::Expression(expression)) => Some(expression),
+                _ => None,
+            };
+            for offset in offsets_within(location.span) {
+                let location = Location::new(Span::single_char(*offset), file);
+                let reference = expression
+                    .and_then(|expression| self.expression_reference(expression, location));
+                usages.insert(*offset, reference);
+            }
+        }
+        let mut found: HashMap<u32, ReferenceId> = usages
+            .into_iter()
+            .filter_map(|(offset, reference)| reference.map(|reference| (offset, reference)))
+            .collect();
+
+        for (referenced, locations) in &self.references {
+            for location in locations.iter().filter(|location| location.file == file) {
+                for offset in offsets_within(location.span) {
+                    found.insert(*offset, *referenced);
+                }
+            }
+        }
+
+        // Declarations take precedence over everything else, in the same order as in
+        // `find_declaration`, so the declarations it checks first are inserted last.
+        let mut declarations = Vec::new();
+        for (index, definition) in self.definitions.iter().enumerate() {
+            let reference = match definition.kind {
+                DefinitionKind::Global(_) => ReferenceId::Global(DefinitionId(index)),
+                DefinitionKind::Local(_) => ReferenceId::Local(DefinitionId(index)),
+                _ => continue,
+            };
+            declarations.push((definition.location, reference));
+        }
+        for (module_id, location) in &self.module_locations {
+            declarations.push((*location, ReferenceId::Module(*module_id)));
+        }
+        for (trait_id, the_trait) in &self.traits {
+            let location = Location::new(the_trait.name.span(), the_trait.location.file);
+            declarations.push((location, ReferenceId::Trait(*trait_id)));
+        }
+        for (struct_id, struct_type) in &self.structs {
+            let struct_type = struct_type.borrow();
+            let file = struct_type.location.file;
+            for index in (0..struct_type.num_fields()).rev() {
+                let location = Location::new(struct_type.field_at(index).0.span(), file);
+                declarations.push((location, ReferenceId::StructMember(*struct_id, index)));
+            }
+            let location = Location::new(struct_type.name.span(), file);
+            declarations.push((location, ReferenceId::Struct(*struct_id)));
+        }
+        for (func_id, meta) in &self.func_meta {
+            declarations.push((meta.name.location, ReferenceId::Function(*func_id)));
+        }
+        for (location, reference) in declarations {
+            if location.file == file {
+                for offset in offsets_within(location.span) {
+                    found.insert(*offset, reference);
+                }
+            }
+        }
+        found
+    }
+
+    /// Returns the [Location] of the name of the given item in its declaration.
+    ///
+    /// Returns `None` for crate root modules as these aren't declared by name.
+    pub fn reference_location(&self, reference: ReferenceId) -> Option<Location> {
+        let location = match reference {
+            ReferenceId::Function(func_id) => self.function_meta(&func_id).name.location,
+            ReferenceId::Struct(struct_id) => {
+                let struct_type = self.get_struct(struct_id);
+                let struct_type = struct_type.borrow();
+                Location::new(struct_type.name.span(), struct_type.location.file)
+            }
+            ReferenceId::StructMember(struct_id, index) => {
+                let struct_type = self.get_struct(struct_id);
+                let struct_type = struct_type.borrow();
+                let (field_name, _) = struct_type.field_at(index);
+                Location::new(field_name.span(), struct_type.location.file)
+            }
+            ReferenceId::Trait(trait_id) => {
+                let the_trait = self.get_trait(trait_id);
+                Location::new(the_trait.name.span(), the_trait.location.file)
+            }
+            ReferenceId::Global(id) | ReferenceId::Local(id) => self.definition(id).location,
+            ReferenceId::Module(module_id) => {
+                return self.module_locations.get(&module_id).copied()
+            }
+        };
+        Some(location)
+    }
+
+    /// Starts recording where items are referred to, for tooling which looks them up with
+    /// [NodeInterner::find_referenced] and [NodeInterner::find_references].
+    pub fn track_references(&mut self) {
+        self.track_references = true;
+    }
+
+    pub fn is_tracking_references(&self) -> bool {
+        self.track_references
+    }
+
+    /// Records that `referenced` is referred to by name at the given [Location].
+    pub fn add_reference(&mut self, referenced: ReferenceId, location: Location) {
+        if self.track_references {
+            self.references.entry(referenced).or_default().insert(location);
+        }
+    }
+
+    /// Records a use of the given definition at `location`.
+    pub fn add_definition_reference(&mut self, id: DefinitionId, location: Location) {
+        if let Some(referenced) = self.definition_reference(id) {
+            self.add_reference(referenced, location);
+        }
+    }
+
+    /// Records a use of the given module or module item at `location`.
+    pub fn add_module_def_reference(&mut self, module_def_id: ModuleDefId, location: Location) {
+        if !self.track_references {
+            return;
+        }
+        match (self.module_def_reference(module_def_id), module_def_id) {
+            (Some(referenced), _) => self.add_reference(referenced, location),
+            // Globals referred to before they are resolved have no definition yet, so the
+            // reference is recorded once they do
+            (None, ModuleDefId::GlobalId(stmt_id)) => {
+                self.unresolved_global_references.entry(stmt_id).or_default().push(location);
+            }
+            (None, _) => (),
+        }
+    }
+
+    /// Returns the [ReferenceId] of the given module or module item.
+    ///
+    /// Type aliases have none, and neither do globals until they are resolved.
+    pub fn module_def_reference(&self, module_def_id: ModuleDefId) -> Option<ReferenceId> {
+        match module_def_id {
+            ModuleDefId::ModuleId(module_id) => Some(ReferenceId::Module(module_id)),
+            ModuleDefId::FunctionId(func_id) => Some(ReferenceId::Function(func_id)),
+            ModuleDefId::TypeId(struct_id) => Some(ReferenceId::Struct(struct_id)),
+            ModuleDefId::TraitId(trait_id) => Some(ReferenceId::Trait(trait_id)),
+            ModuleDefId::GlobalId(stmt_id) => match self.statement(&stmt_id) {
+                HirStatement::Let(let_statement) => {
+                    Some(ReferenceId::Global(let_statement.ident().id))
+                }
+                _ => None,
+            },
+            ModuleDefId::TypeAliasId(_) => None,
+        }
+    }
+
+    /// Records the [Location] of the name of a module in its `mod` statement.
+    pub fn add_module_location(&mut self, module_id: ModuleId, location: Location) {
+        self.module_locations.insert(module_id, location);
+    }
+
+    /// Returns the location of each use of the given item in order of their files and spans,
+    /// optionally preceded by its declaration.
+    pub fn find_references(
+        &self,
+        referenced: ReferenceId,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let declaration = self.reference_location(referenced).filter(|_| include_declaration);
+        let mut uses: Vec<Location> = self
+            .references
+            .get(&referenced)
+            .into_iter()
+            .flatten()
+            .filter(|location| Some(**location) != declaration)
+            .copied()
+            .collect();
+        uses.sort_by_key(|location| (location.file, location.span));
+        declaration.into_iter().chain(uses).collect()
+    }
+
+    /// Returns every item which is either declared or used at exactly the given [Location].
+    ///
+    /// More than one item is returned for struct field shorthands such as `Foo { x }`, where `x`
+    /// refers both to the field and to a local variable.
+    pub fn references_at(&self, location: Location) -> Vec<ReferenceId> {
+        let mut referenced: Vec<_> = self
+            .references
+            .iter()
+            .filter(|(_, locations)| locations.contains(&location))
+            .map(|(referenced, _)| *referenced)
+            .collect();
+
+        let locals = self.definitions.iter().enumerate().filter(|(_, definition)| {
+            definition.location == location && matches!(definition.kind, DefinitionKind::Local(_))
+        });
+        for (index, _) in locals {
+            let local = ReferenceId::Local(DefinitionId(index));
+            if !referenced.contains(&local) {
+                referenced.push(local);
+            }
+        }
+        referenced
+    }
+
+    /// Returns the functions which must share a name with the given function: if it is a trait
+    /// method, or the implementation of one, these are the method declared in the trait and each of
+    /// its implementations. Otherwise only `func_id` is returned.
+    pub fn trait_method_family(&self, func_id: FuncId) -> Vec<FuncId> {
+        let name = self.function_name(&func_id);
+        let trait_id = match self.try_function_meta(&func_id).and_then(|meta| meta.trait_impl) {
+            // Impls of unknown traits are never added to `trait_implementations`
+            Some(impl_id) => self
+                .trait_implementations
+                .get(impl_id.0)
+                .map(|trait_impl| trait_impl.borrow().trait_id),
+            None => self
+                .traits
+                .values()
+                .find(|the_trait| the_trait.method_ids.values().any(|id| *id == func_id))
+                .map(|the_trait| the_trait.id),
+        };
+        let Some(trait_id) = trait_id else {
+            return vec![func_id];
+        };
+
+        let mut family: Vec<_> =
+            self.get_trait(trait_id).method_ids.get(name).copied().into_iter().collect();
+        for trait_impl in &self.trait_implementations {
+            let trait_impl = trait_impl.borrow();
+            if trait_impl.trait_id == trait_id {
+                let methods = trait_impl.methods.iter().filter(|id| self.function_name(id) == name);
+                family.extend(methods);
+            }
+        }
+        if !family.contains(&func_id) {
+            family.push(func_id);
+        }
+        family
+    }
+
+    /// Returns the function whose body contains the given [Location].
+    pub fn find_function_containing(&self, location: Location) -> Option<FuncId> {
+        self.func_meta.keys().copied().find(|func_id| {
+            let body = self.function(func_id);
+            self.try_expr_location(body.as_expr()).map_or(false, |body| body.contains(&location))
+        })
+    }
+
+    /// Returns the functions declared in the given file, in the order they appear.
+    pub fn functions_in_file(&self, file: FileId) -> Vec<FuncId> {
+        let mut functions: Vec<_> =
+            self.func_meta.iter().filter(|(_, meta)| meta.location.file == file).collect();
+        functions.sort_by_key(|(_, meta)| meta.location.span.start());
+        functions.into_iter().map(|(func_id, _)| *func_id).collect()
+    }
+
+    /// Returns the innermost expression whose [Location] contains the given [Location].
+    pub fn find_expression_at(&self, location: Location) -> Option<ExprId> {
+        self.find_location_index(location).map(|index| ExprId(index.into()))
+    }
+
+    /// Finds the item whose name is declared at the given [Location].
+    fn find_declaration(&self, location: Location) -> Option<ReferenceId> {
+        let function =
+            self.func_meta.iter().find(|(_, meta)| meta.name.location.contains(&location));
+        if let Some((func_id, _)) = function {
+            return Some(ReferenceId::Function(*func_id));
+        }
+
+        for (struct_id, struct_type) in &self.structs {
+            let struct_type = struct_type.borrow();
+            if struct_type.location.file != location.file {
+                continue;
+            }
+            if struct_type.name.span().contains(&location.span) {
+                return Some(ReferenceId::Struct(*struct_id));
+            }
+            let field_index = (0..struct_type.num_fields())
+                .find(|index| struct_type.field_at(*index).0.span().contains(&location.span));
+            if let Some(field_index) = field_index {
+                return Some(ReferenceId::StructMember(*struct_id, field_index));
+            }
+        }
+
+        for (trait_id, the_trait) in &self.traits {
+            if the_trait.location.file == location.file
+                && the_trait.name.span().contains(&location.span)
+            {
+                return Some(ReferenceId::Trait(*trait_id));
+            }
+        }
+
+        let module = self.module_locations.iter().find(|(_, name)| name.contains(&location));
+        if let Some((module_id, _)) = module {
+            return Some(ReferenceId::Module(*module_id));
+        }
+
+        self.definitions.iter().enumerate().find_map(|(index, definition)| {
+            if !definition.location.contains(&location) {
+                return None;
+            }
+            match definition.kind {
+                DefinitionKind::Global(_) => Some(ReferenceId::Global(DefinitionId(index))),
+                DefinitionKind::Local(_) => Some(ReferenceId::Local(DefinitionId(index))),
+                _ => None,
+            }
+        })
+    }
+
+    /// Finds the item whose recorded use contains the given [Location], preferring the innermost
+    /// use if they are nested.
+    fn find_recorded_reference(&self, location: Location) -> Option<ReferenceId> {
+        self.references
+            .iter()
+            .flat_map(|(referenced, locations)| {
+                locations
+                    .iter()
+                    .filter(|used| used.contains(&location))
+                    .map(move |used| (used.span.end() - used.span.start(), *referenced))
+            })
+            .min()
+            .map(|(_, referenced)| referenced)
+    }
+
+    /// Finds the item referred to by the innermost expression found at the given [Location].
+    fn find_usage(&self, location: Location) -> Option<ReferenceId> {
+        let index = self.find_location_index(location)?;
+        match self.nodes.get(index.into())? {
+            Node::Expression(expression) => self.expression_reference(expression, location),
+            _ => None,
+        }
+    }
+
+    /// Returns the item referred to by the given [HirExpression].
+    ///
+    /// The [Location] is used to tell apart the struct itself from one of its fields in a constructor expression.
+    fn expression_reference(
+        &self,
+        expression: &HirExpression,
+        location: Location,
+    ) -> Option<ReferenceId> {
+        match expression {
+            HirExpression::Ident(ident) => self.definition_reference(ident.id),
+            HirExpression::Constructor(constructor) => {
+                let struct_type = constructor.r#type.borrow();
+                let field = constructor
+                    .fields
+                    .iter()
+                    .find(|(field_name, _)| field_name.span().contains(&location.span));
+                match field {
+                    Some((field_name, _)) => struct_type
+                        .field_index(&field_name.0.contents)
+                        .map(|index| ReferenceId::StructMember(struct_type.id, index)),
+                    None => Some(ReferenceId::Struct(struct_type.id)),
+                }
+            }
+            HirExpression::MemberAccess(member_access) => {
+                match self.id_type(member_access.lhs).follow_bindings() {
+                    Type::Struct(struct_type, _) => {
+                        let struct_type = struct_type.borrow();
+                        struct_type
+                            .field_index(&member_access.rhs.0.contents)
+                            .map(|index| ReferenceId::StructMember(struct_type.id, index))
+                    }
+                    _ => None,
+                }
+            }
+            HirExpression::Call(call) => {
+                // Method calls are lowered into function calls whose function has no location of its own
+                self.expression_reference(&self.expression(&call.func), location)
+            }
+            HirExpression::TraitMethodReference(method_id) => {
+                let the_trait = self.get_trait(method_id.trait_id);
+                let method = the_trait.methods.get(method_id.method_index)?;
+                the_trait
+                    .method_ids
+                    .get(&method.name.0.contents)
+                    .copied()
+                    .map(ReferenceId::Function)
+            }
+            _ => None,
+        }
+    }
+
+    fn definition_reference(&self, id: DefinitionId) -> Option<ReferenceId> {
+        match self.try_definition(id)?.kind {
+            DefinitionKind::Function(func_id) => Some(ReferenceId::Function(func_id)),
+            DefinitionKind::Global(_) => Some(ReferenceId::Global(id)),
+            DefinitionKind::Local(_) => Some(ReferenceId::Local(id)),
+            DefinitionKind::GenericType(_) => None,
+        }
+    }
+
     /// Retrieves the trait id for a given binary operator.
     /// All binary operators correspond to a trait - although multiple may correspond
     /// to the same trait (such as `==` and `!=`).
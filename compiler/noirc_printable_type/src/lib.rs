@@ -6,7 +6,7 @@ use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "lowercase")]
 pub enum PrintableType {
     Field,
@@ -22,6 +22,9 @@ pub enum PrintableType {
         width: u32,
     },
     Boolean,
+    Tuple {
+        types: Vec<PrintableType>,
+    },
     Struct {
         name: String,
         fields: Vec<(String, PrintableType)>,
@@ -40,6 +43,7 @@ impl PrintableType {
             | Self::UnsignedInteger { .. }
             | Self::Boolean => 1,
             Self::Array { length, typ } => typ.field_count() * (*length as u32),
+            Self::Tuple { types } => types.iter().map(PrintableType::field_count).sum(),
             Self::Struct { fields, .. } => {
                 fields.iter().fold(0, |acc, (_, field_type)| acc + field_type.field_count())
             }
@@ -211,6 +215,21 @@ fn to_string(value: &PrintableValue, typ: &PrintableType) -> Option<String> {
             output.push(']');
         }
 
+        (PrintableValue::Vec(values), PrintableType::Tuple { types }) => {
+            output.push('(');
+            let mut elements = values.iter().zip(types).peekable();
+            while let Some((value, typ)) = elements.next() {
+                output.push_str(&format!(
+                    "{}",
+                    PrintableValueDisplay::Plain(value.clone(), typ.clone())
+                ));
+                if elements.peek().is_some() {
+                    output.push_str(", ");
+                }
+            }
+            output.push(')');
+        }
+
         (PrintableValue::String(s), PrintableType::String { .. }) => {
             output.push_str(s);
         }
@@ -295,7 +314,7 @@ fn format_field_string(field: FieldElement) -> String {
 }
 
 /// Assumes that `field_iterator` contains enough [FieldElement] in order to decode the [PrintableType]
-fn decode_value(
+pub fn decode_value(
     field_iterator: &mut impl Iterator<Item = FieldElement>,
     typ: &PrintableType,
 ) -> PrintableValue {
@@ -317,6 +336,9 @@ fn decode_value(
 
             PrintableValue::Vec(array_elements)
         }
+        PrintableType::Tuple { types } => {
+            PrintableValue::Vec(vecmap(types, |typ| decode_value(field_iterator, typ)))
+        }
         PrintableType::String { length } => {
             let field_elements: Vec<FieldElement> = field_iterator.take(*length as usize).collect();
 
@@ -0,0 +1,667 @@
+use acvm::FieldElement;
+use noirc_printable_type::{PrintableType, PrintableValue, PrintableValueDisplay};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The program state which breakpoint conditions and log messages are evaluated over.
+pub(crate) trait DebugState {
+    fn witness_value(&self, index: u32) -> Option<FieldElement>;
+
+    fn register_value(&self, index: usize) -> Option<FieldElement>;
+
+    /// Returns the value and type of the innermost source variable in scope with the given name.
+    fn variable(&self, name: &str) -> Option<(&PrintableValue, &PrintableType)>;
+}
+
+/// Refinements of a breakpoint, as set from the REPL or through the DAP `SetBreakpoints` request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct BreakpointOptions {
+    /// Execution only stops if the condition holds
+    pub(crate) condition: Option<Condition>,
+    /// Execution only stops once the number of hits, counting only those where the condition
+    /// held, satisfies this
+    pub(crate) hit_condition: Option<HitCondition>,
+    /// Turns the breakpoint into a logpoint, which prints this message instead of stopping
+    pub(crate) log_message: Option<LogMessage>,
+}
+
+impl BreakpointOptions {
+    /// Parses the options of a breakpoint, where blank strings count as missing.
+    pub(crate) fn parse(
+        condition: Option<&str>,
+        hit_condition: Option<&str>,
+        log_message: Option<&str>,
+    ) -> Result<Self, String> {
+        let non_blank = |input: Option<&str>| input.filter(|input| !input.trim().is_empty());
+        Ok(Self {
+            condition: non_blank(condition).map(str::parse).transpose()?,
+            hit_condition: non_blank(hit_condition).map(str::parse).transpose()?,
+            log_message: non_blank(log_message).map(str::parse).transpose()?,
+        })
+    }
+}
+
+/// What to do once execution reaches a breakpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BreakpointHit {
+    Stop,
+    Skip,
+    /// Print the message and carry on
+    Log(String),
+    /// Stop, since the condition could not be evaluated
+    Error(String),
+}
+
+/// A breakpoint set in the debugger, with the number of times it has been hit.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Breakpoint {
+    pub(crate) options: BreakpointOptions,
+    hits: u64,
+}
+
+impl Breakpoint {
+    pub(crate) fn new(options: BreakpointOptions) -> Self {
+        Self { options, hits: 0 }
+    }
+
+    /// Records that execution reached the breakpoint and works out whether it should stop.
+    pub(crate) fn hit(&mut self, state: &dyn DebugState) -> BreakpointHit {
+        if let Some(condition) = &self.options.condition {
+            match condition.evaluate(state) {
+                Ok(true) => (),
+                Ok(false) => return BreakpointHit::Skip,
+                Err(error) => return BreakpointHit::Error(error),
+            }
+        }
+        self.hits += 1;
+        if let Some(hit_condition) = &self.options.hit_condition {
+            if !hit_condition.is_met(self.hits) {
+                return BreakpointHit::Skip;
+            }
+        }
+        match &self.options.log_message {
+            Some(log_message) => BreakpointHit::Log(log_message.render(state)),
+            None => BreakpointHit::Stop,
+        }
+    }
+
+    /// Whether execution going backwards should stop at the breakpoint. Hits aren't counted
+    /// backwards, so the hit condition is ignored, and logpoints never stop.
+    pub(crate) fn stops_in_reverse(&self, state: &dyn DebugState) -> bool {
+        if self.options.log_message.is_some() {
+            return false;
+        }
+        let condition = self.options.condition.as_ref();
+        condition.map_or(true, |condition| condition.evaluate(state).unwrap_or(true))
+    }
+}
+
+/// A number a condition operand evaluates to. Negative signed integers are kept apart from the
+/// field elements so that they order below every other value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Scalar {
+    Negative(i128),
+    NonNegative(FieldElement),
+}
+
+impl Scalar {
+    fn from_value(value: &PrintableValue, typ: &PrintableType) -> Option<Self> {
+        let PrintableValue::Field(field) = value else {
+            return None;
+        };
+        match typ {
+            PrintableType::SignedInteger { width } if *width > 0 && *width < 127 => {
+                // Signed integers are encoded in two's complement
+                let value = field.to_u128();
+                if value >= 1 << (width - 1) {
+                    Some(Scalar::Negative(value as i128 - (1 << width)))
+                } else {
+                    Some(Scalar::NonNegative(*field))
+                }
+            }
+            _ => Some(Scalar::NonNegative(*field)),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        matches!(self, Scalar::NonNegative(field) if field.is_zero())
+    }
+}
+
+impl FromStr for Scalar {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, String> {
+        if input.starts_with('-') {
+            let value: i128 = input.parse().map_err(|_| format!("invalid number {input}"))?;
+            if value == 0 {
+                return Ok(Scalar::NonNegative(FieldElement::zero()));
+            }
+            return Ok(Scalar::Negative(value));
+        }
+        FieldElement::try_from_str(input)
+            .map(Scalar::NonNegative)
+            .ok_or_else(|| format!("invalid number {input}"))
+    }
+}
+
+impl Display for Scalar {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scalar::Negative(value) => write!(f, "{value}"),
+            Scalar::NonNegative(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// A step into a compound source variable, as in `point.x` or `values[2]`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Accessor {
+    Field(String),
+    Index(usize),
+}
+
+impl Display for Accessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Accessor::Field(name) => write!(f, ".{name}"),
+            Accessor::Index(index) => write!(f, "[{index}]"),
+        }
+    }
+}
+
+/// A value referred to by a condition or log message: a literal, a witness (`_3`), a Brillig
+/// register (`$r1`) or a source variable.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Operand {
+    Literal(Scalar),
+    Witness(u32),
+    Register(usize),
+    Variable { name: String, accessors: Vec<Accessor> },
+}
+
+impl Operand {
+    fn scalar(&self, state: &dyn DebugState) -> Result<Scalar, String> {
+        match self {
+            Operand::Literal(scalar) => Ok(*scalar),
+            Operand::Witness(index) => state
+                .witness_value(*index)
+                .map(Scalar::NonNegative)
+                .ok_or_else(|| format!("witness {self} is not solved yet")),
+            Operand::Register(index) => state
+                .register_value(*index)
+                .map(Scalar::NonNegative)
+                .ok_or_else(|| format!("register {self} is not available")),
+            Operand::Variable { .. } => {
+                let (value, typ) = self.variable_value(state)?;
+                Scalar::from_value(value, typ).ok_or_else(|| format!("{self} is not a number"))
+            }
+        }
+    }
+
+    fn display(&self, state: &dyn DebugState) -> Result<String, String> {
+        match self {
+            Operand::Variable { .. } => {
+                let (value, typ) = self.variable_value(state)?;
+                Ok(PrintableValueDisplay::Plain(value.clone(), typ.clone()).to_string())
+            }
+            _ => self.scalar(state).map(|scalar| scalar.to_string()),
+        }
+    }
+
+    fn variable_value<'s>(
+        &self,
+        state: &'s dyn DebugState,
+    ) -> Result<(&'s PrintableValue, &'s PrintableType), String> {
+        let Operand::Variable { name, accessors } = self else {
+            unreachable!("variable_value called on a non-variable operand");
+        };
+        let (mut value, mut typ) =
+            state.variable(name).ok_or_else(|| format!("variable {name} is not in scope"))?;
+        for accessor in accessors {
+            let element = match (accessor, value, typ) {
+                (
+                    Accessor::Field(field),
+                    PrintableValue::Struct(values),
+                    PrintableType::Struct { fields, .. },
+                ) => {
+                    let field_type = fields.iter().find(|(name, _)| name == field);
+                    values.get(field).zip(field_type.map(|(_, typ)| typ))
+                }
+                (
+                    Accessor::Field(field),
+                    PrintableValue::Vec(values),
+                    PrintableType::Tuple { types },
+                ) => field
+                    .parse()
+                    .ok()
+                    .and_then(|index: usize| values.get(index).zip(types.get(index))),
+                (
+                    Accessor::Index(index),
+                    PrintableValue::Vec(values),
+                    PrintableType::Array { typ, .. },
+                ) => values.get(*index).map(|value| (value, typ.as_ref())),
+                _ => None,
+            };
+            (value, typ) = element.ok_or_else(|| format!("{self} does not exist"))?;
+        }
+        Ok((value, typ))
+    }
+}
+
+impl FromStr for Operand {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        match input {
+            "" => return Err("missing operand".to_string()),
+            "true" => return Ok(Operand::Literal(Scalar::NonNegative(FieldElement::one()))),
+            "false" => return Ok(Operand::Literal(Scalar::NonNegative(FieldElement::zero()))),
+            _ => (),
+        }
+        if let Some(index) = input.strip_prefix('_').and_then(|index| index.parse().ok()) {
+            return Ok(Operand::Witness(index));
+        }
+        if let Some(index) = input.strip_prefix("$r") {
+            return index
+                .parse()
+                .map(Operand::Register)
+                .map_err(|_| format!("invalid register {input}"));
+        }
+        if input.starts_with(|char: char| char.is_ascii_digit() || char == '-') {
+            return input.parse().map(Operand::Literal);
+        }
+
+        let is_accessor_start = |char: char| char == '.' || char == '[';
+        let is_identifier = |name: &str| {
+            !name.is_empty() && name.chars().all(|char| char.is_alphanumeric() || char == '_')
+        };
+        let (name, mut rest) = input.split_at(input.find(is_accessor_start).unwrap_or(input.len()));
+        if !is_identifier(name) {
+            return Err(format!("invalid operand {input}"));
+        }
+        let mut accessors = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after_dot) = rest.strip_prefix('.') {
+                let (field, after_field) = after_dot
+                    .split_at(after_dot.find(is_accessor_start).unwrap_or(after_dot.len()));
+                if !is_identifier(field) {
+                    return Err(format!("invalid field access in {input}"));
+                }
+                accessors.push(Accessor::Field(field.to_string()));
+                rest = after_field;
+            } else if let Some(after_bracket) = rest.strip_prefix('[') {
+                let (index, after_index) = after_bracket
+                    .split_once(']')
+                    .ok_or_else(|| format!("missing closing bracket in {input}"))?;
+                let index = index
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid index {index} in {input}"))?;
+                accessors.push(Accessor::Index(index));
+                rest = after_index;
+            } else {
+                return Err(format!("invalid operand {input}"));
+            }
+        }
+        Ok(Operand::Variable { name: name.to_string(), accessors })
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Literal(scalar) => write!(f, "{scalar}"),
+            Operand::Witness(index) => write!(f, "_{index}"),
+            Operand::Register(index) => write!(f, "$r{index}"),
+            Operand::Variable { name, accessors } => {
+                write!(f, "{name}")?;
+                accessors.iter().try_for_each(|accessor| write!(f, "{accessor}"))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Comparator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparator {
+    /// The symbols of the comparators, with those which are a prefix of another one last
+    const SYMBOLS: [(&'static str, Comparator); 6] = [
+        ("==", Comparator::Equal),
+        ("!=", Comparator::NotEqual),
+        ("<=", Comparator::LessOrEqual),
+        (">=", Comparator::GreaterOrEqual),
+        ("<", Comparator::Less),
+        (">", Comparator::Greater),
+    ];
+
+    fn holds(self, lhs: Scalar, rhs: Scalar) -> bool {
+        match self {
+            Comparator::Equal => lhs == rhs,
+            Comparator::NotEqual => lhs != rhs,
+            Comparator::Less => lhs < rhs,
+            Comparator::LessOrEqual => lhs <= rhs,
+            Comparator::Greater => lhs > rhs,
+            Comparator::GreaterOrEqual => lhs >= rhs,
+        }
+    }
+}
+
+/// A comparison between two operands, or a single operand which holds if it is non-zero.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Predicate {
+    lhs: Operand,
+    comparison: Option<(Comparator, Operand)>,
+}
+
+impl Predicate {
+    fn holds(&self, state: &dyn DebugState) -> Result<bool, String> {
+        let lhs = self.lhs.scalar(state)?;
+        match &self.comparison {
+            Some((comparator, rhs)) => Ok(comparator.holds(lhs, rhs.scalar(state)?)),
+            None => Ok(!lhs.is_zero()),
+        }
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, String> {
+        for (index, _) in input.char_indices() {
+            let rest = &input[index..];
+            let comparator =
+                Comparator::SYMBOLS.iter().find(|(symbol, _)| rest.starts_with(symbol));
+            if let Some((symbol, comparator)) = comparator {
+                let lhs = input[..index].parse()?;
+                let rhs = rest[symbol.len()..].parse()?;
+                return Ok(Predicate { lhs, comparison: Some((*comparator, rhs)) });
+            }
+        }
+        Ok(Predicate { lhs: input.parse()?, comparison: None })
+    }
+}
+
+/// A breakpoint condition such as `_3 == 5 && $r1 != 0 || point.x > -2`. `&&` binds tighter
+/// than `||`; there are no parentheses.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Condition {
+    /// The condition holds if all the predicates of any of the alternatives hold
+    alternatives: Vec<Vec<Predicate>>,
+}
+
+impl Condition {
+    /// Evaluates the condition, only looking up the operands needed to decide it.
+    pub(crate) fn evaluate(&self, state: &dyn DebugState) -> Result<bool, String> {
+        'alternatives: for predicates in &self.alternatives {
+            for predicate in predicates {
+                if !predicate.holds(state)? {
+                    continue 'alternatives;
+                }
+            }
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, String> {
+        let alternatives = input
+            .split("||")
+            .map(|alternative| alternative.split("&&").map(str::parse).collect())
+            .collect::<Result<_, _>>()?;
+        Ok(Condition { alternatives })
+    }
+}
+
+/// When to stop, given how many times a breakpoint has been hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HitCondition {
+    /// `N` or `== N`: stop on the Nth hit only
+    Equal(u64),
+    /// `>= N`
+    AtLeast(u64),
+    /// `> N`
+    Greater(u64),
+    /// `% N`: stop on every Nth hit
+    Multiple(u64),
+}
+
+impl HitCondition {
+    pub(crate) fn is_met(&self, hits: u64) -> bool {
+        match *self {
+            HitCondition::Equal(count) => hits == count,
+            HitCondition::AtLeast(count) => hits >= count,
+            HitCondition::Greater(count) => hits > count,
+            HitCondition::Multiple(count) => hits % count == 0,
+        }
+    }
+}
+
+impl FromStr for HitCondition {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        let parse_count = |count: &str| {
+            count.trim().parse::<u64>().map_err(|_| format!("invalid hit condition {input}"))
+        };
+        if let Some(count) = input.strip_prefix(">=") {
+            Ok(HitCondition::AtLeast(parse_count(count)?))
+        } else if let Some(count) = input.strip_prefix('>') {
+            Ok(HitCondition::Greater(parse_count(count)?))
+        } else if let Some(count) = input.strip_prefix("==") {
+            Ok(HitCondition::Equal(parse_count(count)?))
+        } else if let Some(count) = input.strip_prefix('%') {
+            match parse_count(count)? {
+                0 => Err(format!("invalid hit condition {input}")),
+                count => Ok(HitCondition::Multiple(count)),
+            }
+        } else {
+            Ok(HitCondition::Equal(parse_count(input)?))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum LogMessagePart {
+    Text(String),
+    Operand(Operand),
+}
+
+/// The message of a logpoint, in which operands within braces are replaced by their values, as
+/// in `x is {x}`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LogMessage {
+    parts: Vec<LogMessagePart>,
+}
+
+impl LogMessage {
+    pub(crate) fn render(&self, state: &dyn DebugState) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                LogMessagePart::Text(text) => text.clone(),
+                LogMessagePart::Operand(operand) => {
+                    operand.display(state).unwrap_or_else(|error| format!("<{error}>"))
+                }
+            })
+            .collect()
+    }
+}
+
+impl FromStr for LogMessage {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut rest = input;
+        while let Some(open) = rest.find('{') {
+            let (operand, after_operand) = rest[open + 1..]
+                .split_once('}')
+                .ok_or_else(|| format!("missing closing brace in log message {input}"))?;
+            if open > 0 {
+                parts.push(LogMessagePart::Text(rest[..open].to_string()));
+            }
+            parts.push(LogMessagePart::Operand(operand.parse()?));
+            rest = after_operand;
+        }
+        if !rest.is_empty() {
+            parts.push(LogMessagePart::Text(rest.to_string()));
+        }
+        Ok(LogMessage { parts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    struct StubbedState {
+        witnesses: BTreeMap<u32, FieldElement>,
+        variables: Vec<(String, PrintableValue, PrintableType)>,
+    }
+
+    impl DebugState for StubbedState {
+        fn witness_value(&self, index: u32) -> Option<FieldElement> {
+            self.witnesses.get(&index).copied()
+        }
+
+        fn register_value(&self, _index: usize) -> Option<FieldElement> {
+            None
+        }
+
+        fn variable(&self, name: &str) -> Option<(&PrintableValue, &PrintableType)> {
+            let mut variables = self.variables.iter().rev();
+            variables.find(|(var_name, ..)| var_name == name).map(|(_, value, typ)| (value, typ))
+        }
+    }
+
+    fn stubbed_state() -> StubbedState {
+        let point_type = PrintableType::Struct {
+            name: "Point".to_string(),
+            fields: vec![
+                ("x".to_string(), PrintableType::SignedInteger { width: 8 }),
+                ("y".to_string(), PrintableType::Field),
+            ],
+        };
+        let point = PrintableValue::Struct(BTreeMap::from([
+            ("x".to_string(), PrintableValue::Field(FieldElement::from(254_u128))),
+            ("y".to_string(), PrintableValue::Field(FieldElement::from(7_u128))),
+        ]));
+        let values_type = PrintableType::Array {
+            length: 2,
+            typ: Box::new(PrintableType::UnsignedInteger { width: 32 }),
+        };
+        let values = PrintableValue::Vec(vec![
+            PrintableValue::Field(FieldElement::from(3_u128)),
+            PrintableValue::Field(FieldElement::from(4_u128)),
+        ]);
+        StubbedState {
+            witnesses: BTreeMap::from([(1, FieldElement::from(5_u128))]),
+            variables: vec![
+                ("point".to_string(), point, point_type),
+                ("values".to_string(), values, values_type),
+            ],
+        }
+    }
+
+    fn evaluate(condition: &str) -> Result<bool, String> {
+        condition.parse::<Condition>()?.evaluate(&stubbed_state())
+    }
+
+    #[test]
+    fn test_parse_operands() {
+        assert_eq!("_3".parse(), Ok(Operand::Witness(3)));
+        assert_eq!("$r12".parse(), Ok(Operand::Register(12)));
+        assert_eq!("-2".parse(), Ok(Operand::Literal(Scalar::Negative(-2))));
+        assert_eq!(
+            "0x10".parse(),
+            Ok(Operand::Literal(Scalar::NonNegative(FieldElement::from(16_u128))))
+        );
+        assert_eq!(
+            "_foo.bar[1].0".parse(),
+            Ok(Operand::Variable {
+                name: "_foo".to_string(),
+                accessors: vec![
+                    Accessor::Field("bar".to_string()),
+                    Accessor::Index(1),
+                    Accessor::Field("0".to_string()),
+                ],
+            })
+        );
+        assert!("foo[".parse::<Operand>().is_err());
+        assert!("foo bar".parse::<Operand>().is_err());
+        assert!("$rx".parse::<Operand>().is_err());
+    }
+
+    #[test]
+    fn test_evaluate_conditions() {
+        assert_eq!(evaluate("_1 == 5"), Ok(true));
+        assert_eq!(evaluate("_1 != 5 || values[1] >= 4"), Ok(true));
+        assert_eq!(evaluate("_1 > 2 && values[0] < 3"), Ok(false));
+        assert_eq!(evaluate("point.y"), Ok(true));
+        // point.x is an i8 holding -2
+        assert_eq!(evaluate("point.x == -2 && point.x < 0"), Ok(true));
+        assert_eq!(evaluate("true || _2 == 1"), Ok(true));
+        assert!(evaluate("_2 == 1").is_err());
+        assert!(evaluate("point.z == 1").is_err());
+        assert!(evaluate("point == 1").is_err());
+        assert!(evaluate("_1 = 5").is_err());
+    }
+
+    #[test]
+    fn test_hit_conditions() {
+        assert_eq!("3".parse(), Ok(HitCondition::Equal(3)));
+        assert_eq!("== 3".parse(), Ok(HitCondition::Equal(3)));
+        assert_eq!(">= 3".parse(), Ok(HitCondition::AtLeast(3)));
+        assert_eq!("> 3".parse(), Ok(HitCondition::Greater(3)));
+        assert_eq!("%3".parse(), Ok(HitCondition::Multiple(3)));
+        assert!("% 0".parse::<HitCondition>().is_err());
+        assert!("< 3".parse::<HitCondition>().is_err());
+
+        let state = stubbed_state();
+        let options = BreakpointOptions::parse(Some("_1 == 5"), Some("% 2"), None).unwrap();
+        let mut breakpoint = Breakpoint::new(options);
+        let hits: Vec<_> = (0..4).map(|_| breakpoint.hit(&state)).collect();
+        assert_eq!(
+            hits,
+            vec![
+                BreakpointHit::Skip,
+                BreakpointHit::Stop,
+                BreakpointHit::Skip,
+                BreakpointHit::Stop
+            ]
+        );
+    }
+
+    #[test]
+    fn test_log_messages() {
+        let state = stubbed_state();
+        let options =
+            BreakpointOptions::parse(Some(""), None, Some("w={_1} p={point} v={values[2]}"))
+                .unwrap();
+        assert_eq!(options.condition, None);
+        let mut breakpoint = Breakpoint::new(options);
+        assert_eq!(
+            breakpoint.hit(&state),
+            BreakpointHit::Log(
+                "w=5 p=Point { x: -2, y: 0x07 } v=<values[2] does not exist>".to_string()
+            )
+        );
+        assert!("x is {x".parse::<LogMessage>().is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use acvm::acir::brillig::ForeignCallResult;
 use acvm::acir::circuit::{Circuit, Opcode, OpcodeLocation};
 use acvm::acir::native_types::{Witness, WitnessMap};
 use acvm::brillig_vm::{brillig::Value, Registers};
@@ -6,12 +7,30 @@ use acvm::pwg::{
 };
 use acvm::{BlackBoxFunctionSolver, FieldElement};
 
+use fm::FileId;
 use nargo::artifacts::debug::DebugArtifact;
 use nargo::errors::{ExecutionError, Location};
-use nargo::ops::ForeignCallExecutor;
 use nargo::NargoError;
+use noirc_printable_type::{ForeignCallError, PrintableType, PrintableValue};
 
-use std::collections::{hash_set::Iter, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use crate::breakpoints::{Breakpoint, BreakpointHit, BreakpointOptions, DebugState};
+use crate::debug_vars::DebugVarValues;
+use crate::foreign_calls::{DebugForeignCall, DebugForeignCallExecutor};
+
+/// The root directory of the files of the crate called by the debug instrumentation
+const DEBUG_CRATE_DIR: &str = "__debug";
+
+/// The number of steps between snapshots of the execution state. Going back to an earlier step
+/// replays at most this many steps from the last snapshot before it.
+const CHECKPOINT_INTERVAL: usize = 1000;
+
+/// The most snapshots kept of the execution state, as each holds a copy of it. Beyond this, every
+/// other one of the older half of the snapshots is dropped, so that they are spaced further apart
+/// the further back they go and going back there replays more steps.
+const MAX_CHECKPOINTS: usize = 64;
 
 #[derive(Debug)]
 pub(super) enum DebugCommandResult {
@@ -21,12 +40,40 @@ pub(super) enum DebugCommandResult {
     Error(NargoError),
 }
 
+/// A snapshot of the execution state before a given step, from which later steps are replayed.
+struct Checkpoint<'a, B: BlackBoxFunctionSolver> {
+    step: usize,
+    /// Whether the snapshot holds edits made to the execution state, which are lost if it's dropped
+    edited: bool,
+    acvm: ACVM<'a, B>,
+    brillig_solver: Option<BrilligSolver<'a, B>>,
+    variables: DebugVarValues,
+}
+
 pub(super) struct DebugContext<'a, B: BlackBoxFunctionSolver> {
     acvm: ACVM<'a, B>,
     brillig_solver: Option<BrilligSolver<'a, B>>,
-    foreign_call_executor: Box<dyn ForeignCallExecutor + 'a>,
+    foreign_call_executor: Box<dyn DebugForeignCallExecutor + 'a>,
     debug_artifact: &'a DebugArtifact,
-    breakpoints: HashSet<OpcodeLocation>,
+    breakpoints: HashMap<OpcodeLocation, Breakpoint>,
+    /// The messages printed by logpoints since they were last taken
+    log_messages: Vec<String>,
+    /// The files of the crate called by the debug instrumentation, which are hidden from the user
+    instrumentation_files: HashSet<FileId>,
+    /// The number of steps taken since the start, where a step executes an ACIR or Brillig
+    /// opcode, or resolves a foreign call
+    step_count: usize,
+    /// Snapshots of the execution state, ordered by step, of which at most [MAX_CHECKPOINTS] are
+    /// kept besides those holding edits
+    checkpoints: Vec<Checkpoint<'a, B>>,
+    /// The number of steps executed at least once. Steps up to this one which are taken again after
+    /// going back don't count breakpoint hits or print logpoints again.
+    executed_steps: usize,
+    /// The results of the foreign calls made so far, by step. They are reused when stepping
+    /// forward again after going back, so that side effects such as printing only happen once.
+    foreign_call_results: BTreeMap<usize, ForeignCallResult>,
+    /// Whether earlier steps are being replayed, in which case breakpoints are ignored
+    replaying: bool,
 }
 
 impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
@@ -35,14 +82,27 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         circuit: &'a Circuit,
         debug_artifact: &'a DebugArtifact,
         initial_witness: WitnessMap,
-        foreign_call_executor: Box<dyn ForeignCallExecutor + 'a>,
+        foreign_call_executor: Box<dyn DebugForeignCallExecutor + 'a>,
     ) -> Self {
+        let instrumentation_files = debug_artifact
+            .file_map
+            .iter()
+            .filter(|(_, file)| file.path.starts_with(Path::new(DEBUG_CRATE_DIR)))
+            .map(|(file_id, _)| *file_id)
+            .collect();
         Self {
             acvm: ACVM::new(blackbox_solver, &circuit.opcodes, initial_witness),
             brillig_solver: None,
             foreign_call_executor,
             debug_artifact,
-            breakpoints: HashSet::new(),
+            breakpoints: HashMap::new(),
+            log_messages: Vec::new(),
+            instrumentation_files,
+            step_count: 0,
+            checkpoints: Vec::new(),
+            executed_steps: 0,
+            foreign_call_results: BTreeMap::new(),
+            replaying: false,
         }
     }
 
@@ -59,7 +119,9 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         witness: Witness,
         value: FieldElement,
     ) -> Option<FieldElement> {
-        self.acvm.overwrite_witness(witness, value)
+        let previous_value = self.acvm.overwrite_witness(witness, value);
+        self.rewrite_history();
+        previous_value
     }
 
     pub(super) fn get_current_opcode_location(&self) -> Option<OpcodeLocation> {
@@ -82,9 +144,81 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
     /// mapped to a specific source location in the debug artifact (which can
     /// happen for certain opcodes inserted synthetically by the compiler)
     pub(super) fn get_current_source_location(&self) -> Option<Vec<Location>> {
-        self.get_current_opcode_location()
-            .as_ref()
-            .and_then(|location| self.debug_artifact.debug_symbols[0].opcode_location(location))
+        let frames = self.get_current_stack_frames()?;
+        Some(frames.into_iter().map(|(location, _)| location).collect())
+    }
+
+    /// Returns the frames of the source callstack of the currently executing opcode, outermost
+    /// first, each with the name of the function it's in. Every location but the innermost one
+    /// is a call site, and the function of the next frame is the one called there.
+    pub(super) fn get_current_stack_frames(&self) -> Option<Vec<(Location, &str)>> {
+        let debug_symbols = &self.debug_artifact.debug_symbols[0];
+        let mut function = debug_symbols.functions.first();
+        let mut frames = Vec::new();
+        for location in self.get_current_call_stack()? {
+            // The code reporting variables to the debugger isn't part of the program being debugged
+            if !self.instrumentation_files.contains(&location.file) {
+                let name = function.map_or("unknown", |function| function.name.as_str());
+                frames.push((location, name));
+            }
+            function = debug_symbols.called_function(&location);
+        }
+        (!frames.is_empty()).then_some(frames)
+    }
+
+    /// Returns every location of the source callstack of the currently executing opcode,
+    /// including those of the debug instrumentation.
+    fn get_current_call_stack(&self) -> Option<Vec<Location>> {
+        let opcode_location = self.get_current_opcode_location()?;
+        let debug_symbols = &self.debug_artifact.debug_symbols[0];
+        let current_locations = debug_symbols.opcode_location(&opcode_location)?;
+
+        // The locations of a Brillig opcode only cover the Brillig function it belongs to, so
+        // the stack is completed with the ACIR opcode which started the Brillig execution and
+        // the Brillig calls which haven't returned yet
+        let mut caller_opcode_locations = vec![];
+        if let OpcodeLocation::Brillig { acir_index, .. } = opcode_location {
+            caller_opcode_locations.push(OpcodeLocation::Acir(acir_index));
+            if let Some(solver) = &self.brillig_solver {
+                caller_opcode_locations.extend(
+                    solver
+                        .get_call_stack()
+                        .into_iter()
+                        .map(|brillig_index| OpcodeLocation::Brillig { acir_index, brillig_index }),
+                );
+            }
+        }
+        let caller_locations = caller_opcode_locations
+            .iter()
+            .filter_map(|location| debug_symbols.opcode_location(location))
+            .flatten();
+        Some(caller_locations.chain(current_locations).collect())
+    }
+
+    /// Returns the number of calls which haven't returned yet at the currently executing opcode:
+    /// those inlined into its ACIR opcode and, within unconstrained code, the call into Brillig,
+    /// the Brillig calls on the VM's call stack and the calls inlined into each Brillig function.
+    /// This is `None` if an opcode of the callstack isn't mapped to source locations.
+    fn get_current_call_depth(&self) -> Option<usize> {
+        let debug_symbols = &self.debug_artifact.debug_symbols[0];
+        // The locations of an opcode are the sites of the calls inlined into it, followed by its
+        // own location, or by the call it makes if it calls into another function
+        let inlined_calls = |location: &OpcodeLocation| {
+            let locations = debug_symbols.locations.get(location)?;
+            Some(locations.len().saturating_sub(1))
+        };
+
+        let opcode_location = self.get_current_opcode_location()?;
+        let OpcodeLocation::Brillig { acir_index, .. } = opcode_location else {
+            return inlined_calls(&opcode_location);
+        };
+        let mut depth = inlined_calls(&OpcodeLocation::Acir(acir_index))? + 1;
+        if let Some(solver) = &self.brillig_solver {
+            for brillig_index in solver.get_call_stack() {
+                depth += inlined_calls(&OpcodeLocation::Brillig { acir_index, brillig_index })? + 1;
+            }
+        }
+        Some(depth + inlined_calls(&opcode_location)?)
     }
 
     fn get_opcodes_sizes(&self) -> Vec<usize> {
@@ -221,7 +355,7 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
     }
 
     fn handle_foreign_call(&mut self, foreign_call: ForeignCallWaitInfo) -> DebugCommandResult {
-        let foreign_call_result = self.foreign_call_executor.execute(&foreign_call);
+        let foreign_call_result = self.execute_foreign_call(&foreign_call);
         match foreign_call_result {
             Ok(foreign_call_result) => {
                 if let Some(mut solver) = self.brillig_solver.take() {
@@ -237,6 +371,23 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         }
     }
 
+    fn execute_foreign_call(
+        &mut self,
+        foreign_call: &ForeignCallWaitInfo,
+    ) -> Result<ForeignCallResult, ForeignCallError> {
+        // Reporting source variables only affects the variables, which are restored along with
+        // the rest of the execution state, so these calls are always executed
+        if DebugForeignCall::lookup(&foreign_call.function).is_some() {
+            return self.foreign_call_executor.execute(foreign_call);
+        }
+        if let Some(result) = self.foreign_call_results.get(&self.step_count) {
+            return Ok(result.clone());
+        }
+        let result = self.foreign_call_executor.execute(foreign_call)?;
+        self.foreign_call_results.insert(self.step_count, result.clone());
+        Ok(result)
+    }
+
     fn handle_acvm_status(&mut self, status: ACVMStatus) -> DebugCommandResult {
         if let ACVMStatus::RequiresForeignCall(foreign_call) = status {
             return self.handle_foreign_call(foreign_call);
@@ -264,17 +415,19 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
     }
 
     pub(super) fn step_into_opcode(&mut self) -> DebugCommandResult {
-        if self.brillig_solver.is_some() {
-            return self.step_brillig_opcode();
-        }
+        self.record_step(|context| {
+            if context.brillig_solver.is_some() {
+                return context.step_brillig_opcode();
+            }
 
-        match self.acvm.step_into_brillig_opcode() {
-            StepResult::IntoBrillig(solver) => {
-                self.brillig_solver = Some(solver);
-                self.step_brillig_opcode()
+            match context.acvm.step_into_brillig_opcode() {
+                StepResult::IntoBrillig(solver) => {
+                    context.brillig_solver = Some(solver);
+                    context.step_brillig_opcode()
+                }
+                StepResult::Status(status) => context.handle_acvm_status(status),
             }
-            StepResult::Status(status) => self.handle_acvm_status(status),
-        }
+        })
     }
 
     fn currently_executing_brillig(&self) -> bool {
@@ -318,8 +471,10 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         if self.currently_executing_brillig() {
             self.step_out_of_brillig_opcode()
         } else {
-            let status = self.acvm.solve_opcode();
-            self.handle_acvm_status(status)
+            self.record_step(|context| {
+                let status = context.acvm.solve_opcode();
+                context.handle_acvm_status(status)
+            })
         }
     }
 
@@ -337,6 +492,23 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         }
     }
 
+    /// Steps until the current source function returns to its caller.
+    pub(super) fn step_out(&mut self) -> DebugCommandResult {
+        let Some(start_depth) = self.get_current_call_depth() else {
+            return self.next();
+        };
+        loop {
+            let result = self.step_into_opcode();
+            if !matches!(result, DebugCommandResult::Ok) {
+                return result;
+            }
+            let new_depth = self.get_current_call_depth();
+            if new_depth.is_some_and(|depth| depth < start_depth) {
+                return DebugCommandResult::Ok;
+            }
+        }
+    }
+
     pub(super) fn cont(&mut self) -> DebugCommandResult {
         loop {
             let result = self.step_into_opcode();
@@ -346,6 +518,232 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         }
     }
 
+    /// Takes a step, snapshotting the execution state first if a snapshot is due.
+    fn record_step(
+        &mut self,
+        step: impl FnOnce(&mut Self) -> DebugCommandResult,
+    ) -> DebugCommandResult {
+        match self.find_checkpoint(self.step_count) {
+            // The snapshot holds any edits made to the execution state at this step, which are
+            // part of the history from then on
+            Ok(index) => self.restore_checkpoint(index),
+            // Snapshots are only taken past the last one, so that those which were dropped aren't
+            // taken again when replaying
+            Err(index)
+                if index == self.checkpoints.len()
+                    && self.step_count % CHECKPOINT_INTERVAL == 0 =>
+            {
+                let checkpoint = self.checkpoint(false);
+                self.checkpoints.push(checkpoint);
+                self.thin_checkpoints();
+            }
+            Err(_) => (),
+        }
+        let result = step(self);
+        self.step_count += 1;
+        self.executed_steps = self.executed_steps.max(self.step_count);
+        result
+    }
+
+    /// Drops every other one of the older half of the snapshots once there are more than
+    /// [MAX_CHECKPOINTS], keeping the first one, which every replay can start from, and those
+    /// holding edits.
+    fn thin_checkpoints(&mut self) {
+        let unedited = self.checkpoints.iter().filter(|checkpoint| !checkpoint.edited).count();
+        if unedited <= MAX_CHECKPOINTS {
+            return;
+        }
+        let older_half = self.checkpoints.len() / 2;
+        let mut index = 0;
+        self.checkpoints.retain(|checkpoint| {
+            let keep = index == 0 || index >= older_half || index % 2 == 0 || checkpoint.edited;
+            index += 1;
+            keep
+        });
+    }
+
+    fn checkpoint(&self, edited: bool) -> Checkpoint<'a, B> {
+        Checkpoint {
+            step: self.step_count,
+            edited,
+            acvm: self.acvm.clone(),
+            brillig_solver: self.brillig_solver.clone(),
+            variables: self.foreign_call_executor.save_variables(),
+        }
+    }
+
+    fn find_checkpoint(&self, step: usize) -> Result<usize, usize> {
+        self.checkpoints.binary_search_by_key(&step, |checkpoint| checkpoint.step)
+    }
+
+    fn restore_checkpoint(&mut self, index: usize) {
+        let checkpoint = &self.checkpoints[index];
+        self.acvm = checkpoint.acvm.clone();
+        self.brillig_solver = checkpoint.brillig_solver.clone();
+        self.foreign_call_executor.restore_variables(checkpoint.variables.clone());
+        self.step_count = checkpoint.step;
+    }
+
+    /// Forgets about the steps after the current one, which may not happen anymore once the
+    /// execution state has been edited, and snapshots the edited state.
+    fn rewrite_history(&mut self) {
+        let step = self.step_count;
+        self.checkpoints.retain(|checkpoint| checkpoint.step < step);
+        self.foreign_call_results.retain(|result_step, _| *result_step < step);
+        self.executed_steps = step;
+        let checkpoint = self.checkpoint(true);
+        self.checkpoints.push(checkpoint);
+    }
+
+    /// Replays execution up to `to_step`, from the last snapshot before `from_step`, ignoring
+    /// breakpoints. `inspect` is called with the state before each step from `from_step` on.
+    fn replay(&mut self, from_step: usize, to_step: usize, mut inspect: impl FnMut(&Self)) {
+        let index = self.find_checkpoint(from_step).unwrap_or_else(|index| index - 1);
+        self.restore_checkpoint(index);
+        let start_step = self.step_count;
+        self.replaying = true;
+        loop {
+            // Later snapshots may hold edits made to the execution state, which have to be
+            // restored before inspecting it
+            if self.step_count > start_step {
+                if let Ok(index) = self.find_checkpoint(self.step_count) {
+                    self.restore_checkpoint(index);
+                }
+            }
+            if self.step_count >= to_step {
+                break;
+            }
+            if self.step_count >= from_step {
+                inspect(self);
+            }
+            self.step_into_opcode();
+        }
+        self.replaying = false;
+    }
+
+    /// Replays the steps from the last snapshot before `end_step` up to `end_step`. Returns the
+    /// step the snapshot was taken at, and what `inspect` returned before each step from then on.
+    fn replay_segment_before<T>(
+        &mut self,
+        end_step: usize,
+        mut inspect: impl FnMut(&Self) -> T,
+    ) -> (usize, Vec<T>) {
+        let index = self.find_checkpoint(end_step - 1).unwrap_or_else(|index| index - 1);
+        let start_step = self.checkpoints[index].step;
+        let mut inspected = Vec::new();
+        self.replay(start_step, end_step, |context| inspected.push(inspect(context)));
+        (start_step, inspected)
+    }
+
+    /// Goes back to the last earlier step at which `stop` holds, or to the start. Returns
+    /// whether such a step was found.
+    ///
+    /// The steps are replayed a segment between snapshots at a time, latest first, so that only
+    /// the segments after the step found are replayed.
+    fn reverse_until(&mut self, mut stop: impl FnMut(&Self) -> bool) -> bool {
+        let mut segment_end = self.step_count;
+        while segment_end > 0 {
+            let (segment_start, stops) = self.replay_segment_before(segment_end, &mut stop);
+            if let Some(offset) = stops.iter().rposition(|holds| *holds) {
+                let step = segment_start + offset;
+                self.replay(step, step, |_| ());
+                return true;
+            }
+            segment_end = segment_start;
+        }
+        self.replay(0, 0, |_| ());
+        false
+    }
+
+    pub(super) fn can_step_back(&self) -> bool {
+        self.step_count > 0
+    }
+
+    /// Undoes the last step into an opcode.
+    pub(super) fn reverse_step_into_opcode(&mut self) -> DebugCommandResult {
+        if self.can_step_back() {
+            let step = self.step_count - 1;
+            self.replay(step, step, |_| ());
+        }
+        DebugCommandResult::Ok
+    }
+
+    /// Goes back to the previous ACIR opcode, skipping over the Brillig opcodes executed since.
+    pub(super) fn reverse_step_acir_opcode(&mut self) -> DebugCommandResult {
+        if self.can_step_back() {
+            self.reverse_until(|context| context.brillig_solver.is_none());
+        }
+        DebugCommandResult::Ok
+    }
+
+    /// Goes back to where execution first reached the previous source location.
+    pub(super) fn reverse_next(&mut self) -> DebugCommandResult {
+        if !self.can_step_back() {
+            return DebugCommandResult::Ok;
+        }
+        let current_location = self.get_current_source_location();
+        let is_other_than = |location: &Option<Vec<Location>>, other: &Option<Vec<Location>>| {
+            location.is_some() && location != other
+        };
+
+        // The source locations before each step from `first_step` up to the current one, which
+        // are replayed a segment between snapshots at a time until the steps spent at the
+        // previous location have all been found
+        let mut locations = Vec::new();
+        let mut first_step = self.step_count;
+        // The last step at the previous location, and the first step after the one before it
+        let mut end = None;
+        let mut start = None;
+        while start.is_none() && first_step > 0 {
+            let (segment_start, mut segment) = self
+                .replay_segment_before(first_step, |context| context.get_current_source_location());
+            let scanned = segment.len();
+            segment.append(&mut locations);
+            locations = segment;
+            first_step = segment_start;
+
+            if end.is_none() {
+                end = locations[..scanned]
+                    .iter()
+                    .rposition(|location| is_other_than(location, &current_location))
+                    .map(|index| first_step + index);
+            }
+            if let Some(end) = end {
+                let previous_location = &locations[end - first_step];
+                // Steps scanned in earlier segments are known not to be at another location
+                let unsearched = scanned.min(end - first_step);
+                start = locations[..unsearched]
+                    .iter()
+                    .rposition(|location| is_other_than(location, previous_location))
+                    .map(|index| first_step + index + 1);
+            }
+        }
+
+        let step = match end {
+            Some(end) => {
+                let previous_location = &locations[end - first_step];
+                (start.unwrap_or(0)..end)
+                    .find(|step| locations[step - first_step] == *previous_location)
+                    .unwrap_or(end)
+            }
+            None => 0,
+        };
+        self.replay(step, step, |_| ());
+        DebugCommandResult::Ok
+    }
+
+    /// Runs backwards until a breakpoint whose condition holds, or the start, is reached.
+    pub(super) fn reverse_continue(&mut self) -> DebugCommandResult {
+        if self.can_step_back() && self.reverse_until(|context| context.breakpoint_holds()) {
+            DebugCommandResult::BreakpointReached(
+                self.get_current_opcode_location()
+                    .expect("Breakpoint reached but we have no location"),
+            )
+        } else {
+            DebugCommandResult::Ok
+        }
+    }
+
     pub(super) fn is_executing_brillig(&self) -> bool {
         let opcodes = self.get_opcodes();
         let acir_index = self.acvm.instruction_pointer();
@@ -359,6 +757,7 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
     pub(super) fn set_brillig_register(&mut self, register_index: usize, value: FieldElement) {
         if let Some(solver) = self.brillig_solver.as_mut() {
             solver.set_register(register_index, value.into());
+            self.rewrite_history();
         }
     }
 
@@ -369,15 +768,64 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
     pub(super) fn write_brillig_memory(&mut self, ptr: usize, value: FieldElement) {
         if let Some(solver) = self.brillig_solver.as_mut() {
             solver.write_memory_at(ptr, value.into());
+            self.rewrite_history();
         }
     }
 
-    fn breakpoint_reached(&self) -> bool {
-        if let Some(location) = self.get_current_opcode_location() {
-            self.breakpoints.contains(&location)
-        } else {
-            false
+    /// Returns the name, value and type of each source variable in scope.
+    pub(super) fn get_variables(&self) -> Vec<(&str, &PrintableValue, &PrintableType)> {
+        self.foreign_call_executor.get_variables()
+    }
+
+    /// Whether execution should stop at the current opcode. Breakpoints whose condition doesn't
+    /// hold are passed over, and logpoints record their message instead of stopping.
+    fn breakpoint_reached(&mut self) -> bool {
+        if self.replaying {
+            return false;
+        }
+        // Steps taken again after going back stop like steps backwards, without counting hits or
+        // printing logpoints a second time
+        if self.step_count < self.executed_steps {
+            return self.breakpoint_holds();
         }
+        let Some(location) = self.get_current_opcode_location() else {
+            return false;
+        };
+        // The breakpoint is taken out while it's hit, as its condition is evaluated over `self`
+        let Some(mut breakpoint) = self.breakpoints.remove(&location) else {
+            return false;
+        };
+        let hit = breakpoint.hit(&*self);
+        self.breakpoints.insert(location, breakpoint);
+        match hit {
+            BreakpointHit::Stop => true,
+            BreakpointHit::Skip => false,
+            BreakpointHit::Log(message) => {
+                self.log_messages.push(message);
+                false
+            }
+            BreakpointHit::Error(error) => {
+                self.log_messages.push(format!(
+                    "Could not evaluate condition of breakpoint at {location}: {error}"
+                ));
+                true
+            }
+        }
+    }
+
+    /// Whether execution going backwards should stop at the current opcode.
+    fn breakpoint_holds(&self) -> bool {
+        let Some(location) = self.get_current_opcode_location() else {
+            return false;
+        };
+        self.breakpoints
+            .get(&location)
+            .map_or(false, |breakpoint| breakpoint.stops_in_reverse(self))
+    }
+
+    /// Returns the messages printed by logpoints since the last call.
+    pub(super) fn take_log_messages(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.log_messages)
     }
 
     pub(super) fn is_valid_opcode_location(&self, location: &OpcodeLocation) -> bool {
@@ -399,23 +847,31 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
     }
 
     pub(super) fn is_breakpoint_set(&self, location: &OpcodeLocation) -> bool {
-        self.breakpoints.contains(location)
+        self.breakpoints.contains_key(location)
     }
 
     pub(super) fn add_breakpoint(&mut self, location: OpcodeLocation) -> bool {
-        self.breakpoints.insert(location)
+        self.add_breakpoint_with_options(location, BreakpointOptions::default())
     }
 
-    pub(super) fn delete_breakpoint(&mut self, location: &OpcodeLocation) -> bool {
-        self.breakpoints.remove(location)
+    /// Sets a breakpoint with a condition, hit condition or log message. Returns false if a
+    /// breakpoint was already set at the location, in which case its options are replaced.
+    pub(super) fn add_breakpoint_with_options(
+        &mut self,
+        location: OpcodeLocation,
+        options: BreakpointOptions,
+    ) -> bool {
+        self.breakpoints.insert(location, Breakpoint::new(options)).is_none()
     }
 
-    pub(super) fn iterate_breakpoints(&self) -> Iter<'_, OpcodeLocation> {
-        self.breakpoints.iter()
+    pub(super) fn delete_breakpoint(&mut self, location: &OpcodeLocation) -> bool {
+        self.breakpoints.remove(location).is_some()
     }
 
-    pub(super) fn clear_breakpoints(&mut self) {
-        self.breakpoints.clear();
+    pub(super) fn iterate_breakpoints(
+        &self,
+    ) -> impl Iterator<Item = (&OpcodeLocation, &BreakpointOptions)> {
+        self.breakpoints.iter().map(|(location, breakpoint)| (location, &breakpoint.options))
     }
 
     pub(super) fn is_solved(&self) -> bool {
@@ -427,11 +883,28 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
     }
 }
 
+impl<'a, B: BlackBoxFunctionSolver> DebugState for DebugContext<'a, B> {
+    fn witness_value(&self, index: u32) -> Option<FieldElement> {
+        self.get_witness_map().get(&Witness(index)).copied()
+    }
+
+    fn register_value(&self, index: usize) -> Option<FieldElement> {
+        let registers = self.get_brillig_registers()?;
+        registers.inner.get(index).map(|value| value.to_field())
+    }
+
+    fn variable(&self, name: &str) -> Option<(&PrintableValue, &PrintableType)> {
+        let variables = self.get_variables().into_iter().rev();
+        variables.find(|(var_name, ..)| *var_name == name).map(|(_, value, typ)| (value, typ))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::context::{DebugCommandResult, DebugContext};
 
+    use crate::foreign_calls::DefaultDebugForeignCallExecutor;
     use acvm::{
         acir::{
             circuit::{
@@ -444,7 +917,11 @@ mod tests {
             BinaryFieldOp, Opcode as BrilligOpcode, RegisterIndex, RegisterOrMemory,
         },
     };
-    use nargo::{artifacts::debug::DebugArtifact, ops::DefaultForeignCallExecutor};
+    use nargo::artifacts::debug::DebugArtifact;
+    use noirc_errors::{
+        debug_info::{DebugFunction, DebugInfo},
+        Span,
+    };
     use std::collections::BTreeMap;
 
     struct StubbedSolver;
@@ -529,7 +1006,7 @@ mod tests {
             circuit,
             debug_artifact,
             initial_witness,
-            Box::new(DefaultForeignCallExecutor::new(true, None)),
+            Box::new(DefaultDebugForeignCallExecutor::new(true)),
         );
 
         assert_eq!(context.get_current_opcode_location(), Some(OpcodeLocation::Acir(0)));
@@ -623,7 +1100,7 @@ mod tests {
             circuit,
             debug_artifact,
             initial_witness,
-            Box::new(DefaultForeignCallExecutor::new(true, None)),
+            Box::new(DefaultDebugForeignCallExecutor::new(true)),
         );
 
         // set breakpoint
@@ -646,6 +1123,260 @@ mod tests {
         assert_eq!(context.get_current_opcode_location(), None);
     }
 
+    #[test]
+    fn test_conditional_breakpoints_and_logpoints() {
+        let fe_1 = FieldElement::one();
+        let w_x = Witness(1);
+        let w_y = Witness(2);
+        let w_z = Witness(3);
+
+        let blackbox_solver = &StubbedSolver;
+
+        // This Brillig block is equivalent to: z = x + y
+        let brillig_opcodes = Brillig {
+            inputs: vec![
+                BrilligInputs::Single(Expression {
+                    linear_combinations: vec![(fe_1, w_x)],
+                    ..Expression::default()
+                }),
+                BrilligInputs::Single(Expression {
+                    linear_combinations: vec![(fe_1, w_y)],
+                    ..Expression::default()
+                }),
+            ],
+            outputs: vec![BrilligOutputs::Simple(w_z)],
+            bytecode: vec![
+                BrilligOpcode::BinaryFieldOp {
+                    destination: RegisterIndex::from(0),
+                    op: BinaryFieldOp::Add,
+                    lhs: RegisterIndex::from(0),
+                    rhs: RegisterIndex::from(1),
+                },
+                BrilligOpcode::Stop,
+            ],
+            predicate: None,
+        };
+        let opcodes =
+            vec![Opcode::Brillig(brillig_opcodes), Opcode::AssertZero(Expression::default())];
+        let current_witness_index = 3;
+        let circuit = &Circuit { current_witness_index, opcodes, ..Circuit::default() };
+
+        let debug_artifact =
+            &DebugArtifact { debug_symbols: vec![], file_map: BTreeMap::new(), warnings: vec![] };
+
+        let initial_witness = BTreeMap::from([(w_x, fe_1), (w_y, fe_1)]).into();
+
+        let mut context = DebugContext::new(
+            blackbox_solver,
+            circuit,
+            debug_artifact,
+            initial_witness,
+            Box::new(DefaultDebugForeignCallExecutor::new(true)),
+        );
+
+        // a logpoint which only logs once the sum is in register 0
+        let logpoint =
+            BreakpointOptions::parse(Some("$r0 == 2"), None, Some("z is {$r0}")).unwrap();
+        let logpoint_location = OpcodeLocation::Brillig { acir_index: 0, brillig_index: 1 };
+        assert!(context.add_breakpoint_with_options(logpoint_location, logpoint));
+
+        // a breakpoint whose condition doesn't hold once z is solved
+        let breakpoint = BreakpointOptions::parse(Some("_3 != 2"), None, None).unwrap();
+        assert!(context.add_breakpoint_with_options(OpcodeLocation::Acir(1), breakpoint));
+
+        // the Brillig block runs through the logpoint
+        let result = context.step_acir_opcode();
+        assert!(matches!(result, DebugCommandResult::Ok));
+        assert_eq!(context.get_current_opcode_location(), Some(OpcodeLocation::Acir(1)));
+        assert_eq!(context.take_log_messages(), vec!["z is 2".to_string()]);
+        assert!(context.take_log_messages().is_empty());
+
+        // running the Brillig block again after going back doesn't print the logpoint again
+        context.reverse_step_acir_opcode();
+        assert_eq!(context.get_current_opcode_location(), Some(OpcodeLocation::Acir(0)));
+        let result = context.step_acir_opcode();
+        assert!(matches!(result, DebugCommandResult::Ok));
+        assert!(context.take_log_messages().is_empty());
+
+        // execution doesn't stop at the conditional breakpoint
+        let result = context.cont();
+        assert!(matches!(result, DebugCommandResult::Done));
+    }
+
+    #[test]
+    fn test_reverse_stepping() {
+        let fe_1 = FieldElement::one();
+        let w_x = Witness(1);
+        let w_y = Witness(2);
+        let w_z = Witness(3);
+
+        let blackbox_solver = &StubbedSolver;
+
+        // This Brillig block is equivalent to: z = x + y
+        let brillig_opcodes = Brillig {
+            inputs: vec![
+                BrilligInputs::Single(Expression {
+                    linear_combinations: vec![(fe_1, w_x)],
+                    ..Expression::default()
+                }),
+                BrilligInputs::Single(Expression {
+                    linear_combinations: vec![(fe_1, w_y)],
+                    ..Expression::default()
+                }),
+            ],
+            outputs: vec![BrilligOutputs::Simple(w_z)],
+            bytecode: vec![
+                BrilligOpcode::BinaryFieldOp {
+                    destination: RegisterIndex::from(0),
+                    op: BinaryFieldOp::Add,
+                    lhs: RegisterIndex::from(0),
+                    rhs: RegisterIndex::from(1),
+                },
+                BrilligOpcode::Stop,
+            ],
+            predicate: None,
+        };
+        let opcodes =
+            vec![Opcode::Brillig(brillig_opcodes), Opcode::AssertZero(Expression::default())];
+        let current_witness_index = 3;
+        let circuit = &Circuit { current_witness_index, opcodes, ..Circuit::default() };
+
+        let debug_artifact =
+            &DebugArtifact { debug_symbols: vec![], file_map: BTreeMap::new(), warnings: vec![] };
+
+        let initial_witness = BTreeMap::from([(w_x, fe_1), (w_y, fe_1)]).into();
+
+        let mut context = DebugContext::new(
+            blackbox_solver,
+            circuit,
+            debug_artifact,
+            initial_witness,
+            Box::new(DefaultDebugForeignCallExecutor::new(true)),
+        );
+        assert!(!context.can_step_back());
+
+        let brillig_location = OpcodeLocation::Brillig { acir_index: 0, brillig_index: 1 };
+        context.step_into_opcode();
+        context.step_into_opcode();
+        assert_eq!(context.get_current_opcode_location(), Some(OpcodeLocation::Acir(1)));
+        assert_eq!(context.get_witness_map().get(&w_z), Some(&FieldElement::from(2_u128)));
+
+        // stepping back undoes the Brillig block writing z
+        let result = context.reverse_step_into_opcode();
+        assert!(matches!(result, DebugCommandResult::Ok));
+        assert_eq!(context.get_current_opcode_location(), Some(brillig_location));
+        assert_eq!(context.get_witness_map().get(&w_z), None);
+        assert_eq!(
+            context.get_brillig_registers().map(|registers| registers.inner[0].to_field()),
+            Some(FieldElement::from(2_u128))
+        );
+
+        // going back over the whole Brillig block reaches the start
+        let result = context.reverse_step_acir_opcode();
+        assert!(matches!(result, DebugCommandResult::Ok));
+        assert_eq!(context.get_current_opcode_location(), Some(OpcodeLocation::Acir(0)));
+        assert!(!context.can_step_back());
+
+        // running backwards stops at the breakpoint within the Brillig block
+        let result = context.step_acir_opcode();
+        assert!(matches!(result, DebugCommandResult::Ok));
+        assert!(context.add_breakpoint(brillig_location));
+        let result = context.reverse_continue();
+        assert!(
+            matches!(result, DebugCommandResult::BreakpointReached(location) if location == brillig_location)
+        );
+
+        let result = context.cont();
+        assert!(matches!(result, DebugCommandResult::Done));
+    }
+
+    #[test]
+    fn test_step_out_of_nested_unconstrained_calls() {
+        let fe_1 = FieldElement::one();
+        let blackbox_solver = &StubbedSolver;
+
+        // main calls unconstrained f, which calls unconstrained g, which calls unconstrained h
+        let brillig_opcodes = Brillig {
+            inputs: vec![],
+            outputs: vec![],
+            bytecode: vec![
+                // f
+                BrilligOpcode::Call { location: 3 },
+                BrilligOpcode::Const {
+                    destination: RegisterIndex::from(0),
+                    value: Value::from(fe_1),
+                },
+                BrilligOpcode::Stop,
+                // g
+                BrilligOpcode::Call { location: 6 },
+                BrilligOpcode::Const {
+                    destination: RegisterIndex::from(1),
+                    value: Value::from(fe_1),
+                },
+                BrilligOpcode::Return,
+                // h
+                BrilligOpcode::Const {
+                    destination: RegisterIndex::from(2),
+                    value: Value::from(fe_1),
+                },
+                BrilligOpcode::Return,
+            ],
+            predicate: None,
+        };
+        let opcodes = vec![Opcode::Brillig(brillig_opcodes)];
+        let circuit = &Circuit { current_witness_index: 1, opcodes, ..Circuit::default() };
+
+        let location = |start| Location::new(Span::single_char(start), FileId::dummy());
+        let brillig = |brillig_index| OpcodeLocation::Brillig { acir_index: 0, brillig_index };
+        let locations = BTreeMap::from([
+            (OpcodeLocation::Acir(0), vec![location(0)]),
+            (brillig(0), vec![location(10)]),
+            (brillig(1), vec![location(11)]),
+            (brillig(2), vec![location(12)]),
+            (brillig(3), vec![location(20)]),
+            (brillig(4), vec![location(21)]),
+            (brillig(5), vec![location(22)]),
+            (brillig(6), vec![location(30)]),
+            (brillig(7), vec![location(31)]),
+        ]);
+        let functions = ["main", "f", "g", "h"]
+            .map(|name| DebugFunction { name: name.to_string(), location: location(0) })
+            .to_vec();
+        let calls = HashMap::from([(location(0), 1), (location(10), 2), (location(20), 3)]);
+        let debug_info =
+            DebugInfo::new(locations, BTreeMap::new(), BTreeMap::new(), functions, calls);
+        let debug_artifact = &DebugArtifact {
+            debug_symbols: vec![debug_info],
+            file_map: BTreeMap::new(),
+            warnings: vec![],
+        };
+
+        let mut context = DebugContext::new(
+            blackbox_solver,
+            circuit,
+            debug_artifact,
+            WitnessMap::new(),
+            Box::new(DefaultDebugForeignCallExecutor::new(true)),
+        );
+
+        // step into f, then g, then h
+        for _ in 0..2 {
+            assert!(matches!(context.step_into_opcode(), DebugCommandResult::Ok));
+        }
+        assert_eq!(context.get_current_opcode_location(), Some(brillig(6)));
+        let names: Vec<_> =
+            context.get_current_stack_frames().unwrap().into_iter().map(|(_, name)| name).collect();
+        assert_eq!(names, vec!["main", "f", "g", "h"]);
+
+        // step out of h, back into g
+        assert!(matches!(context.step_out(), DebugCommandResult::Ok));
+        assert_eq!(context.get_current_opcode_location(), Some(brillig(4)));
+
+        // step out of g, back into f
+        assert!(matches!(context.step_out(), DebugCommandResult::Ok));
+        assert_eq!(context.get_current_opcode_location(), Some(brillig(1)));
+    }
+
     #[test]
     fn test_offset_opcode_location() {
         let blackbox_solver = &StubbedSolver;
@@ -673,7 +1404,7 @@ mod tests {
             &circuit,
             &debug_artifact,
             WitnessMap::new(),
-            Box::new(DefaultForeignCallExecutor::new(true, None)),
+            Box::new(DefaultDebugForeignCallExecutor::new(true)),
         );
 
         assert_eq!(context.offset_opcode_location(&None, 0), (None, 0));
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io::{Read, Write};
 use std::str::FromStr;
 
@@ -7,29 +7,38 @@ use acvm::acir::native_types::WitnessMap;
 use acvm::BlackBoxFunctionSolver;
 use codespan_reporting::files::{Files, SimpleFile};
 
+use crate::breakpoints::BreakpointOptions;
 use crate::context::DebugCommandResult;
 use crate::context::DebugContext;
 
 use dap::errors::ServerError;
-use dap::events::StoppedEventBody;
+use dap::events::{OutputEventBody, StoppedEventBody};
 use dap::prelude::Event;
 use dap::requests::{Command, Request, SetBreakpointsArguments};
 use dap::responses::{
     ContinueResponse, DisassembleResponse, ResponseBody, ScopesResponse, SetBreakpointsResponse,
     SetExceptionBreakpointsResponse, SetInstructionBreakpointsResponse, StackTraceResponse,
-    ThreadsResponse,
+    ThreadsResponse, VariablesResponse,
 };
 use dap::server::Server;
 use dap::types::{
-    Breakpoint, DisassembledInstruction, Source, StackFrame, SteppingGranularity,
-    StoppedEventReason, Thread,
+    Breakpoint, DisassembledInstruction, OutputEventCategory, Scope, Source, StackFrame,
+    SteppingGranularity, StoppedEventReason, Thread, Variable,
 };
 use nargo::artifacts::debug::DebugArtifact;
-use nargo::ops::DefaultForeignCallExecutor;
+use noirc_printable_type::PrintableValueDisplay;
+
+use crate::debug_vars::format_type;
+use crate::foreign_calls::DefaultDebugForeignCallExecutor;
 
 use fm::FileId;
 use noirc_driver::CompiledProgram;
 
+/// The `variablesReference` of the scope holding the source variables in scope
+const LOCALS_REFERENCE: i64 = 1;
+/// The `variablesReference` of the scope holding the solved witnesses
+const WITNESS_MAP_REFERENCE: i64 = 2;
+
 pub struct DapSession<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> {
     server: Server<R, W>,
     context: DebugContext<'a, B>,
@@ -37,8 +46,8 @@ pub struct DapSession<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> {
     running: bool,
     source_to_opcodes: BTreeMap<FileId, Vec<(usize, OpcodeLocation)>>,
     next_breakpoint_id: i64,
-    instruction_breakpoints: Vec<(OpcodeLocation, i64)>,
-    source_breakpoints: BTreeMap<FileId, Vec<(OpcodeLocation, i64)>>,
+    instruction_breakpoints: Vec<(OpcodeLocation, i64, BreakpointOptions)>,
+    source_breakpoints: BTreeMap<FileId, Vec<(OpcodeLocation, i64, BreakpointOptions)>>,
 }
 
 // BTreeMap<FileId, Vec<(usize, OpcodeLocation)>
@@ -57,7 +66,7 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
             circuit,
             debug_artifact,
             initial_witness,
-            Box::new(DefaultForeignCallExecutor::new(true, None)),
+            Box::new(DefaultDebugForeignCallExecutor::from_artifact(true, debug_artifact)),
         );
         Self {
             server,
@@ -179,7 +188,10 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
                         _ => self.handle_next(req)?,
                     }
                 }
-                Command::StepOut(ref args) => {
+                Command::StepOut(_) => {
+                    self.handle_step_out(req)?;
+                }
+                Command::Next(ref args) => {
                     let granularity =
                         args.granularity.as_ref().unwrap_or(&SteppingGranularity::Statement);
                     match granularity {
@@ -187,23 +199,25 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
                         _ => self.handle_next(req)?,
                     }
                 }
-                Command::Next(ref args) => {
+                Command::Continue(_) => {
+                    self.handle_continue(req)?;
+                }
+                Command::StepBack(ref args) => {
                     let granularity =
                         args.granularity.as_ref().unwrap_or(&SteppingGranularity::Statement);
                     match granularity {
-                        SteppingGranularity::Instruction => self.handle_step(req)?,
-                        _ => self.handle_next(req)?,
+                        SteppingGranularity::Instruction => self.handle_step_back(req)?,
+                        _ => self.handle_reverse_next(req)?,
                     }
                 }
-                Command::Continue(_) => {
-                    self.handle_continue(req)?;
+                Command::ReverseContinue(_) => {
+                    self.handle_reverse_continue(req)?;
                 }
                 Command::Scopes(_) => {
-                    // FIXME: this needs a proper implementation when we can
-                    // show the parameters and variables
-                    self.server.respond(
-                        req.success(ResponseBody::Scopes(ScopesResponse { scopes: vec![] })),
-                    )?;
+                    self.handle_scopes(req)?;
+                }
+                Command::Variables(_) => {
+                    self.handle_variables(req)?;
                 }
                 _ => {
                     eprintln!("ERROR: unhandled command: {:?}", req.command);
@@ -213,22 +227,82 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
         Ok(())
     }
 
+    fn handle_scopes(&mut self, req: Request) -> Result<(), ServerError> {
+        let scopes = vec![
+            Scope {
+                name: String::from("Locals"),
+                variables_reference: LOCALS_REFERENCE,
+                ..Scope::default()
+            },
+            Scope {
+                name: String::from("Witness Map"),
+                variables_reference: WITNESS_MAP_REFERENCE,
+                ..Scope::default()
+            },
+        ];
+        self.server.respond(req.success(ResponseBody::Scopes(ScopesResponse { scopes })))?;
+        Ok(())
+    }
+
+    fn handle_variables(&mut self, req: Request) -> Result<(), ServerError> {
+        let Command::Variables(ref args) = req.command else {
+            unreachable!("handle_variables called on a different request");
+        };
+        let variables = match args.variables_reference {
+            LOCALS_REFERENCE => self
+                .context
+                .get_variables()
+                .into_iter()
+                .map(|(name, value, typ)| {
+                    let value = PrintableValueDisplay::Plain(value.clone(), typ.clone());
+                    Variable {
+                        name: name.to_string(),
+                        value: value.to_string(),
+                        type_field: Some(format_type(typ)),
+                        variables_reference: 0,
+                        ..Variable::default()
+                    }
+                })
+                .collect(),
+            WITNESS_MAP_REFERENCE => self
+                .context
+                .get_witness_map()
+                .clone()
+                .into_iter()
+                .map(|(witness, value)| Variable {
+                    name: format!("_{}", witness.witness_index()),
+                    value: value.to_string(),
+                    type_field: Some(String::from("Field")),
+                    variables_reference: 0,
+                    ..Variable::default()
+                })
+                .collect(),
+            _ => vec![],
+        };
+        self.server
+            .respond(req.success(ResponseBody::Variables(VariablesResponse { variables })))?;
+        Ok(())
+    }
+
     fn handle_stack_trace(&mut self, req: Request) -> Result<(), ServerError> {
         let opcode_location = self.context.get_current_opcode_location();
-        let source_location = self.context.get_current_source_location();
-        let frames = match source_location {
+        let stack_frames = self.context.get_current_stack_frames();
+        let frames = match stack_frames {
             None => vec![],
-            Some(locations) => locations
+            // The innermost frame, where execution is, comes first
+            Some(stack_frames) => stack_frames
                 .iter()
+                .rev()
                 .enumerate()
-                .map(|(index, location)| {
+                .map(|(index, (location, name))| {
                     let line_number = self.debug_artifact.location_line_number(*location).unwrap();
                     let column_number =
                         self.debug_artifact.location_column_number(*location).unwrap();
-                    let ip_reference = opcode_location.map(|location| location.to_string());
+                    let ip_reference =
+                        opcode_location.filter(|_| index == 0).map(|location| location.to_string());
                     StackFrame {
                         id: index as i64,
-                        name: format!("frame #{index}"),
+                        name: name.to_string(),
                         source: Some(Source {
                             path: self.debug_artifact.file_map[&location.file]
                                 .path
@@ -322,6 +396,13 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
         self.handle_execution_result(result)
     }
 
+    fn handle_step_out(&mut self, req: Request) -> Result<(), ServerError> {
+        let result = self.context.step_out();
+        eprintln!("INFO: stepped out with result {result:?}");
+        self.server.respond(req.ack()?)?;
+        self.handle_execution_result(result)
+    }
+
     fn handle_continue(&mut self, req: Request) -> Result<(), ServerError> {
         let result = self.context.cont();
         eprintln!("INFO: continue with result {result:?}");
@@ -331,15 +412,36 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
         self.handle_execution_result(result)
     }
 
+    fn handle_step_back(&mut self, req: Request) -> Result<(), ServerError> {
+        let result = self.context.reverse_step_into_opcode();
+        eprintln!("INFO: stepped back by instruction with result {result:?}");
+        self.server.respond(req.ack()?)?;
+        self.handle_execution_result(result)
+    }
+
+    fn handle_reverse_next(&mut self, req: Request) -> Result<(), ServerError> {
+        let result = self.context.reverse_next();
+        eprintln!("INFO: stepped back by statement with result {result:?}");
+        self.server.respond(req.ack()?)?;
+        self.handle_execution_result(result)
+    }
+
+    fn handle_reverse_continue(&mut self, req: Request) -> Result<(), ServerError> {
+        let result = self.context.reverse_continue();
+        eprintln!("INFO: reverse continue with result {result:?}");
+        self.server.respond(req.ack()?)?;
+        self.handle_execution_result(result)
+    }
+
     fn find_breakpoints_at_location(&self, opcode_location: &OpcodeLocation) -> Vec<i64> {
         let mut result = vec![];
-        for (location, id) in &self.instruction_breakpoints {
+        for (location, id, _) in &self.instruction_breakpoints {
             if opcode_location == location {
                 result.push(*id);
             }
         }
         for breakpoints in self.source_breakpoints.values() {
-            for (location, id) in breakpoints {
+            for (location, id, _) in breakpoints {
                 if opcode_location == location {
                     result.push(*id);
                 }
@@ -348,7 +450,24 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
         result
     }
 
+    fn send_log_messages(&mut self) -> Result<(), ServerError> {
+        for message in self.context.take_log_messages() {
+            self.server.send_event(Event::Output(OutputEventBody {
+                category: Some(OutputEventCategory::Console),
+                output: format!("{message}\n"),
+                group: None,
+                variables_reference: None,
+                source: None,
+                line: None,
+                column: None,
+                data: None,
+            }))?;
+        }
+        Ok(())
+    }
+
     fn handle_execution_result(&mut self, result: DebugCommandResult) -> Result<(), ServerError> {
+        self.send_log_messages()?;
         match result {
             DebugCommandResult::Done => {
                 self.running = false;
@@ -397,16 +516,51 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
         id
     }
 
-    fn reinstall_breakpoints(&mut self) {
-        self.context.clear_breakpoints();
-        for (location, _) in &self.instruction_breakpoints {
-            self.context.add_breakpoint(*location);
+    /// Brings the debugger's breakpoints in line with those set through DAP. Breakpoints whose
+    /// location and options are unchanged are left alone, so that their hit counts are kept.
+    fn sync_breakpoints(&mut self) {
+        let mut wanted: HashMap<OpcodeLocation, BreakpointOptions> = HashMap::new();
+        let breakpoints =
+            self.instruction_breakpoints.iter().chain(self.source_breakpoints.values().flatten());
+        for (location, _, options) in breakpoints {
+            wanted.insert(*location, options.clone());
         }
-        for breakpoints in self.source_breakpoints.values() {
-            for (location, _) in breakpoints {
-                self.context.add_breakpoint(*location);
+
+        let current: Vec<(OpcodeLocation, BreakpointOptions)> = self
+            .context
+            .iterate_breakpoints()
+            .map(|(location, options)| (*location, options.clone()))
+            .collect();
+        for (location, options) in current {
+            match wanted.get(&location) {
+                Some(wanted_options) if *wanted_options == options => {
+                    wanted.remove(&location);
+                }
+                Some(_) => (),
+                None => {
+                    self.context.delete_breakpoint(&location);
+                }
             }
         }
+        for (location, options) in wanted {
+            self.context.add_breakpoint_with_options(location, options);
+        }
+    }
+
+    /// Returns the id of the breakpoint in `previous` with the same location and options, or a
+    /// new one, so that unchanged breakpoints keep their ids.
+    fn breakpoint_id(
+        &mut self,
+        previous: &[(OpcodeLocation, i64, BreakpointOptions)],
+        location: OpcodeLocation,
+        options: &BreakpointOptions,
+    ) -> i64 {
+        previous
+            .iter()
+            .find(|(previous_location, _, previous_options)| {
+                *previous_location == location && previous_options == options
+            })
+            .map_or_else(|| self.get_next_breakpoint_id(), |(_, id, _)| *id)
     }
 
     fn handle_set_instruction_breakpoints(&mut self, req: Request) -> Result<(), ServerError> {
@@ -415,7 +569,8 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
         };
 
         // compute breakpoints to set and return
-        let mut breakpoints_to_set: Vec<(OpcodeLocation, i64)> = vec![];
+        let previous = std::mem::take(&mut self.instruction_breakpoints);
+        let mut breakpoints_to_set: Vec<(OpcodeLocation, i64, BreakpointOptions)> = vec![];
         let breakpoints: Vec<Breakpoint> = args.breakpoints.iter().map(|breakpoint| {
             let Ok(location) = OpcodeLocation::from_str(breakpoint.instruction_reference.as_str()) else {
                 return Breakpoint {
@@ -431,8 +586,18 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
                     ..Breakpoint::default()
                 };
             }
-            let id = self.get_next_breakpoint_id();
-            breakpoints_to_set.push((location, id));
+            let options = match BreakpointOptions::parse(
+                breakpoint.condition.as_deref(),
+                breakpoint.hit_condition.as_deref(),
+                None,
+            ) {
+                Ok(options) => options,
+                Err(error) => {
+                    return Breakpoint { verified: false, message: Some(error), ..Breakpoint::default() };
+                }
+            };
+            let id = self.breakpoint_id(&previous, location, &options);
+            breakpoints_to_set.push((location, id, options));
             Breakpoint {
                 id: Some(id),
                 verified: true,
@@ -442,7 +607,7 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
 
         // actually set the computed breakpoints
         self.instruction_breakpoints = breakpoints_to_set;
-        self.reinstall_breakpoints();
+        self.sync_breakpoints();
 
         // response to request
         self.server.respond(req.success(ResponseBody::SetInstructionBreakpoints(
@@ -496,7 +661,8 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
         let Some(ref breakpoints) = &args.breakpoints else {
             return vec![];
         };
-        let mut breakpoints_to_set: Vec<(OpcodeLocation, i64)> = vec![];
+        let previous = self.source_breakpoints.remove(&file_id).unwrap_or_default();
+        let mut breakpoints_to_set: Vec<(OpcodeLocation, i64, BreakpointOptions)> = vec![];
         let breakpoints = breakpoints
             .iter()
             .map(|breakpoint| {
@@ -518,9 +684,23 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
                         ..Breakpoint::default()
                     };
                 }
+                let options = match BreakpointOptions::parse(
+                    breakpoint.condition.as_deref(),
+                    breakpoint.hit_condition.as_deref(),
+                    breakpoint.log_message.as_deref(),
+                ) {
+                    Ok(options) => options,
+                    Err(error) => {
+                        return Breakpoint {
+                            verified: false,
+                            message: Some(error),
+                            ..Breakpoint::default()
+                        };
+                    }
+                };
                 let instruction_reference = format!("{}", location);
-                let breakpoint_id = self.get_next_breakpoint_id();
-                breakpoints_to_set.push((location, breakpoint_id));
+                let breakpoint_id = self.breakpoint_id(&previous, location, &options);
+                breakpoints_to_set.push((location, breakpoint_id, options));
                 Breakpoint {
                     id: Some(breakpoint_id),
                     verified: true,
@@ -542,7 +722,7 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
             unreachable!("handle_set_source_breakpoints called on a different request");
         };
         let breakpoints = self.map_source_breakpoints(args);
-        self.reinstall_breakpoints();
+        self.sync_breakpoints();
         self.server.respond(
             req.success(ResponseBody::SetBreakpoints(SetBreakpointsResponse { breakpoints })),
         )?;
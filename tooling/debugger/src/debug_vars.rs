@@ -0,0 +1,148 @@
+use acvm::FieldElement;
+use noirc_errors::debug_info::{
+    DebugTypeId, DebugTypes, DebugVarId, DebugVariable, DebugVariables,
+};
+use noirc_printable_type::{decode_value, PrintableType, PrintableValue};
+use std::collections::{BTreeMap, HashMap};
+
+/// The value of each variable in scope, in the order the variables were declared.
+pub(crate) type DebugVarValues = BTreeMap<DebugVarId, PrintableValue>;
+
+/// The source variables of the program being debugged, with the values of those in scope.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct DebugVars {
+    variables: HashMap<DebugVarId, DebugVariable>,
+    types: HashMap<DebugTypeId, PrintableType>,
+    active: DebugVarValues,
+}
+
+impl DebugVars {
+    pub(crate) fn insert_variables(&mut self, variables: &DebugVariables) {
+        self.variables.extend(variables.iter().map(|(id, var)| (*id, var.clone())));
+    }
+
+    pub(crate) fn insert_types(&mut self, types: &DebugTypes) {
+        self.types.extend(types.iter().map(|(id, typ)| (*id, typ.clone())));
+    }
+
+    /// Returns the name, value and type of each variable in scope.
+    pub(crate) fn get_variables(&self) -> Vec<(&str, &PrintableValue, &PrintableType)> {
+        self.active
+            .iter()
+            .filter_map(|(var_id, value)| {
+                let variable = self.variables.get(var_id)?;
+                let typ = self.types.get(&variable.debug_type_id)?;
+                Some((variable.name.as_str(), value, typ))
+            })
+            .collect()
+    }
+
+    /// Records the value of a variable, decoded from the fields it was encoded into.
+    pub(crate) fn assign(&mut self, var_id: DebugVarId, values: &[FieldElement]) {
+        let Some(typ) =
+            self.variables.get(&var_id).and_then(|var| self.types.get(&var.debug_type_id))
+        else {
+            return;
+        };
+        let value = decode_value(&mut values.iter().copied(), typ);
+        self.active.insert(var_id, value);
+    }
+
+    pub(crate) fn drop(&mut self, var_id: DebugVarId) {
+        self.active.remove(&var_id);
+    }
+
+    /// Returns the values of the variables in scope, which are all that changes as the program
+    /// runs.
+    pub(crate) fn values(&self) -> &DebugVarValues {
+        &self.active
+    }
+
+    pub(crate) fn restore_values(&mut self, values: DebugVarValues) {
+        self.active = values;
+    }
+}
+
+/// Renders a type the way it's written in Noir source.
+pub(crate) fn format_type(typ: &PrintableType) -> String {
+    match typ {
+        PrintableType::Field => "Field".to_string(),
+        PrintableType::Array { length, typ } => format!("[{}; {length}]", format_type(typ)),
+        PrintableType::SignedInteger { width } => format!("i{width}"),
+        PrintableType::UnsignedInteger { width } => format!("u{width}"),
+        PrintableType::Boolean => "bool".to_string(),
+        PrintableType::Tuple { types } => {
+            let types: Vec<_> = types.iter().map(format_type).collect();
+            format!("({})", types.join(", "))
+        }
+        PrintableType::Struct { name, .. } => name.clone(),
+        PrintableType::String { length } => format!("str<{length}>"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debug_vars() -> DebugVars {
+        let point = PrintableType::Struct {
+            name: "Point".to_string(),
+            fields: vec![
+                ("x".to_string(), PrintableType::Field),
+                ("y".to_string(), PrintableType::UnsignedInteger { width: 8 }),
+            ],
+        };
+        let types = DebugTypes::from([
+            (DebugTypeId(0), point),
+            (
+                DebugTypeId(1),
+                PrintableType::Array { length: 2, typ: Box::new(PrintableType::Boolean) },
+            ),
+        ]);
+        let variables = DebugVariables::from([
+            (DebugVarId(0), DebugVariable { name: "p".to_string(), debug_type_id: DebugTypeId(0) }),
+            (
+                DebugVarId(1),
+                DebugVariable { name: "flags".to_string(), debug_type_id: DebugTypeId(1) },
+            ),
+        ]);
+
+        let mut debug_vars = DebugVars::default();
+        debug_vars.insert_variables(&variables);
+        debug_vars.insert_types(&types);
+        debug_vars
+    }
+
+    #[test]
+    fn test_assigned_variables_are_decoded() {
+        let mut debug_vars = debug_vars();
+        assert!(debug_vars.get_variables().is_empty());
+
+        debug_vars.assign(DebugVarId(1), &[FieldElement::one(), FieldElement::zero()]);
+        debug_vars.assign(DebugVarId(0), &[FieldElement::from(3_u128), FieldElement::from(4_u128)]);
+
+        let variables = debug_vars.get_variables();
+        let names: Vec<_> = variables.iter().map(|(name, _, _)| *name).collect();
+        assert_eq!(names, vec!["p", "flags"]);
+
+        let (_, value, typ) = &variables[0];
+        assert_eq!(format_type(typ), "Point");
+        let PrintableValue::Struct(fields) = value else {
+            panic!("Expected a struct value");
+        };
+        assert_eq!(fields["y"], PrintableValue::Field(FieldElement::from(4_u128)));
+        assert_eq!(format_type(variables[1].2), "[bool; 2]");
+    }
+
+    #[test]
+    fn test_dropped_variables_are_hidden() {
+        let mut debug_vars = debug_vars();
+        debug_vars.assign(DebugVarId(1), &[FieldElement::one(), FieldElement::zero()]);
+        debug_vars.drop(DebugVarId(1));
+        assert!(debug_vars.get_variables().is_empty());
+
+        // Variables without debug info are ignored
+        debug_vars.assign(DebugVarId(7), &[FieldElement::one()]);
+        assert!(debug_vars.get_variables().is_empty());
+    }
+}
@@ -0,0 +1,118 @@
+use acvm::{
+    acir::brillig::{ForeignCallParam, ForeignCallResult},
+    pwg::ForeignCallWaitInfo,
+};
+use nargo::{
+    artifacts::debug::DebugArtifact,
+    ops::{DefaultForeignCallExecutor, ForeignCallExecutor},
+};
+use noirc_errors::debug_info::DebugVarId;
+use noirc_printable_type::{ForeignCallError, PrintableType, PrintableValue};
+
+use crate::debug_vars::{DebugVarValues, DebugVars};
+
+/// The foreign calls made by programs instrumented for the debugger, which report the values
+/// of their source variables.
+pub(crate) enum DebugForeignCall {
+    VarAssign,
+    VarDrop,
+}
+
+impl DebugForeignCall {
+    pub(crate) fn lookup(op_name: &str) -> Option<DebugForeignCall> {
+        match op_name {
+            "__debug_var_assign" => Some(DebugForeignCall::VarAssign),
+            "__debug_var_drop" => Some(DebugForeignCall::VarDrop),
+            _ => None,
+        }
+    }
+}
+
+/// A [ForeignCallExecutor] which also keeps track of the source variables of the program
+/// being debugged.
+pub(crate) trait DebugForeignCallExecutor: ForeignCallExecutor {
+    /// Returns the name, value and type of each variable in scope.
+    fn get_variables(&self) -> Vec<(&str, &PrintableValue, &PrintableType)>;
+
+    /// Returns the values of the variables in scope, so that they can be restored when
+    /// stepping back.
+    fn save_variables(&self) -> DebugVarValues;
+
+    fn restore_variables(&mut self, values: DebugVarValues);
+}
+
+pub(crate) struct DefaultDebugForeignCallExecutor {
+    executor: DefaultForeignCallExecutor,
+    debug_vars: DebugVars,
+}
+
+impl DefaultDebugForeignCallExecutor {
+    pub(crate) fn new(show_output: bool) -> Self {
+        Self {
+            executor: DefaultForeignCallExecutor::new(show_output, None),
+            debug_vars: DebugVars::default(),
+        }
+    }
+
+    /// Creates an executor which knows about the variables described in the artifact's debug info.
+    pub(crate) fn from_artifact(show_output: bool, artifact: &DebugArtifact) -> Self {
+        let mut executor = Self::new(show_output);
+        for debug_info in &artifact.debug_symbols {
+            executor.debug_vars.insert_variables(&debug_info.variables);
+            executor.debug_vars.insert_types(&debug_info.types);
+        }
+        executor
+    }
+}
+
+fn debug_var_id(param: &ForeignCallParam) -> DebugVarId {
+    DebugVarId(param.unwrap_value().to_u128() as u32)
+}
+
+impl DebugForeignCallExecutor for DefaultDebugForeignCallExecutor {
+    fn get_variables(&self) -> Vec<(&str, &PrintableValue, &PrintableType)> {
+        self.debug_vars.get_variables()
+    }
+
+    fn save_variables(&self) -> DebugVarValues {
+        self.debug_vars.values().clone()
+    }
+
+    fn restore_variables(&mut self, values: DebugVarValues) {
+        self.debug_vars.restore_values(values);
+    }
+}
+
+impl ForeignCallExecutor for DefaultDebugForeignCallExecutor {
+    fn execute(
+        &mut self,
+        foreign_call: &ForeignCallWaitInfo,
+    ) -> Result<ForeignCallResult, ForeignCallError> {
+        let foreign_call_name = foreign_call.function.as_str();
+        match DebugForeignCall::lookup(foreign_call_name) {
+            Some(DebugForeignCall::VarAssign) => {
+                let (var_id, values) = foreign_call
+                    .inputs
+                    .split_first()
+                    .ok_or(ForeignCallError::MissingForeignCallInputs)?;
+                // Struct fields and tuple elements are passed as separate inputs
+                let values: Vec<_> = values
+                    .iter()
+                    .flat_map(|param| param.values())
+                    .map(|value| value.to_field())
+                    .collect();
+                self.debug_vars.assign(debug_var_id(var_id), &values);
+                Ok(ForeignCallResult { values: vec![] })
+            }
+            Some(DebugForeignCall::VarDrop) => {
+                let var_id = foreign_call
+                    .inputs
+                    .first()
+                    .ok_or(ForeignCallError::MissingForeignCallInputs)?;
+                self.debug_vars.drop(debug_var_id(var_id));
+                Ok(ForeignCallResult { values: vec![] })
+            }
+            None => self.executor.execute(foreign_call),
+        }
+    }
+}
@@ -1,5 +1,8 @@
+mod breakpoints;
 mod context;
 mod dap;
+mod debug_vars;
+mod foreign_calls;
 mod repl;
 mod source_code_printer;
 
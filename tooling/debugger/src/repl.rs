@@ -4,11 +4,15 @@ use acvm::acir::circuit::{Circuit, Opcode, OpcodeLocation};
 use acvm::acir::native_types::{Witness, WitnessMap};
 use acvm::{BlackBoxFunctionSolver, FieldElement};
 
-use nargo::{artifacts::debug::DebugArtifact, ops::DefaultForeignCallExecutor, NargoError};
+use nargo::{artifacts::debug::DebugArtifact, NargoError};
+use noirc_printable_type::PrintableValueDisplay;
 
 use easy_repl::{command, CommandStatus, Repl};
 use std::cell::RefCell;
 
+use crate::breakpoints::BreakpointOptions;
+use crate::debug_vars::format_type;
+use crate::foreign_calls::DefaultDebugForeignCallExecutor;
 use crate::source_code_printer::print_source_code_location;
 
 pub struct ReplDebugger<'a, B: BlackBoxFunctionSolver> {
@@ -32,7 +36,7 @@ impl<'a, B: BlackBoxFunctionSolver> ReplDebugger<'a, B> {
             circuit,
             debug_artifact,
             initial_witness.clone(),
-            Box::new(DefaultForeignCallExecutor::new(true, None)),
+            Box::new(DefaultDebugForeignCallExecutor::from_artifact(true, debug_artifact)),
         );
         Self {
             context,
@@ -134,6 +138,32 @@ impl<'a, B: BlackBoxFunctionSolver> ReplDebugger<'a, B> {
         }
     }
 
+    fn add_breakpoint_with_options_at(
+        &mut self,
+        location: OpcodeLocation,
+        condition: Option<&str>,
+        hit_condition: Option<&str>,
+        log_message: Option<&str>,
+    ) {
+        if !self.context.is_valid_opcode_location(&location) {
+            println!("Invalid opcode location {location}");
+            return;
+        }
+        let options = match BreakpointOptions::parse(condition, hit_condition, log_message) {
+            Ok(options) => options,
+            Err(error) => {
+                println!("ERROR: {error}");
+                return;
+            }
+        };
+        let kind = if log_message.is_some() { "logpoint" } else { "breakpoint" };
+        if self.context.add_breakpoint_with_options(location, options) {
+            println!("Added {kind} at opcode {location}");
+        } else {
+            println!("Replaced breakpoint at opcode {location} with a {kind}");
+        }
+    }
+
     fn delete_breakpoint_at(&mut self, location: OpcodeLocation) {
         if self.context.delete_breakpoint(&location) {
             println!("Breakpoint at opcode {location} deleted");
@@ -158,6 +188,9 @@ impl<'a, B: BlackBoxFunctionSolver> ReplDebugger<'a, B> {
     }
 
     fn handle_debug_command_result(&mut self, result: DebugCommandResult) {
+        for message in self.context.take_log_messages() {
+            println!("{message}");
+        }
         match &result {
             DebugCommandResult::BreakpointReached(location) => {
                 println!("Stopped at breakpoint in opcode {}", location);
@@ -192,6 +225,13 @@ impl<'a, B: BlackBoxFunctionSolver> ReplDebugger<'a, B> {
         }
     }
 
+    fn step_out(&mut self) {
+        if self.validate_in_progress() {
+            let result = self.context.step_out();
+            self.handle_debug_command_result(result);
+        }
+    }
+
     fn cont(&mut self) {
         if self.validate_in_progress() {
             println!("(Continuing execution...)");
@@ -200,18 +240,62 @@ impl<'a, B: BlackBoxFunctionSolver> ReplDebugger<'a, B> {
         }
     }
 
+    fn validate_can_step_back(&self) -> bool {
+        if self.context.can_step_back() {
+            true
+        } else {
+            println!("Already at the start of execution");
+            false
+        }
+    }
+
+    fn reverse_step_acir_opcode(&mut self) {
+        if self.validate_can_step_back() {
+            let result = self.context.reverse_step_acir_opcode();
+            self.handle_debug_command_result(result);
+        }
+    }
+
+    fn reverse_step_into_opcode(&mut self) {
+        if self.validate_can_step_back() {
+            let result = self.context.reverse_step_into_opcode();
+            self.handle_debug_command_result(result);
+        }
+    }
+
+    fn reverse_next(&mut self) {
+        if self.validate_can_step_back() {
+            let result = self.context.reverse_next();
+            self.handle_debug_command_result(result);
+        }
+    }
+
+    fn reverse_cont(&mut self) {
+        if self.validate_can_step_back() {
+            println!("(Continuing execution backwards...)");
+            let result = self.context.reverse_continue();
+            if matches!(result, DebugCommandResult::Ok) {
+                println!("Reached the start of execution");
+            }
+            self.handle_debug_command_result(result);
+        }
+    }
+
     fn restart_session(&mut self) {
-        let breakpoints: Vec<OpcodeLocation> =
-            self.context.iterate_breakpoints().copied().collect();
+        let breakpoints: Vec<(OpcodeLocation, BreakpointOptions)> = self
+            .context
+            .iterate_breakpoints()
+            .map(|(location, options)| (*location, options.clone()))
+            .collect();
         self.context = DebugContext::new(
             self.blackbox_solver,
             self.circuit,
             self.debug_artifact,
             self.initial_witness.clone(),
-            Box::new(DefaultForeignCallExecutor::new(true, None)),
+            Box::new(DefaultDebugForeignCallExecutor::from_artifact(true, self.debug_artifact)),
         );
-        for opcode_location in breakpoints {
-            self.context.add_breakpoint(opcode_location);
+        for (opcode_location, options) in breakpoints {
+            self.context.add_breakpoint_with_options(opcode_location, options);
         }
         self.last_result = DebugCommandResult::Ok;
         println!("Restarted debugging session.");
@@ -305,6 +389,31 @@ impl<'a, B: BlackBoxFunctionSolver> ReplDebugger<'a, B> {
         self.context.write_brillig_memory(index, field_value);
     }
 
+    pub fn show_vars(&self) {
+        let variables = self.context.get_variables();
+        if variables.is_empty() {
+            println!("No variables in scope");
+            return;
+        }
+        for (name, value, typ) in variables {
+            let value = PrintableValueDisplay::Plain(value.clone(), typ.clone());
+            println!("{name}: {} = {value}", format_type(typ));
+        }
+    }
+
+    pub fn show_stacktrace(&self) {
+        let Some(frames) = self.context.get_current_stack_frames() else {
+            println!("No source location for the current opcode");
+            return;
+        };
+        for (index, (location, name)) in frames.iter().rev().enumerate() {
+            let path = self.debug_artifact.file_map[&location.file].path.display();
+            let line = self.debug_artifact.location_line_number(*location).unwrap();
+            let column = self.debug_artifact.location_column_number(*location).unwrap();
+            println!("#{index} {name} at {path}:{line}:{column}");
+        }
+    }
+
     fn is_solved(&self) -> bool {
         self.context.is_solved()
     }
@@ -357,6 +466,16 @@ pub fn run<B: BlackBoxFunctionSolver>(
                 }
             },
         )
+        .add(
+            "out",
+            command! {
+                "step until the current function returns",
+                () => || {
+                    ref_context.borrow_mut().step_out();
+                    Ok(CommandStatus::Done)
+                }
+            },
+        )
         .add(
             "continue",
             command! {
@@ -367,6 +486,46 @@ pub fn run<B: BlackBoxFunctionSolver>(
                 }
             },
         )
+        .add(
+            "rstep",
+            command! {
+                "step back to the previous ACIR opcode",
+                () => || {
+                    ref_context.borrow_mut().reverse_step_acir_opcode();
+                    Ok(CommandStatus::Done)
+                }
+            },
+        )
+        .add(
+            "rinto",
+            command! {
+                "step back into the previous opcode",
+                () => || {
+                    ref_context.borrow_mut().reverse_step_into_opcode();
+                    Ok(CommandStatus::Done)
+                }
+            },
+        )
+        .add(
+            "rnext",
+            command! {
+                "step back to where execution reached the previous source location",
+                () => || {
+                    ref_context.borrow_mut().reverse_next();
+                    Ok(CommandStatus::Done)
+                }
+            },
+        )
+        .add(
+            "rcontinue",
+            command! {
+                "continue execution backwards until a breakpoint or the start of the program",
+                () => || {
+                    ref_context.borrow_mut().reverse_cont();
+                    Ok(CommandStatus::Done)
+                }
+            },
+        )
         .add(
             "restart",
             command! {
@@ -397,6 +556,51 @@ pub fn run<B: BlackBoxFunctionSolver>(
                 }
             },
         )
+        .add(
+            "break",
+            command! {
+                "add a breakpoint at an opcode location which only stops if the condition holds",
+                (LOCATION:OpcodeLocation, condition: String) => |location, condition| {
+                    ref_context.borrow_mut().add_breakpoint_with_options_at(
+                        location,
+                        Some(&condition),
+                        None,
+                        None,
+                    );
+                    Ok(CommandStatus::Done)
+                }
+            },
+        )
+        .add(
+            "hitcount",
+            command! {
+                "add a breakpoint at an opcode location which stops once its hit count satisfies the hit condition",
+                (LOCATION:OpcodeLocation, hit_condition: String) => |location, hit_condition| {
+                    ref_context.borrow_mut().add_breakpoint_with_options_at(
+                        location,
+                        None,
+                        Some(&hit_condition),
+                        None,
+                    );
+                    Ok(CommandStatus::Done)
+                }
+            },
+        )
+        .add(
+            "logpoint",
+            command! {
+                "add a logpoint at an opcode location, which prints the message without stopping",
+                (LOCATION:OpcodeLocation, message: String) => |location, message| {
+                    ref_context.borrow_mut().add_breakpoint_with_options_at(
+                        location,
+                        None,
+                        None,
+                        Some(&message),
+                    );
+                    Ok(CommandStatus::Done)
+                }
+            },
+        )
         .add(
             "delete",
             command! {
@@ -477,6 +681,26 @@ pub fn run<B: BlackBoxFunctionSolver>(
                 }
             },
         )
+        .add(
+            "vars",
+            command! {
+                "show the source variables in scope",
+                () => || {
+                    ref_context.borrow().show_vars();
+                    Ok(CommandStatus::Done)
+                }
+            },
+        )
+        .add(
+            "stacktrace",
+            command! {
+                "show the source call stack",
+                () => || {
+                    ref_context.borrow().show_stacktrace();
+                    Ok(CommandStatus::Done)
+                }
+            },
+        )
         .build()
         .expect("Failed to initialize debugger repl");
 
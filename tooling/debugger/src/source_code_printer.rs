@@ -233,7 +233,7 @@ mod tests {
     use fm::FileManager;
     use nargo::artifacts::debug::DebugArtifact;
     use noirc_errors::{debug_info::DebugInfo, Location, Span};
-    use std::collections::BTreeMap;
+    use std::collections::{BTreeMap, HashMap};
     use std::ops::Range;
     use std::path::Path;
     use std::path::PathBuf;
@@ -276,7 +276,13 @@ mod tests {
         let mut opcode_locations = BTreeMap::<OpcodeLocation, Vec<Location>>::new();
         opcode_locations.insert(OpcodeLocation::Acir(42), vec![loc]);
 
-        let debug_symbols = vec![DebugInfo::new(opcode_locations)];
+        let debug_symbols = vec![DebugInfo::new(
+            opcode_locations,
+            BTreeMap::default(),
+            BTreeMap::default(),
+            vec![],
+            HashMap::default(),
+        )];
         let debug_artifact = DebugArtifact::new(debug_symbols, &fm);
 
         let location_rendered: Vec<_> = render_location(&debug_artifact, &loc).collect();
@@ -17,15 +17,19 @@ use async_lsp::{
     router::Router, AnyEvent, AnyNotification, AnyRequest, ClientSocket, Error, LspService,
     ResponseError,
 };
-use fm::codespan_files as files;
+use fm::{codespan_files as files, FileId};
 use lsp_types::CodeLens;
-use nargo::workspace::Workspace;
-use nargo_toml::{find_file_manifest, resolve_workspace_from_toml, PackageSelection};
+use nargo::{
+    insert_all_files_for_workspace_into_file_manager, package::Package, prepare_dependencies,
+    workspace::Workspace,
+};
+use nargo_toml::{
+    find_file_manifest, resolve_workspace_without_lockfile, NetworkMode, PackageSelection,
+};
 use noirc_driver::{file_manager_with_stdlib, prepare_crate, NOIR_ARTIFACT_VERSION_STRING};
 use noirc_frontend::{
     graph::{CrateId, CrateName},
-    hir::{Context, FunctionNameMatch},
-    node_interner::NodeInterner,
+    hir::{def_collector::dc_crate::CompilationError, Context, FunctionNameMatch},
 };
 
 use notifications::{
@@ -33,8 +37,12 @@ use notifications::{
     on_did_open_text_document, on_did_save_text_document, on_exit, on_initialized,
 };
 use requests::{
-    on_code_lens_request, on_formatting, on_goto_definition_request, on_initialize,
-    on_profile_run_request, on_shutdown, on_test_run_request, on_tests_request,
+    on_code_action_request, on_code_lens_request, on_completion_request,
+    on_document_symbol_request, on_formatting, on_goto_definition_request, on_hover_request,
+    on_initialize, on_inlay_hint_request, on_prepare_rename_request, on_profile_run_request,
+    on_references_request, on_rename_request, on_semantic_tokens_full_request,
+    on_semantic_tokens_range_request, on_shutdown, on_signature_help_request, on_test_run_request,
+    on_tests_request, on_workspace_symbol_request,
 };
 use serde_json::Value as JsonValue;
 use thiserror::Error;
@@ -63,7 +71,8 @@ pub struct LspState {
     open_documents_count: usize,
     input_files: HashMap<String, String>,
     cached_lenses: HashMap<String, Vec<CodeLens>>,
-    cached_definitions: HashMap<String, NodeInterner>,
+    /// Checked packages, by their root directory
+    cached_packages: HashMap<PathBuf, CachedPackage>,
 }
 
 impl LspState {
@@ -74,12 +83,51 @@ impl LspState {
             solver: WrapperSolver(Box::new(solver)),
             input_files: HashMap::new(),
             cached_lenses: HashMap::new(),
-            cached_definitions: HashMap::new(),
+            cached_packages: HashMap::new(),
             open_documents_count: 0,
         }
     }
 }
 
+/// A package which has been type checked, kept so that requests can be answered without checking
+/// it again until one of its documents changes.
+pub(crate) struct CachedPackage {
+    context: Context<'static>,
+    crate_id: CrateId,
+    /// The errors and warnings found while checking the package
+    errors: Vec<(CompilationError, FileId)>,
+}
+
+impl CachedPackage {
+    /// Type checks `package`, compiling the documents in `input_files`, keyed by URI, in place of
+    /// the files on disk.
+    fn check(
+        input_files: &HashMap<String, String>,
+        workspace: &Workspace,
+        package: &Package,
+    ) -> Self {
+        let mut file_manager = file_manager_with_stdlib(&workspace.root_dir);
+        // Files added first take precedence, so unsaved changes shadow the contents on disk
+        for (uri, source) in input_files {
+            if let Some(path) = Url::parse(uri).ok().and_then(|uri| uri.to_file_path().ok()) {
+                file_manager.add_file_with_source(&path, source.clone());
+            }
+        }
+        insert_all_files_for_workspace_into_file_manager(workspace, &mut file_manager);
+
+        let mut context = Context::new(file_manager);
+        context.def_interner.track_references();
+        let crate_id = prepare_crate(&mut context, &package.entry_path);
+        prepare_dependencies(&mut context, crate_id, &package.dependencies);
+
+        // Errors don't stop the package from being cached so that code which is still being
+        // written can be processed as far as possible
+        let errors = noirc_driver::collect_crate_errors(&mut context, crate_id, false);
+
+        CachedPackage { context, crate_id, errors }
+    }
+}
+
 pub struct NargoLspService {
     router: Router<LspState>,
 }
@@ -97,6 +145,18 @@ impl NargoLspService {
             .request::<request::NargoTestRun, _>(on_test_run_request)
             .request::<request::NargoProfileRun, _>(on_profile_run_request)
             .request::<request::GotoDefinition, _>(on_goto_definition_request)
+            .request::<request::HoverRequest, _>(on_hover_request)
+            .request::<request::References, _>(on_references_request)
+            .request::<request::PrepareRenameRequest, _>(on_prepare_rename_request)
+            .request::<request::Rename, _>(on_rename_request)
+            .request::<request::Completion, _>(on_completion_request)
+            .request::<request::DocumentSymbolRequest, _>(on_document_symbol_request)
+            .request::<request::WorkspaceSymbolRequest, _>(on_workspace_symbol_request)
+            .request::<request::InlayHintRequest, _>(on_inlay_hint_request)
+            .request::<request::SemanticTokensFullRequest, _>(on_semantic_tokens_full_request)
+            .request::<request::SemanticTokensRangeRequest, _>(on_semantic_tokens_range_request)
+            .request::<request::SignatureHelpRequest, _>(on_signature_help_request)
+            .request::<request::CodeActionRequest, _>(on_code_action_request)
             .notification::<notification::Initialized>(on_initialized)
             .notification::<notification::DidChangeConfiguration>(on_did_change_configuration)
             .notification::<notification::DidOpenTextDocument>(on_did_open_text_document)
@@ -207,10 +267,11 @@ pub(crate) fn resolve_workspace_for_source_path(file_path: &Path) -> Result<Work
         ))
     })?;
 
-    let workspace = resolve_workspace_from_toml(
+    let workspace = resolve_workspace_without_lockfile(
         &toml_path,
         PackageSelection::All,
         Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        NetworkMode::Online,
     )
     .map_err(|err| LspError::WorkspaceResolutionError(err.to_string()))?;
 
@@ -1,9 +1,8 @@
 use std::ops::ControlFlow;
 
 use async_lsp::{ErrorCode, LanguageClient, ResponseError};
-use nargo::{insert_all_files_for_workspace_into_file_manager, prepare_package};
-use noirc_driver::{check_crate, file_manager_with_stdlib};
-use noirc_errors::{DiagnosticKind, FileDiagnostic};
+use noirc_driver::check_crate;
+use noirc_errors::{CustomDiagnostic, DiagnosticKind, FileDiagnostic};
 
 use crate::requests::collect_lenses_for_package;
 use crate::types::{
@@ -14,7 +13,7 @@ use crate::types::{
 
 use crate::{
     byte_span_to_range, get_package_tests_in_crate, prepare_source,
-    resolve_workspace_for_source_path, LspState,
+    resolve_workspace_for_source_path, CachedPackage, LspState,
 };
 
 pub(super) fn on_initialized(
@@ -54,6 +53,8 @@ pub(super) fn on_did_change_text_document(
 ) -> ControlFlow<Result<(), async_lsp::Error>> {
     let text = params.content_changes.into_iter().next().unwrap().text;
     state.input_files.insert(params.text_document.uri.to_string(), text.clone());
+    // Packages depending on the changed document must be checked again by the next request
+    state.cached_packages.clear();
 
     let (mut context, crate_id) = prepare_source(text);
     let _ = check_crate(&mut context, crate_id, false, false);
@@ -98,7 +99,7 @@ pub(super) fn on_did_close_text_document(
     state.open_documents_count -= 1;
 
     if state.open_documents_count == 0 {
-        state.cached_definitions.clear();
+        state.cached_packages.clear();
     }
 
     ControlFlow::Continue(())
@@ -128,23 +129,15 @@ fn process_noir_document(
         ResponseError::new(ErrorCode::REQUEST_FAILED, lsp_error.to_string())
     })?;
 
-    let mut workspace_file_manager = file_manager_with_stdlib(&workspace.root_dir);
-    insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
-
     let diagnostics: Vec<_> = workspace
         .into_iter()
         .flat_map(|package| -> Vec<Diagnostic> {
-            let (mut context, crate_id) = prepare_package(&workspace_file_manager, package);
-
-            let file_diagnostics = match check_crate(&mut context, crate_id, false, false) {
-                Ok(((), warnings)) => warnings,
-                Err(errors_and_warnings) => errors_and_warnings,
-            };
-
-            let package_root_dir: String = package.root_dir.as_os_str().to_string_lossy().into();
+            let checked = CachedPackage::check(&state.input_files, &workspace, package);
+            let context = &checked.context;
+            let crate_id = checked.crate_id;
 
             // We don't add test headings for a package if it contains no `#[test]` functions
-            if let Some(tests) = get_package_tests_in_crate(&context, &crate_id, &package.name) {
+            if let Some(tests) = get_package_tests_in_crate(context, &crate_id, &package.name) {
                 let _ = state.client.notify::<notification::NargoUpdateTests>(NargoPackageTests {
                     package: package.name.to_string(),
                     tests,
@@ -152,7 +145,7 @@ fn process_noir_document(
             }
 
             let collected_lenses = crate::requests::collect_lenses_for_package(
-                &context,
+                context,
                 crate_id,
                 &workspace,
                 package,
@@ -160,12 +153,15 @@ fn process_noir_document(
             );
             state.cached_lenses.insert(document_uri.to_string(), collected_lenses);
 
-            state.cached_definitions.insert(package_root_dir, context.def_interner);
-
+            let file_diagnostics: Vec<FileDiagnostic> = checked
+                .errors
+                .iter()
+                .map(|(error, file_id)| CustomDiagnostic::from(error.clone()).in_file(*file_id))
+                .collect();
             let fm = &context.file_manager;
             let files = fm.as_file_map();
 
-            file_diagnostics
+            let diagnostics = file_diagnostics
                 .into_iter()
                 .filter_map(|FileDiagnostic { file_id, diagnostic, call_stack: _ }| {
                     // Ignore diagnostics for any file that wasn't the file we saved
@@ -193,7 +189,10 @@ fn process_noir_document(
                         ..Default::default()
                     })
                 })
-                .collect()
+                .collect();
+
+            state.cached_packages.insert(package.root_dir.clone(), checked);
+            diagnostics
         })
         .collect();
     let _ = state.client.publish_diagnostics(PublishDiagnosticsParams {
@@ -0,0 +1,637 @@
+use std::{
+    collections::HashMap,
+    future::{self, Future},
+};
+
+use async_lsp::ResponseError;
+use fm::FileId;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Position, Range, TextEdit,
+    Url, WorkspaceEdit,
+};
+use noirc_errors::{Location, Span};
+use noirc_frontend::{
+    graph::CrateId,
+    hir::{
+        def_collector::{dc_crate::CompilationError, errors::DefCollectorErrorKind},
+        def_map::{LocalModuleId, ModuleDefId, ModuleId},
+        resolution::{errors::ResolverError, import::PathResolutionError},
+        type_check::TypeCheckError,
+        Context,
+    },
+    hir_def::{
+        expr::{HirExpression, HirIdent},
+        stmt::{HirPattern, HirStatement},
+    },
+    node_interner::{ExprId, NodeInterner, ReferenceId, TraitId},
+    FunctionVisibility, Ident, Type,
+};
+
+use crate::{byte_span_to_range, types::CodeActionResult, LspState};
+
+use super::{
+    completion::{child_expressions, is_identifier_char, module_at, statement_expressions},
+    process_document_with_errors,
+};
+
+pub(crate) fn on_code_action_request(
+    state: &mut LspState,
+    params: CodeActionParams,
+) -> impl Future<Output = Result<CodeActionResult, ResponseError>> {
+    // Quick fixes are the only kind of code action provided
+    if let Some(only) = &params.context.only {
+        let quick_fix = CodeActionKind::QUICKFIX;
+        if !only.iter().any(|kind| quick_fix.as_str().starts_with(kind.as_str())) {
+            return future::ready(Ok(None));
+        }
+    }
+
+    let uri = params.text_document.uri;
+    let range = params.range;
+    let result = process_document_with_errors(state, &uri, |file_id, context, crate_id, errors| {
+        let quick_fixes = QuickFixes { context, crate_id, file_id, uri: &uri };
+        let actions = errors
+            .iter()
+            .filter(|(_, error_file)| *error_file == file_id)
+            .filter(|(error, _)| {
+                let error_range = error_span(error).and_then(|span| quick_fixes.range_of(span));
+                error_range
+                    .map_or(false, |error| error.start <= range.end && error.end >= range.start)
+            })
+            .flat_map(|(error, _)| quick_fixes.for_error(error))
+            .collect();
+        Some(actions)
+    });
+    future::ready(result)
+}
+
+/// The span of the code a quick fix is offered for, if the error is one which can be fixed.
+fn error_span(error: &CompilationError) -> Option<Span> {
+    match error {
+        CompilationError::ResolverError(ResolverError::PathResolutionError(
+            PathResolutionError::Unresolved(ident),
+        ))
+        | CompilationError::ResolverError(ResolverError::UnusedVariable { ident }) => {
+            Some(ident.span())
+        }
+        CompilationError::ResolverError(ResolverError::VariableNotDeclared { span, .. })
+        | CompilationError::TypeError(TypeCheckError::TypeAnnotationsNeeded { span })
+        | CompilationError::DefinitionError(DefCollectorErrorKind::TraitMissingMethod {
+            trait_impl_span: span,
+            ..
+        }) => Some(*span),
+        _ => None,
+    }
+}
+
+/// Builds the quick fixes for errors in a single document. All of the edits apply to that
+/// document.
+struct QuickFixes<'a> {
+    context: &'a Context,
+    crate_id: CrateId,
+    file_id: FileId,
+    uri: &'a Url,
+}
+
+impl<'a> QuickFixes<'a> {
+    fn for_error(&self, error: &CompilationError) -> Vec<CodeActionOrCommand> {
+        match error {
+            CompilationError::ResolverError(ResolverError::PathResolutionError(
+                PathResolutionError::Unresolved(ident),
+            )) => self.import(&ident.0.contents, ident.span()),
+            CompilationError::ResolverError(ResolverError::VariableNotDeclared { name, span }) => {
+                self.import(name, *span)
+            }
+            CompilationError::ResolverError(ResolverError::UnusedVariable { ident }) => {
+                self.unused_variable(ident)
+            }
+            CompilationError::TypeError(TypeCheckError::TypeAnnotationsNeeded { span }) => {
+                self.type_annotation(*span).into_iter().collect()
+            }
+            CompilationError::DefinitionError(DefCollectorErrorKind::TraitMissingMethod {
+                method_name,
+                trait_impl_span,
+                ..
+            }) => self.missing_trait_method(method_name, *trait_impl_span).into_iter().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn interner(&self) -> &'a NodeInterner {
+        &self.context.def_interner
+    }
+
+    fn source(&self) -> &'a str {
+        self.context.file_manager.fetch_file(self.file_id)
+    }
+
+    fn range_of(&self, span: Span) -> Option<Range> {
+        let files = self.context.file_manager.as_file_map();
+        byte_span_to_range(files, self.file_id, span.into())
+    }
+
+    fn edit(&self, span: Span, new_text: String) -> Option<TextEdit> {
+        Some(TextEdit { range: self.range_of(span)?, new_text })
+    }
+
+    fn quick_fix(&self, title: String, edits: Vec<TextEdit>) -> CodeActionOrCommand {
+        let changes = HashMap::from([(self.uri.clone(), edits)]);
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(WorkspaceEdit { changes: Some(changes), ..WorkspaceEdit::default() }),
+            ..CodeAction::default()
+        })
+    }
+
+    /// Offers a `use` statement for each item named `name` which is declared in the current
+    /// crate, its dependencies or the standard library.
+    fn import(&self, name: &str, span: Span) -> Vec<CodeActionOrCommand> {
+        // Only the first segment of a path can be brought into scope
+        let source = self.source();
+        if source[..span.start() as usize].trim_end().ends_with("::") {
+            return Vec::new();
+        }
+
+        let location = Location::new(span, self.file_id);
+        let current_module = module_at(self.context, location);
+        let module_body = current_module.and_then(|module| self.inline_module_body(module));
+        let (position, indent) = use_statement_position(source, module_body);
+
+        let mut paths = self.import_paths(name, current_module);
+        paths.sort();
+        paths.dedup();
+        paths
+            .into_iter()
+            .map(|path| {
+                let edit = TextEdit {
+                    range: Range { start: position, end: position },
+                    new_text: format!("{indent}use {path};\n"),
+                };
+                self.quick_fix(format!("Import `{path}`"), vec![edit])
+            })
+            .collect()
+    }
+
+    /// The span between the braces of `module`, if it's declared inline in this document with
+    /// `mod name { ... }`.
+    fn inline_module_body(&self, module: ModuleId) -> Option<Span> {
+        let name = self.interner().reference_location(ReferenceId::Module(module))?;
+        if name.file != self.file_id {
+            return None;
+        }
+        let source = self.source();
+        let after_name = &source[name.span.end() as usize..];
+        let open = source.len() - after_name.trim_start().strip_prefix('{')?.len() - 1;
+        let close = closing_brace(source, open)?;
+        // Modules declared on a single line have no line of their own to add the import on
+        if !source[open..close].contains('\n') {
+            return None;
+        }
+        Some(Span::from(open as u32 + 1..close as u32))
+    }
+
+    fn import_paths(&self, name: &str, current_module: Option<ModuleId>) -> Vec<String> {
+        let context = self.context;
+        let dependencies = context.crate_graph[self.crate_id]
+            .dependencies
+            .iter()
+            .map(|dependency| (dependency.crate_id, Some(dependency.as_name())));
+
+        let mut paths = Vec::new();
+        for (krate, dependency_name) in std::iter::once((self.crate_id, None)).chain(dependencies) {
+            let Some(def_map) = context.def_map(&krate) else {
+                continue;
+            };
+            let crate_path = match dependency_name {
+                Some(dependency_name) => format!("dep::{dependency_name}"),
+                None => "crate".to_string(),
+            };
+
+            for (index, module) in def_map.modules().iter() {
+                let module_id = ModuleId { krate, local_id: LocalModuleId(index) };
+                if current_module == Some(module_id) {
+                    continue;
+                }
+
+                // The scope also holds the module's imports, which aren't where the item lives
+                let definitions: Vec<_> =
+                    module.type_definitions().chain(module.value_definitions()).collect();
+                let scope = module.scope();
+                let items = scope.types().iter().chain(scope.values());
+                let found = items
+                    .filter(|(ident, _)| ident.0.contents == name)
+                    .filter_map(|(_, items)| items.get(&None))
+                    .any(|(id, _, _)| {
+                        definitions.contains(id)
+                            && self.is_importable(*id, module_id, current_module)
+                    });
+                if !found {
+                    continue;
+                }
+
+                let module_path =
+                    def_map.get_module_path_with_separator(index, module.parent, "::");
+                if module_path.is_empty() {
+                    paths.push(format!("{crate_path}::{name}"));
+                } else {
+                    paths.push(format!("{crate_path}::{module_path}::{name}"));
+                }
+            }
+        }
+        paths
+    }
+
+    /// Whether the item declared in `module` can be referred to from `current_module`.
+    fn is_importable(
+        &self,
+        id: ModuleDefId,
+        module: ModuleId,
+        current_module: Option<ModuleId>,
+    ) -> bool {
+        let ModuleDefId::FunctionId(func_id) = id else {
+            return true;
+        };
+        match self.interner().function_visibility(func_id) {
+            FunctionVisibility::Public => true,
+            FunctionVisibility::PublicCrate => module.krate == self.crate_id,
+            // Private functions are visible within their module and its descendants
+            FunctionVisibility::Private => current_module
+                .map_or(false, |current_module| self.is_descendant(current_module, module)),
+        }
+    }
+
+    fn is_descendant(&self, module: ModuleId, ancestor: ModuleId) -> bool {
+        let Some(def_map) = self.context.def_map(&module.krate) else {
+            return false;
+        };
+        let mut current = Some(module.local_id);
+        while let Some(local_id) = current {
+            if module.krate == ancestor.krate && local_id == ancestor.local_id {
+                return true;
+            }
+            current = def_map.modules()[local_id.0].parent;
+        }
+        false
+    }
+
+    fn unused_variable(&self, ident: &Ident) -> Vec<CodeActionOrCommand> {
+        let name = &ident.0.contents;
+        let span = ident.span();
+        let mut actions = Vec::new();
+
+        if let Some(edit) = self.edit(Span::empty(span.start()), "_".to_string()) {
+            actions.push(self.quick_fix(format!("Prefix `{name}` with an underscore"), vec![edit]));
+        }
+
+        // A `let` binding can be removed while keeping its initializer, in case it has side effects
+        if let Some(binding) = let_binding_span(self.source(), span) {
+            if let Some(edit) = self.edit(binding, String::new()) {
+                actions
+                    .push(self.quick_fix(format!("Remove unused variable `{name}`"), vec![edit]));
+            }
+        }
+        actions
+    }
+
+    /// Annotates the variable whose type couldn't be inferred with as much of its type as is known.
+    /// Unknown parts of the type are shown as `_`, to be filled in.
+    fn type_annotation(&self, span: Span) -> Option<CodeActionOrCommand> {
+        let interner = self.interner();
+        let location = Location::new(Span::single_char(span.start()), self.file_id);
+        let Some(ReferenceId::Local(definition_id)) = interner.find_referenced(location) else {
+            return None;
+        };
+
+        let mut bindings = Vec::new();
+        for func_id in interner.functions_in_file(self.file_id) {
+            let body = interner.function(&func_id);
+            unannotated_bindings(interner, *body.as_expr(), &mut bindings);
+        }
+        let ident = bindings.into_iter().find(|ident| ident.id == definition_id)?;
+
+        let name = interner.definition_name(ident.id);
+        let typ = interner.id_type(ident.id).follow_bindings();
+        let edit = self.edit(Span::empty(ident.location.span.end()), format!(": {typ}"))?;
+        Some(self.quick_fix(format!("Add type annotation to `{name}`"), vec![edit]))
+    }
+
+    /// Adds an empty implementation of the trait method to the `impl` block, using the signature
+    /// it was declared with in the trait.
+    fn missing_trait_method(
+        &self,
+        method_name: &Ident,
+        trait_impl_span: Span,
+    ) -> Option<CodeActionOrCommand> {
+        let interner = self.interner();
+        let trait_file = all_traits(self.context).find_map(|trait_id| {
+            let the_trait = interner.get_trait(trait_id);
+            let is_declared_here = the_trait
+                .methods
+                .iter()
+                .any(|method| method.name == *method_name && method.span == method_name.span());
+            is_declared_here.then_some(the_trait.location.file)
+        })?;
+
+        let trait_source = self.context.file_manager.fetch_file(trait_file);
+        let signature = method_signature(trait_source, method_name.span())?;
+
+        let source = self.source();
+        let close = closing_brace(source, trait_impl_span.end() as usize)?;
+        let line_start = source[..close].rfind('\n').map_or(0, |newline| newline + 1);
+        let before_brace = &source[line_start..close];
+        let indent: String = before_brace.chars().take_while(|char| char.is_whitespace()).collect();
+
+        let edit = if before_brace.trim().is_empty() {
+            // The closing brace is on a line of its own, so the method goes on the line before it
+            let new_text = format!("{indent}    {signature} {{}}\n");
+            self.edit(Span::empty(line_start as u32), new_text)?
+        } else {
+            let new_text = format!("\n{indent}    {signature} {{}}\n{indent}");
+            self.edit(Span::empty(close as u32), new_text)?
+        };
+        let title = format!("Implement missing method `{}`", method_name.0.contents);
+        Some(self.quick_fix(title, vec![edit]))
+    }
+}
+
+/// The position at which to add a `use` statement, and the indentation it needs: after the last
+/// `use` statement of the module, or at the start of the module if there are none.
+///
+/// The module is the whole file, unless the span of the body of a module declared inline in it
+/// is given.
+fn use_statement_position(source: &str, module_body: Option<Span>) -> (Position, String) {
+    let lines: Vec<_> = source.lines().collect();
+    let (first_line, end_line, indent) = match module_body {
+        Some(body) => {
+            let line_of = |index: u32| source[..index as usize].matches('\n').count();
+            // The body's items start on the line after its opening brace
+            let first_line = line_of(body.start()) + 1;
+            let end_line = line_of(body.end()).max(first_line);
+            let mod_line = lines[first_line - 1];
+            let mod_indent: String = mod_line.chars().take_while(|char| *char == ' ').collect();
+            let item_indent = lines[first_line..end_line]
+                .iter()
+                .find(|line| !line.trim().is_empty())
+                .map(|line| line.chars().take_while(|char| *char == ' ').collect())
+                .unwrap_or_else(|| format!("{mod_indent}    "));
+            (first_line, end_line, item_indent)
+        }
+        None => (0, lines.len(), String::new()),
+    };
+
+    let use_prefix = format!("{indent}use ");
+    let module_lines = &lines[first_line..end_line];
+    let Some(last_use) = module_lines.iter().rposition(|line| line.starts_with(&use_prefix)) else {
+        return (Position { line: first_line as u32, character: 0 }, indent);
+    };
+    // Imports of several items may span multiple lines
+    let end = (last_use..module_lines.len())
+        .find(|line| module_lines[*line].contains(';'))
+        .unwrap_or(last_use);
+    (Position { line: (first_line + end) as u32 + 1, character: 0 }, indent)
+}
+
+/// For a variable bound by `let x = ...` or `let mut x: T = ...`, returns the span from `let`
+/// up to the initializer.
+fn let_binding_span(source: &str, ident_span: Span) -> Option<Span> {
+    let before = source[..ident_span.start() as usize].trim_end();
+    let before = match before.strip_suffix("mut") {
+        Some(rest) if rest.ends_with(char::is_whitespace) => rest.trim_end(),
+        _ => before,
+    };
+    let let_start = before.strip_suffix("let")?.len();
+    if before[..let_start].ends_with(is_identifier_char) {
+        return None;
+    }
+
+    let after = &source[ident_span.end() as usize..];
+    let equals = after.find('=')?;
+    let between = after[..equals].trim();
+    if !(between.is_empty() || between.starts_with(':')) || between.contains(';') {
+        return None;
+    }
+    let rest = &after[equals + 1..];
+    let initializer_start = source.len() - rest.trim_start().len();
+    Some(Span::from(let_start as u32..initializer_start as u32))
+}
+
+/// Collects the variables declared without a type annotation by `let` statements in the expression.
+fn unannotated_bindings(interner: &NodeInterner, expr_id: ExprId, bindings: &mut Vec<HirIdent>) {
+    match interner.expression(&expr_id) {
+        HirExpression::Block(block) => {
+            for statement in block.statements() {
+                let statement = interner.statement(statement);
+                if let HirStatement::Let(let_statement) = &statement {
+                    // Bindings without a type annotation are resolved with an error type
+                    if matches!(let_statement.r#type, Type::Error) {
+                        match &let_statement.pattern {
+                            HirPattern::Identifier(ident) => bindings.push(ident.clone()),
+                            HirPattern::Mutable(pattern, _) => {
+                                if let HirPattern::Identifier(ident) = pattern.as_ref() {
+                                    bindings.push(ident.clone());
+                                }
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                for expr_id in statement_expressions(&statement) {
+                    unannotated_bindings(interner, expr_id, bindings);
+                }
+            }
+        }
+        HirExpression::Lambda(lambda) => unannotated_bindings(interner, lambda.body, bindings),
+        expression => {
+            for expr_id in child_expressions(expression) {
+                unannotated_bindings(interner, expr_id, bindings);
+            }
+        }
+    }
+}
+
+fn all_traits(context: &Context) -> impl Iterator<Item = TraitId> + '_ {
+    let def_maps = context.crates().filter_map(|krate| context.def_map(&krate));
+    def_maps.flat_map(|def_map| {
+        def_map.modules().iter().flat_map(|(_, module)| {
+            module.type_definitions().filter_map(|id| match id {
+                ModuleDefId::TraitId(trait_id) => Some(trait_id),
+                _ => None,
+            })
+        })
+    })
+}
+
+/// The declaration of a trait method without a default implementation, from `fn` up to but
+/// excluding its terminating `;`.
+fn method_signature(source: &str, name_span: Span) -> Option<String> {
+    let start = source[..name_span.start() as usize].rfind("fn")?;
+    let mut depth = 0;
+    for (offset, char) in source[start..].char_indices() {
+        match char {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            // Default implementations are never reported as missing
+            '{' => return None,
+            ';' if depth == 0 => return Some(source[start..start + offset].trim_end().to_string()),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// The byte index of the `}` closing the first block which opens after `start`.
+fn closing_brace(source: &str, start: usize) -> Option<usize> {
+    let open = start + source[start..].find('{')?;
+    let mut depth = 0;
+    for (offset, char) in source[open..].char_indices() {
+        match char {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + offset);
+                }
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod code_action_tests {
+    use crate::requests::{
+        on_code_action_request,
+        test_utils::{init_lsp_server, insert_statement, insertion_line, test_program_source},
+    };
+
+    use super::*;
+    use lsp_types::{
+        CodeActionContext, PartialResultParams, TextDocumentIdentifier, WorkDoneProgressParams,
+    };
+    use tokio::test;
+
+    /// Requests the code actions for `lines` of the 7_function test program, once `statement`
+    /// has been inserted at the start of `main` and `items` has been appended to the end of the
+    /// file.
+    async fn code_actions(
+        statement: &str,
+        items: &str,
+        lines: (u32, u32),
+    ) -> Vec<(String, Vec<TextEdit>)> {
+        let (mut state, _, noir_text_document) = init_lsp_server();
+        insert_statement(&mut state, &noir_text_document, statement, items);
+
+        let params = CodeActionParams {
+            text_document: TextDocumentIdentifier { uri: noir_text_document.clone() },
+            range: Range {
+                start: Position { line: lines.0, character: 0 },
+                end: Position { line: lines.1, character: u32::MAX },
+            },
+            context: CodeActionContext { diagnostics: Vec::new(), only: None, trigger_kind: None },
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+            partial_result_params: PartialResultParams { partial_result_token: None },
+        };
+        let actions = on_code_action_request(&mut state, params)
+            .await
+            .expect("Could not execute on_code_action_request")
+            .expect("Could not collect code actions");
+
+        actions
+            .into_iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(CodeAction { title, edit: Some(edit), .. }) => {
+                    let mut changes = edit.changes.expect("Expected text edits");
+                    (title, changes.remove(&noir_text_document).unwrap_or_default())
+                }
+                _ => panic!("Expected a code action with an edit"),
+            })
+            .collect()
+    }
+
+    fn insertion(line: u32, character: u32, new_text: &str) -> TextEdit {
+        let position = Position { line, character };
+        TextEdit { range: Range { start: position, end: position }, new_text: new_text.to_string() }
+    }
+
+    #[test]
+    async fn test_import_unresolved_function() {
+        let line = insertion_line();
+        let actions = code_actions("let _ = pedersen_hash([a]);", "", (line, line)).await;
+        assert_eq!(
+            actions,
+            vec![(
+                "Import `dep::std::hash::pedersen_hash`".to_string(),
+                vec![insertion(0, 0, "use dep::std::hash::pedersen_hash;\n")]
+            )]
+        );
+    }
+
+    #[test]
+    async fn test_import_in_inline_module() {
+        let items =
+            "mod hashing {\n    fn hash(a: Field) -> Field {\n        pedersen_hash([a])\n    }\n}\n";
+        let line_count = test_program_source().lines().count() as u32;
+        // The module starts right after the inserted statement
+        let mod_line = line_count + 1;
+
+        let actions = code_actions("", items, (mod_line + 2, mod_line + 2)).await;
+        assert_eq!(
+            actions,
+            vec![(
+                "Import `dep::std::hash::pedersen_hash`".to_string(),
+                vec![insertion(mod_line + 1, 0, "    use dep::std::hash::pedersen_hash;\n")]
+            )]
+        );
+    }
+
+    #[test]
+    async fn test_unused_variable() {
+        let line = insertion_line();
+        let actions = code_actions("let unused = x;", "", (line, line)).await;
+        let removal = TextEdit {
+            range: Range {
+                start: Position { line, character: 4 },
+                end: Position { line, character: 17 },
+            },
+            new_text: String::new(),
+        };
+        assert_eq!(
+            actions,
+            vec![
+                ("Prefix `unused` with an underscore".to_string(), vec![insertion(line, 8, "_")]),
+                ("Remove unused variable `unused`".to_string(), vec![removal]),
+            ]
+        );
+    }
+
+    #[test]
+    async fn test_type_annotation() {
+        let statement = "let z = dep::std::unsafe::zeroed(); let _ = z.len();";
+        let line = insertion_line();
+        let actions = code_actions(statement, "", (line, line)).await;
+        assert_eq!(
+            actions,
+            vec![("Add type annotation to `z`".to_string(), vec![insertion(line, 9, ": _")])]
+        );
+    }
+
+    #[test]
+    async fn test_missing_trait_method() {
+        let items =
+            "trait Describe {\n    fn describe(self) -> Field;\n}\n\nimpl Describe for my2 {\n}\n";
+        let line_count = test_program_source().lines().count() as u32;
+        // The impl starts 4 lines into the appended items, after the inserted statement
+        let impl_line = line_count + 1 + 4;
+
+        let actions = code_actions("", items, (impl_line, impl_line + 1)).await;
+        assert_eq!(
+            actions,
+            vec![(
+                "Implement missing method `describe`".to_string(),
+                vec![insertion(impl_line + 1, 0, "    fn describe(self) -> Field {}\n")]
+            )]
+        );
+    }
+}
@@ -0,0 +1,627 @@
+use std::future::{self, Future};
+
+use async_lsp::{ErrorCode, ResponseError};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionParams, CompletionResponse, Documentation,
+    MarkupContent, MarkupKind, Position,
+};
+use noirc_errors::{Location, Span};
+use noirc_frontend::{
+    hir::{
+        def_map::{ModuleDefId, ModuleId},
+        Context,
+    },
+    hir_def::{
+        expr::{HirArrayLiteral, HirExpression, HirLiteral},
+        stmt::{HirPattern, HirStatement},
+    },
+    node_interner::{DefinitionId, ExprId, FuncId, NodeInterner},
+    Ident, Path, PathKind, Type,
+};
+
+use crate::{types::CompletionResult, LspState};
+
+use super::{
+    goto_definition::character_to_line_offset,
+    hover::{doc_comments, format_function},
+    process_request_with_input_files, uri_to_file_path,
+};
+
+/// Inserted at the cursor before the document is compiled so that half-typed code such as
+/// `foo.` or `foo::` still parses into a member access or a path which can be completed.
+const COMPLETION_MARKER: &str = "__completion__";
+
+const KEYWORDS: &[&str] = &[
+    "as",
+    "assert",
+    "assert_eq",
+    "bool",
+    "comptime",
+    "contract",
+    "crate",
+    "dep",
+    "else",
+    "false",
+    "Field",
+    "fmtstr",
+    "fn",
+    "for",
+    "global",
+    "if",
+    "impl",
+    "in",
+    "let",
+    "mod",
+    "mut",
+    "pub",
+    "str",
+    "struct",
+    "trait",
+    "true",
+    "type",
+    "unconstrained",
+    "use",
+    "where",
+];
+
+pub(crate) fn on_completion_request(
+    state: &mut LspState,
+    params: CompletionParams,
+) -> impl Future<Output = Result<CompletionResult, ResponseError>> {
+    future::ready(complete(state, params))
+}
+
+fn complete(
+    state: &mut LspState,
+    params: CompletionParams,
+) -> Result<CompletionResult, ResponseError> {
+    let text_document_position = params.text_document_position;
+    let uri = &text_document_position.text_document.uri;
+    let source = match state.input_files.get(uri.as_str()) {
+        Some(source) => source.clone(),
+        None => std::fs::read_to_string(uri_to_file_path(uri)?)
+            .map_err(|err| ResponseError::new(ErrorCode::REQUEST_FAILED, err.to_string()))?,
+    };
+    let cursor = byte_index(&source, text_document_position.position).ok_or_else(|| {
+        ResponseError::new(ErrorCode::REQUEST_FAILED, "Could not convert position to byte index")
+    })?;
+    let completion = CompletionContext::new(&source[..cursor]);
+
+    let mut input_files = state.input_files.clone();
+    let completed_source = format!("{}{COMPLETION_MARKER}{}", &source[..cursor], &source[cursor..]);
+    input_files.insert(uri.to_string(), completed_source);
+
+    process_request_with_input_files(
+        &input_files,
+        text_document_position,
+        |location, context, _| {
+            let mut items = match &completion.kind {
+                CompletionKind::Member { object_end } => {
+                    member_completions(context, location.file, *object_end)
+                }
+                CompletionKind::Path(path) => module_at(context, location)
+                    .map(|module| path_completions(context, module, path.clone()))
+                    .unwrap_or_default(),
+                CompletionKind::Name => name_completions(context, location),
+            };
+            items.retain(|item| item.label.starts_with(&completion.prefix));
+            items.sort_by(|item, other| item.label.cmp(&other.label));
+            items.dedup_by(|item, other| item.label == other.label);
+            Some(CompletionResponse::Array(items))
+        },
+    )
+}
+
+/// What is being completed, worked out from the text before the cursor as the code there
+/// usually doesn't parse yet.
+struct CompletionContext {
+    /// The part of the name which has already been typed
+    prefix: String,
+    kind: CompletionKind,
+}
+
+enum CompletionKind {
+    /// A field or method of the expression ending at the given byte index, as in `foo.`
+    Member { object_end: usize },
+    /// An item within a module, struct or trait, as in `foo::`
+    Path(Path),
+    /// A local variable, an item in scope or a keyword
+    Name,
+}
+
+impl CompletionContext {
+    fn new(before_cursor: &str) -> Self {
+        let prefix_start = before_cursor.trim_end_matches(is_identifier_char).len();
+        let prefix = before_cursor[prefix_start..].to_string();
+        let before_prefix = before_cursor[..prefix_start].trim_end();
+
+        let kind = if let Some(before_path) = before_prefix.strip_suffix("::") {
+            path_ending_at(before_path).map_or(CompletionKind::Name, CompletionKind::Path)
+        } else if let Some(object) = before_prefix.strip_suffix('.') {
+            if object.ends_with('.') {
+                // A range such as `0..`
+                CompletionKind::Name
+            } else {
+                CompletionKind::Member { object_end: object.trim_end().len() }
+            }
+        } else {
+            CompletionKind::Name
+        };
+
+        CompletionContext { prefix, kind }
+    }
+}
+
+pub(super) fn is_identifier_char(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == '_'
+}
+
+/// Parses the path at the end of `text`, such as `foo::bar` in `let x = foo::bar`.
+pub(super) fn path_ending_at(text: &str) -> Option<Path> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    loop {
+        let segment_start = rest.trim_end_matches(is_identifier_char).len();
+        if segment_start == rest.len() {
+            return None;
+        }
+        segments.push(rest[segment_start..].to_string());
+        rest = &rest[..segment_start];
+        match rest.strip_suffix("::") {
+            Some(before_separator) => rest = before_separator,
+            None => break,
+        }
+    }
+    segments.reverse();
+
+    let kind = match segments[0].as_str() {
+        "crate" => PathKind::Crate,
+        "dep" => PathKind::Dep,
+        _ => PathKind::Plain,
+    };
+    if kind != PathKind::Plain {
+        segments.remove(0);
+    }
+    let segments = segments.into_iter().map(Ident::from).collect();
+    Some(Path { segments, kind, span: Span::default() })
+}
+
+pub(super) fn byte_index(source: &str, position: Position) -> Option<usize> {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        line_start += source[line_start..].find('\n')? + 1;
+    }
+    let line_end = source[line_start..].find('\n').map_or(source.len(), |end| line_start + end);
+    let line = &source[line_start..line_end];
+    character_to_line_offset(line, position.character).ok().map(|offset| line_start + offset)
+}
+
+fn member_completions(
+    context: &Context,
+    file: fm::FileId,
+    object_end: usize,
+) -> Vec<CompletionItem> {
+    let interner = &context.def_interner;
+    let Some(object_last_char) = object_end.checked_sub(1) else {
+        return Vec::new();
+    };
+    let object_location = Location::new(Span::single_char(object_last_char as u32), file);
+    let Some(object) = interner.find_expression_at(object_location) else {
+        return Vec::new();
+    };
+
+    let typ = match interner.id_type(object).follow_bindings() {
+        Type::MutableReference(element) => element.follow_bindings(),
+        typ => typ,
+    };
+
+    let mut items = Vec::new();
+    match &typ {
+        Type::Struct(struct_type, generics) => {
+            for (name, field_type) in struct_type.borrow().get_fields(generics) {
+                items.push(completion_item(
+                    name,
+                    CompletionItemKind::FIELD,
+                    field_type.to_string(),
+                ));
+            }
+        }
+        Type::Tuple(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                let detail = element.to_string();
+                items.push(completion_item(index.to_string(), CompletionItemKind::FIELD, detail));
+            }
+        }
+        _ => (),
+    }
+
+    let methods = interner.methods_for_type(&typ);
+    for method in methods.into_iter().filter(|method| has_self_parameter(interner, *method)) {
+        items.push(function_completion(context, method, CompletionItemKind::METHOD));
+    }
+    items
+}
+
+pub(super) fn has_self_parameter(interner: &NodeInterner, func_id: FuncId) -> bool {
+    let meta = interner.function_meta(&func_id);
+    let Some((pattern, _, _)) = meta.parameters.0.first() else {
+        return false;
+    };
+    let pattern = match pattern {
+        HirPattern::Mutable(pattern, _) => pattern.as_ref(),
+        pattern => pattern,
+    };
+    matches!(pattern, HirPattern::Identifier(ident) if interner.definition(ident.id).name == "self")
+}
+
+fn path_completions(context: &Context, module: ModuleId, path: Path) -> Vec<CompletionItem> {
+    let interner = &context.def_interner;
+
+    if path.kind == PathKind::Dep && path.segments.is_empty() {
+        let Some(def_map) = context.def_map(&module.krate) else {
+            return Vec::new();
+        };
+        return def_map
+            .extern_prelude()
+            .keys()
+            .map(|name| completion_item(name.clone(), CompletionItemKind::MODULE, String::new()))
+            .collect();
+    }
+
+    match context.resolve_path(module, path) {
+        Some(ModuleDefId::ModuleId(module)) => module_completions(context, module),
+        Some(ModuleDefId::TypeId(struct_id)) => {
+            let typ = Type::Struct(interner.get_struct(struct_id), Vec::new());
+            let methods = interner.methods_for_type(&typ);
+            let methods = methods.into_iter();
+            methods
+                .map(|method| function_completion(context, method, CompletionItemKind::FUNCTION))
+                .collect()
+        }
+        Some(ModuleDefId::TraitId(trait_id)) => {
+            let the_trait = interner.get_trait(trait_id);
+            let methods = the_trait.methods.iter();
+            methods
+                .map(|method| {
+                    let detail = method.typ.to_string();
+                    completion_item(method.name.to_string(), CompletionItemKind::FUNCTION, detail)
+                })
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Completes the names of the items declared in, or imported into, the given module.
+fn module_completions(context: &Context, module: ModuleId) -> Vec<CompletionItem> {
+    let Some(def_map) = context.def_map(&module.krate) else {
+        return Vec::new();
+    };
+    let scope = def_map.modules()[module.local_id.0].scope();
+    let items = scope.types().iter().chain(scope.values());
+    items
+        .flat_map(|(name, items)| {
+            items.values().map(|(id, _, _)| module_def_completion(context, name.to_string(), *id))
+        })
+        .collect()
+}
+
+fn module_def_completion(context: &Context, name: String, id: ModuleDefId) -> CompletionItem {
+    let interner = &context.def_interner;
+    match id {
+        ModuleDefId::FunctionId(func_id) => {
+            let mut item = function_completion(context, func_id, CompletionItemKind::FUNCTION);
+            // Imports may rename the function
+            item.label = name;
+            item
+        }
+        ModuleDefId::TypeId(struct_id) => {
+            let location = interner.get_struct(struct_id).borrow().location;
+            let item = completion_item(name, CompletionItemKind::STRUCT, String::new());
+            with_documentation(context, item, location)
+        }
+        ModuleDefId::TraitId(trait_id) => {
+            let location = interner.get_trait(trait_id).location;
+            let item = completion_item(name, CompletionItemKind::INTERFACE, String::new());
+            with_documentation(context, item, location)
+        }
+        ModuleDefId::ModuleId(_) => {
+            completion_item(name, CompletionItemKind::MODULE, String::new())
+        }
+        ModuleDefId::TypeAliasId(_) => {
+            completion_item(name, CompletionItemKind::STRUCT, String::new())
+        }
+        ModuleDefId::GlobalId(_) => {
+            completion_item(name, CompletionItemKind::CONSTANT, String::new())
+        }
+    }
+}
+
+/// Completes local variables in scope at `location`, the items in scope in the
+/// surrounding module and keywords.
+fn name_completions(context: &Context, location: Location) -> Vec<CompletionItem> {
+    let interner = &context.def_interner;
+    let mut items = Vec::new();
+
+    if let Some(func_id) = interner.find_function_containing(location) {
+        // Later definitions shadow earlier ones with the same name
+        for local in locals_in_scope(interner, func_id, location).into_iter().rev() {
+            let name = interner.definition(local).name.clone();
+            if items.iter().any(|item: &CompletionItem| item.label == name) {
+                continue;
+            }
+            let detail = interner.id_type(local).to_string();
+            items.push(completion_item(name, CompletionItemKind::VARIABLE, detail));
+        }
+    }
+
+    if let Some(module) = module_at(context, location) {
+        items.extend(module_completions(context, module));
+    }
+
+    items.extend(KEYWORDS.iter().map(|keyword| {
+        completion_item(keyword.to_string(), CompletionItemKind::KEYWORD, String::new())
+    }));
+    items
+}
+
+/// Returns the module containing `location`, if it is within a function, or otherwise the
+/// first module declared in the file.
+pub(super) fn module_at(context: &Context, location: Location) -> Option<ModuleId> {
+    let interner = &context.def_interner;
+    if let Some(func_id) = interner.find_function_containing(location) {
+        return Some(interner.function_module(func_id));
+    }
+
+    context.crates().find_map(|krate| {
+        let def_map = context.def_map(&krate)?;
+        let (local_id, _) =
+            def_map.modules().iter().find(|(_, module)| module.location.file == location.file)?;
+        Some(ModuleId { krate, local_id: noirc_frontend::hir::def_map::LocalModuleId(local_id) })
+    })
+}
+
+/// Returns the local variables, including parameters, which are in scope at `location`
+/// within the given function, in the order they were declared.
+pub(super) fn locals_in_scope(
+    interner: &NodeInterner,
+    func_id: FuncId,
+    location: Location,
+) -> Vec<DefinitionId> {
+    let mut locals = Vec::new();
+    for (pattern, _, _) in &interner.function_meta(&func_id).parameters.0 {
+        pattern_definitions(pattern, &mut locals);
+    }
+    let body = *interner.function(&func_id).as_expr();
+    collect_locals(interner, body, location, &mut locals);
+    locals
+}
+
+/// Descends into the expression containing `location`, collecting the definitions visible from it.
+fn collect_locals(
+    interner: &NodeInterner,
+    expr_id: ExprId,
+    location: Location,
+    locals: &mut Vec<DefinitionId>,
+) {
+    let contains = |expr_id: &ExprId| {
+        interner.try_expr_location(expr_id).map_or(false, |expr| expr.contains(&location))
+    };
+
+    match interner.expression(&expr_id) {
+        HirExpression::Block(block) => {
+            for statement in block.statements() {
+                let statement = interner.statement(statement);
+                if let HirStatement::For(for_loop) = &statement {
+                    if contains(&for_loop.block) {
+                        locals.push(for_loop.identifier.id);
+                    }
+                }
+                let expressions = statement_expressions(&statement);
+
+                if let Some(inner) = expressions.iter().find(|expr| contains(expr)) {
+                    return collect_locals(interner, *inner, location, locals);
+                }
+                let starts_after_location = expressions.first().and_then(|expr| {
+                    let expr_location = interner.try_expr_location(expr)?;
+                    Some(expr_location.span.start() > location.span.start())
+                });
+                if starts_after_location == Some(true) {
+                    return;
+                }
+                if let HirStatement::Let(let_statement) = &statement {
+                    pattern_definitions(&let_statement.pattern, locals);
+                }
+            }
+        }
+        HirExpression::Lambda(lambda) => {
+            if contains(&lambda.body) {
+                for (pattern, _) in &lambda.parameters {
+                    pattern_definitions(pattern, locals);
+                }
+                collect_locals(interner, lambda.body, location, locals);
+            }
+        }
+        expression => {
+            if let Some(inner) = child_expressions(expression).iter().find(|expr| contains(expr)) {
+                collect_locals(interner, *inner, location, locals);
+            }
+        }
+    }
+}
+
+/// Returns the expressions directly contained in a statement, in source order.
+pub(super) fn statement_expressions(statement: &HirStatement) -> Vec<ExprId> {
+    match statement {
+        HirStatement::Let(let_statement) => vec![let_statement.expression],
+        HirStatement::For(for_loop) => {
+            vec![for_loop.start_range, for_loop.end_range, for_loop.block]
+        }
+        HirStatement::Assign(assign) => vec![assign.expression],
+        HirStatement::Constrain(constrain) => vec![constrain.0],
+        HirStatement::Expression(expr) | HirStatement::Semi(expr) => vec![*expr],
+        HirStatement::Error => Vec::new(),
+    }
+}
+
+/// Returns the expressions directly contained in any expression other than a block or lambda.
+pub(super) fn child_expressions(expression: HirExpression) -> Vec<ExprId> {
+    match expression {
+        HirExpression::Literal(HirLiteral::Array(HirArrayLiteral::Standard(elements)))
+        | HirExpression::Tuple(elements) => elements,
+        HirExpression::Literal(HirLiteral::Array(HirArrayLiteral::Repeated {
+            repeated_element,
+            ..
+        })) => vec![repeated_element],
+        HirExpression::Literal(HirLiteral::FmtStr(_, captures)) => captures,
+        HirExpression::Prefix(prefix) => vec![prefix.rhs],
+        HirExpression::Infix(infix) => vec![infix.lhs, infix.rhs],
+        HirExpression::Index(index) => vec![index.collection, index.index],
+        HirExpression::Constructor(constructor) => {
+            constructor.fields.into_iter().map(|(_, expr)| expr).collect()
+        }
+        HirExpression::MemberAccess(access) => vec![access.lhs],
+        HirExpression::Call(call) => std::iter::once(call.func).chain(call.arguments).collect(),
+        HirExpression::MethodCall(call) => {
+            std::iter::once(call.object).chain(call.arguments).collect()
+        }
+        HirExpression::Cast(cast) => vec![cast.lhs],
+        HirExpression::If(if_expr) => {
+            let branches = std::iter::once(if_expr.consequence).chain(if_expr.alternative);
+            std::iter::once(if_expr.condition).chain(branches).collect()
+        }
+        HirExpression::Block(_)
+        | HirExpression::Lambda(_)
+        | HirExpression::Ident(_)
+        | HirExpression::Literal(_)
+        | HirExpression::TraitMethodReference(_)
+        | HirExpression::Error => Vec::new(),
+    }
+}
+
+fn pattern_definitions(pattern: &HirPattern, locals: &mut Vec<DefinitionId>) {
+    match pattern {
+        HirPattern::Identifier(ident) => locals.push(ident.id),
+        HirPattern::Mutable(pattern, _) => pattern_definitions(pattern, locals),
+        HirPattern::Tuple(patterns, _) => {
+            for pattern in patterns {
+                pattern_definitions(pattern, locals);
+            }
+        }
+        HirPattern::Struct(_, fields, _) => {
+            for (_, pattern) in fields {
+                pattern_definitions(pattern, locals);
+            }
+        }
+    }
+}
+
+fn function_completion(
+    context: &Context,
+    func_id: FuncId,
+    kind: CompletionItemKind,
+) -> CompletionItem {
+    let interner = &context.def_interner;
+    let name = interner.function_name(&func_id).to_string();
+    let item = completion_item(name, kind, format_function(interner, func_id));
+    with_documentation(context, item, interner.function_meta(&func_id).name.location)
+}
+
+fn completion_item(label: String, kind: CompletionItemKind, detail: String) -> CompletionItem {
+    let detail = if detail.is_empty() { None } else { Some(detail) };
+    CompletionItem { label, kind: Some(kind), detail, ..CompletionItem::default() }
+}
+
+fn with_documentation(
+    context: &Context,
+    mut item: CompletionItem,
+    declaration: Location,
+) -> CompletionItem {
+    item.documentation = doc_comments(context, declaration).map(|value| {
+        Documentation::MarkupContent(MarkupContent { kind: MarkupKind::Markdown, value })
+    });
+    item
+}
+
+#[cfg(test)]
+mod completion_tests {
+    use crate::requests::{
+        on_completion_request,
+        test_utils::{init_lsp_server, insert_statement},
+    };
+
+    use super::*;
+    use lsp_types::{
+        PartialResultParams, TextDocumentIdentifier, TextDocumentPositionParams,
+        WorkDoneProgressParams,
+    };
+    use tokio::test;
+
+    fn completion_labels(response: CompletionResult) -> Vec<String> {
+        match response.expect("Could not find completions") {
+            CompletionResponse::Array(items) => items.into_iter().map(|item| item.label).collect(),
+            CompletionResponse::List(list) => {
+                list.items.into_iter().map(|item| item.label).collect()
+            }
+        }
+    }
+
+    /// Completes at the end of `line`, once it has been inserted at the start of `main` in the
+    /// 7_function test program.
+    async fn complete_after(line: &str) -> Vec<String> {
+        let (mut state, _, noir_text_document) = init_lsp_server();
+        let position = insert_statement(&mut state, &noir_text_document, line, "");
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: noir_text_document },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+            partial_result_params: PartialResultParams { partial_result_token: None },
+            context: None,
+        };
+
+        let response = on_completion_request(&mut state, params)
+            .await
+            .expect("Could not execute on_completion_request");
+        completion_labels(response)
+    }
+
+    #[test]
+    async fn test_completes_struct_fields() {
+        let labels = complete_after("let _ = ss.").await;
+        assert!(labels.contains(&"a".to_string()));
+        assert!(labels.contains(&"b".to_string()));
+    }
+
+    #[test]
+    async fn test_completes_locals_and_keywords() {
+        let labels = complete_after("let _ = ").await;
+        assert!(labels.contains(&"ss".to_string()));
+        assert!(labels.contains(&"x".to_string()));
+        assert!(labels.contains(&"let".to_string()));
+        // `my` is only declared further down in `main`
+        assert!(!labels.contains(&"my".to_string()));
+    }
+
+    #[test]
+    async fn test_completes_module_items() {
+        let labels = complete_after("let _ = crate::test_multiple").await;
+        assert!(labels.contains(&"test_multiple4".to_string()));
+        assert!(labels.contains(&"test_multiple5".to_string()));
+    }
+
+    #[test]
+    async fn test_completion_context() {
+        let context = CompletionContext::new("let x = foo::bar::ba");
+        assert_eq!(context.prefix, "ba");
+        assert!(matches!(
+            context.kind,
+            CompletionKind::Path(path) if path.segments.len() == 2 && path.kind == PathKind::Plain
+        ));
+
+        let context = CompletionContext::new("for i in 0..");
+        assert!(matches!(context.kind, CompletionKind::Name));
+    }
+}
@@ -0,0 +1,420 @@
+use std::future::{self, Future};
+
+use async_lsp::ResponseError;
+use lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
+use noirc_errors::Location;
+use noirc_frontend::{
+    hir::{def_map::ModuleId, Context},
+    hir_def::{function::FuncMeta, stmt::HirPattern},
+    node_interner::{FuncId, NodeInterner, ReferenceId},
+    FunctionVisibility, Type, Visibility,
+};
+
+use crate::{byte_span_to_range, types::HoverResult, LspState};
+
+use super::process_request;
+
+pub(crate) fn on_hover_request(
+    state: &mut LspState,
+    params: HoverParams,
+) -> impl Future<Output = Result<HoverResult, ResponseError>> {
+    let result =
+        process_request(state, params.text_document_position_params, |location, context, _| {
+            hover_at(location, context)
+        });
+    future::ready(result)
+}
+
+fn hover_at(location: Location, context: &Context) -> Option<Hover> {
+    let interner = &context.def_interner;
+    let expression_location =
+        interner.find_expression_at(location).map(|expr_id| interner.expr_location(&expr_id));
+
+    let (value, hovered_location) = match interner.find_referenced(location) {
+        Some(reference) => {
+            let hovered_location = interner
+                .reference_location(reference)
+                .filter(|declaration_location| declaration_location.contains(&location))
+                .or(expression_location);
+            (format_reference(reference, context), hovered_location)
+        }
+        None => {
+            let expr_id = interner.find_expression_at(location)?;
+            let typ = interner.id_type(expr_id);
+            if matches!(typ, Type::Error) {
+                return None;
+            }
+            (noir_code_block(&typ.to_string()), expression_location)
+        }
+    };
+
+    let range = hovered_location.and_then(|location| {
+        let files = context.file_manager.as_file_map();
+        byte_span_to_range(files, location.file, location.span.into())
+    });
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }),
+        range,
+    })
+}
+
+/// Renders the hover contents for an item: the module it is declared in, its signature
+/// and the doc comments written above its declaration.
+fn format_reference(reference: ReferenceId, context: &Context) -> String {
+    let interner = &context.def_interner;
+
+    let (module, signature) = match reference {
+        ReferenceId::Function(func_id) => {
+            (Some(interner.function_module(func_id)), format_function(interner, func_id))
+        }
+        // Structs and traits each have a module of their own, declared in the module containing them
+        ReferenceId::Struct(struct_id) | ReferenceId::StructMember(struct_id, _) => {
+            (parent_module(context, struct_id.module_id()), format_struct(interner, reference))
+        }
+        ReferenceId::Trait(trait_id) => {
+            (parent_module(context, trait_id.0), format_trait(interner, reference))
+        }
+        ReferenceId::Global(id) => {
+            let definition = interner.definition(id);
+            (None, format!("global {}: {}", definition.name, interner.id_type(id)))
+        }
+        ReferenceId::Local(id) => {
+            let definition = interner.definition(id);
+            let mutability = if definition.mutable { "mut " } else { "" };
+            (None, format!("let {mutability}{}: {}", definition.name, interner.id_type(id)))
+        }
+        ReferenceId::Module(module_id) => {
+            let name = module_name(context, module_id).unwrap_or_default();
+            (parent_module(context, module_id), format!("mod {name}"))
+        }
+    };
+
+    let mut contents = String::new();
+    if let Some(module) = module {
+        let path = module_path(context, module);
+        if !path.is_empty() {
+            contents.push_str(&noir_code_block(&path));
+            contents.push('\n');
+        }
+    }
+    contents.push_str(&noir_code_block(&signature));
+
+    let declaration_location = match reference {
+        ReferenceId::Struct(struct_id) => Some(interner.get_struct(struct_id).borrow().location),
+        ReferenceId::Trait(trait_id) => Some(interner.get_trait(trait_id).location),
+        _ => interner.reference_location(reference),
+    };
+    if let Some(docs) = declaration_location.and_then(|location| doc_comments(context, location)) {
+        contents.push_str("\n---\n\n");
+        contents.push_str(&docs);
+    }
+
+    contents
+}
+
+fn noir_code_block(code: &str) -> String {
+    format!("```noir\n{code}\n```")
+}
+
+fn parent_module(context: &Context, module: ModuleId) -> Option<ModuleId> {
+    let def_map = context.def_map(&module.krate)?;
+    let parent = def_map.modules()[module.local_id.0].parent?;
+    Some(ModuleId { krate: module.krate, local_id: parent })
+}
+
+/// Returns the name a module is declared with in its parent module.
+pub(crate) fn module_name(context: &Context, module: ModuleId) -> Option<String> {
+    let def_map = context.def_map(&module.krate)?;
+    let parent = def_map.modules()[module.local_id.0].parent?;
+    def_map.modules()[parent.0]
+        .children
+        .iter()
+        .find(|(_, child)| **child == module.local_id)
+        .map(|(name, _)| name.to_string())
+}
+
+/// Returns the `::` separated path to the given module, prefixed by the name of its crate
+/// when the module isn't part of the root crate.
+pub(crate) fn module_path(context: &Context, module: ModuleId) -> String {
+    let Some(def_map) = context.def_map(&module.krate) else {
+        return String::new();
+    };
+
+    let parent = def_map.modules()[module.local_id.0].parent;
+    let path = def_map.get_module_path_with_separator(module.local_id.0, parent, "::");
+
+    let crate_name = if module.krate.is_root() {
+        None
+    } else if module.krate.is_stdlib() {
+        Some("std".to_string())
+    } else {
+        context
+            .crates()
+            .flat_map(|crate_id| context.crate_graph[crate_id].dependencies.iter())
+            .find(|dependency| dependency.crate_id == module.krate)
+            .map(|dependency| dependency.as_name())
+    };
+
+    match crate_name {
+        Some(crate_name) if path.is_empty() => crate_name,
+        Some(crate_name) => format!("{crate_name}::{path}"),
+        None => path,
+    }
+}
+
+/// Renders the signature of a function as it would be written in source, e.g.
+/// `pub fn foo<T>(x: pub Field, y: T) -> Field where T: Eq`.
+pub(crate) fn format_function(interner: &NodeInterner, func_id: FuncId) -> String {
+    let modifiers = interner.function_modifiers(&func_id);
+    let meta = interner.function_meta(&func_id);
+
+    let mut string = String::new();
+    if let Some(attribute) = &modifiers.attributes.function {
+        string.push_str(&format!("{attribute}\n"));
+    }
+    match modifiers.visibility {
+        FunctionVisibility::Public => string.push_str("pub "),
+        FunctionVisibility::PublicCrate => string.push_str("pub(crate) "),
+        FunctionVisibility::Private => (),
+    }
+    if modifiers.is_unconstrained {
+        string.push_str("unconstrained ");
+    }
+    string.push_str("fn ");
+    string.push_str(&modifiers.name);
+
+    let mut generics = Vec::new();
+    for (_, typ, _) in &meta.parameters.0 {
+        collect_named_generics(typ, &mut generics);
+    }
+    collect_named_generics(meta.return_type(), &mut generics);
+    if !generics.is_empty() {
+        string.push_str(&format!("<{}>", generics.join(", ")));
+    }
+
+    string.push_str(&format!("({})", function_parameters(interner, meta).join(", ")));
+
+    let return_type = meta.return_type();
+    if *return_type != Type::Unit {
+        let visibility = format_visibility(meta.return_visibility);
+        string.push_str(&format!(" -> {visibility}{return_type}"));
+    }
+
+    if !meta.trait_constraints.is_empty() {
+        let constraints = meta.trait_constraints.iter().map(|constraint| {
+            let trait_name = interner
+                .try_get_trait(constraint.trait_id)
+                .map_or_else(|| "_".to_string(), |the_trait| the_trait.name.to_string());
+            format!("{}: {trait_name}", constraint.typ)
+        });
+        string.push_str(&format!(" where {}", constraints.collect::<Vec<_>>().join(", ")));
+    }
+
+    string
+}
+
+/// Renders each parameter of a function as `pattern: type`.
+pub(crate) fn function_parameters(interner: &NodeInterner, meta: &FuncMeta) -> Vec<String> {
+    meta.parameters
+        .0
+        .iter()
+        .map(|(pattern, typ, visibility)| {
+            let visibility = format_visibility(*visibility);
+            format!("{}: {visibility}{typ}", format_pattern(interner, pattern))
+        })
+        .collect()
+}
+
+fn format_visibility(visibility: Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public => "pub ",
+        Visibility::Private => "",
+        Visibility::DataBus => "call_data ",
+    }
+}
+
+fn format_pattern(interner: &NodeInterner, pattern: &HirPattern) -> String {
+    match pattern {
+        HirPattern::Identifier(ident) => interner.definition_name(ident.id).to_string(),
+        HirPattern::Mutable(pattern, _) => format!("mut {}", format_pattern(interner, pattern)),
+        HirPattern::Tuple(patterns, _) => {
+            let patterns = patterns.iter().map(|pattern| format_pattern(interner, pattern));
+            format!("({})", patterns.collect::<Vec<_>>().join(", "))
+        }
+        HirPattern::Struct(typ, fields, _) => {
+            let fields = fields
+                .iter()
+                .map(|(name, pattern)| format!("{name}: {}", format_pattern(interner, pattern)));
+            format!("{typ} {{ {} }}", fields.collect::<Vec<_>>().join(", "))
+        }
+    }
+}
+
+/// Collects the names of the generics used in `typ`, in order of appearance and without duplicates.
+fn collect_named_generics(typ: &Type, generics: &mut Vec<String>) {
+    match typ {
+        Type::NamedGeneric(_, name) => {
+            if !generics.iter().any(|generic| generic == name.as_str()) {
+                generics.push(name.to_string());
+            }
+        }
+        Type::Array(length, element) => {
+            collect_named_generics(length, generics);
+            collect_named_generics(element, generics);
+        }
+        Type::String(length) => collect_named_generics(length, generics),
+        Type::FmtString(length, elements) => {
+            collect_named_generics(length, generics);
+            collect_named_generics(elements, generics);
+        }
+        Type::Struct(_, args) | Type::Tuple(args) => {
+            for arg in args {
+                collect_named_generics(arg, generics);
+            }
+        }
+        Type::Function(args, ret, _) => {
+            for arg in args {
+                collect_named_generics(arg, generics);
+            }
+            collect_named_generics(ret, generics);
+        }
+        Type::MutableReference(element) => collect_named_generics(element, generics),
+        Type::Forall(_, typ) => collect_named_generics(typ, generics),
+        _ => (),
+    }
+}
+
+/// Renders a struct declaration, or only the given field for a [ReferenceId::StructMember].
+fn format_struct(interner: &NodeInterner, reference: ReferenceId) -> String {
+    let (struct_id, member) = match reference {
+        ReferenceId::Struct(struct_id) => (struct_id, None),
+        ReferenceId::StructMember(struct_id, index) => (struct_id, Some(index)),
+        _ => unreachable!("Expected a struct or a struct member"),
+    };
+    let struct_type = interner.get_struct(struct_id);
+    let struct_type = struct_type.borrow();
+
+    if let Some(index) = member {
+        let (name, typ) = struct_type.field_at(index);
+        return format!("{}\n{name}: {typ}", struct_type.name);
+    }
+
+    let mut generics = Vec::new();
+    for index in 0..struct_type.num_fields() {
+        collect_named_generics(&struct_type.field_at(index).1, &mut generics);
+    }
+
+    let mut string = format!("struct {}", struct_type.name);
+    if !generics.is_empty() {
+        string.push_str(&format!("<{}>", generics.join(", ")));
+    }
+    string.push_str(" {\n");
+    for index in 0..struct_type.num_fields() {
+        let (name, typ) = struct_type.field_at(index);
+        string.push_str(&format!("    {name}: {typ},\n"));
+    }
+    string.push('}');
+    string
+}
+
+/// Renders a trait declaration along with the signatures of its methods.
+fn format_trait(interner: &NodeInterner, reference: ReferenceId) -> String {
+    let ReferenceId::Trait(trait_id) = reference else {
+        unreachable!("Expected a trait");
+    };
+    let the_trait = interner.get_trait(trait_id);
+
+    let mut string = format!("trait {} {{\n", the_trait.name);
+    for method in &the_trait.methods {
+        let Some(func_id) = the_trait.method_ids.get(&method.name.0.contents) else {
+            continue;
+        };
+        if interner.try_function_meta(func_id).is_some() {
+            string.push_str(&format!("    {};\n", format_function(interner, *func_id)));
+        }
+    }
+    string.push('}');
+    string
+}
+
+/// Collects the `///` doc comments written directly above the item declared at `location`,
+/// skipping any attributes found between the comments and the item.
+pub(crate) fn doc_comments(context: &Context, location: Location) -> Option<String> {
+    let source = context.file_manager.fetch_file(location.file);
+    let preceding_source = source.get(..location.span.start() as usize)?;
+
+    let mut lines = preceding_source.lines().rev();
+    // The last line is the start of the declaration itself, unless the declaration starts a line
+    if !preceding_source.ends_with('\n') {
+        lines.next();
+    }
+
+    let mut comments = Vec::new();
+    for line in lines {
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix("///") {
+            comments.push(comment.strip_prefix(' ').unwrap_or(comment));
+        } else if !line.starts_with("#[") {
+            break;
+        }
+    }
+
+    if comments.is_empty() {
+        None
+    } else {
+        comments.reverse();
+        Some(comments.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod hover_tests {
+    use lsp_types::{Position, TextDocumentIdentifier, TextDocumentPositionParams};
+    use tokio::test;
+
+    use crate::requests::test_utils::init_lsp_server;
+
+    use super::*;
+
+    #[test]
+    async fn test_on_hover_request() {
+        let (mut state, _, noir_text_document) = init_lsp_server();
+
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: noir_text_document },
+                position: Position { line: 95, character: 5 },
+            },
+            work_done_progress_params: Default::default(),
+        };
+
+        let response =
+            on_hover_request(&mut state, params).await.expect("Could execute on_hover_request");
+
+        let Some(Hover { contents: HoverContents::Markup(markup), .. }) = response else {
+            panic!("Expected a markdown hover response");
+        };
+        assert!(markup.value.contains("fn test_multiple5(a: (u32, u32))"));
+    }
+
+    #[test]
+    async fn hover_requests_reuse_the_checked_package() {
+        let (mut state, root_path, noir_text_document) = init_lsp_server();
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: noir_text_document },
+                position: Position { line: 95, character: 5 },
+            },
+            work_done_progress_params: Default::default(),
+        };
+
+        let first = on_hover_request(&mut state, params.clone()).await.unwrap();
+        assert_eq!(state.cached_packages.len(), 1);
+        assert!(state.cached_packages.contains_key(&root_path));
+
+        let second = on_hover_request(&mut state, params).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(state.cached_packages.len(), 1);
+    }
+}
@@ -0,0 +1,264 @@
+use std::future::{self, Future};
+
+use async_lsp::ResponseError;
+use fm::FileId;
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, InlayHintParams, Position, Range};
+use noirc_errors::Span;
+use noirc_frontend::{
+    hir::Context,
+    hir_def::{
+        expr::{HirExpression, HirIdent},
+        stmt::{HirPattern, HirStatement},
+    },
+    node_interner::{DefinitionKind, ExprId, NodeInterner},
+    Type,
+};
+
+use crate::{byte_span_to_range, types::InlayHintResult, LspState};
+
+use super::{
+    completion::{child_expressions, statement_expressions},
+    process_document,
+};
+
+pub(crate) fn on_inlay_hint_request(
+    state: &mut LspState,
+    params: InlayHintParams,
+) -> impl Future<Output = Result<InlayHintResult, ResponseError>> {
+    let range = params.range;
+    let result = process_document(state, &params.text_document.uri, |file_id, context, _| {
+        let mut collector = InlayHintCollector { context, file_id, range, hints: Vec::new() };
+        for func_id in context.def_interner.functions_in_file(file_id) {
+            let body = context.def_interner.function(&func_id);
+            collector.collect_in_expression(body.as_expr());
+        }
+        Some(collector.hints)
+    });
+    future::ready(result)
+}
+
+/// Walks the type checked bodies of the functions in a file, collecting the hints which lie
+/// within the requested range.
+struct InlayHintCollector<'a> {
+    context: &'a Context,
+    file_id: FileId,
+    range: Range,
+    hints: Vec<InlayHint>,
+}
+
+impl<'a> InlayHintCollector<'a> {
+    fn interner(&self) -> &'a NodeInterner {
+        &self.context.def_interner
+    }
+
+    fn collect_in_expression(&mut self, expr_id: &ExprId) {
+        let interner = self.interner();
+        // Function bodies from other files, such as trait defaults, aren't part of this document
+        match interner.try_expr_location(expr_id) {
+            Some(location) if location.file == self.file_id => (),
+            _ => return,
+        }
+
+        match interner.expression(expr_id) {
+            HirExpression::Block(block) => {
+                for statement in block.statements() {
+                    let statement = interner.statement(statement);
+                    if let HirStatement::Let(let_statement) = &statement {
+                        // Bindings without a type annotation are resolved with an error type
+                        if matches!(let_statement.r#type, Type::Error) {
+                            self.collect_type_hints(&let_statement.pattern);
+                        }
+                    }
+                    for expr_id in statement_expressions(&statement) {
+                        self.collect_in_expression(&expr_id);
+                    }
+                }
+            }
+            HirExpression::Lambda(lambda) => {
+                for (pattern, _) in &lambda.parameters {
+                    if !self.is_annotated(pattern) {
+                        self.collect_type_hints(pattern);
+                    }
+                }
+                self.collect_in_expression(&lambda.body);
+            }
+            HirExpression::Call(call) => {
+                self.collect_parameter_hints(call.func, &call.arguments, call.location.span);
+                self.collect_in_expression(&call.func);
+                for argument in &call.arguments {
+                    self.collect_in_expression(argument);
+                }
+            }
+            expression => {
+                for expr_id in child_expressions(expression) {
+                    self.collect_in_expression(&expr_id);
+                }
+            }
+        }
+    }
+
+    /// Shows the inferred type after each name bound by the pattern.
+    fn collect_type_hints(&mut self, pattern: &HirPattern) {
+        for ident in pattern_identifiers(pattern) {
+            let typ = self.interner().id_type(ident.id).follow_bindings();
+            if matches!(typ, Type::Error) {
+                continue;
+            }
+            if let Some(range) = self.range_of(ident.location.span) {
+                self.push_hint(range.end, format!(": {typ}"), InlayHintKind::TYPE);
+            }
+        }
+    }
+
+    /// Shows the name of the parameter before each argument of a call to a known function.
+    fn collect_parameter_hints(&mut self, func: ExprId, arguments: &[ExprId], call_span: Span) {
+        let interner = self.interner();
+        let HirExpression::Ident(func_ident) = interner.expression(&func) else {
+            return;
+        };
+        let DefinitionKind::Function(func_id) = interner.definition(func_ident.id).kind else {
+            return;
+        };
+
+        let parameters = &interner.function_meta(&func_id).parameters.0;
+        // Method calls are type checked into calls spanning the whole expression, with the object
+        // as their first argument, which shouldn't be labelled with `self`
+        let is_method_call = func_ident.location.span == call_span;
+        let skip = usize::from(is_method_call && !parameters.is_empty());
+
+        for ((pattern, _, _), argument) in parameters.iter().zip(arguments).skip(skip) {
+            let Some(name) = pattern_identifiers(pattern).first().map(|ident| ident.id) else {
+                continue;
+            };
+            let name = interner.definition_name(name);
+            if name.starts_with('_') || self.is_named(argument, name) {
+                continue;
+            }
+            let Some(argument_location) = interner.try_expr_location(argument) else {
+                continue;
+            };
+            if let Some(range) = self.range_of(argument_location.span) {
+                self.push_hint(range.start, format!("{name}:"), InlayHintKind::PARAMETER);
+            }
+        }
+    }
+
+    /// Whether the argument is a variable sharing the parameter's name, making a hint redundant.
+    fn is_named(&self, argument: &ExprId, name: &str) -> bool {
+        let interner = self.interner();
+        match interner.expression(argument) {
+            HirExpression::Ident(ident) => interner.definition_name(ident.id) == name,
+            _ => false,
+        }
+    }
+
+    /// Whether the closure parameter is followed by a type annotation in the source.
+    fn is_annotated(&self, pattern: &HirPattern) -> bool {
+        let Some(ident) = pattern_identifiers(pattern).last().copied() else {
+            return true;
+        };
+        let source = self.context.file_manager.fetch_file(self.file_id);
+        source
+            .get(ident.location.span.end() as usize..)
+            .map_or(true, |rest| rest.trim_start().starts_with(':'))
+    }
+
+    fn range_of(&self, span: Span) -> Option<Range> {
+        let files = self.context.file_manager.as_file_map();
+        byte_span_to_range(files, self.file_id, span.into())
+    }
+
+    fn push_hint(&mut self, position: Position, label: String, kind: InlayHintKind) {
+        if position < self.range.start || position > self.range.end {
+            return;
+        }
+        let is_parameter = kind == InlayHintKind::PARAMETER;
+        self.hints.push(InlayHint {
+            position,
+            label: InlayHintLabel::String(label),
+            kind: Some(kind),
+            text_edits: None,
+            tooltip: None,
+            padding_left: None,
+            padding_right: Some(is_parameter),
+            data: None,
+        });
+    }
+}
+
+fn pattern_identifiers(pattern: &HirPattern) -> Vec<&HirIdent> {
+    match pattern {
+        HirPattern::Identifier(ident) => vec![ident],
+        HirPattern::Mutable(pattern, _) => pattern_identifiers(pattern),
+        HirPattern::Tuple(patterns, _) => patterns.iter().flat_map(pattern_identifiers).collect(),
+        HirPattern::Struct(_, fields, _) => {
+            fields.iter().flat_map(|(_, pattern)| pattern_identifiers(pattern)).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod inlay_hint_tests {
+    use crate::requests::{on_inlay_hint_request, test_utils::init_lsp_server};
+
+    use super::*;
+    use lsp_types::{TextDocumentIdentifier, WorkDoneProgressParams};
+    use tokio::test;
+
+    async fn inlay_hints(range: Range) -> Vec<(Position, String)> {
+        let (mut state, _, noir_text_document) = init_lsp_server();
+
+        let params = InlayHintParams {
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+            text_document: TextDocumentIdentifier { uri: noir_text_document },
+            range,
+        };
+        let hints = on_inlay_hint_request(&mut state, params)
+            .await
+            .expect("Could not execute on_inlay_hint_request")
+            .expect("Could not collect inlay hints");
+
+        hints
+            .into_iter()
+            .map(|hint| match hint.label {
+                InlayHintLabel::String(label) => (hint.position, label),
+                InlayHintLabel::LabelParts(_) => panic!("Expected a string label"),
+            })
+            .collect()
+    }
+
+    fn lines(start: u32, end: u32) -> Range {
+        Range {
+            start: Position { line: start, character: 0 },
+            end: Position { line: end, character: u32::MAX },
+        }
+    }
+
+    #[test]
+    async fn test_inferred_let_types() {
+        // `let mut ss: my_struct = ...` is annotated while `let my = ...` isn't
+        assert!(inlay_hints(lines(93, 93)).await.is_empty());
+        let hints = inlay_hints(lines(96, 96)).await;
+        assert_eq!(hints, vec![(Position { line: 96, character: 10 }, ": my2".to_string())]);
+    }
+
+    #[test]
+    async fn test_parameter_names() {
+        // `test2(x as Field, y);` calls `fn test2(z: Field, t: u32)`
+        let hints = inlay_hints(lines(109, 109)).await;
+        assert_eq!(
+            hints,
+            vec![
+                (Position { line: 109, character: 10 }, "z:".to_string()),
+                (Position { line: 109, character: 22 }, "t:".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    async fn test_parameter_names_matching_arguments_are_hidden() {
+        // `let e = test_multiple(x, y);` calls `fn test_multiple(x: u32, y: u32)`
+        let hints = inlay_hints(lines(118, 118)).await;
+        assert_eq!(hints, vec![(Position { line: 118, character: 9 }, ": (u32, u32)".to_string())]);
+    }
+}
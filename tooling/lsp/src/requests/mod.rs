@@ -1,14 +1,26 @@
-use std::future::Future;
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::{Path, PathBuf},
+};
 
 use crate::types::{CodeLensOptions, InitializeParams};
-use async_lsp::ResponseError;
-use lsp_types::{Position, TextDocumentSyncCapability, TextDocumentSyncKind};
+use async_lsp::{ErrorCode, ResponseError};
+use lsp_types::{
+    Position, TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use nargo::{package::Package, workspace::Workspace};
 use nargo_fmt::Config;
+use noirc_frontend::{
+    graph::CrateId,
+    hir::{def_collector::dc_crate::CompilationError, Context},
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    resolve_workspace_for_source_path,
     types::{InitializeResult, NargoCapability, NargoTestsOptions, ServerCapabilities},
-    LspState,
+    CachedPackage, LspState,
 };
 
 // Handlers
@@ -21,15 +33,32 @@ use crate::{
 // They are not attached to the `NargoLspService` struct so they can be unit tested with only `LspState`
 // and params passed in.
 
+mod code_action;
 mod code_lens_request;
+mod completion;
 mod goto_definition;
+mod hover;
+mod inlay_hint;
 mod profile_run;
+mod references;
+mod rename;
+mod semantic_tokens;
+mod signature_help;
+mod symbols;
 mod test_run;
 mod tests;
 
+use goto_definition::position_to_byte_index;
+
 pub(crate) use {
-    code_lens_request::collect_lenses_for_package, code_lens_request::on_code_lens_request,
-    goto_definition::on_goto_definition_request, profile_run::on_profile_run_request,
+    code_action::on_code_action_request, code_lens_request::collect_lenses_for_package,
+    code_lens_request::on_code_lens_request, completion::on_completion_request,
+    goto_definition::on_goto_definition_request, hover::on_hover_request,
+    inlay_hint::on_inlay_hint_request, profile_run::on_profile_run_request,
+    references::on_references_request, rename::on_prepare_rename_request,
+    rename::on_rename_request, semantic_tokens::on_semantic_tokens_full_request,
+    semantic_tokens::on_semantic_tokens_range_request, signature_help::on_signature_help_request,
+    symbols::on_document_symbol_request, symbols::on_workspace_symbol_request,
     test_run::on_test_run_request, tests::on_tests_request,
 };
 
@@ -88,6 +117,43 @@ pub(crate) fn on_initialize(
                 document_formatting_provider: true,
                 nargo: Some(nargo),
                 definition_provider: Some(lsp_types::OneOf::Left(true)),
+                hover_provider: Some(lsp_types::HoverProviderCapability::Simple(true)),
+                references_provider: Some(lsp_types::OneOf::Left(true)),
+                completion_provider: Some(lsp_types::CompletionOptions {
+                    trigger_characters: Some(vec![".".to_string(), ":".to_string()]),
+                    ..lsp_types::CompletionOptions::default()
+                }),
+                document_symbol_provider: Some(lsp_types::OneOf::Left(true)),
+                workspace_symbol_provider: Some(lsp_types::OneOf::Left(true)),
+                inlay_hint_provider: Some(lsp_types::OneOf::Left(true)),
+                semantic_tokens_provider: Some(
+                    lsp_types::SemanticTokensOptions {
+                        legend: semantic_tokens::semantic_tokens_legend(),
+                        range: Some(true),
+                        full: Some(lsp_types::SemanticTokensFullOptions::Bool(true)),
+                        ..lsp_types::SemanticTokensOptions::default()
+                    }
+                    .into(),
+                ),
+                signature_help_provider: Some(lsp_types::SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: lsp_types::WorkDoneProgressOptions {
+                        work_done_progress: None,
+                    },
+                }),
+                code_action_provider: Some(lsp_types::CodeActionProviderCapability::Options(
+                    lsp_types::CodeActionOptions {
+                        code_action_kinds: Some(vec![lsp_types::CodeActionKind::QUICKFIX]),
+                        ..lsp_types::CodeActionOptions::default()
+                    },
+                )),
+                rename_provider: Some(lsp_types::OneOf::Right(lsp_types::RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: lsp_types::WorkDoneProgressOptions {
+                        work_done_progress: None,
+                    },
+                })),
             },
             server_info: None,
         })
@@ -130,6 +196,205 @@ fn on_formatting_inner(
     }
 }
 
+/// Type checks the package containing the document at the given position and runs `callback` with
+/// the [Location][noirc_errors::Location] of that position, the checked [Context] and the package's [CrateId].
+///
+/// Open documents are compiled using their unsaved contents rather than the files on disk. The
+/// package is only checked if it isn't already cached.
+pub(crate) fn process_request<F, T>(
+    state: &mut LspState,
+    text_document_position_params: TextDocumentPositionParams,
+    callback: F,
+) -> Result<T, ResponseError>
+where
+    F: FnOnce(noirc_errors::Location, &Context, CrateId) -> T,
+{
+    let uri = &text_document_position_params.text_document.uri;
+    let position = &text_document_position_params.position;
+    process_document(state, uri, |file_id, context, crate_id| {
+        let location = position_to_location(context, file_id, position)?;
+        Ok(callback(location, context, crate_id))
+    })?
+}
+
+/// Like [process_request] but compiles the documents in `input_files`, keyed by URI, in place of
+/// the open documents.
+///
+/// As the documents differ from the open ones, the package is always checked again.
+pub(crate) fn process_request_with_input_files<F, T>(
+    input_files: &HashMap<String, String>,
+    text_document_position_params: TextDocumentPositionParams,
+    callback: F,
+) -> Result<T, ResponseError>
+where
+    F: FnOnce(noirc_errors::Location, &Context, CrateId) -> T,
+{
+    let uri = &text_document_position_params.text_document.uri;
+    let file_path = uri_to_file_path(uri)?;
+    let (workspace, package) = package_for_file(&file_path)?;
+    let checked = CachedPackage::check(input_files, &workspace, &package);
+
+    let file_id = file_id_for_path(&checked.context, &file_path)?;
+    let location =
+        position_to_location(&checked.context, file_id, &text_document_position_params.position)?;
+    Ok(callback(location, &checked.context, checked.crate_id))
+}
+
+/// Type checks the package containing the given document and runs `callback` with the document's
+/// [FileId][fm::FileId], the checked [Context] and the package's [CrateId].
+pub(crate) fn process_document<F, T>(
+    state: &mut LspState,
+    uri: &Url,
+    callback: F,
+) -> Result<T, ResponseError>
+where
+    F: FnOnce(fm::FileId, &Context, CrateId) -> T,
+{
+    process_document_with_errors(state, uri, |file_id, context, crate_id, _| {
+        callback(file_id, context, crate_id)
+    })
+}
+
+/// Like [process_document] but also passes `callback` the errors and warnings found while checking
+/// the package.
+pub(crate) fn process_document_with_errors<F, T>(
+    state: &mut LspState,
+    uri: &Url,
+    callback: F,
+) -> Result<T, ResponseError>
+where
+    F: FnOnce(fm::FileId, &Context, CrateId, &[(CompilationError, fm::FileId)]) -> T,
+{
+    let file_path = uri_to_file_path(uri)?;
+    let checked = cached_package_for_file(state, &file_path)?;
+    let file_id = file_id_for_path(&checked.context, &file_path)?;
+    Ok(callback(file_id, &checked.context, checked.crate_id, &checked.errors))
+}
+
+/// Type checks `package` and runs `callback` with the checked [Context] and the package's [CrateId].
+///
+/// The package is only checked if it isn't already cached.
+pub(crate) fn check_package<F, T>(
+    state: &mut LspState,
+    workspace: &Workspace,
+    package: &Package,
+    callback: F,
+) -> T
+where
+    F: FnOnce(&Context, CrateId) -> T,
+{
+    let checked = cached_package(state, workspace, package);
+    callback(&checked.context, checked.crate_id)
+}
+
+/// Returns the checked package containing `file_path`, checking it if it isn't already cached.
+fn cached_package_for_file<'a>(
+    state: &'a mut LspState,
+    file_path: &Path,
+) -> Result<&'a CachedPackage, ResponseError> {
+    let cached_root = state
+        .cached_packages
+        .iter()
+        .filter(|(root_dir, checked)| {
+            file_path.starts_with(root_dir)
+                && checked.context.file_manager.name_to_id(file_path.to_path_buf()).is_some()
+        })
+        .map(|(root_dir, _)| root_dir)
+        .max_by_key(|root_dir| root_dir.components().count())
+        .cloned();
+    if let Some(root_dir) = cached_root {
+        return Ok(&state.cached_packages[&root_dir]);
+    }
+
+    let (workspace, package) = package_for_file(file_path)?;
+    Ok(cached_package(state, &workspace, &package))
+}
+
+/// Returns the checked `package`, checking it if it isn't already cached.
+fn cached_package<'a>(
+    state: &'a mut LspState,
+    workspace: &Workspace,
+    package: &Package,
+) -> &'a CachedPackage {
+    let input_files = &state.input_files;
+    state
+        .cached_packages
+        .entry(package.root_dir.clone())
+        .or_insert_with(|| CachedPackage::check(input_files, workspace, package))
+}
+
+/// Resolves the workspace containing `file_path` and finds the package the file belongs to.
+fn package_for_file(file_path: &Path) -> Result<(Workspace, Package), ResponseError> {
+    let workspace = resolve_workspace_for_source_path(file_path)
+        .map_err(|err| ResponseError::new(ErrorCode::REQUEST_FAILED, err.to_string()))?;
+    let package = workspace
+        .members
+        .iter()
+        .find(|package| file_path.starts_with(&package.root_dir))
+        .or_else(|| workspace.members.first())
+        .cloned()
+        .ok_or_else(|| {
+            ResponseError::new(ErrorCode::REQUEST_FAILED, "Could not find package for file")
+        })?;
+    Ok((workspace, package))
+}
+
+fn file_id_for_path(context: &Context, file_path: &Path) -> Result<fm::FileId, ResponseError> {
+    context.file_manager.name_to_id(file_path.to_path_buf()).ok_or(ResponseError::new(
+        ErrorCode::REQUEST_FAILED,
+        format!("Could not find file in file manager. File path: {:?}", file_path),
+    ))
+}
+
+fn position_to_location(
+    context: &Context,
+    file_id: fm::FileId,
+    position: &Position,
+) -> Result<noirc_errors::Location, ResponseError> {
+    let files = context.file_manager.as_file_map();
+    let byte_index = position_to_byte_index(files, file_id, position).map_err(|err| {
+        ResponseError::new(
+            ErrorCode::REQUEST_FAILED,
+            format!("Could not convert position to byte index. Error: {:?}", err),
+        )
+    })?;
+
+    Ok(noirc_errors::Location {
+        file: file_id,
+        span: noirc_errors::Span::single_char(byte_index as u32),
+    })
+}
+
+/// Returns every package which could refer to items declared in `file_path`: the members of the
+/// workspace containing the file and, if the client opened a different workspace, its members too.
+pub(crate) fn workspace_packages(
+    state: &LspState,
+    file_path: &Path,
+) -> Result<Vec<(Workspace, Package)>, ResponseError> {
+    let workspace = resolve_workspace_for_source_path(file_path)
+        .map_err(|err| ResponseError::new(ErrorCode::REQUEST_FAILED, err.to_string()))?;
+    let root_workspace = state
+        .root_path
+        .as_deref()
+        .and_then(|root_path| resolve_workspace_for_source_path(root_path).ok())
+        .filter(|root_workspace| root_workspace.root_dir != workspace.root_dir);
+
+    let mut packages: Vec<(Workspace, Package)> = Vec::new();
+    for workspace in std::iter::once(workspace).chain(root_workspace) {
+        for package in &workspace.members {
+            if packages.iter().all(|(_, other)| other.root_dir != package.root_dir) {
+                packages.push((workspace.clone(), package.clone()));
+            }
+        }
+    }
+    Ok(packages)
+}
+
+pub(crate) fn uri_to_file_path(uri: &Url) -> Result<PathBuf, ResponseError> {
+    uri.to_file_path()
+        .map_err(|_| ResponseError::new(ErrorCode::REQUEST_FAILED, "URI is not a valid file path"))
+}
+
 pub(crate) fn on_shutdown(
     _state: &mut LspState,
     _params: (),
@@ -137,6 +402,73 @@ pub(crate) fn on_shutdown(
     async { Ok(()) }
 }
 
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use std::path::PathBuf;
+
+    use async_lsp::ClientSocket;
+    use lsp_types::Url;
+
+    use crate::{solver::MockBackend, LspState};
+
+    /// The statement at the start of `main` in the 7_function test program after which tests
+    /// insert their own statements.
+    const INSERTION_ANCHOR: &str = "let mut ss: my_struct = ";
+
+    /// Creates the state of a server without a client, along with the root directory of the
+    /// 7_function test program and the URI of its `main.nr`.
+    pub(crate) fn init_lsp_server() -> (LspState, PathBuf, Url) {
+        let client = ClientSocket::new_closed();
+        let state = LspState::new(&client, MockBackend);
+
+        let root_path = test_program_dir();
+        let noir_text_document = Url::from_file_path(root_path.join("src/main.nr").as_path())
+            .expect("Could not convert text document path to URI");
+        (state, root_path, noir_text_document)
+    }
+
+    pub(crate) fn test_program_dir() -> PathBuf {
+        std::env::current_dir()
+            .unwrap()
+            .join("../../test_programs/execution_success/7_function")
+            .canonicalize()
+            .expect("Could not resolve root path")
+    }
+
+    /// The source of the 7_function test program's `main.nr`.
+    pub(crate) fn test_program_source() -> String {
+        std::fs::read_to_string(test_program_dir().join("src/main.nr")).unwrap()
+    }
+
+    /// The line on which [insert_statement] inserts its statement.
+    pub(crate) fn insertion_line() -> u32 {
+        let source = test_program_source();
+        let anchor_line = source
+            .lines()
+            .position(|line| line.trim_start().starts_with(INSERTION_ANCHOR))
+            .expect("Could not find the insertion anchor in the test program");
+        anchor_line as u32 + 1
+    }
+
+    /// Opens the 7_function test program's `main.nr` in `state` once `statement` has been
+    /// inserted after `let mut ss: my_struct = ...;` at the start of `main` and `items` has been
+    /// appended to the end of the file. Returns the position at the end of the statement.
+    pub(crate) fn insert_statement(
+        state: &mut LspState,
+        noir_text_document: &Url,
+        statement: &str,
+        items: &str,
+    ) -> lsp_types::Position {
+        let line = insertion_line();
+        let source = test_program_source();
+        let mut lines: Vec<_> = source.lines().map(|line| line.to_string()).collect();
+        lines.insert(line as usize, format!("    {statement}"));
+        let source = format!("{}\n{items}", lines.join("\n"));
+        state.input_files.insert(noir_text_document.to_string(), source);
+        lsp_types::Position { line, character: 4 + statement.len() as u32 }
+    }
+}
+
 #[cfg(test)]
 mod initialization {
     use async_lsp::ClientSocket;
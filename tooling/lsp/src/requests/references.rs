@@ -0,0 +1,175 @@
+use std::{
+    future::{self, Future},
+    path::PathBuf,
+};
+
+use async_lsp::ResponseError;
+use lsp_types::{ReferenceParams, TextDocumentPositionParams};
+use noirc_errors::{Location, Span};
+use noirc_frontend::{
+    hir::Context,
+    node_interner::{NodeInterner, ReferenceId},
+};
+
+use crate::{types::ReferencesResult, LspState};
+
+use super::{
+    check_package, goto_definition::to_lsp_location, process_request, uri_to_file_path,
+    workspace_packages,
+};
+
+pub(crate) fn on_references_request(
+    state: &mut LspState,
+    params: ReferenceParams,
+) -> impl Future<Output = Result<ReferencesResult, ResponseError>> {
+    let include_declaration = params.context.include_declaration;
+    let result = collect_references(state, params.text_document_position, |context, reference| {
+        let files = context.file_manager.as_file_map();
+        context
+            .def_interner
+            .find_references(reference, include_declaration)
+            .into_iter()
+            .filter_map(|location| to_lsp_location(files, location.file, location.span))
+            .collect()
+    });
+    future::ready(result)
+}
+
+/// Where an item is declared, kept as a path rather than a [FileId][fm::FileId] so that the
+/// item can be found again in the [Context] of each package which may refer to it.
+struct Declaration {
+    path: PathBuf,
+    span: Span,
+}
+
+/// Finds the item referred to at the given position and runs `collect` for it, and for each trait
+/// method sharing its name, in every package of the workspace.
+///
+/// Returns the results of `collect` without duplicates, or `None` if there is no item at the position.
+pub(super) fn collect_references<F, T>(
+    state: &mut LspState,
+    text_document_position_params: TextDocumentPositionParams,
+    mut collect: F,
+) -> Result<Option<Vec<T>>, ResponseError>
+where
+    F: FnMut(&Context, ReferenceId) -> Vec<T>,
+    T: PartialEq,
+{
+    let file_path = uri_to_file_path(&text_document_position_params.text_document.uri)?;
+
+    let mut results = Vec::new();
+    let declaration =
+        process_request(state, text_document_position_params, |location, context, _| {
+            let interner = &context.def_interner;
+            let reference = interner.find_referenced(location)?;
+            let declaration = interner.reference_location(reference).map(|location| {
+                let path = context.file_manager.path(location.file).to_path_buf();
+                Declaration { path, span: location.span }
+            });
+            if declaration.is_none() {
+                // Items without a declaration, such as crate roots, are only found in this package
+                for reference in related_references(interner, reference) {
+                    results.extend(collect(context, reference));
+                }
+            }
+            Some(declaration)
+        })?;
+    let declaration = match declaration {
+        None => return Ok(None),
+        Some(None) => return Ok(Some(results)),
+        Some(Some(declaration)) => declaration,
+    };
+
+    for (workspace, package) in workspace_packages(state, &file_path)? {
+        check_package(state, &workspace, &package, |context, _| {
+            let Some(file_id) = context.file_manager.name_to_id(declaration.path.clone()) else {
+                return;
+            };
+            let interner = &context.def_interner;
+            let location = Location::new(declaration.span, file_id);
+            let Some(reference) = interner.find_referenced(location) else {
+                return;
+            };
+            for reference in related_references(interner, reference) {
+                for result in collect(context, reference) {
+                    if !results.contains(&result) {
+                        results.push(result);
+                    }
+                }
+            }
+        });
+    }
+
+    Ok(Some(results))
+}
+
+/// Trait methods must keep the same name as their implementations, so these are searched together.
+fn related_references(interner: &NodeInterner, reference: ReferenceId) -> Vec<ReferenceId> {
+    match reference {
+        ReferenceId::Function(func_id) => {
+            interner.trait_method_family(func_id).into_iter().map(ReferenceId::Function).collect()
+        }
+        _ => vec![reference],
+    }
+}
+
+#[cfg(test)]
+mod references_tests {
+    use crate::requests::{on_references_request, test_utils::init_lsp_server};
+
+    use super::*;
+    use lsp_types::{
+        PartialResultParams, Position, ReferenceContext, TextDocumentIdentifier, Url,
+        WorkDoneProgressParams,
+    };
+    use tokio::test;
+
+    fn references_params(
+        noir_text_document: Url,
+        position: Position,
+        include_declaration: bool,
+    ) -> ReferenceParams {
+        ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: noir_text_document },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+            partial_result_params: PartialResultParams { partial_result_token: None },
+            context: ReferenceContext { include_declaration },
+        }
+    }
+
+    #[test]
+    async fn test_on_references_request_for_struct_field() {
+        let (mut state, _, noir_text_document) = init_lsp_server();
+
+        // The `a` in `ss.a = 61;`
+        let params =
+            references_params(noir_text_document, Position { line: 97, character: 7 }, true);
+        let locations = on_references_request(&mut state, params)
+            .await
+            .expect("Could not execute on_references_request")
+            .expect("Could not find references for the struct field");
+
+        let lines: Vec<_> = locations.iter().map(|location| location.range.start.line).collect();
+        // Its declaration, its use in the constructor and the assignment
+        assert!(lines.contains(&61));
+        assert!(lines.contains(&93));
+        assert!(lines.contains(&97));
+    }
+
+    #[test]
+    async fn test_on_references_request_excludes_declaration() {
+        let (mut state, _, noir_text_document) = init_lsp_server();
+
+        let params =
+            references_params(noir_text_document, Position { line: 97, character: 7 }, false);
+        let locations = on_references_request(&mut state, params)
+            .await
+            .expect("Could not execute on_references_request")
+            .expect("Could not find references for the struct field");
+
+        assert!(locations.iter().all(|location| location.range.start.line != 61));
+    }
+}
@@ -0,0 +1,552 @@
+use std::{
+    collections::HashMap,
+    future::{self, Future},
+    path::PathBuf,
+};
+
+use async_lsp::{ErrorCode, ResponseError};
+use fm::FILE_EXTENSION;
+use lsp_types::{
+    DocumentChangeOperation, DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier,
+    PrepareRenameResponse, RenameFile, RenameParams, ResourceOp, TextDocumentEdit,
+    TextDocumentPositionParams, TextEdit, Url, WorkspaceEdit,
+};
+use noirc_errors::{Location, Span};
+use noirc_frontend::{
+    hir::{
+        def_map::{LocalModuleId, ModuleDefId, ModuleId},
+        Context,
+    },
+    lexer::Lexer,
+    node_interner::ReferenceId,
+    token::Token,
+    Ident, Path,
+};
+
+use crate::{
+    byte_span_to_range,
+    types::{PrepareRenameResult, RenameResult},
+    LspState,
+};
+
+use super::{
+    completion::{locals_in_scope, module_at},
+    goto_definition::to_lsp_location,
+    hover::{module_name, module_path},
+    process_request,
+    references::collect_references,
+    uri_to_file_path, workspace_packages,
+};
+
+pub(crate) fn on_prepare_rename_request(
+    state: &mut LspState,
+    params: TextDocumentPositionParams,
+) -> impl Future<Output = Result<PrepareRenameResult, ResponseError>> {
+    future::ready(prepare_rename(state, params))
+}
+
+pub(crate) fn on_rename_request(
+    state: &mut LspState,
+    params: RenameParams,
+) -> impl Future<Output = Result<RenameResult, ResponseError>> {
+    future::ready(rename(state, params))
+}
+
+/// Returns the range of the name at the given position, provided it refers to an item which can
+/// be renamed: one declared by name in a package of the workspace rather than in a dependency.
+fn prepare_rename(
+    state: &mut LspState,
+    params: TextDocumentPositionParams,
+) -> Result<PrepareRenameResult, ResponseError> {
+    let file_path = uri_to_file_path(&params.text_document.uri)?;
+    let package_roots: Vec<PathBuf> = workspace_packages(state, &file_path)?
+        .into_iter()
+        .map(|(_, package)| package.root_dir)
+        .collect();
+
+    process_request(state, params, |location, context, _| {
+        let interner = &context.def_interner;
+        let Some(reference) = interner.find_referenced(location) else {
+            return Ok(None);
+        };
+
+        let declaration = interner.reference_location(reference).ok_or_else(|| {
+            ResponseError::new(ErrorCode::REQUEST_FAILED, "The crate root can't be renamed")
+        })?;
+        let declaration_path = context.file_manager.path(declaration.file);
+        if !package_roots.iter().any(|root| declaration_path.starts_with(root)) {
+            return Err(ResponseError::new(
+                ErrorCode::REQUEST_FAILED,
+                "Only items declared in this workspace can be renamed",
+            ));
+        }
+
+        let name_location = std::iter::once(declaration)
+            .chain(interner.find_references(reference, false))
+            .find(|name_location| name_location.contains(&location));
+        let Some(name_location) = name_location else {
+            return Ok(None);
+        };
+
+        let files = context.file_manager.as_file_map();
+        let range = byte_span_to_range(files, name_location.file, name_location.span.into());
+        let placeholder = source_text(context, name_location).to_string();
+        Ok(range.map(|range| PrepareRenameResponse::RangeWithPlaceholder { range, placeholder }))
+    })?
+}
+
+fn rename(state: &mut LspState, params: RenameParams) -> Result<RenameResult, ResponseError> {
+    let new_name = params.new_name;
+    if !is_identifier(&new_name) {
+        return Err(ResponseError::new(
+            ErrorCode::INVALID_PARAMS,
+            format!("`{new_name}` is not a valid identifier"),
+        ));
+    }
+
+    if prepare_rename(state, params.text_document_position.clone())?.is_none() {
+        return Ok(None);
+    }
+
+    let mut conflicts = Vec::new();
+    let changes =
+        collect_references(state, params.text_document_position, |context, reference| {
+            conflicts.extend(rename_conflict(context, reference, &new_name));
+            rename_changes(context, reference, &new_name)
+        })?;
+    if let Some(conflict) = conflicts.into_iter().next() {
+        return Err(ResponseError::new(ErrorCode::REQUEST_FAILED, conflict));
+    }
+    Ok(changes.map(workspace_edit))
+}
+
+/// Returns why `reference` can't be renamed to `new_name`, if the new name is already taken in
+/// any of the scopes the item is known by its current name in: the modules declaring or importing
+/// it, and the functions using it without a path, where a local variable would shadow it.
+fn rename_conflict(context: &Context, reference: ReferenceId, new_name: &str) -> Option<String> {
+    let interner = &context.def_interner;
+    let declaration = interner.reference_location(reference)?;
+    let old_name = source_text(context, declaration);
+    if old_name == new_name {
+        return None;
+    }
+    let new_ident = Ident::from(new_name);
+
+    let is_type = match reference {
+        ReferenceId::StructMember(struct_id, _) => {
+            let struct_type = interner.get_struct(struct_id);
+            let struct_type = struct_type.borrow();
+            return struct_type
+                .field_index(new_name)
+                .map(|_| format!("`{}` already has a field named `{new_name}`", struct_type.name));
+        }
+        ReferenceId::Struct(_) | ReferenceId::Trait(_) | ReferenceId::Module(_) => true,
+        ReferenceId::Function(_) | ReferenceId::Global(_) | ReferenceId::Local(_) => false,
+    };
+
+    for module in modules_with_item(context, reference, old_name) {
+        let def_map = context.def_map(&module.krate)?;
+        let found = def_map.modules()[module.local_id.0].scope().find_name(&new_ident);
+        let taken = if is_type { found.types.is_some() } else { found.values.is_some() };
+        if taken {
+            let module_path = module_path(context, module);
+            let module =
+                if module_path.is_empty() { "the crate root".to_string() } else { module_path };
+            return Some(format!("`{new_name}` is already declared or imported in {module}"));
+        }
+    }
+    if is_type {
+        return None;
+    }
+
+    for location in interner.find_references(reference, true) {
+        if source_text(context, location) != old_name || is_qualified(context, location) {
+            continue;
+        }
+        let Some(func_id) = interner.find_function_containing(location) else {
+            continue;
+        };
+        let shadowed_by_local =
+            locals_in_scope(interner, func_id, location).into_iter().any(|local| {
+                ReferenceId::Local(local) != reference
+                    && interner.definition(local).name == new_name
+            });
+        // A local renamed after a function or global would shadow it for the rest of its scope
+        let shadows_item = matches!(reference, ReferenceId::Local(_))
+            && module_at(context, location).map_or(false, |module| {
+                let path = Path::from_single(new_name.to_string(), Span::default());
+                matches!(
+                    context.resolve_path(module, path),
+                    Some(ModuleDefId::FunctionId(_) | ModuleDefId::GlobalId(_))
+                )
+            });
+        if shadowed_by_local || shadows_item {
+            let function = interner.function_name(&func_id);
+            return Some(format!("`{new_name}` is already in scope in `{function}`"));
+        }
+    }
+    None
+}
+
+/// Returns every module which has the item in scope under `name`, whether it is declared there or
+/// imported without an alias.
+fn modules_with_item(context: &Context, reference: ReferenceId, name: &str) -> Vec<ModuleId> {
+    let interner = &context.def_interner;
+    let mut modules = Vec::new();
+    for krate in context.crates() {
+        let Some(def_map) = context.def_map(&krate) else {
+            continue;
+        };
+        for (index, module) in def_map.modules().iter() {
+            let scope = module.scope();
+            let has_item = scope.types().iter().chain(scope.values()).any(|(ident, items)| {
+                ident.0.contents == name
+                    && items
+                        .values()
+                        .any(|(id, _, _)| interner.module_def_reference(*id) == Some(reference))
+            });
+            if has_item {
+                modules.push(ModuleId { krate, local_id: LocalModuleId(index) });
+            }
+        }
+    }
+    modules
+}
+
+/// Whether the name at `location` is the last segment of a path such as `foo::bar`, which is
+/// resolved in the module named by the path rather than in the surrounding scope.
+fn is_qualified(context: &Context, location: Location) -> bool {
+    let source = context.file_manager.fetch_file(location.file);
+    source[..location.span.start() as usize].trim_end().ends_with("::")
+}
+
+#[derive(PartialEq)]
+enum RenameChange {
+    Edit(Url, TextEdit),
+    /// Modules declared with `mod foo;` live in `foo.nr`, with their own submodules in `foo/`,
+    /// so these must be renamed along with the module.
+    File {
+        old_uri: Url,
+        new_uri: Url,
+    },
+}
+
+fn rename_changes(context: &Context, reference: ReferenceId, new_name: &str) -> Vec<RenameChange> {
+    let interner = &context.def_interner;
+    let files = context.file_manager.as_file_map();
+    let Some(declaration) = interner.reference_location(reference) else {
+        return Vec::new();
+    };
+    let old_name = source_text(context, declaration);
+
+    let mut changes: Vec<_> = interner
+        .find_references(reference, true)
+        .into_iter()
+        // Uses through an alias such as `use foo::bar as baz;` keep the alias
+        .filter(|location| source_text(context, *location) == old_name)
+        .filter_map(|location| {
+            // In a constructor such as `Foo { a }` the field and the local share a location,
+            // so the shorthand has to be expanded to only rename one of them.
+            let shared_with = interner.references_at(location);
+            let new_text = match reference {
+                ReferenceId::StructMember(..)
+                    if shared_with.iter().any(|other| matches!(other, ReferenceId::Local(_))) =>
+                {
+                    format!("{new_name}: {old_name}")
+                }
+                ReferenceId::Local(_)
+                    if shared_with
+                        .iter()
+                        .any(|other| matches!(other, ReferenceId::StructMember(..))) =>
+                {
+                    format!("{old_name}: {new_name}")
+                }
+                _ => new_name.to_string(),
+            };
+
+            let location = to_lsp_location(files, location.file, location.span)?;
+            Some(RenameChange::Edit(location.uri, TextEdit { range: location.range, new_text }))
+        })
+        .collect();
+
+    if let ReferenceId::Module(module) = reference {
+        changes.extend(module_file_renames(context, module, new_name));
+    }
+    changes
+}
+
+fn module_file_renames(context: &Context, module: ModuleId, new_name: &str) -> Vec<RenameChange> {
+    let Some(def_map) = context.def_map(&module.krate) else {
+        return Vec::new();
+    };
+    let module_data = &def_map.modules()[module.local_id.0];
+    let Some(parent) = module_data.parent else {
+        return Vec::new();
+    };
+    // Modules declared inline with `mod foo { ... }` share their parent's file
+    if module_data.location.file == def_map.modules()[parent.0].location.file {
+        return Vec::new();
+    }
+
+    let path = context.file_manager.path(module_data.location.file);
+    let old_name = module_name(context, module);
+    if path.file_stem().and_then(|stem| stem.to_str()) != old_name.as_deref() {
+        return Vec::new();
+    }
+
+    let mut renames =
+        vec![(path.to_path_buf(), path.with_file_name(format!("{new_name}.{FILE_EXTENSION}")))];
+    let submodules_dir = path.with_extension("");
+    if submodules_dir.is_dir() {
+        renames.push((submodules_dir.clone(), submodules_dir.with_file_name(new_name)));
+    }
+
+    renames
+        .into_iter()
+        .filter_map(|(old_path, new_path)| {
+            let old_uri = Url::from_file_path(old_path).ok()?;
+            let new_uri = Url::from_file_path(new_path).ok()?;
+            Some(RenameChange::File { old_uri, new_uri })
+        })
+        .collect()
+}
+
+fn workspace_edit(changes: Vec<RenameChange>) -> WorkspaceEdit {
+    let mut edits: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    let mut file_renames = Vec::new();
+    for change in changes {
+        match change {
+            RenameChange::Edit(uri, edit) => edits.entry(uri).or_default().push(edit),
+            RenameChange::File { old_uri, new_uri } => file_renames.push(RenameFile {
+                old_uri,
+                new_uri,
+                options: None,
+                annotation_id: None,
+            }),
+        }
+    }
+
+    if file_renames.is_empty() {
+        return WorkspaceEdit { changes: Some(edits), ..WorkspaceEdit::default() };
+    }
+
+    // Files are only renamed once all of the edits to them have been applied
+    let mut operations: Vec<_> = edits
+        .into_iter()
+        .map(|(uri, edits)| {
+            DocumentChangeOperation::Edit(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                edits: edits.into_iter().map(OneOf::Left).collect(),
+            })
+        })
+        .collect();
+    operations.extend(
+        file_renames
+            .into_iter()
+            .map(|rename| DocumentChangeOperation::Op(ResourceOp::Rename(rename))),
+    );
+
+    WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        ..WorkspaceEdit::default()
+    }
+}
+
+fn source_text(context: &Context, location: Location) -> &str {
+    let source = context.file_manager.fetch_file(location.file);
+    &source[location.span.start() as usize..location.span.end() as usize]
+}
+
+fn is_identifier(name: &str) -> bool {
+    let (tokens, errors) = Lexer::lex(name);
+    errors.is_empty()
+        && matches!(
+            tokens.0.as_slice(),
+            [ident, eof] if matches!(ident.token(), Token::Ident(_)) && *eof.token() == Token::EOF
+        )
+}
+
+#[cfg(test)]
+mod rename_tests {
+    use crate::requests::{
+        on_prepare_rename_request, on_rename_request, test_utils::init_lsp_server,
+    };
+
+    use super::*;
+    use lsp_types::{Position, TextDocumentIdentifier, WorkDoneProgressParams};
+    use tokio::test;
+
+    fn text_document_position(
+        noir_text_document: Url,
+        position: Position,
+    ) -> TextDocumentPositionParams {
+        TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: noir_text_document },
+            position,
+        }
+    }
+
+    #[test]
+    async fn test_on_prepare_rename_request() {
+        let (mut state, _, noir_text_document) = init_lsp_server();
+
+        // The `ss` in `ss.a = 61;`
+        let params =
+            text_document_position(noir_text_document, Position { line: 97, character: 5 });
+        let response = on_prepare_rename_request(&mut state, params)
+            .await
+            .expect("Could not execute on_prepare_rename_request");
+
+        assert!(matches!(
+            response,
+            Some(PrepareRenameResponse::RangeWithPlaceholder { placeholder, .. }) if placeholder == "ss"
+        ));
+    }
+
+    #[test]
+    async fn test_on_rename_request() {
+        let (mut state, _, noir_text_document) = init_lsp_server();
+
+        let params = RenameParams {
+            text_document_position: text_document_position(
+                noir_text_document,
+                Position { line: 97, character: 5 },
+            ),
+            new_name: "renamed".to_string(),
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+        };
+        let workspace_edit = on_rename_request(&mut state, params)
+            .await
+            .expect("Could not execute on_rename_request")
+            .expect("Could not rename the local");
+
+        let edits: Vec<_> = workspace_edit.changes.unwrap().into_values().flatten().collect();
+        // Its declaration and each of its uses in `main`
+        assert!(edits.len() > 1);
+        assert!(edits.iter().all(|edit| edit.new_text == "renamed"));
+        assert!(edits.iter().any(|edit| edit.range.start == Position { line: 93, character: 12 }));
+    }
+
+    #[test]
+    async fn test_on_rename_request_rejects_invalid_names() {
+        let (mut state, _, noir_text_document) = init_lsp_server();
+
+        let params = RenameParams {
+            text_document_position: text_document_position(
+                noir_text_document,
+                Position { line: 97, character: 5 },
+            ),
+            new_name: "fn".to_string(),
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+        };
+        assert!(on_rename_request(&mut state, params).await.is_err());
+    }
+
+    /// Renames the item at `position` in a package whose `main.nr` is `source`.
+    async fn rename_in_package(
+        source: &str,
+        position: Position,
+        new_name: &str,
+    ) -> Result<RenameResult, ResponseError> {
+        let (mut state, _, _) = init_lsp_server();
+
+        let dir = tempfile::tempdir().unwrap();
+        let root_path = dir.path().canonicalize().unwrap();
+        std::fs::write(
+            root_path.join("Nargo.toml"),
+            "[package]\nname = \"rename\"\ntype = \"bin\"\nauthors = [\"\"]\n\n[dependencies]\n",
+        )
+        .unwrap();
+        std::fs::create_dir(root_path.join("src")).unwrap();
+        std::fs::write(root_path.join("src/main.nr"), source).unwrap();
+        let noir_text_document = Url::from_file_path(root_path.join("src/main.nr")).unwrap();
+
+        let params = RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: noir_text_document },
+                position,
+            },
+            new_name: new_name.to_string(),
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+        };
+        on_rename_request(&mut state, params).await
+    }
+
+    fn edit_starts(workspace_edit: WorkspaceEdit) -> Vec<Position> {
+        let edits: Vec<_> = workspace_edit.changes.unwrap().into_values().flatten().collect();
+        let mut starts: Vec<_> = edits.iter().map(|edit| edit.range.start).collect();
+        starts.sort_by_key(|position| (position.line, position.character));
+        starts
+    }
+
+    #[test]
+    async fn test_on_rename_request_renames_imported_globals() {
+        let source = "use crate::foo::GLOBAL;
+
+mod foo {
+    global GLOBAL: Field = 1;
+}
+
+fn main(x: Field) {
+    assert(x != GLOBAL);
+}
+";
+        // The `GLOBAL` in `assert(x != GLOBAL);`
+        let workspace_edit =
+            rename_in_package(source, Position { line: 7, character: 17 }, "RENAMED")
+                .await
+                .expect("Could not execute on_rename_request")
+                .expect("Could not rename the global");
+
+        // Its import, its declaration and its use in `main`
+        assert_eq!(
+            edit_starts(workspace_edit),
+            vec![
+                Position { line: 0, character: 16 },
+                Position { line: 3, character: 11 },
+                Position { line: 7, character: 16 },
+            ]
+        );
+    }
+
+    const ALIASED_SOURCE: &str = "use crate::foo::bar as baz;
+
+mod foo {
+    fn bar() -> Field { 1 }
+    fn other() -> Field { 2 }
+}
+
+fn main(x: Field) {
+    let y = 3;
+    assert(x != baz() + y);
+}
+";
+
+    #[test]
+    async fn test_on_rename_request_keeps_aliases() {
+        // The `bar` in `fn bar()`
+        let position = Position { line: 3, character: 7 };
+        let workspace_edit = rename_in_package(ALIASED_SOURCE, position, "renamed")
+            .await
+            .expect("Could not execute on_rename_request")
+            .expect("Could not rename the function");
+
+        // Its import and its declaration, but not its uses through `baz`
+        assert_eq!(
+            edit_starts(workspace_edit),
+            vec![Position { line: 0, character: 16 }, Position { line: 3, character: 7 }]
+        );
+    }
+
+    #[test]
+    async fn test_on_rename_request_rejects_names_already_taken() {
+        // `other` is declared in the same module as `bar`
+        let bar = Position { line: 3, character: 7 };
+        assert!(rename_in_package(ALIASED_SOURCE, bar, "other").await.is_err());
+
+        // `x` is a parameter in scope wherever `y` is used
+        let y = Position { line: 8, character: 8 };
+        assert!(rename_in_package(ALIASED_SOURCE, y, "x").await.is_err());
+        assert!(rename_in_package(ALIASED_SOURCE, y, "z").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,495 @@
+use std::{
+    collections::HashSet,
+    future::{self, Future},
+};
+
+use async_lsp::ResponseError;
+use fm::FileId;
+use lsp_types::{
+    Position, Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensLegend, SemanticTokensParams, SemanticTokensRangeParams, SemanticTokensResult,
+};
+use noirc_errors::Span;
+use noirc_frontend::{
+    hir::Context,
+    hir_def::stmt::HirPattern,
+    lexer::Lexer,
+    node_interner::{DefinitionId, NodeInterner, ReferenceId},
+    parse_program,
+    parser::ItemKind,
+    token::{Keyword, Token},
+    FunctionKind, NoirFunction, ParsedModule, TraitImplItem, TraitItem,
+};
+
+use crate::{
+    byte_span_to_range,
+    types::{SemanticTokensFullResult, SemanticTokensRangeResult},
+    LspState,
+};
+
+use super::{completion::has_self_parameter, process_document};
+
+const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::STRUCT,
+    SemanticTokenType::INTERFACE,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::METHOD,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::STRING,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::DECORATOR,
+];
+
+/// Marks functions which are executed as Brillig rather than ACIR.
+const UNCONSTRAINED: SemanticTokenModifier = SemanticTokenModifier::new("unconstrained");
+/// Marks functions whose results are provided by the caller of the program.
+const ORACLE: SemanticTokenModifier = SemanticTokenModifier::new("oracle");
+const MUTABLE: SemanticTokenModifier = SemanticTokenModifier::new("mutable");
+
+const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::DECLARATION,
+    SemanticTokenModifier::READONLY,
+    SemanticTokenModifier::DOCUMENTATION,
+    SemanticTokenModifier::DEFAULT_LIBRARY,
+    UNCONSTRAINED,
+    ORACLE,
+    MUTABLE,
+];
+
+pub(crate) fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+pub(crate) fn on_semantic_tokens_full_request(
+    state: &mut LspState,
+    params: SemanticTokensParams,
+) -> impl Future<Output = Result<SemanticTokensFullResult, ResponseError>> {
+    let result = process_document(state, &params.text_document.uri, |file_id, context, _| {
+        let tokens = semantic_tokens(context, file_id, None);
+        Some(SemanticTokensResult::Tokens(tokens))
+    });
+    future::ready(result)
+}
+
+pub(crate) fn on_semantic_tokens_range_request(
+    state: &mut LspState,
+    params: SemanticTokensRangeParams,
+) -> impl Future<Output = Result<SemanticTokensRangeResult, ResponseError>> {
+    let range = params.range;
+    let result = process_document(state, &params.text_document.uri, |file_id, context, _| {
+        let tokens = semantic_tokens(context, file_id, Some(range));
+        Some(lsp_types::SemanticTokensRangeResult::Tokens(tokens))
+    });
+    future::ready(result)
+}
+
+/// A token before it is encoded relative to the previous token.
+struct AbsoluteToken {
+    position: Position,
+    length: u32,
+    token_type: SemanticTokenType,
+    modifiers: Vec<SemanticTokenModifier>,
+}
+
+/// Classifies each token of the file, using the type checked [Context] to tell apart the
+/// identifiers which the lexer can't.
+fn semantic_tokens(context: &Context, file_id: FileId, range: Option<Range>) -> SemanticTokens {
+    let source = context.file_manager.fetch_file(file_id);
+    let interner = &context.def_interner;
+    let generics = generic_scopes(source);
+    let parameters = function_parameters(interner, file_id);
+
+    let lexed: Vec<_> = Lexer::new(source).skip_comments(false).flatten().collect();
+    let identifiers: Vec<u32> = lexed
+        .iter()
+        .filter(|token| matches!(token.token(), Token::Ident(_)))
+        .map(|token| token.to_span().start())
+        .collect();
+    let references = interner.find_all_referenced(file_id, &identifiers);
+
+    let mut tokens = Vec::new();
+    for token in lexed {
+        let span = token.to_span();
+        let classification = match token.token() {
+            Token::Ident(name) => {
+                if generics.iter().any(|(scope, generic)| scope.contains(&span) && generic == name)
+                {
+                    Some((SemanticTokenType::TYPE_PARAMETER, Vec::new()))
+                } else {
+                    references.get(&span.start()).map(|reference| {
+                        let reference = *reference;
+                        let mut classification = classify(interner, reference, &parameters);
+                        let declaration = interner.reference_location(reference);
+                        if declaration.map_or(false, |declaration| declaration.span == span) {
+                            classification.1.push(SemanticTokenModifier::DECLARATION);
+                        }
+                        classification
+                    })
+                }
+            }
+            Token::Keyword(Keyword::Field | Keyword::Bool | Keyword::String | Keyword::Char)
+            | Token::IntType(_) => Some((SemanticTokenType::TYPE, Vec::new())),
+            Token::Keyword(_) | Token::Bool(_) => Some((SemanticTokenType::KEYWORD, Vec::new())),
+            Token::Int(_) => Some((SemanticTokenType::NUMBER, Vec::new())),
+            Token::Str(_) | Token::RawStr(..) | Token::FmtStr(_) => {
+                Some((SemanticTokenType::STRING, Vec::new()))
+            }
+            Token::LineComment(_, doc_style) | Token::BlockComment(_, doc_style) => {
+                let modifiers = match doc_style {
+                    Some(_) => vec![SemanticTokenModifier::DOCUMENTATION],
+                    None => Vec::new(),
+                };
+                Some((SemanticTokenType::COMMENT, modifiers))
+            }
+            Token::Attribute(_) => Some((SemanticTokenType::DECORATOR, Vec::new())),
+            _ => None,
+        };
+
+        if let Some((token_type, modifiers)) = classification {
+            tokens.extend(split_into_lines(context, file_id, span, token_type, modifiers));
+        }
+    }
+
+    if let Some(range) = range {
+        tokens.retain(|token| token.position >= range.start && token.position < range.end);
+    }
+    SemanticTokens { result_id: None, data: encode(tokens) }
+}
+
+fn classify(
+    interner: &NodeInterner,
+    reference: ReferenceId,
+    parameters: &HashSet<DefinitionId>,
+) -> (SemanticTokenType, Vec<SemanticTokenModifier>) {
+    let mut modifiers = Vec::new();
+    let token_type = match reference {
+        ReferenceId::Function(func_id) => {
+            if interner.function_modifiers(&func_id).is_unconstrained {
+                modifiers.push(UNCONSTRAINED);
+            }
+            let meta = interner.function_meta(&func_id);
+            if meta.kind == FunctionKind::Oracle {
+                modifiers.push(ORACLE);
+            }
+            if interner.function_module(func_id).krate.is_stdlib() {
+                modifiers.push(SemanticTokenModifier::DEFAULT_LIBRARY);
+            }
+            if has_self_parameter(interner, func_id) {
+                SemanticTokenType::METHOD
+            } else {
+                SemanticTokenType::FUNCTION
+            }
+        }
+        ReferenceId::Struct(_) => SemanticTokenType::STRUCT,
+        ReferenceId::StructMember(..) => SemanticTokenType::PROPERTY,
+        ReferenceId::Trait(_) => SemanticTokenType::INTERFACE,
+        ReferenceId::Global(_) => {
+            modifiers.push(SemanticTokenModifier::READONLY);
+            SemanticTokenType::VARIABLE
+        }
+        ReferenceId::Local(definition_id) => {
+            if interner.definition(definition_id).mutable {
+                modifiers.push(MUTABLE);
+            }
+            if parameters.contains(&definition_id) {
+                SemanticTokenType::PARAMETER
+            } else {
+                SemanticTokenType::VARIABLE
+            }
+        }
+        ReferenceId::Module(_) => SemanticTokenType::NAMESPACE,
+    };
+    (token_type, modifiers)
+}
+
+/// The definitions of the parameters of the functions declared in the file.
+fn function_parameters(interner: &NodeInterner, file_id: FileId) -> HashSet<DefinitionId> {
+    fn pattern_definitions(pattern: &HirPattern, definitions: &mut HashSet<DefinitionId>) {
+        match pattern {
+            HirPattern::Identifier(ident) => {
+                definitions.insert(ident.id);
+            }
+            HirPattern::Mutable(pattern, _) => pattern_definitions(pattern, definitions),
+            HirPattern::Tuple(patterns, _) => {
+                for pattern in patterns {
+                    pattern_definitions(pattern, definitions);
+                }
+            }
+            HirPattern::Struct(_, fields, _) => {
+                for (_, pattern) in fields {
+                    pattern_definitions(pattern, definitions);
+                }
+            }
+        }
+    }
+
+    let mut definitions = HashSet::new();
+    for func_id in interner.functions_in_file(file_id) {
+        for (pattern, _, _) in &interner.function_meta(&func_id).parameters.0 {
+            pattern_definitions(pattern, &mut definitions);
+        }
+    }
+    definitions
+}
+
+/// Returns the names of the generics declared by each item of the file along with the span of
+/// the item, in which the names refer to those generics.
+///
+/// Generics aren't tracked by the [NodeInterner] so these are found from the parsed module instead.
+fn generic_scopes(source: &str) -> Vec<(Span, String)> {
+    fn function_generics(function: &NoirFunction, scopes: &mut Vec<(Span, String)>) {
+        for generic in &function.def.generics {
+            scopes.push((function.span(), generic.to_string()));
+        }
+    }
+
+    fn module_generics(module: &ParsedModule, scopes: &mut Vec<(Span, String)>) {
+        for item in &module.items {
+            let (generics, span) = match &item.kind {
+                ItemKind::Function(function) => (&function.def.generics, item.span),
+                ItemKind::Struct(noir_struct) => (&noir_struct.generics, item.span),
+                ItemKind::Trait(noir_trait) => {
+                    for trait_item in &noir_trait.items {
+                        if let TraitItem::Function { generics, .. } = trait_item {
+                            let generics = generics.iter().map(|generic| generic.to_string());
+                            scopes.extend(generics.map(|generic| (item.span, generic)));
+                        }
+                    }
+                    (&noir_trait.generics, item.span)
+                }
+                ItemKind::Impl(type_impl) => {
+                    for method in &type_impl.methods {
+                        function_generics(method, scopes);
+                    }
+                    (&type_impl.generics, item.span)
+                }
+                ItemKind::TraitImpl(trait_impl) => {
+                    for impl_item in &trait_impl.items {
+                        if let TraitImplItem::Function(function) = impl_item {
+                            function_generics(function, scopes);
+                        }
+                    }
+                    (&trait_impl.impl_generics, item.span)
+                }
+                ItemKind::Submodules(submodule) => {
+                    module_generics(&submodule.contents, scopes);
+                    continue;
+                }
+                ItemKind::Import(_)
+                | ItemKind::TypeAlias(_)
+                | ItemKind::Global(_)
+                | ItemKind::ModuleDecl(_) => continue,
+            };
+            scopes.extend(generics.iter().map(|generic| (span, generic.to_string())));
+        }
+    }
+
+    let (module, _) = parse_program(source);
+    let mut scopes = Vec::new();
+    module_generics(&module, &mut scopes);
+    scopes
+}
+
+/// Clients may not support tokens spanning multiple lines, such as block comments,
+/// so these are split into a token per line.
+fn split_into_lines(
+    context: &Context,
+    file_id: FileId,
+    span: Span,
+    token_type: SemanticTokenType,
+    modifiers: Vec<SemanticTokenModifier>,
+) -> Vec<AbsoluteToken> {
+    let files = context.file_manager.as_file_map();
+    let Some(range) = byte_span_to_range(files, file_id, span.into()) else {
+        return Vec::new();
+    };
+    let source = context.file_manager.fetch_file(file_id);
+    let text = &source[span.start() as usize..span.end() as usize];
+
+    text.split('\n')
+        .enumerate()
+        .map(|(index, line)| {
+            let character = if index == 0 { range.start.character } else { 0 };
+            AbsoluteToken {
+                position: Position { line: range.start.line + index as u32, character },
+                length: line.trim_end_matches('\r').encode_utf16().count() as u32,
+                token_type: token_type.clone(),
+                modifiers: modifiers.clone(),
+            }
+        })
+        .filter(|token| token.length > 0)
+        .collect()
+}
+
+/// Encodes the tokens as the deltas between the start of each token and the one before it.
+fn encode(mut tokens: Vec<AbsoluteToken>) -> Vec<SemanticToken> {
+    tokens.sort_by_key(|token| token.position);
+
+    let mut previous = Position { line: 0, character: 0 };
+    tokens
+        .into_iter()
+        .map(|token| {
+            let delta_line = token.position.line - previous.line;
+            let delta_start = if delta_line == 0 {
+                token.position.character - previous.character
+            } else {
+                token.position.character
+            };
+            previous = token.position;
+
+            let token_type = TOKEN_TYPES.iter().position(|typ| *typ == token.token_type);
+            let token_modifiers_bitset = token
+                .modifiers
+                .iter()
+                .filter_map(|modifier| TOKEN_MODIFIERS.iter().position(|other| other == modifier))
+                .fold(0, |bitset, index| bitset | (1 << index));
+
+            SemanticToken {
+                delta_line,
+                delta_start,
+                length: token.length,
+                token_type: token_type.expect("token types should be in the legend") as u32,
+                token_modifiers_bitset,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod semantic_tokens_tests {
+    use crate::requests::{
+        on_semantic_tokens_full_request, on_semantic_tokens_range_request,
+        test_utils::init_lsp_server,
+    };
+
+    use super::*;
+    use lsp_types::{PartialResultParams, TextDocumentIdentifier, WorkDoneProgressParams};
+    use tokio::test;
+
+    /// Decodes the tokens into their positions, types and modifiers.
+    fn decode(
+        tokens: SemanticTokens,
+    ) -> Vec<(Position, SemanticTokenType, Vec<SemanticTokenModifier>)> {
+        let mut position = Position { line: 0, character: 0 };
+        tokens
+            .data
+            .into_iter()
+            .map(|token| {
+                if token.delta_line == 0 {
+                    position.character += token.delta_start;
+                } else {
+                    position = Position {
+                        line: position.line + token.delta_line,
+                        character: token.delta_start,
+                    };
+                }
+                let modifiers = TOKEN_MODIFIERS
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| token.token_modifiers_bitset & (1 << index) != 0)
+                    .map(|(_, modifier)| modifier.clone())
+                    .collect();
+                (position, TOKEN_TYPES[token.token_type as usize].clone(), modifiers)
+            })
+            .collect()
+    }
+
+    async fn full_tokens() -> Vec<(Position, SemanticTokenType, Vec<SemanticTokenModifier>)> {
+        let (mut state, _, uri) = init_lsp_server();
+
+        let params = SemanticTokensParams {
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+            partial_result_params: PartialResultParams { partial_result_token: None },
+            text_document: TextDocumentIdentifier { uri },
+        };
+        let response = on_semantic_tokens_full_request(&mut state, params)
+            .await
+            .expect("Could not execute on_semantic_tokens_full_request");
+        let Some(SemanticTokensResult::Tokens(tokens)) = response else {
+            panic!("Expected semantic tokens");
+        };
+        decode(tokens)
+    }
+
+    fn token_at(
+        tokens: &[(Position, SemanticTokenType, Vec<SemanticTokenModifier>)],
+        line: u32,
+        character: u32,
+    ) -> (SemanticTokenType, Vec<SemanticTokenModifier>) {
+        let (_, token_type, modifiers) = tokens
+            .iter()
+            .find(|(position, _, _)| *position == Position { line, character })
+            .unwrap_or_else(|| panic!("Expected a token at {line}:{character}"));
+        (token_type.clone(), modifiers.clone())
+    }
+
+    #[test]
+    async fn test_classifies_identifiers() {
+        let tokens = full_tokens().await;
+
+        // fn main(x: u32, ...
+        assert_eq!(token_at(&tokens, 92, 0).0, SemanticTokenType::KEYWORD);
+        assert_eq!(
+            token_at(&tokens, 92, 3),
+            (SemanticTokenType::FUNCTION, vec![SemanticTokenModifier::DECLARATION])
+        );
+        assert_eq!(token_at(&tokens, 92, 8).0, SemanticTokenType::PARAMETER);
+        assert_eq!(token_at(&tokens, 92, 11).0, SemanticTokenType::TYPE);
+
+        // let mut ss: my_struct = my_struct { b: x, a: x + 2 };
+        assert_eq!(
+            token_at(&tokens, 93, 12),
+            (SemanticTokenType::VARIABLE, vec![SemanticTokenModifier::DECLARATION, MUTABLE])
+        );
+        assert_eq!(token_at(&tokens, 93, 16).0, SemanticTokenType::STRUCT);
+
+        // ss.a = 61;
+        assert_eq!(token_at(&tokens, 97, 7).0, SemanticTokenType::PROPERTY);
+        assert_eq!(token_at(&tokens, 97, 11).0, SemanticTokenType::NUMBER);
+    }
+
+    #[test]
+    async fn test_range_only_includes_tokens_in_range() {
+        let (mut state, _, uri) = init_lsp_server();
+
+        let params = SemanticTokensRangeParams {
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+            partial_result_params: PartialResultParams { partial_result_token: None },
+            text_document: TextDocumentIdentifier { uri },
+            range: Range {
+                start: Position { line: 97, character: 0 },
+                end: Position { line: 98, character: 0 },
+            },
+        };
+        let response = on_semantic_tokens_range_request(&mut state, params)
+            .await
+            .expect("Could not execute on_semantic_tokens_range_request");
+        let Some(lsp_types::SemanticTokensRangeResult::Tokens(tokens)) = response else {
+            panic!("Expected semantic tokens");
+        };
+
+        let tokens = decode(tokens);
+        // `ss`, `a` and `61`
+        assert_eq!(tokens.len(), 3);
+        assert!(tokens.iter().all(|(position, _, _)| position.line == 97));
+    }
+
+    #[test]
+    async fn test_classifies_generics() {
+        let tokens = full_tokens().await;
+
+        // fn foo<N>(a: [Field; N]) -> [Field; N] {
+        assert_eq!(token_at(&tokens, 84, 7).0, SemanticTokenType::TYPE_PARAMETER);
+        assert_eq!(token_at(&tokens, 84, 21).0, SemanticTokenType::TYPE_PARAMETER);
+    }
+}
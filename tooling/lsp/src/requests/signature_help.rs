@@ -0,0 +1,278 @@
+use std::future::{self, Future};
+
+use async_lsp::{ErrorCode, ResponseError};
+use lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, SignatureHelp,
+    SignatureHelpParams, SignatureInformation,
+};
+use noirc_errors::{Location, Span};
+use noirc_frontend::{
+    hir::{def_map::ModuleDefId, Context},
+    node_interner::{FuncId, ReferenceId},
+    Path, Type,
+};
+
+use crate::{types::SignatureHelpResult, LspState};
+
+use super::{
+    completion::{byte_index, has_self_parameter, is_identifier_char, module_at, path_ending_at},
+    hover::{doc_comments, format_function, function_parameters},
+    process_request_with_input_files, uri_to_file_path,
+};
+
+pub(crate) fn on_signature_help_request(
+    state: &mut LspState,
+    params: SignatureHelpParams,
+) -> impl Future<Output = Result<SignatureHelpResult, ResponseError>> {
+    future::ready(signature_help(state, params))
+}
+
+fn signature_help(
+    state: &mut LspState,
+    params: SignatureHelpParams,
+) -> Result<SignatureHelpResult, ResponseError> {
+    let text_document_position = params.text_document_position_params;
+    let uri = &text_document_position.text_document.uri;
+    let source = match state.input_files.get(uri.as_str()) {
+        Some(source) => source.clone(),
+        None => std::fs::read_to_string(uri_to_file_path(uri)?)
+            .map_err(|err| ResponseError::new(ErrorCode::REQUEST_FAILED, err.to_string()))?,
+    };
+    let cursor = byte_index(&source, text_document_position.position).ok_or_else(|| {
+        ResponseError::new(ErrorCode::REQUEST_FAILED, "Could not convert position to byte index")
+    })?;
+    let Some(call) = CallContext::new(&source[..cursor]) else {
+        return Ok(None);
+    };
+
+    // The call is usually still being typed, so it's closed to give the parser a chance of
+    // keeping it in the program
+    let mut input_files = state.input_files.clone();
+    if !source[cursor..].trim_start().starts_with(')') {
+        input_files.insert(uri.to_string(), format!("{})", &source[..cursor]) + &source[cursor..]);
+    }
+
+    process_request_with_input_files(
+        &input_files,
+        text_document_position,
+        |location, context, _| {
+            let func_id = called_function(context, location, &call)?;
+            Some(signature_help_for(context, func_id, &call))
+        },
+    )
+}
+
+/// The call surrounding the cursor, worked out from the text before the cursor.
+struct CallContext {
+    /// The byte index at which the name of the called function starts
+    callee_start: usize,
+    callee_name: String,
+    /// The path to the called function, as in `std::hash::pedersen_hash(`
+    path: Option<Path>,
+    /// For method calls such as `foo.bar(`, the byte index at which the object `foo` ends
+    object_end: Option<usize>,
+    /// The number of arguments preceding the cursor
+    argument_index: u32,
+}
+
+impl CallContext {
+    fn new(before_cursor: &str) -> Option<Self> {
+        let mut depth = 0;
+        let mut argument_index = 0;
+        for (index, char) in before_cursor.char_indices().rev() {
+            match char {
+                ')' | ']' | '}' => depth += 1,
+                '(' | '[' | '{' if depth > 0 => depth -= 1,
+                '(' => match Self::with_open_paren(&before_cursor[..index], argument_index) {
+                    Some(call) => return Some(call),
+                    // The commas seen so far separate the elements of a tuple within an argument
+                    None => argument_index = 0,
+                },
+                '[' => argument_index = 0,
+                '{' | ';' => return None,
+                ',' if depth == 0 => argument_index += 1,
+                _ => (),
+            }
+        }
+        None
+    }
+
+    fn with_open_paren(before_paren: &str, argument_index: u32) -> Option<Self> {
+        let before_paren = before_paren.trim_end();
+        let callee_start = before_paren.trim_end_matches(is_identifier_char).len();
+        if callee_start == before_paren.len() {
+            // A tuple or parenthesized expression rather than a call
+            return None;
+        }
+        let callee_name = before_paren[callee_start..].to_string();
+
+        let before_callee = before_paren[..callee_start].trim_end();
+        let object_end = match before_callee.strip_suffix('.') {
+            Some(object) if !object.ends_with('.') => Some(object.trim_end().len()),
+            _ => None,
+        };
+
+        let path = path_ending_at(before_paren);
+        Some(CallContext { callee_start, callee_name, path, object_end, argument_index })
+    }
+}
+
+fn called_function(context: &Context, location: Location, call: &CallContext) -> Option<FuncId> {
+    let interner = &context.def_interner;
+    let callee = Location::new(Span::single_char(call.callee_start as u32), location.file);
+    if let Some(ReferenceId::Function(func_id)) = interner.find_referenced(callee) {
+        return Some(func_id);
+    }
+
+    // The call couldn't be resolved as part of the program, so the function is looked up by name
+    if let Some(object_end) = call.object_end {
+        let object_last_char = object_end.checked_sub(1)?;
+        let object_location =
+            Location::new(Span::single_char(object_last_char as u32), callee.file);
+        let object = interner.find_expression_at(object_location)?;
+        let typ = match interner.id_type(object).follow_bindings() {
+            Type::MutableReference(element) => element.follow_bindings(),
+            typ => typ,
+        };
+        interner
+            .methods_for_type(&typ)
+            .into_iter()
+            .find(|method| interner.function_name(method) == call.callee_name)
+    } else {
+        let module = module_at(context, callee)?;
+        match context.resolve_path(module, call.path.clone()?)? {
+            ModuleDefId::FunctionId(func_id) => Some(func_id),
+            _ => None,
+        }
+    }
+}
+
+fn signature_help_for(context: &Context, func_id: FuncId, call: &CallContext) -> SignatureHelp {
+    let interner = &context.def_interner;
+    let meta = interner.function_meta(&func_id);
+
+    // Attributes are rendered on a line of their own, which signature help has no room for
+    let signature = format_function(interner, func_id);
+    let label = signature.lines().last().unwrap_or_default().to_string();
+
+    // Each parameter is labelled by its offsets within the signature, in UTF-16 code units
+    let utf16_len = |text: &str| text.encode_utf16().count() as u32;
+    let mut offset = label.find('(').map_or(0, |paren| utf16_len(&label[..=paren]));
+    let parameters = function_parameters(interner, meta)
+        .iter()
+        .map(|parameter| {
+            let start = offset;
+            let end = start + utf16_len(parameter);
+            offset = end + utf16_len(", ");
+            ParameterInformation {
+                label: ParameterLabel::LabelOffsets([start, end]),
+                documentation: None,
+            }
+        })
+        .collect();
+
+    // The object of a method call is passed as its `self` parameter
+    let skipped = u32::from(call.object_end.is_some() && has_self_parameter(interner, func_id));
+    let active_parameter = call.argument_index + skipped;
+
+    let documentation = doc_comments(context, meta.name.location).map(|value| {
+        Documentation::MarkupContent(MarkupContent { kind: MarkupKind::Markdown, value })
+    });
+
+    SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation,
+            parameters: Some(parameters),
+            active_parameter: Some(active_parameter),
+        }],
+        active_signature: Some(0),
+        active_parameter: Some(active_parameter),
+    }
+}
+
+#[cfg(test)]
+mod signature_help_tests {
+    use crate::requests::{
+        on_signature_help_request,
+        test_utils::{init_lsp_server, insert_statement},
+    };
+
+    use super::*;
+    use lsp_types::{TextDocumentIdentifier, TextDocumentPositionParams, WorkDoneProgressParams};
+    use tokio::test;
+
+    /// Requests signature help at the end of `line`, once it has been inserted at the start of
+    /// `main` in the 7_function test program.
+    async fn signature_help_after(line: &str) -> Option<SignatureHelp> {
+        let (mut state, _, noir_text_document) = init_lsp_server();
+        let position = insert_statement(&mut state, &noir_text_document, line, "");
+
+        let params = SignatureHelpParams {
+            context: None,
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: noir_text_document },
+                position,
+            },
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+        };
+        on_signature_help_request(&mut state, params)
+            .await
+            .expect("Could not execute on_signature_help_request")
+    }
+
+    fn parameter_labels(signature: &SignatureInformation) -> Vec<&str> {
+        // The signatures in these tests are ASCII, so UTF-16 offsets are also byte offsets
+        let parameters = signature.parameters.iter().flatten();
+        parameters
+            .map(|parameter| match parameter.label {
+                ParameterLabel::LabelOffsets([start, end]) => {
+                    &signature.label[start as usize..end as usize]
+                }
+                ParameterLabel::Simple(_) => panic!("Expected parameter offsets"),
+            })
+            .collect()
+    }
+
+    #[test]
+    async fn test_signature_help_for_function() {
+        let help =
+            signature_help_after("test2(x as Field, ").await.expect("Expected signature help");
+        let signature = &help.signatures[0];
+        assert_eq!(signature.label, "fn test2(z: Field, t: u32)");
+        assert_eq!(parameter_labels(signature), vec!["z: Field", "t: u32"]);
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
+    #[test]
+    async fn test_signature_help_for_stdlib_function() {
+        let help = signature_help_after("let _ = dep::std::hash::pedersen_hash(")
+            .await
+            .expect("Expected signature help");
+        let signature = &help.signatures[0];
+        assert!(signature.label.starts_with("pub fn pedersen_hash"));
+        assert_eq!(signature.parameters.as_ref().map(Vec::len), Some(1));
+        assert_eq!(help.active_parameter, Some(0));
+    }
+
+    #[test]
+    async fn test_no_signature_help_outside_calls() {
+        assert!(signature_help_after("let _ = (x, ").await.is_none());
+    }
+
+    #[test]
+    async fn test_call_context() {
+        let call = CallContext::new("foo.bar(a, baz(b, c), ").unwrap();
+        assert_eq!(call.callee_name, "bar");
+        assert_eq!(call.object_end, Some(3));
+        assert_eq!(call.argument_index, 2);
+
+        // Commas within an array or tuple argument don't separate arguments
+        let call = CallContext::new("std::hash::pedersen_hash([x, ").unwrap();
+        assert_eq!(call.callee_name, "pedersen_hash");
+        assert_eq!(call.argument_index, 0);
+        assert_eq!(call.path.map(|path| path.to_string()), Some("std::hash::pedersen_hash".into()));
+        assert!(CallContext::new("let x = [a, ").is_none());
+        assert!(CallContext::new("foo(a);\nlet x = (a, ").is_none());
+    }
+}
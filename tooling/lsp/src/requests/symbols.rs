@@ -0,0 +1,382 @@
+use std::{
+    future::{self, Future},
+    path::{Path, PathBuf},
+};
+
+use async_lsp::{ErrorCode, ResponseError};
+use fm::{FileId, FileMap, PathString, FILE_EXTENSION};
+use lsp_types::{
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, Location, SymbolInformation,
+    SymbolKind, Url, WorkspaceSymbolParams, WorkspaceSymbolResponse,
+};
+use noirc_errors::Span;
+use noirc_frontend::{
+    parse_program, parser::ItemKind, NoirFunction, ParsedModule, Pattern, TraitImplItem, TraitItem,
+};
+
+use crate::{
+    byte_span_to_range,
+    types::{DocumentSymbolResult, WorkspaceSymbolResult},
+    LspState,
+};
+
+use super::{uri_to_file_path, workspace_packages};
+
+pub(crate) fn on_document_symbol_request(
+    state: &mut LspState,
+    params: DocumentSymbolParams,
+) -> impl Future<Output = Result<DocumentSymbolResult, ResponseError>> {
+    future::ready(document_symbols(state, params))
+}
+
+pub(crate) fn on_workspace_symbol_request(
+    state: &mut LspState,
+    params: WorkspaceSymbolParams,
+) -> impl Future<Output = Result<WorkspaceSymbolResult, ResponseError>> {
+    future::ready(workspace_symbols(state, params))
+}
+
+fn document_symbols(
+    state: &LspState,
+    params: DocumentSymbolParams,
+) -> Result<DocumentSymbolResult, ResponseError> {
+    let file_path = uri_to_file_path(&params.text_document.uri)?;
+    let source = read_source(state, &file_path)
+        .map_err(|err| ResponseError::new(ErrorCode::REQUEST_FAILED, err.to_string()))?;
+
+    let symbols = Outline::new(&file_path, source).symbols();
+    Ok(Some(DocumentSymbolResponse::Nested(symbols)))
+}
+
+fn workspace_symbols(
+    state: &LspState,
+    params: WorkspaceSymbolParams,
+) -> Result<WorkspaceSymbolResult, ResponseError> {
+    // Search the workspace opened by the client as well as those of any open documents
+    let open_documents = state
+        .input_files
+        .keys()
+        .filter_map(|uri| Url::parse(uri).ok().and_then(|uri| uri.to_file_path().ok()));
+    let mut package_dirs: Vec<PathBuf> = Vec::new();
+    for path in state.root_path.iter().cloned().chain(open_documents) {
+        let Ok(packages) = workspace_packages(state, &path) else {
+            continue;
+        };
+        for (_, package) in packages {
+            let Some(source_dir) = package.entry_path.parent() else {
+                continue;
+            };
+            if !package_dirs.iter().any(|dir| dir == source_dir) {
+                package_dirs.push(source_dir.to_path_buf());
+            }
+        }
+    }
+
+    let mut symbols = Vec::new();
+    for file_path in package_dirs.iter().flat_map(|dir| noir_files_in_dir(dir)) {
+        let Ok(source) = read_source(state, &file_path) else {
+            continue;
+        };
+        let Ok(uri) = Url::from_file_path(&file_path) else {
+            continue;
+        };
+        let outline = Outline::new(&file_path, source).symbols();
+        flatten_symbols(&uri, outline, None, &params.query, &mut symbols);
+    }
+
+    Ok(Some(WorkspaceSymbolResponse::Flat(symbols)))
+}
+
+/// Reads the contents of the given file, preferring the unsaved contents of an open document.
+fn read_source(state: &LspState, file_path: &Path) -> std::io::Result<String> {
+    let uri = Url::from_file_path(file_path).map(|uri| uri.to_string()).unwrap_or_default();
+    match state.input_files.get(&uri) {
+        Some(source) => Ok(source.clone()),
+        None => std::fs::read_to_string(file_path),
+    }
+}
+
+fn noir_files_in_dir(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        if path.is_dir() {
+            files.extend(noir_files_in_dir(&path));
+        } else if path.extension().map_or(false, |extension| extension == FILE_EXTENSION) {
+            files.push(path);
+        }
+    }
+    files
+}
+
+fn flatten_symbols(
+    uri: &Url,
+    outline: Vec<DocumentSymbol>,
+    container_name: Option<&str>,
+    query: &str,
+    symbols: &mut Vec<SymbolInformation>,
+) {
+    for symbol in outline {
+        if fuzzy_match(&symbol.name, query) {
+            #[allow(deprecated)]
+            symbols.push(SymbolInformation {
+                name: symbol.name.clone(),
+                kind: symbol.kind,
+                tags: None,
+                deprecated: None,
+                location: Location { uri: uri.clone(), range: symbol.selection_range },
+                container_name: container_name.map(|name| name.to_string()),
+            });
+        }
+        let children = symbol.children.unwrap_or_default();
+        flatten_symbols(uri, children, Some(&symbol.name), query, symbols);
+    }
+}
+
+/// Returns whether each character of `query` appears in `name`, in order and ignoring case.
+fn fuzzy_match(name: &str, query: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query.chars().flat_map(char::to_lowercase).all(|query_char| name.any(|char| char == query_char))
+}
+
+/// Builds the outline of a single file from its [ParsedModule].
+///
+/// Only parsing is required so that an outline is available even when the file doesn't type check.
+struct Outline {
+    files: FileMap,
+    file_id: FileId,
+}
+
+impl Outline {
+    fn new(file_path: &Path, source: String) -> Self {
+        let mut files = FileMap::default();
+        let file_id = files.add_file(PathString::from(file_path.to_path_buf()), source);
+        Outline { files, file_id }
+    }
+
+    fn symbols(&self) -> Vec<DocumentSymbol> {
+        let source = self.files.get_file(self.file_id).map(|file| file.source()).unwrap_or("");
+        // Parsing recovers from errors so we still outline the items which did parse
+        let (module, _) = parse_program(source);
+        self.module_symbols(module)
+    }
+
+    fn module_symbols(&self, module: ParsedModule) -> Vec<DocumentSymbol> {
+        let mut symbols = Vec::new();
+        for item in module.items {
+            let symbol = match item.kind {
+                ItemKind::Function(function) => {
+                    self.function_symbol(&function, item.span, SymbolKind::FUNCTION)
+                }
+                ItemKind::Struct(noir_struct) => {
+                    let fields = noir_struct.fields.iter().filter_map(|(name, typ)| {
+                        let symbol = self.symbol(name.to_string(), SymbolKind::FIELD, name.span());
+                        symbol.map(|symbol| DocumentSymbol {
+                            detail: Some(typ.to_string()),
+                            ..symbol
+                        })
+                    });
+                    let fields = fields.collect();
+                    self.symbol_with_children(
+                        noir_struct.name.to_string(),
+                        SymbolKind::STRUCT,
+                        item.span,
+                        noir_struct.name.span(),
+                        fields,
+                    )
+                }
+                ItemKind::Trait(noir_trait) => {
+                    let items = noir_trait.items.iter().filter_map(|trait_item| match trait_item {
+                        TraitItem::Function { name, .. } => {
+                            self.symbol(name.to_string(), SymbolKind::METHOD, name.span())
+                        }
+                        TraitItem::Constant { name, .. } => {
+                            self.symbol(name.to_string(), SymbolKind::CONSTANT, name.span())
+                        }
+                        TraitItem::Type { name } => {
+                            self.symbol(name.to_string(), SymbolKind::TYPE_PARAMETER, name.span())
+                        }
+                    });
+                    let items = items.collect();
+                    self.symbol_with_children(
+                        noir_trait.name.to_string(),
+                        SymbolKind::INTERFACE,
+                        item.span,
+                        noir_trait.name.span(),
+                        items,
+                    )
+                }
+                ItemKind::TraitImpl(trait_impl) => {
+                    let items = trait_impl.items.iter().filter_map(|impl_item| match impl_item {
+                        TraitImplItem::Function(function) => {
+                            self.function_symbol(function, function.span(), SymbolKind::METHOD)
+                        }
+                        TraitImplItem::Constant(name, _, _) => {
+                            self.symbol(name.to_string(), SymbolKind::CONSTANT, name.span())
+                        }
+                        TraitImplItem::Type { name, .. } => {
+                            self.symbol(name.to_string(), SymbolKind::TYPE_PARAMETER, name.span())
+                        }
+                    });
+                    let items = items.collect();
+                    let name =
+                        format!("impl {} for {}", trait_impl.trait_name, trait_impl.object_type);
+                    let name_span = trait_impl.trait_name.span();
+                    self.symbol_with_children(name, SymbolKind::OBJECT, item.span, name_span, items)
+                }
+                ItemKind::Impl(type_impl) => {
+                    let methods = type_impl.methods.iter().filter_map(|method| {
+                        self.function_symbol(method, method.span(), SymbolKind::METHOD)
+                    });
+                    let methods = methods.collect();
+                    let name = format!("impl {}", type_impl.object_type);
+                    let name_span = type_impl.type_span;
+                    self.symbol_with_children(
+                        name,
+                        SymbolKind::OBJECT,
+                        item.span,
+                        name_span,
+                        methods,
+                    )
+                }
+                ItemKind::TypeAlias(type_alias) => self.symbol_with_children(
+                    type_alias.name.to_string(),
+                    SymbolKind::TYPE_PARAMETER,
+                    item.span,
+                    type_alias.name.span(),
+                    Vec::new(),
+                ),
+                ItemKind::Global(global) => match &global.pattern {
+                    Pattern::Identifier(name) => self.symbol_with_children(
+                        name.to_string(),
+                        SymbolKind::CONSTANT,
+                        item.span,
+                        name.span(),
+                        Vec::new(),
+                    ),
+                    _ => None,
+                },
+                ItemKind::ModuleDecl(name) => {
+                    self.symbol(name.to_string(), SymbolKind::MODULE, name.span())
+                }
+                ItemKind::Submodules(submodule) => {
+                    let kind =
+                        if submodule.is_contract { SymbolKind::CLASS } else { SymbolKind::MODULE };
+                    let name_span = submodule.name.span();
+                    let children = self.module_symbols(submodule.contents);
+                    let name = submodule.name.to_string();
+                    self.symbol_with_children(name, kind, item.span, name_span, children)
+                }
+                ItemKind::Import(_) => None,
+            };
+            symbols.extend(symbol);
+        }
+        symbols
+    }
+
+    fn function_symbol(
+        &self,
+        function: &NoirFunction,
+        span: Span,
+        kind: SymbolKind,
+    ) -> Option<DocumentSymbol> {
+        let name = function.name_ident();
+        self.symbol_with_children(name.to_string(), kind, span, name.span(), Vec::new())
+    }
+
+    fn symbol(&self, name: String, kind: SymbolKind, span: Span) -> Option<DocumentSymbol> {
+        self.symbol_with_children(name, kind, span, span, Vec::new())
+    }
+
+    fn symbol_with_children(
+        &self,
+        name: String,
+        kind: SymbolKind,
+        span: Span,
+        name_span: Span,
+        children: Vec<DocumentSymbol>,
+    ) -> Option<DocumentSymbol> {
+        let range = byte_span_to_range(&self.files, self.file_id, span.into())?;
+        let selection_range = byte_span_to_range(&self.files, self.file_id, name_span.into())?;
+        let children = if children.is_empty() { None } else { Some(children) };
+
+        #[allow(deprecated)]
+        Some(DocumentSymbol {
+            name,
+            detail: None,
+            kind,
+            tags: None,
+            deprecated: None,
+            range,
+            selection_range,
+            children,
+        })
+    }
+}
+
+#[cfg(test)]
+mod symbols_tests {
+    use crate::requests::{
+        on_document_symbol_request, on_workspace_symbol_request, test_utils::init_lsp_server,
+    };
+
+    use super::*;
+    use lsp_types::{PartialResultParams, TextDocumentIdentifier, WorkDoneProgressParams};
+    use tokio::test;
+
+    #[test]
+    async fn test_on_document_symbol_request() {
+        let (mut state, _, uri) = init_lsp_server();
+
+        let params = DocumentSymbolParams {
+            text_document: TextDocumentIdentifier { uri },
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+            partial_result_params: PartialResultParams { partial_result_token: None },
+        };
+        let response = on_document_symbol_request(&mut state, params)
+            .await
+            .expect("Could not execute on_document_symbol_request");
+
+        let Some(DocumentSymbolResponse::Nested(symbols)) = response else {
+            panic!("Expected nested document symbols");
+        };
+        let main = symbols.iter().find(|symbol| symbol.name == "main").unwrap();
+        assert_eq!(main.kind, SymbolKind::FUNCTION);
+
+        let my_struct = symbols.iter().find(|symbol| symbol.name == "my_struct").unwrap();
+        assert_eq!(my_struct.kind, SymbolKind::STRUCT);
+        let fields: Vec<_> =
+            my_struct.children.iter().flatten().map(|field| field.name.as_str()).collect();
+        assert_eq!(fields, vec!["a", "b"]);
+    }
+
+    #[test]
+    async fn test_on_workspace_symbol_request() {
+        let (mut state, root_path, _) = init_lsp_server();
+        state.root_path = Some(root_path);
+
+        let params = WorkspaceSymbolParams {
+            partial_result_params: PartialResultParams { partial_result_token: None },
+            work_done_progress_params: WorkDoneProgressParams { work_done_token: None },
+            query: "tstmul5".to_string(),
+        };
+        let response = on_workspace_symbol_request(&mut state, params)
+            .await
+            .expect("Could not execute on_workspace_symbol_request");
+
+        let Some(WorkspaceSymbolResponse::Flat(symbols)) = response else {
+            panic!("Expected flat workspace symbols");
+        };
+        let names: Vec<_> = symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(names, vec!["test_multiple5"]);
+    }
+
+    #[test]
+    async fn test_fuzzy_match() {
+        assert!(fuzzy_match("test_multiple5", "TM5"));
+        assert!(fuzzy_match("anything", ""));
+        assert!(!fuzzy_match("main", "mian"));
+    }
+}
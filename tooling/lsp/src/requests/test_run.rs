@@ -3,10 +3,12 @@ use std::future::{self, Future};
 use async_lsp::{ErrorCode, ResponseError};
 use nargo::{
     insert_all_files_for_workspace_into_file_manager,
-    ops::{run_test, TestStatus},
+    ops::{read_test_cases, run_test, TestInputs, TestStatus},
     prepare_package,
 };
-use nargo_toml::{find_package_manifest, resolve_workspace_from_toml, PackageSelection};
+use nargo_toml::{
+    find_package_manifest, resolve_workspace_without_lockfile, NetworkMode, PackageSelection,
+};
 use noirc_driver::{
     check_crate, file_manager_with_stdlib, CompileOptions, NOIR_ARTIFACT_VERSION_STRING,
 };
@@ -40,10 +42,11 @@ fn on_test_run_request_inner(
     let crate_name = params.id.crate_name();
     let function_name = params.id.function_name();
 
-    let workspace = resolve_workspace_from_toml(
+    let workspace = resolve_workspace_without_lockfile(
         &toml_path,
         PackageSelection::Selected(crate_name.clone()),
         Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        NetworkMode::Online,
     )
     .map_err(|err| {
         // If we found a manifest, but the workspace is invalid, we raise an error about it
@@ -71,17 +74,26 @@ fn on_test_run_request_inner(
                 FunctionNameMatch::Exact(function_name),
             );
 
-            let (_, test_function) = test_functions.into_iter().next().ok_or_else(|| {
-                ResponseError::new(
-                    ErrorCode::REQUEST_FAILED,
-                    format!("Could not locate test named: {function_name} in {crate_name}"),
-                )
-            })?;
+            let (test_name, test_function) =
+                test_functions.into_iter().next().ok_or_else(|| {
+                    ResponseError::new(
+                        ErrorCode::REQUEST_FAILED,
+                        format!("Could not locate test named: {function_name} in {crate_name}"),
+                    )
+                })?;
+
+            // Tests with fixed cases in the package's `Tests.toml` are run with those, as by `nargo test`
+            let mut test_cases = read_test_cases(&package.root_dir)
+                .map_err(|err| ResponseError::new(ErrorCode::REQUEST_FAILED, err))?;
+            let inputs =
+                test_cases.remove(&test_name).map_or_else(TestInputs::default, TestInputs::Cases);
 
             let test_result = run_test(
                 &state.solver,
                 &context,
                 test_function,
+                &inputs,
+                None,
                 false,
                 None,
                 &CompileOptions::default(),
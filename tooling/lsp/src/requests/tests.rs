@@ -3,7 +3,9 @@ use std::future::{self, Future};
 use async_lsp::{ErrorCode, LanguageClient, ResponseError};
 use lsp_types::{LogMessageParams, MessageType};
 use nargo::{insert_all_files_for_workspace_into_file_manager, prepare_package};
-use nargo_toml::{find_package_manifest, resolve_workspace_from_toml, PackageSelection};
+use nargo_toml::{
+    find_package_manifest, resolve_workspace_without_lockfile, NetworkMode, PackageSelection,
+};
 use noirc_driver::{check_crate, file_manager_with_stdlib, NOIR_ARTIFACT_VERSION_STRING};
 
 use crate::{
@@ -40,10 +42,11 @@ fn on_tests_request_inner(
         }
     };
 
-    let workspace = resolve_workspace_from_toml(
+    let workspace = resolve_workspace_without_lockfile(
         &toml_path,
         PackageSelection::All,
         Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        NetworkMode::Online,
     )
     .map_err(|err| {
         // If we found a manifest, but the workspace is invalid, we raise an error about it
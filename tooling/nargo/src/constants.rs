@@ -15,6 +15,8 @@ pub const EXPORT_DIR: &str = "export";
 pub const PROVER_INPUT_FILE: &str = "Prover";
 /// The file from which Nargo pulls verifier inputs
 pub const VERIFIER_INPUT_FILE: &str = "Verifier";
+/// The file from which Nargo pulls fixed arguments for test functions with parameters
+pub const TEST_CASES_FILE: &str = "Tests";
 /// The package definition file for a Noir project.
 pub const PKG_FILE: &str = "Nargo.toml";
 
@@ -0,0 +1,228 @@
+//! Coverage of source code by the opcodes which ran while executing circuits.
+//!
+//! Every opcode is mapped back to the source locations it was generated from through
+//! [`DebugInfo::locations`], so a line is covered once any opcode generated from it has run.
+//! Constrained code is always executed in full, as both sides of its conditionals are evaluated,
+//! whereas unconstrained code follows the branches taken by its conditional jumps.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use acvm::acir::brillig::Opcode as BrilligOpcode;
+use acvm::acir::circuit::{Circuit, Opcode, OpcodeLocation};
+use fm::{codespan_files::Files, FileManager};
+use noirc_errors::{debug_info::DebugInfo, Location};
+
+use crate::ops::ExecutionObserver;
+
+/// How often each opcode of a circuit ran, and which way each conditional jump of its
+/// unconstrained functions went.
+#[derive(Debug, Default, Clone)]
+pub struct OpcodeCoverage {
+    hits: HashMap<OpcodeLocation, usize>,
+    branches: HashMap<OpcodeLocation, BranchCoverage>,
+}
+
+impl ExecutionObserver for OpcodeCoverage {
+    fn acir_opcode(&mut self, acir_index: usize) {
+        *self.hits.entry(OpcodeLocation::Acir(acir_index)).or_default() += 1;
+    }
+
+    fn brillig_opcode(
+        &mut self,
+        location: OpcodeLocation,
+        opcode: &BrilligOpcode,
+        next_program_counter: usize,
+        _memory_size: usize,
+    ) {
+        *self.hits.entry(location).or_default() += 1;
+
+        let (BrilligOpcode::JumpIf { .. } | BrilligOpcode::JumpIfNot { .. }) = opcode else {
+            return;
+        };
+        let OpcodeLocation::Brillig { brillig_index, .. } = location else { return };
+        let branch = self.branches.entry(location).or_default();
+        if next_program_counter != brillig_index + 1 {
+            branch.taken += 1;
+        } else {
+            branch.not_taken += 1;
+        }
+    }
+}
+
+/// Line and branch coverage of source files, accumulated over any number of executions.
+#[derive(Debug, Default, Clone)]
+pub struct SourceCoverage {
+    pub files: BTreeMap<PathBuf, FileCoverage>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FileCoverage {
+    /// Every line which opcodes were generated from, by line number
+    pub lines: BTreeMap<usize, LineCoverage>,
+    /// Every conditional jump of unconstrained code, by the line and column of its condition
+    pub branches: BTreeMap<(usize, usize), BranchCoverage>,
+}
+
+/// How many times a line ran, split by whether it ran as constrained or unconstrained code.
+/// Either is `None` if the line was never compiled to that kind of code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LineCoverage {
+    pub constrained_hits: Option<usize>,
+    pub unconstrained_hits: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BranchCoverage {
+    pub taken: usize,
+    pub not_taken: usize,
+}
+
+impl LineCoverage {
+    pub fn hits(&self) -> usize {
+        self.constrained_hits.unwrap_or_default() + self.unconstrained_hits.unwrap_or_default()
+    }
+
+    pub fn is_covered(&self) -> bool {
+        self.hits() > 0
+    }
+
+    fn merge(&mut self, other: LineCoverage, combine: impl Fn(usize, usize) -> usize) {
+        let merge_hits = |hits: Option<usize>, other_hits: Option<usize>| match (hits, other_hits) {
+            (Some(hits), Some(other_hits)) => Some(combine(hits, other_hits)),
+            (hits, other_hits) => hits.or(other_hits),
+        };
+        self.constrained_hits = merge_hits(self.constrained_hits, other.constrained_hits);
+        self.unconstrained_hits = merge_hits(self.unconstrained_hits, other.unconstrained_hits);
+    }
+}
+
+impl BranchCoverage {
+    /// The number of the branch's two directions which were followed at least once
+    pub fn covered_directions(&self) -> usize {
+        usize::from(self.taken > 0) + usize::from(self.not_taken > 0)
+    }
+
+    fn merge(&mut self, other: BranchCoverage) {
+        self.taken += other.taken;
+        self.not_taken += other.not_taken;
+    }
+}
+
+impl FileCoverage {
+    fn merge(&mut self, other: FileCoverage) {
+        for (line, coverage) in other.lines {
+            self.lines
+                .entry(line)
+                .or_default()
+                .merge(coverage, |hits, other_hits| hits + other_hits);
+        }
+        for (position, coverage) in other.branches {
+            self.branches.entry(position).or_default().merge(coverage);
+        }
+    }
+}
+
+impl SourceCoverage {
+    /// Adds the coverage of an execution of `circuit`.
+    pub fn add_execution(
+        &mut self,
+        file_manager: &FileManager,
+        circuit: &Circuit,
+        debug: &DebugInfo,
+        opcodes: &OpcodeCoverage,
+    ) {
+        let mut execution = SourceCoverage::default();
+        for (opcode_location, call_stack) in &debug.locations {
+            let hits = opcodes.hits.get(opcode_location).copied().unwrap_or_default();
+            let line_coverage = match opcode_location {
+                OpcodeLocation::Acir(_) => {
+                    LineCoverage { constrained_hits: Some(hits), unconstrained_hits: None }
+                }
+                OpcodeLocation::Brillig { .. } => {
+                    LineCoverage { constrained_hits: None, unconstrained_hits: Some(hits) }
+                }
+            };
+            // Every function call leading to the opcode ran along with it.
+            for location in call_stack {
+                let Some((path, line, _)) = source_position(file_manager, location) else {
+                    continue;
+                };
+                let file = execution.files.entry(path).or_default();
+                // A line which many opcodes were generated from ran as often as the most run of them.
+                file.lines.entry(line).or_default().merge(line_coverage, usize::max);
+            }
+
+            if is_conditional_jump(circuit, opcode_location) {
+                let Some(location) = call_stack.last() else { continue };
+                let Some((path, line, column)) = source_position(file_manager, location) else {
+                    continue;
+                };
+                let branch = opcodes.branches.get(opcode_location).copied().unwrap_or_default();
+                let file = execution.files.entry(path).or_default();
+                file.branches.entry((line, column)).or_default().merge(branch);
+            }
+        }
+        self.merge(execution);
+    }
+
+    /// Adds up the coverage of both `self` and `other`.
+    pub fn merge(&mut self, other: SourceCoverage) {
+        for (path, file) in other.files {
+            self.files.entry(path).or_default().merge(file);
+        }
+    }
+}
+
+pub(crate) fn source_position(
+    file_manager: &FileManager,
+    location: &Location,
+) -> Option<(PathBuf, usize, usize)> {
+    let position =
+        file_manager.as_file_map().location(location.file, location.span.start() as usize).ok()?;
+    let path = file_manager.path(location.file).to_path_buf();
+    Some((path, position.line_number, position.column_number))
+}
+
+fn is_conditional_jump(circuit: &Circuit, location: &OpcodeLocation) -> bool {
+    let OpcodeLocation::Brillig { acir_index, brillig_index } = location else {
+        return false;
+    };
+    let Some(Opcode::Brillig(brillig)) = circuit.opcodes.get(*acir_index) else {
+        return false;
+    };
+    matches!(
+        brillig.bytecode.get(*brillig_index),
+        Some(BrilligOpcode::JumpIf { .. } | BrilligOpcode::JumpIfNot { .. })
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BranchCoverage, FileCoverage, LineCoverage};
+
+    #[test]
+    fn merges_coverage_of_several_executions() {
+        let mut coverage = FileCoverage::default();
+        coverage
+            .lines
+            .insert(1, LineCoverage { constrained_hits: Some(1), unconstrained_hits: None });
+        coverage.branches.insert((2, 5), BranchCoverage { taken: 1, not_taken: 0 });
+
+        let mut other = FileCoverage::default();
+        other
+            .lines
+            .insert(1, LineCoverage { constrained_hits: Some(2), unconstrained_hits: Some(0) });
+        other.lines.insert(3, LineCoverage { constrained_hits: None, unconstrained_hits: Some(0) });
+        other.branches.insert((2, 5), BranchCoverage { taken: 0, not_taken: 3 });
+        coverage.merge(other);
+
+        assert_eq!(
+            coverage.lines[&1],
+            LineCoverage { constrained_hits: Some(3), unconstrained_hits: Some(0) }
+        );
+        assert!(coverage.lines[&1].is_covered());
+        assert!(!coverage.lines[&3].is_covered());
+        assert_eq!(coverage.branches[&(2, 5)].covered_directions(), 2);
+    }
+}
@@ -10,6 +10,7 @@ pub use noirc_errors::Location;
 
 use noirc_frontend::graph::CrateName;
 use noirc_printable_type::ForeignCallError;
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// Errors covering situations where a package cannot be compiled.
@@ -32,6 +33,16 @@ impl From<ReportedErrors> for CompileError {
     }
 }
 
+/// Errors covering situations where a package's fixed test cases cannot be used.
+#[derive(Debug, Error)]
+pub enum TestCasesError {
+    #[error("Error: could not parse test cases in {}: {1}", .0.display())]
+    NotValid(PathBuf, String),
+
+    #[error("Error: {} has test cases for `{1}`, but the package has no test function with that name", .0.display())]
+    UnknownTest(PathBuf, String),
+}
+
 #[derive(Debug, Error)]
 pub enum NargoError {
     /// Error while compiling Noir into ACIR.
@@ -9,18 +9,20 @@
 
 pub mod artifacts;
 pub mod constants;
+pub mod coverage;
 pub mod errors;
 pub mod ops;
 pub mod package;
+pub mod profile;
 pub mod workspace;
 
 use std::collections::BTreeMap;
 
-use fm::FileManager;
+use fm::{FileId, FileManager, FILE_EXTENSION};
 use noirc_driver::{add_dep, prepare_crate, prepare_dependency};
 use noirc_frontend::{
     graph::{CrateId, CrateName},
-    hir::Context,
+    hir::{def_map::parse_file, Context, ParsedFiles},
 };
 use package::{Dependency, Package};
 
@@ -95,12 +97,45 @@ fn insert_all_files_for_packages_dependencies_into_file_manager(
     }
 }
 
+/// The Noir source files in the file manager.
+pub fn noir_file_ids(file_manager: &FileManager) -> impl Iterator<Item = FileId> + '_ {
+    file_manager.as_file_map().all_file_ids().copied().filter(|&file_id| {
+        file_manager
+            .path(file_id)
+            .extension()
+            .map_or(false, |extension| extension == FILE_EXTENSION)
+    })
+}
+
+/// Parses all of the Noir source files in the file manager, so that files used by several
+/// packages of the workspace are only parsed once.
+pub fn parse_all(file_manager: &FileManager) -> ParsedFiles {
+    noir_file_ids(file_manager)
+        .map(|file_id| (file_id, parse_file(file_manager, file_id)))
+        .collect()
+}
+
 pub fn prepare_package<'file_manager>(
     file_manager: &'file_manager FileManager,
     package: &Package,
 ) -> (Context<'file_manager>, CrateId) {
-    let mut context = Context::from_ref_file_manager(file_manager);
+    prepare_package_context(Context::from_ref_file_manager(file_manager), package)
+}
+
+/// Like [prepare_package], but reuses the results of parsing the package's files.
+pub fn prepare_package_with_parsed_files<'file_manager>(
+    file_manager: &'file_manager FileManager,
+    parsed_files: &'file_manager ParsedFiles,
+    package: &Package,
+) -> (Context<'file_manager>, CrateId) {
+    let context = Context::from_ref_file_manager_and_parsed_files(file_manager, parsed_files);
+    prepare_package_context(context, package)
+}
 
+fn prepare_package_context<'file_manager>(
+    mut context: Context<'file_manager>,
+    package: &Package,
+) -> (Context<'file_manager>, CrateId) {
     let crate_id = prepare_crate(&mut context, &package.entry_path);
 
     prepare_dependencies(&mut context, crate_id, &package.dependencies);
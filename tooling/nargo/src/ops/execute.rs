@@ -1,4 +1,9 @@
-use acvm::pwg::{ACVMStatus, ErrorLocation, OpcodeResolutionError, ACVM};
+use acvm::acir::brillig::Opcode as BrilligOpcode;
+use acvm::acir::circuit::{Opcode, OpcodeLocation};
+use acvm::pwg::{
+    ACVMStatus, BrilligSolver, BrilligSolverStatus, ErrorLocation, OpcodeResolutionError,
+    StepResult, ACVM,
+};
 use acvm::BlackBoxFunctionSolver;
 use acvm::{acir::circuit::Circuit, acir::native_types::WitnessMap};
 
@@ -24,30 +29,7 @@ pub fn execute_circuit<B: BlackBoxFunctionSolver, F: ForeignCallExecutor>(
             ACVMStatus::InProgress => {
                 unreachable!("Execution should not stop while in `InProgress` state.")
             }
-            ACVMStatus::Failure(error) => {
-                let call_stack = match &error {
-                    OpcodeResolutionError::UnsatisfiedConstrain {
-                        opcode_location: ErrorLocation::Resolved(opcode_location),
-                    } => Some(vec![*opcode_location]),
-                    OpcodeResolutionError::BrilligFunctionFailed { call_stack, .. } => {
-                        Some(call_stack.clone())
-                    }
-                    _ => None,
-                };
-
-                return Err(NargoError::ExecutionError(match call_stack {
-                    Some(call_stack) => {
-                        if let Some(assert_message) = circuit.get_assert_message(
-                            *call_stack.last().expect("Call stacks should not be empty"),
-                        ) {
-                            ExecutionError::AssertionFailed(assert_message.to_owned(), call_stack)
-                        } else {
-                            ExecutionError::SolvingError(error)
-                        }
-                    }
-                    None => ExecutionError::SolvingError(error),
-                }));
-            }
+            ACVMStatus::Failure(error) => return Err(execution_error(circuit, error)),
             ACVMStatus::RequiresForeignCall(foreign_call) => {
                 let foreign_call_result = foreign_call_executor.execute(&foreign_call)?;
                 acvm.resolve_pending_foreign_call(foreign_call_result);
@@ -57,3 +39,135 @@ pub fn execute_circuit<B: BlackBoxFunctionSolver, F: ForeignCallExecutor>(
 
     Ok(acvm.finalize())
 }
+
+/// Watches the opcodes run by [`execute_circuit_with_observers`], to measure an execution.
+pub trait ExecutionObserver {
+    /// Called as the ACIR opcode at `acir_index` starts running
+    fn acir_opcode(&mut self, _acir_index: usize) {}
+
+    /// Called once a Brillig opcode has run, with the program counter it moved on to and the
+    /// size of the Brillig VM's memory
+    fn brillig_opcode(
+        &mut self,
+        _location: OpcodeLocation,
+        _opcode: &BrilligOpcode,
+        _next_program_counter: usize,
+        _memory_size: usize,
+    ) {
+    }
+
+    /// Called as a Brillig opcode makes a foreign call
+    fn foreign_call(&mut self, _location: OpcodeLocation) {}
+}
+
+/// Executes the circuit like [`execute_circuit`], one opcode at a time, showing every ACIR and
+/// Brillig opcode which runs to the `observers`.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn execute_circuit_with_observers<B: BlackBoxFunctionSolver, F: ForeignCallExecutor>(
+    circuit: &Circuit,
+    initial_witness: WitnessMap,
+    blackbox_solver: &B,
+    foreign_call_executor: &mut F,
+    observers: &mut [&mut dyn ExecutionObserver],
+) -> Result<WitnessMap, NargoError> {
+    let mut acvm = ACVM::new(blackbox_solver, &circuit.opcodes, initial_witness);
+
+    loop {
+        let acir_index = acvm.instruction_pointer();
+        for observer in observers.iter_mut() {
+            observer.acir_opcode(acir_index);
+        }
+
+        let solver_status = match acvm.step_into_brillig_opcode() {
+            StepResult::Status(status) => status,
+            StepResult::IntoBrillig(solver) => {
+                let solver = run_brillig_with_observers(
+                    circuit,
+                    acir_index,
+                    solver,
+                    foreign_call_executor,
+                    observers,
+                )?;
+                acvm.finish_brillig_with_solver(solver)
+            }
+        };
+
+        match solver_status {
+            ACVMStatus::Solved => break,
+            ACVMStatus::InProgress => (),
+            ACVMStatus::Failure(error) => return Err(execution_error(circuit, error)),
+            ACVMStatus::RequiresForeignCall(_) => {
+                unreachable!("Foreign calls are resolved while stepping through Brillig opcodes")
+            }
+        }
+    }
+
+    Ok(acvm.finalize())
+}
+
+/// Steps through the Brillig opcodes of an unconstrained function call until it finishes.
+fn run_brillig_with_observers<'a, B: BlackBoxFunctionSolver, F: ForeignCallExecutor>(
+    circuit: &Circuit,
+    acir_index: usize,
+    mut solver: BrilligSolver<'a, B>,
+    foreign_call_executor: &mut F,
+    observers: &mut [&mut dyn ExecutionObserver],
+) -> Result<BrilligSolver<'a, B>, NargoError> {
+    let Opcode::Brillig(brillig) = &circuit.opcodes[acir_index] else {
+        unreachable!("Not executing a Brillig opcode");
+    };
+
+    loop {
+        let brillig_index = solver.program_counter();
+        let location = OpcodeLocation::Brillig { acir_index, brillig_index };
+        let status = solver.step();
+
+        // A foreign call opcode runs again once its result is known.
+        if !matches!(status, Ok(BrilligSolverStatus::ForeignCallWait(_))) {
+            for observer in observers.iter_mut() {
+                observer.brillig_opcode(
+                    location,
+                    &brillig.bytecode[brillig_index],
+                    solver.program_counter(),
+                    solver.get_memory().len(),
+                );
+            }
+        }
+
+        match status {
+            Ok(BrilligSolverStatus::InProgress) => (),
+            Ok(BrilligSolverStatus::Finished) => return Ok(solver),
+            Ok(BrilligSolverStatus::ForeignCallWait(foreign_call)) => {
+                for observer in observers.iter_mut() {
+                    observer.foreign_call(location);
+                }
+                let foreign_call_result = foreign_call_executor.execute(&foreign_call)?;
+                solver.resolve_pending_foreign_call(foreign_call_result);
+            }
+            Err(error) => return Err(execution_error(circuit, error)),
+        }
+    }
+}
+
+fn execution_error(circuit: &Circuit, error: OpcodeResolutionError) -> NargoError {
+    let call_stack = match &error {
+        OpcodeResolutionError::UnsatisfiedConstrain {
+            opcode_location: ErrorLocation::Resolved(opcode_location),
+        } => Some(vec![*opcode_location]),
+        OpcodeResolutionError::BrilligFunctionFailed { call_stack, .. } => Some(call_stack.clone()),
+        _ => None,
+    };
+
+    NargoError::ExecutionError(match call_stack {
+        Some(call_stack) => {
+            if let Some(assert_message) = circuit
+                .get_assert_message(*call_stack.last().expect("Call stacks should not be empty"))
+            {
+                ExecutionError::AssertionFailed(assert_message.to_owned(), call_stack)
+            } else {
+                ExecutionError::SolvingError(error)
+            }
+        }
+        None => ExecutionError::SolvingError(error),
+    })
+}
@@ -95,6 +95,8 @@ pub struct DefaultForeignCallExecutor {
     mocked_responses: Vec<MockedCall>,
     /// Whether to print [`ForeignCall::Print`] output.
     show_output: bool,
+    /// The [`ForeignCall::Print`] output collected so far, if it's collected instead of printed
+    captured_output: Option<String>,
     /// JSON RPC client to resolve foreign calls
     external_resolver: Option<Client>,
 }
@@ -112,6 +114,21 @@ impl DefaultForeignCallExecutor {
             ..DefaultForeignCallExecutor::default()
         }
     }
+
+    /// Creates an executor which collects the output of [`ForeignCall::Print`] instead of
+    /// printing it, to be taken with [`DefaultForeignCallExecutor::take_output`].
+    pub fn capturing_output(resolver_url: Option<&str>) -> Self {
+        DefaultForeignCallExecutor {
+            captured_output: Some(String::new()),
+            ..DefaultForeignCallExecutor::new(true, resolver_url)
+        }
+    }
+
+    /// Takes the output collected by an executor created with
+    /// [`DefaultForeignCallExecutor::capturing_output`].
+    pub fn take_output(&mut self) -> String {
+        self.captured_output.as_mut().map(std::mem::take).unwrap_or_default()
+    }
 }
 
 impl DefaultForeignCallExecutor {
@@ -132,15 +149,14 @@ impl DefaultForeignCallExecutor {
         decode_string_value(&fields)
     }
 
-    fn execute_print(foreign_call_inputs: &[ForeignCallParam]) -> Result<(), ForeignCallError> {
+    fn format_print(foreign_call_inputs: &[ForeignCallParam]) -> Result<String, ForeignCallError> {
         let skip_newline = foreign_call_inputs[0].unwrap_value().is_zero();
         let display_values: PrintableValueDisplay = foreign_call_inputs
             .split_first()
             .ok_or(ForeignCallError::MissingForeignCallInputs)?
             .1
             .try_into()?;
-        print!("{display_values}{}", if skip_newline { "" } else { "\n" });
-        Ok(())
+        Ok(format!("{display_values}{}", if skip_newline { "" } else { "\n" }))
     }
 }
 
@@ -153,7 +169,11 @@ impl ForeignCallExecutor for DefaultForeignCallExecutor {
         match ForeignCall::lookup(foreign_call_name) {
             Some(ForeignCall::Print) => {
                 if self.show_output {
-                    Self::execute_print(&foreign_call.inputs)?;
+                    let output = Self::format_print(&foreign_call.inputs)?;
+                    match &mut self.captured_output {
+                        Some(captured_output) => captured_output.push_str(&output),
+                        None => print!("{output}"),
+                    }
                 }
                 Ok(ForeignCallResult { values: vec![] })
             }
@@ -0,0 +1,241 @@
+//! Generation and shrinking of arguments for test functions which take parameters.
+
+use acvm::FieldElement;
+use noirc_abi::{input_parser::InputValue, Abi, AbiType, InputMap, Sign};
+use rand::Rng;
+
+/// The number of sets of arguments a parameterized test is run with by default.
+pub const DEFAULT_FUZZ_RUNS: usize = 100;
+
+/// How often a generated value is one of the edge cases of its type rather than a uniformly random one.
+const EDGE_CASE_PROBABILITY: f64 = 0.25;
+
+/// Generates random arguments for all of the ABI's parameters.
+pub(crate) fn generate_inputs(abi: &Abi, rng: &mut impl Rng) -> InputMap {
+    abi.parameters
+        .iter()
+        .map(|param| (param.name.clone(), generate_value(&param.typ, rng)))
+        .collect()
+}
+
+fn generate_value(typ: &AbiType, rng: &mut impl Rng) -> InputValue {
+    match typ {
+        AbiType::Field => InputValue::Field(generate_field(rng)),
+        AbiType::Integer { sign, width } => InputValue::Field(generate_integer(*sign, *width, rng)),
+        AbiType::Boolean => InputValue::Field(rng.gen::<bool>().into()),
+        AbiType::Array { length, typ } => {
+            InputValue::Vec((0..*length).map(|_| generate_value(typ, rng)).collect())
+        }
+        AbiType::Tuple { fields } => {
+            InputValue::Vec(fields.iter().map(|typ| generate_value(typ, rng)).collect())
+        }
+        AbiType::Struct { fields, .. } => InputValue::Struct(
+            fields.iter().map(|(name, typ)| (name.clone(), generate_value(typ, rng))).collect(),
+        ),
+        AbiType::String { length } => {
+            InputValue::String((0..*length).map(|_| rng.gen_range(' '..='~')).collect())
+        }
+    }
+}
+
+fn generate_field(rng: &mut impl Rng) -> FieldElement {
+    if rng.gen_bool(EDGE_CASE_PROBABILITY) {
+        let edge_cases = [FieldElement::zero(), FieldElement::one(), -FieldElement::one()];
+        return edge_cases[rng.gen_range(0..edge_cases.len())];
+    }
+    if rng.gen() {
+        FieldElement::from(rng.gen::<u64>() as u128)
+    } else {
+        FieldElement::from_be_bytes_reduce(&rng.gen::<[u8; 32]>())
+    }
+}
+
+fn generate_integer(sign: Sign, width: u32, rng: &mut impl Rng) -> FieldElement {
+    let max = max_value(width);
+    if rng.gen_bool(EDGE_CASE_PROBABILITY) {
+        let mut edge_cases = vec![0, 1, max];
+        if sign == Sign::Signed {
+            // Signed integers are encoded in two's complement, so these are the minimum and
+            // maximum values of the type.
+            let min = 1 << (width - 1);
+            edge_cases.extend([min, min - 1]);
+        }
+        return FieldElement::from(edge_cases[rng.gen_range(0..edge_cases.len())]);
+    }
+    FieldElement::from(rng.gen::<u128>() & max)
+}
+
+/// The largest unsigned value which fits in `width` bits.
+fn max_value(width: u32) -> u128 {
+    if width >= 128 {
+        u128::MAX
+    } else {
+        (1 << width) - 1
+    }
+}
+
+/// Returns simpler variations of `inputs` which each differ from it in a single value.
+///
+/// Values are simpler when they are closer to zero, so repeatedly picking a candidate which still
+/// fails a test converges on a minimal failing case.
+pub(crate) fn shrink_candidates(abi: &Abi, inputs: &InputMap) -> Vec<InputMap> {
+    let mut candidates = Vec::new();
+    for param in &abi.parameters {
+        let Some(value) = inputs.get(&param.name) else { continue };
+        for simpler_value in simpler_values(value, &param.typ) {
+            let mut candidate = inputs.clone();
+            candidate.insert(param.name.clone(), simpler_value);
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+fn simpler_values(value: &InputValue, typ: &AbiType) -> Vec<InputValue> {
+    match (value, typ) {
+        (InputValue::Field(field), AbiType::Field) => {
+            // Large field elements are first truncated to their lower 128 bits.
+            if !field.fits_in_u128() {
+                return vec![
+                    InputValue::Field(FieldElement::zero()),
+                    InputValue::Field(FieldElement::from(field.to_u128())),
+                ];
+            }
+            smaller_magnitudes(field.to_u128())
+                .into_iter()
+                .map(|value| InputValue::Field(value.into()))
+                .collect()
+        }
+        (InputValue::Field(field), AbiType::Boolean) if !field.is_zero() => {
+            vec![InputValue::Field(FieldElement::zero())]
+        }
+        (InputValue::Field(field), AbiType::Integer { sign, width }) => {
+            simpler_integers(field.to_u128(), *sign, *width)
+                .into_iter()
+                .map(|value| InputValue::Field(value.into()))
+                .collect()
+        }
+        (InputValue::Vec(elements), AbiType::Array { typ, .. }) => {
+            simpler_elements(elements, |_| typ)
+        }
+        (InputValue::Vec(elements), AbiType::Tuple { fields }) => {
+            simpler_elements(elements, |index| &fields[index])
+        }
+        (InputValue::Struct(values), AbiType::Struct { fields, .. }) => {
+            let mut candidates = Vec::new();
+            for (name, typ) in fields {
+                let Some(value) = values.get(name) else { continue };
+                for simpler_value in simpler_values(value, typ) {
+                    let mut candidate = values.clone();
+                    candidate.insert(name.clone(), simpler_value);
+                    candidates.push(InputValue::Struct(candidate));
+                }
+            }
+            candidates
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn simpler_elements<'a>(
+    elements: &[InputValue],
+    element_type: impl Fn(usize) -> &'a AbiType,
+) -> Vec<InputValue> {
+    let mut candidates = Vec::new();
+    for (index, element) in elements.iter().enumerate() {
+        for simpler_element in simpler_values(element, element_type(index)) {
+            let mut candidate = elements.to_vec();
+            candidate[index] = simpler_element;
+            candidates.push(InputValue::Vec(candidate));
+        }
+    }
+    candidates
+}
+
+/// Shrinks integers towards zero, keeping the sign of negative signed integers.
+fn simpler_integers(value: u128, sign: Sign, width: u32) -> Vec<u128> {
+    let max = max_value(width);
+    let is_negative = sign == Sign::Signed && (value >> (width - 1)) & 1 == 1;
+    if !is_negative {
+        return smaller_magnitudes(value);
+    }
+
+    let magnitude = (max - value).wrapping_add(1) & max;
+    smaller_magnitudes(magnitude)
+        .into_iter()
+        .map(|magnitude| (max - magnitude).wrapping_add(1) & max)
+        .collect()
+}
+
+fn smaller_magnitudes(magnitude: u128) -> Vec<u128> {
+    let mut magnitudes = vec![0, magnitude / 2, magnitude.saturating_sub(1)];
+    magnitudes.retain(|candidate| *candidate < magnitude);
+    magnitudes.dedup();
+    magnitudes
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::FieldElement;
+    use noirc_abi::{input_parser::InputValue, Abi, AbiParameter, AbiType, AbiVisibility, Sign};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{generate_inputs, shrink_candidates, simpler_integers};
+
+    fn abi(parameters: Vec<(&str, AbiType)>) -> Abi {
+        Abi {
+            parameters: parameters
+                .into_iter()
+                .map(|(name, typ)| AbiParameter {
+                    name: name.to_string(),
+                    typ,
+                    visibility: AbiVisibility::Private,
+                })
+                .collect(),
+            param_witnesses: Default::default(),
+            return_type: None,
+            return_witnesses: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn generates_values_matching_the_abi() {
+        let abi = abi(vec![
+            ("x", AbiType::Integer { sign: Sign::Unsigned, width: 8 }),
+            ("xs", AbiType::Array { length: 3, typ: Box::new(AbiType::Boolean) }),
+            ("s", AbiType::String { length: 5 }),
+        ]);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..100 {
+            let inputs = generate_inputs(&abi, &mut rng);
+            let InputValue::Field(x) = &inputs["x"] else { panic!("expected a field") };
+            assert!(x.to_u128() <= u8::MAX as u128);
+            let InputValue::Vec(xs) = &inputs["xs"] else { panic!("expected an array") };
+            assert_eq!(xs.len(), 3);
+            assert!(xs.iter().all(|x| matches!(x, InputValue::Field(x) if x.num_bits() <= 1)));
+            let InputValue::String(s) = &inputs["s"] else { panic!("expected a string") };
+            assert_eq!(s.len(), 5);
+        }
+    }
+
+    #[test]
+    fn shrinking_converges_on_minimal_failing_case() {
+        let abi = abi(vec![("x", AbiType::Integer { sign: Sign::Unsigned, width: 32 })]);
+        let fails = |inputs: &noirc_abi::InputMap| matches!(&inputs["x"], InputValue::Field(x) if x.to_u128() >= 1000);
+
+        let mut inputs = [("x".to_string(), InputValue::Field(FieldElement::from(123_456_u128)))]
+            .into_iter()
+            .collect();
+        while let Some(simpler) = shrink_candidates(&abi, &inputs).into_iter().find(fails) {
+            inputs = simpler;
+        }
+        assert_eq!(inputs["x"], InputValue::Field(FieldElement::from(1000_u128)));
+    }
+
+    #[test]
+    fn shrinks_negative_integers_towards_zero() {
+        // -4 as an i8
+        assert_eq!(simpler_integers(0xfc, Sign::Signed, 8), vec![0, 0xfe, 0xfd]);
+    }
+}
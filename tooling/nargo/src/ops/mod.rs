@@ -1,11 +1,16 @@
 pub use self::compile::{compile_program, compile_workspace};
-pub use self::execute::execute_circuit;
+pub use self::execute::{execute_circuit, execute_circuit_with_observers, ExecutionObserver};
 pub use self::foreign_calls::{DefaultForeignCallExecutor, ForeignCallExecutor};
+pub use self::fuzz::DEFAULT_FUZZ_RUNS;
 pub use self::optimize::{optimize_contract, optimize_program};
-pub use self::test::{run_test, TestStatus};
+pub use self::test::{
+    compile_test, read_test_cases, run_compiled_test, run_test, test_cases_path, TestInputs,
+    TestStatus,
+};
 
 mod compile;
 mod execute;
 mod foreign_calls;
+mod fuzz;
 mod optimize;
 mod test;
@@ -1,12 +1,27 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
 use acvm::{acir::native_types::WitnessMap, BlackBoxFunctionSolver};
-use noirc_driver::{compile_no_check, CompileOptions};
+use fm::FileManager;
+use noirc_abi::{input_parser::Format, Abi, InputMap};
+use noirc_driver::{compile_no_check, CompileOptions, CompiledProgram};
 use noirc_errors::{debug_info::DebugInfo, FileDiagnostic};
 use noirc_evaluator::errors::RuntimeError;
 use noirc_frontend::hir::{def_map::TestFunction, Context};
+use rand::{rngs::StdRng, SeedableRng};
 
-use crate::{errors::try_to_diagnose_runtime_error, NargoError};
+use crate::{
+    constants::TEST_CASES_FILE,
+    coverage::{OpcodeCoverage, SourceCoverage},
+    errors::{try_to_diagnose_runtime_error, TestCasesError},
+    NargoError,
+};
 
-use super::{execute_circuit, DefaultForeignCallExecutor};
+use super::{
+    execute_circuit, execute_circuit_with_observers,
+    fuzz::{generate_inputs, shrink_candidates, DEFAULT_FUZZ_RUNS},
+    DefaultForeignCallExecutor,
+};
 
 pub enum TestStatus {
     Pass,
@@ -14,28 +29,277 @@ pub enum TestStatus {
     CompileError(FileDiagnostic),
 }
 
+impl TestStatus {
+    fn passed(&self) -> bool {
+        matches!(self, TestStatus::Pass)
+    }
+}
+
+/// The arguments a test function which takes parameters is run with.
+/// Test functions without parameters are run once, regardless of these.
+#[derive(Debug, Clone)]
+pub enum TestInputs {
+    /// Arguments randomly generated from `seed`, for `runs` runs of the test.
+    Generated { runs: usize, seed: u64 },
+    /// Fixed test cases, each a TOML table mapping the test's parameters to their values.
+    Cases(Vec<String>),
+}
+
+impl Default for TestInputs {
+    fn default() -> Self {
+        TestInputs::Generated { runs: DEFAULT_FUZZ_RUNS, seed: rand::random() }
+    }
+}
+
+/// The file holding the fixed test cases of the package at `package_root`.
+pub fn test_cases_path(package_root: &Path) -> PathBuf {
+    package_root.join(TEST_CASES_FILE).with_extension(Format::Toml.ext())
+}
+
+/// Returns the fixed test cases of the package at `package_root`, keyed by the fully qualified
+/// name of the test function they're for. Each case is a TOML table mapping the test's parameters
+/// to values.
+///
+/// A missing file holds no test cases.
+pub fn read_test_cases(
+    package_root: &Path,
+) -> Result<BTreeMap<String, Vec<String>>, TestCasesError> {
+    let file_path = test_cases_path(package_root);
+    if !file_path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let not_valid = |error: String| TestCasesError::NotValid(file_path.clone(), error);
+    let input_string =
+        std::fs::read_to_string(&file_path).map_err(|error| not_valid(error.to_string()))?;
+    let test_cases: BTreeMap<String, Vec<toml::Table>> =
+        toml::from_str(&input_string).map_err(|error| not_valid(error.to_string()))?;
+    test_cases
+        .into_iter()
+        .map(|(test_name, cases)| {
+            let cases = cases
+                .iter()
+                .map(toml::to_string)
+                .collect::<Result<_, _>>()
+                .map_err(|error| not_valid(error.to_string()))?;
+            Ok((test_name, cases))
+        })
+        .collect()
+}
+
+/// The maximum number of times a failing test is rerun while looking for simpler arguments which also fail it.
+const MAX_SHRINK_RUNS: usize = 256;
+
+#[allow(clippy::too_many_arguments)]
 pub fn run_test<B: BlackBoxFunctionSolver>(
     blackbox_solver: &B,
     context: &Context,
     test_function: TestFunction,
+    inputs: &TestInputs,
+    coverage: Option<&mut SourceCoverage>,
     show_output: bool,
     foreign_call_resolver_url: Option<&str>,
     config: &CompileOptions,
 ) -> TestStatus {
-    let program = compile_no_check(context, config, test_function.get_id(), None, false);
-    match program {
-        Ok(program) => {
-            // Run the backend to ensure the PWG evaluates functions like std::hash::pedersen,
-            // otherwise constraints involving these expressions will not error.
-            let circuit_execution = execute_circuit(
+    let program = match compile_test(context, &test_function, config) {
+        Ok(program) => program,
+        Err(status) => return status,
+    };
+    let mut output = String::new();
+    let status = run_compiled_test(
+        blackbox_solver,
+        &context.file_manager,
+        &program,
+        &test_function,
+        inputs,
+        coverage,
+        show_output.then_some(&mut output),
+        foreign_call_resolver_url,
+    );
+    print!("{output}");
+    status
+}
+
+/// Compiles the test function into a program which can be run with [run_compiled_test].
+///
+/// A test whose compilation fails already has its outcome, which is returned instead.
+pub fn compile_test(
+    context: &Context,
+    test_function: &TestFunction,
+    config: &CompileOptions,
+) -> Result<CompiledProgram, TestStatus> {
+    compile_no_check(context, config, test_function.get_id(), None, false)
+        .map_err(|err| test_status_program_compile_fail(err, test_function))
+}
+
+/// Runs a test function compiled with [compile_test]. If `output` is given, whatever the test
+/// prints is collected into it rather than discarded.
+///
+/// Unlike compiling the test, this doesn't need the frontend's [Context], so tests can be run
+/// on other threads than the one they were compiled on.
+#[allow(clippy::too_many_arguments)]
+pub fn run_compiled_test<B: BlackBoxFunctionSolver>(
+    blackbox_solver: &B,
+    file_manager: &FileManager,
+    program: &CompiledProgram,
+    test_function: &TestFunction,
+    inputs: &TestInputs,
+    coverage: Option<&mut SourceCoverage>,
+    mut output: Option<&mut String>,
+    foreign_call_resolver_url: Option<&str>,
+) -> TestStatus {
+    let collect_coverage = coverage.is_some();
+    let mut opcode_coverage = OpcodeCoverage::default();
+    let mut run_case = |inputs: &InputMap| {
+        let initial_witness = match program.abi.encode(inputs, None) {
+            Ok(initial_witness) => initial_witness,
+            Err(error) => {
+                return TestStatus::Fail {
+                    message: format!("error: Invalid test arguments: {error}"),
+                    error_diagnostic: None,
+                }
+            }
+        };
+        // Run the backend to ensure the PWG evaluates functions like std::hash::pedersen,
+        // otherwise constraints involving these expressions will not error.
+        let mut foreign_call_executor = match output {
+            Some(_) => DefaultForeignCallExecutor::capturing_output(foreign_call_resolver_url),
+            None => DefaultForeignCallExecutor::new(false, foreign_call_resolver_url),
+        };
+        let circuit_execution = if collect_coverage {
+            execute_circuit_with_observers(
                 &program.circuit,
-                WitnessMap::new(),
+                initial_witness,
                 blackbox_solver,
-                &mut DefaultForeignCallExecutor::new(show_output, foreign_call_resolver_url),
-            );
-            test_status_program_compile_pass(test_function, program.debug, circuit_execution)
+                &mut foreign_call_executor,
+                &mut [&mut opcode_coverage],
+            )
+        } else {
+            execute_circuit(
+                &program.circuit,
+                initial_witness,
+                blackbox_solver,
+                &mut foreign_call_executor,
+            )
+        };
+        if let Some(output) = output.as_deref_mut() {
+            output.push_str(&foreign_call_executor.take_output());
+        }
+        test_status_program_compile_pass(test_function, &program.debug, circuit_execution)
+    };
+
+    let status = if program.abi.parameters.is_empty() {
+        run_case(&InputMap::new())
+    } else {
+        match inputs {
+            TestInputs::Generated { runs, seed } => {
+                run_generated_cases(program, *runs, *seed, run_case)
+            }
+            TestInputs::Cases(cases) => run_fixed_cases(&program.abi, cases, run_case),
+        }
+    };
+
+    if let Some(coverage) = coverage {
+        coverage.add_execution(file_manager, &program.circuit, &program.debug, &opcode_coverage);
+    }
+    status
+}
+
+/// Runs the test with randomly generated arguments until it fails, then shrinks the arguments
+/// it failed with to a simpler failing case.
+fn run_generated_cases(
+    program: &CompiledProgram,
+    runs: usize,
+    seed: u64,
+    mut run_case: impl FnMut(&InputMap) -> TestStatus,
+) -> TestStatus {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for run in 0..runs {
+        let mut inputs = generate_inputs(&program.abi, &mut rng);
+        let mut status = run_case(&inputs);
+        if status.passed() {
+            continue;
+        }
+
+        let mut shrink_runs = 0;
+        'shrink: while shrink_runs < MAX_SHRINK_RUNS {
+            for candidate in shrink_candidates(&program.abi, &inputs) {
+                if shrink_runs == MAX_SHRINK_RUNS {
+                    break 'shrink;
+                }
+                shrink_runs += 1;
+
+                let candidate_status = run_case(&candidate);
+                if !candidate_status.passed() {
+                    inputs = candidate;
+                    status = candidate_status;
+                    continue 'shrink;
+                }
+            }
+            // None of the simpler arguments fail the test.
+            break;
         }
-        Err(err) => test_status_program_compile_fail(err, test_function),
+
+        let context = format!(
+            "Failed on run {} of {runs} with seed {seed}, after shrinking the arguments {shrink_runs} times",
+            run + 1
+        );
+        return with_failing_inputs(status, &context, &program.abi, &inputs);
+    }
+    TestStatus::Pass
+}
+
+fn run_fixed_cases(
+    abi: &Abi,
+    cases: &[String],
+    mut run_case: impl FnMut(&InputMap) -> TestStatus,
+) -> TestStatus {
+    if cases.is_empty() {
+        return TestStatus::Fail {
+            message: "error: The test takes parameters, but has no test cases".to_string(),
+            error_diagnostic: None,
+        };
+    }
+    for (index, case) in cases.iter().enumerate() {
+        let inputs = match Format::Toml.parse(case, abi) {
+            Ok(inputs) => inputs,
+            Err(error) => {
+                return TestStatus::Fail {
+                    message: format!("error: Could not parse test case {}: {error}", index + 1),
+                    error_diagnostic: None,
+                }
+            }
+        };
+        let status = run_case(&inputs);
+        if !status.passed() {
+            let context = format!("Failed on test case {} of {}", index + 1, cases.len());
+            return with_failing_inputs(status, &context, abi, &inputs);
+        }
+    }
+    TestStatus::Pass
+}
+
+/// Appends the arguments a test failed with to its failure message, so that the failure can be reproduced.
+fn with_failing_inputs(
+    status: TestStatus,
+    context: &str,
+    abi: &Abi,
+    inputs: &InputMap,
+) -> TestStatus {
+    match status {
+        TestStatus::Fail { message, error_diagnostic } => {
+            let inputs =
+                Format::Toml.serialize(inputs, abi).unwrap_or_else(|_| format!("{inputs:?}"));
+            TestStatus::Fail {
+                message: format!(
+                    "{}\n{context}\nFailing arguments:\n{}",
+                    message.trim_end(),
+                    inputs.trim_end()
+                ),
+                error_diagnostic,
+            }
+        }
+        status => status,
     }
 }
 
@@ -45,7 +309,7 @@ pub fn run_test<B: BlackBoxFunctionSolver>(
 /// that a constraint was never satisfiable.
 /// An example of this is the program `assert(false)`
 /// In that case, we check if the test function should fail, and if so, we return `TestStatus::Pass`.
-fn test_status_program_compile_fail(err: RuntimeError, test_function: TestFunction) -> TestStatus {
+fn test_status_program_compile_fail(err: RuntimeError, test_function: &TestFunction) -> TestStatus {
     // The test has failed compilation, but it should never fail. Report error.
     if !test_function.should_fail() {
         return TestStatus::CompileError(err.into());
@@ -66,8 +330,8 @@ fn test_status_program_compile_fail(err: RuntimeError, test_function: TestFuncti
 /// We now check whether execution passed/failed and whether it should have
 /// passed/failed to determine the test status.
 fn test_status_program_compile_pass(
-    test_function: TestFunction,
-    debug: DebugInfo,
+    test_function: &TestFunction,
+    debug: &DebugInfo,
     circuit_execution: Result<WitnessMap, NargoError>,
 ) -> TestStatus {
     let circuit_execution_err = match circuit_execution {
@@ -88,7 +352,7 @@ fn test_status_program_compile_pass(
     // If we reach here, then the circuit execution failed.
     //
     // Check if the function should have passed
-    let diagnostic = try_to_diagnose_runtime_error(&circuit_execution_err, &debug);
+    let diagnostic = try_to_diagnose_runtime_error(&circuit_execution_err, debug);
     let test_should_have_passed = !test_function.should_fail();
     if test_should_have_passed {
         return TestStatus::Fail {
@@ -105,7 +369,7 @@ fn test_status_program_compile_pass(
 }
 
 fn check_expected_failure_message(
-    test_function: TestFunction,
+    test_function: &TestFunction,
     failed_assertion: Option<String>,
     error_diagnostic: Option<FileDiagnostic>,
 ) -> TestStatus {
@@ -135,3 +399,77 @@ fn check_expected_failure_message(
         error_diagnostic,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use noirc_abi::{
+        input_parser::{Format, InputValue},
+        Abi, AbiParameter, AbiType, AbiVisibility,
+    };
+    use tempfile::TempDir;
+
+    use super::{read_test_cases, run_fixed_cases, TestStatus};
+
+    #[test]
+    fn reads_test_cases_for_each_test() {
+        let input_dir = TempDir::new().unwrap().into_path();
+        std::fs::write(
+            input_dir.join("Tests.toml"),
+            r#"
+[[test_add]]
+x = 1
+y = "2"
+
+[["foo::test_add"]]
+x = 3
+y = "4"
+
+[["foo::test_add"]]
+x = 5
+y = "6"
+"#,
+        )
+        .unwrap();
+
+        let test_cases = read_test_cases(&input_dir).unwrap();
+        assert_eq!(test_cases["test_add"].len(), 1);
+        assert_eq!(test_cases["foo::test_add"].len(), 2);
+
+        let abi = Abi {
+            parameters: ["x", "y"]
+                .map(|name| AbiParameter {
+                    name: name.into(),
+                    typ: AbiType::Field,
+                    visibility: AbiVisibility::Private,
+                })
+                .to_vec(),
+            return_type: None,
+            param_witnesses: BTreeMap::new(),
+            return_witnesses: Vec::new(),
+        };
+        let case = Format::Toml.parse(&test_cases["foo::test_add"][1], &abi).unwrap();
+        assert_eq!(case["x"], InputValue::Field(5u128.into()));
+        assert_eq!(case["y"], InputValue::Field(6u128.into()));
+
+        let missing_dir = TempDir::new().unwrap().into_path();
+        assert!(read_test_cases(&missing_dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn fails_without_test_cases() {
+        let abi = Abi {
+            parameters: vec![AbiParameter {
+                name: "x".into(),
+                typ: AbiType::Field,
+                visibility: AbiVisibility::Private,
+            }],
+            return_type: None,
+            param_witnesses: BTreeMap::new(),
+            return_witnesses: Vec::new(),
+        };
+        let status = run_fixed_cases(&abi, &[], |_| TestStatus::Pass);
+        assert!(matches!(status, TestStatus::Fail { .. }));
+    }
+}
@@ -0,0 +1,138 @@
+//! Execution profiles of unconstrained code.
+//!
+//! Constrained code runs the same opcodes on every execution, so its cost is best measured by the
+//! size of its circuit. Unconstrained code loops and branches at runtime, so instead its Brillig
+//! opcodes are counted as they run and attributed to the source locations they were generated from.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use acvm::acir::brillig::Opcode as BrilligOpcode;
+use acvm::acir::circuit::OpcodeLocation;
+use fm::FileManager;
+use noirc_errors::debug_info::DebugInfo;
+
+use crate::coverage::source_position;
+use crate::ops::ExecutionObserver;
+
+/// What each Brillig opcode of a circuit did while it was executed.
+#[derive(Debug, Default, Clone)]
+pub struct BrilligProfile {
+    opcodes: HashMap<OpcodeLocation, OpcodeStats>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeStats {
+    /// How many times the opcodes ran
+    pub executions: usize,
+    /// How many foreign calls the opcodes made
+    pub foreign_calls: usize,
+    /// The most memory slots the Brillig VM had in use after any of the opcodes ran
+    pub memory_high_water_mark: usize,
+}
+
+/// The stats of all the Brillig opcodes generated from a single source location.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationStats {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub stats: OpcodeStats,
+}
+
+impl OpcodeStats {
+    fn merge(&mut self, other: OpcodeStats) {
+        self.executions += other.executions;
+        self.foreign_calls += other.foreign_calls;
+        self.memory_high_water_mark = self.memory_high_water_mark.max(other.memory_high_water_mark);
+    }
+}
+
+impl ExecutionObserver for BrilligProfile {
+    fn brillig_opcode(
+        &mut self,
+        location: OpcodeLocation,
+        _opcode: &BrilligOpcode,
+        _next_program_counter: usize,
+        memory_size: usize,
+    ) {
+        let stats = self.opcodes.entry(location).or_default();
+        stats.executions += 1;
+        stats.memory_high_water_mark = stats.memory_high_water_mark.max(memory_size);
+    }
+
+    fn foreign_call(&mut self, location: OpcodeLocation) {
+        self.opcodes.entry(location).or_default().foreign_calls += 1;
+    }
+}
+
+impl BrilligProfile {
+    /// The stats of every Brillig opcode which ran, added up.
+    pub fn total(&self) -> OpcodeStats {
+        let mut total = OpcodeStats::default();
+        for stats in self.opcodes.values() {
+            total.merge(*stats);
+        }
+        total
+    }
+
+    /// The stats of the opcodes generated from each source location, most executed first.
+    ///
+    /// Opcodes are attributed to the innermost location of their call stack, which is the
+    /// expression they were generated from rather than the calls leading to it.
+    pub fn by_location(&self, file_manager: &FileManager, debug: &DebugInfo) -> Vec<LocationStats> {
+        let mut locations: HashMap<(PathBuf, usize, usize), OpcodeStats> = HashMap::new();
+        for (opcode_location, stats) in &self.opcodes {
+            let Some(location) = debug.locations.get(opcode_location).and_then(|stack| stack.last())
+            else {
+                continue;
+            };
+            let Some(position) = source_position(file_manager, location) else { continue };
+            locations.entry(position).or_default().merge(*stats);
+        }
+
+        let mut locations: Vec<LocationStats> = locations
+            .into_iter()
+            .map(|((file, line, column), stats)| LocationStats { file, line, column, stats })
+            .collect();
+        locations.sort_by(|a, b| {
+            b.stats
+                .executions
+                .cmp(&a.stats.executions)
+                .then_with(|| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)))
+        });
+        locations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::brillig::Opcode as BrilligOpcode;
+    use acvm::acir::circuit::OpcodeLocation;
+
+    use super::{BrilligProfile, OpcodeStats};
+    use crate::ops::ExecutionObserver;
+
+    #[test]
+    fn counts_executions_foreign_calls_and_peak_memory() {
+        let mut profile = BrilligProfile::default();
+        let stop = BrilligOpcode::Stop;
+        let first = OpcodeLocation::Brillig { acir_index: 0, brillig_index: 0 };
+        let second = OpcodeLocation::Brillig { acir_index: 0, brillig_index: 1 };
+
+        profile.acir_opcode(0);
+        profile.brillig_opcode(first, &stop, 1, 4);
+        profile.foreign_call(second);
+        profile.brillig_opcode(second, &stop, 2, 2);
+        profile.brillig_opcode(first, &stop, 1, 8);
+
+        assert_eq!(
+            profile.opcodes[&first],
+            OpcodeStats { executions: 2, foreign_calls: 0, memory_high_water_mark: 8 }
+        );
+        assert_eq!(
+            profile.total(),
+            OpcodeStats { executions: 3, foreign_calls: 1, memory_high_water_mark: 8 }
+        );
+    }
+}
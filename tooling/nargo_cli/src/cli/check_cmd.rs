@@ -6,7 +6,7 @@ use fm::FileManager;
 use iter_extended::btree_map;
 use nargo::{
     errors::CompileError, insert_all_files_for_workspace_into_file_manager, package::Package,
-    prepare_package,
+    parse_all, prepare_package_with_parsed_files, workspace::Workspace,
 };
 use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
 use noirc_abi::{AbiParameter, AbiType, MAIN_RETURN_NAME};
@@ -16,10 +16,11 @@ use noirc_driver::{
 };
 use noirc_frontend::{
     graph::{CrateId, CrateName},
-    hir::Context,
+    hir::{Context, ParsedFiles},
 };
 
 use super::fs::write_to_file;
+use super::watch::watch_workspace;
 use super::NargoConfig;
 
 /// Checks the constraint system for errors
@@ -33,6 +34,10 @@ pub(crate) struct CheckCommand {
     #[clap(long, conflicts_with = "package")]
     workspace: bool,
 
+    /// Run again whenever a source file or manifest of the workspace changes
+    #[clap(long)]
+    watch: bool,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
@@ -46,17 +51,40 @@ pub(crate) fn run(
     let default_selection =
         if args.workspace { PackageSelection::All } else { PackageSelection::DefaultOrAll };
     let selection = args.package.map_or(default_selection, PackageSelection::Selected);
+
+    if args.watch {
+        return watch_workspace(
+            &toml_path,
+            selection,
+            config.network_mode(),
+            |file_manager, parsed_files, workspace| {
+                check_workspace(file_manager, parsed_files, workspace, &args.compile_options)
+            },
+        );
+    }
+
     let workspace = resolve_workspace_from_toml(
         &toml_path,
         selection,
         Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        config.network_mode(),
     )?;
 
     let mut workspace_file_manager = file_manager_with_stdlib(&workspace.root_dir);
     insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
+    let parsed_files = parse_all(&workspace_file_manager);
 
-    for package in &workspace {
-        check_package(&workspace_file_manager, package, &args.compile_options)?;
+    check_workspace(&workspace_file_manager, &parsed_files, &workspace, &args.compile_options)
+}
+
+fn check_workspace(
+    file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
+    workspace: &Workspace,
+    compile_options: &CompileOptions,
+) -> Result<(), CliError> {
+    for package in workspace {
+        check_package(file_manager, parsed_files, package, compile_options)?;
         println!("[{}] Constraint system successfully built!", package.name);
     }
     Ok(())
@@ -64,10 +92,12 @@ pub(crate) fn run(
 
 fn check_package(
     file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
     package: &Package,
     compile_options: &CompileOptions,
 ) -> Result<(), CompileError> {
-    let (mut context, crate_id) = prepare_package(file_manager, package);
+    let (mut context, crate_id) =
+        prepare_package_with_parsed_files(file_manager, parsed_files, package);
     check_crate_and_report_errors(
         &mut context,
         crate_id,
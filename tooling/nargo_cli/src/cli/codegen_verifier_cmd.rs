@@ -12,10 +12,12 @@ use clap::Args;
 use fm::FileManager;
 use nargo::insert_all_files_for_workspace_into_file_manager;
 use nargo::package::Package;
+use nargo::parse_all;
 use nargo::workspace::Workspace;
 use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
 use noirc_driver::{file_manager_with_stdlib, CompileOptions, NOIR_ARTIFACT_VERSION_STRING};
 use noirc_frontend::graph::CrateName;
+use noirc_frontend::hir::ParsedFiles;
 
 /// Generates a Solidity verifier smart contract for the program
 #[derive(Debug, Clone, Args)]
@@ -45,15 +47,18 @@ pub(crate) fn run(
         &toml_path,
         selection,
         Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        config.network_mode(),
     )?;
 
     let mut workspace_file_manager = file_manager_with_stdlib(&workspace.root_dir);
     insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
+    let parsed_files = parse_all(&workspace_file_manager);
 
     let expression_width = backend.get_backend_info()?;
     for package in &workspace {
         let smart_contract_string = smart_contract_for_package(
             &workspace_file_manager,
+            &parsed_files,
             &workspace,
             backend,
             package,
@@ -74,14 +79,21 @@ pub(crate) fn run(
 
 fn smart_contract_for_package(
     file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
     workspace: &Workspace,
     backend: &Backend,
     package: &Package,
     compile_options: &CompileOptions,
     expression_width: ExpressionWidth,
 ) -> Result<String, CliError> {
-    let program =
-        compile_bin_package(file_manager, workspace, package, compile_options, expression_width)?;
+    let program = compile_bin_package(
+        file_manager,
+        parsed_files,
+        workspace,
+        package,
+        compile_options,
+        expression_width,
+    )?;
 
     let mut smart_contract_string = backend.eth_contract(&program.circuit)?;
 
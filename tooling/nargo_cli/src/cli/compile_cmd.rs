@@ -9,13 +9,14 @@ use nargo::artifacts::program::ProgramArtifact;
 use nargo::errors::CompileError;
 use nargo::insert_all_files_for_workspace_into_file_manager;
 use nargo::package::Package;
-use nargo::prepare_package;
 use nargo::workspace::Workspace;
+use nargo::{parse_all, prepare_package_with_parsed_files};
 use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
 use noirc_driver::file_manager_with_stdlib;
 use noirc_driver::NOIR_ARTIFACT_VERSION_STRING;
 use noirc_driver::{CompilationResult, CompileOptions, CompiledContract, CompiledProgram};
 use noirc_frontend::graph::CrateName;
+use noirc_frontend::hir::ParsedFiles;
 
 use clap::Args;
 
@@ -27,6 +28,7 @@ use super::fs::program::{
     read_debug_artifact_from_file, read_program_from_file, save_contract_to_file,
     save_debug_artifact_to_file, save_program_to_file,
 };
+use super::watch::watch_workspace;
 use super::NargoConfig;
 use rayon::prelude::*;
 
@@ -41,6 +43,10 @@ pub(crate) struct CompileCommand {
     #[clap(long, conflicts_with = "package")]
     workspace: bool,
 
+    /// Run again whenever a source file or manifest of the workspace changes
+    #[clap(long)]
+    watch: bool,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
@@ -54,16 +60,55 @@ pub(crate) fn run(
     let default_selection =
         if args.workspace { PackageSelection::All } else { PackageSelection::DefaultOrAll };
     let selection = args.package.map_or(default_selection, PackageSelection::Selected);
+    let expression_width = backend.get_backend_info_or_default();
+
+    if args.watch {
+        // Unchanged programs aren't compiled again as their artifacts' hashes still match
+        return watch_workspace(
+            &toml_path,
+            selection,
+            config.network_mode(),
+            |file_manager, parsed_files, workspace| {
+                compile_and_save_workspace(
+                    file_manager,
+                    parsed_files,
+                    workspace,
+                    expression_width,
+                    &args.compile_options,
+                )
+            },
+        );
+    }
 
     let workspace = resolve_workspace_from_toml(
         &toml_path,
         selection,
         Some(NOIR_ARTIFACT_VERSION_STRING.to_owned()),
+        config.network_mode(),
     )?;
-    let circuit_dir = workspace.target_directory_path();
 
     let mut workspace_file_manager = file_manager_with_stdlib(&workspace.root_dir);
     insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
+    let parsed_files = parse_all(&workspace_file_manager);
+
+    compile_and_save_workspace(
+        &workspace_file_manager,
+        &parsed_files,
+        &workspace,
+        expression_width,
+        &args.compile_options,
+    )
+}
+
+/// Compiles the binary and contract packages of the workspace, saving their artifacts.
+fn compile_and_save_workspace(
+    file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
+    workspace: &Workspace,
+    expression_width: ExpressionWidth,
+    compile_options: &CompileOptions,
+) -> Result<(), CliError> {
+    let circuit_dir = workspace.target_directory_path();
 
     let (binary_packages, contract_packages): (Vec<_>, Vec<_>) = workspace
         .into_iter()
@@ -71,14 +116,14 @@ pub(crate) fn run(
         .cloned()
         .partition(|package| package.is_binary());
 
-    let expression_width = backend.get_backend_info_or_default();
     let (_, compiled_contracts) = compile_workspace(
-        &workspace_file_manager,
-        &workspace,
+        file_manager,
+        parsed_files,
+        workspace,
         &binary_packages,
         &contract_packages,
         expression_width,
-        &args.compile_options,
+        compile_options,
     )?;
 
     // Save build artifacts to disk.
@@ -91,6 +136,7 @@ pub(crate) fn run(
 
 pub(super) fn compile_workspace(
     file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
     workspace: &Workspace,
     binary_packages: &[Package],
     contract_packages: &[Package],
@@ -101,12 +147,21 @@ pub(super) fn compile_workspace(
     let program_results: Vec<CompilationResult<CompiledProgram>> = binary_packages
         .par_iter()
         .map(|package| {
-            compile_program(file_manager, workspace, package, compile_options, expression_width)
+            compile_program(
+                file_manager,
+                parsed_files,
+                workspace,
+                package,
+                compile_options,
+                expression_width,
+            )
         })
         .collect();
     let contract_results: Vec<CompilationResult<CompiledContract>> = contract_packages
         .par_iter()
-        .map(|package| compile_contract(file_manager, package, compile_options, expression_width))
+        .map(|package| {
+            compile_contract(file_manager, parsed_files, package, compile_options, expression_width)
+        })
         .collect();
 
     // Report any warnings/errors which were encountered during compilation.
@@ -138,6 +193,7 @@ pub(super) fn compile_workspace(
 
 pub(crate) fn compile_bin_package(
     file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
     workspace: &Workspace,
     package: &Package,
     compile_options: &CompileOptions,
@@ -147,8 +203,14 @@ pub(crate) fn compile_bin_package(
         return Err(CompileError::LibraryCrate(package.name.clone()).into());
     }
 
-    let compilation_result =
-        compile_program(file_manager, workspace, package, compile_options, expression_width);
+    let compilation_result = compile_program(
+        file_manager,
+        parsed_files,
+        workspace,
+        package,
+        compile_options,
+        expression_width,
+    );
 
     let program = report_errors(
         compilation_result,
@@ -162,12 +224,14 @@ pub(crate) fn compile_bin_package(
 
 fn compile_program(
     file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
     workspace: &Workspace,
     package: &Package,
     compile_options: &CompileOptions,
     expression_width: ExpressionWidth,
 ) -> CompilationResult<CompiledProgram> {
-    let (mut context, crate_id) = prepare_package(file_manager, package);
+    let (mut context, crate_id) =
+        prepare_package_with_parsed_files(file_manager, parsed_files, package);
 
     let program_artifact_path = workspace.package_build_path(package);
     let mut debug_artifact_path = program_artifact_path.clone();
@@ -206,19 +270,25 @@ fn compile_program(
 
     // Apply backend specific optimizations.
     let optimized_program = nargo::ops::optimize_program(program, expression_width);
-    let only_acir = compile_options.only_acir;
-    save_program(optimized_program.clone(), package, &workspace.target_directory_path(), only_acir);
+    // Programs instrumented for the debugger shouldn't replace the package's artifacts
+    if !compile_options.instrument_debug {
+        let only_acir = compile_options.only_acir;
+        let target_dir = workspace.target_directory_path();
+        save_program(optimized_program.clone(), package, &target_dir, only_acir);
+    }
 
     Ok((optimized_program, warnings))
 }
 
 fn compile_contract(
     file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
     package: &Package,
     compile_options: &CompileOptions,
     expression_width: ExpressionWidth,
 ) -> CompilationResult<CompiledContract> {
-    let (mut context, crate_id) = prepare_package(file_manager, package);
+    let (mut context, crate_id) =
+        prepare_package_with_parsed_files(file_manager, parsed_files, package);
     let (contract, warnings) =
         match noirc_driver::compile_contract(&mut context, crate_id, compile_options) {
             Ok(contracts_and_warnings) => contracts_and_warnings,
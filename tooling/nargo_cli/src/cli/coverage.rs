@@ -0,0 +1,308 @@
+//! Reports of the source coverage collected by `nargo test --coverage` and `nargo execute --coverage`.
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use nargo::coverage::{FileCoverage, SourceCoverage};
+use nargo::workspace::Workspace;
+use prettytable::{row, table};
+
+use super::fs::{create_named_dir, write_to_file};
+use super::test_cmd::report::escape_xml;
+
+/// The directory within the target directory which coverage reports are written to
+const COVERAGE_DIR: &str = "coverage";
+const LCOV_FILE: &str = "lcov.info";
+const COBERTURA_FILE: &str = "cobertura.xml";
+
+/// Writes lcov and Cobertura reports of the coverage of the workspace's files, and prints a
+/// summary of each file's coverage.
+///
+/// Files outside of the workspace, such as the standard library's, are left out.
+pub(crate) fn report_coverage(workspace: &Workspace, coverage: &SourceCoverage) {
+    let files: Vec<(PathBuf, &FileCoverage)> = coverage
+        .files
+        .iter()
+        .filter_map(|(path, file)| {
+            let relative_path = path.strip_prefix(&workspace.root_dir).ok()?;
+            Some((relative_path.to_path_buf(), file))
+        })
+        .collect();
+
+    let coverage_dir =
+        create_named_dir(&workspace.target_directory_path().join(COVERAGE_DIR), COVERAGE_DIR);
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    write_to_file(
+        lcov_report(&workspace.root_dir, &files).as_bytes(),
+        &coverage_dir.join(LCOV_FILE),
+    );
+    write_to_file(
+        cobertura_report(&workspace.root_dir, &files, timestamp).as_bytes(),
+        &coverage_dir.join(COBERTURA_FILE),
+    );
+
+    let mut summary = table!([
+        Fm->"File",
+        Fm->"Lines",
+        Fm->"Constrained Lines",
+        Fm->"Unconstrained Lines",
+        Fm->"Branches"
+    ]);
+    for (path, file) in &files {
+        let counts = CoverageCounts::new(file);
+        summary.add_row(row![
+            Fm->path.display(),
+            Fc->ratio(counts.lines_covered, counts.lines),
+            Fc->ratio(counts.constrained_lines_covered, counts.constrained_lines),
+            Fc->ratio(counts.unconstrained_lines_covered, counts.unconstrained_lines),
+            Fc->ratio(counts.branches_covered, counts.branches),
+        ]);
+    }
+    let total = files.iter().map(|(_, file)| CoverageCounts::new(file)).sum::<CoverageCounts>();
+    summary.add_row(row![
+        Fm->"Total",
+        Fc->ratio(total.lines_covered, total.lines),
+        Fc->ratio(total.constrained_lines_covered, total.constrained_lines),
+        Fc->ratio(total.unconstrained_lines_covered, total.unconstrained_lines),
+        Fc->ratio(total.branches_covered, total.branches),
+    ]);
+
+    eprint!("{summary}");
+    eprintln!("Coverage reports written to {}", coverage_dir.display());
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct CoverageCounts {
+    lines: usize,
+    lines_covered: usize,
+    constrained_lines: usize,
+    constrained_lines_covered: usize,
+    unconstrained_lines: usize,
+    unconstrained_lines_covered: usize,
+    /// Each conditional jump counts as two branches, one for either direction
+    branches: usize,
+    branches_covered: usize,
+}
+
+impl CoverageCounts {
+    fn new(file: &FileCoverage) -> Self {
+        let mut counts = CoverageCounts::default();
+        for line in file.lines.values() {
+            counts.lines += 1;
+            counts.lines_covered += usize::from(line.is_covered());
+            if let Some(hits) = line.constrained_hits {
+                counts.constrained_lines += 1;
+                counts.constrained_lines_covered += usize::from(hits > 0);
+            }
+            if let Some(hits) = line.unconstrained_hits {
+                counts.unconstrained_lines += 1;
+                counts.unconstrained_lines_covered += usize::from(hits > 0);
+            }
+        }
+        for branch in file.branches.values() {
+            counts.branches += 2;
+            counts.branches_covered += branch.covered_directions();
+        }
+        counts
+    }
+}
+
+impl std::iter::Sum for CoverageCounts {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(CoverageCounts::default(), |total, counts| CoverageCounts {
+            lines: total.lines + counts.lines,
+            lines_covered: total.lines_covered + counts.lines_covered,
+            constrained_lines: total.constrained_lines + counts.constrained_lines,
+            constrained_lines_covered: total.constrained_lines_covered
+                + counts.constrained_lines_covered,
+            unconstrained_lines: total.unconstrained_lines + counts.unconstrained_lines,
+            unconstrained_lines_covered: total.unconstrained_lines_covered
+                + counts.unconstrained_lines_covered,
+            branches: total.branches + counts.branches,
+            branches_covered: total.branches_covered + counts.branches_covered,
+        })
+    }
+}
+
+fn ratio(covered: usize, total: usize) -> String {
+    if total == 0 {
+        return "-".to_string();
+    }
+    format!("{covered}/{total} ({:.1}%)", rate(covered, total) * 100.0)
+}
+
+fn rate(covered: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        covered as f64 / total as f64
+    }
+}
+
+/// The hit counts of either direction of each conditional jump on each line
+fn branches_by_line(file: &FileCoverage) -> impl Iterator<Item = (usize, [usize; 2])> + '_ {
+    file.branches.iter().map(|((line, _), branch)| (*line, [branch.taken, branch.not_taken]))
+}
+
+/// Writes a report in the `lcov` tracefile format, as read by `genhtml` and most CI services.
+fn lcov_report(root_dir: &Path, files: &[(PathBuf, &FileCoverage)]) -> String {
+    let mut lcov = String::new();
+    for (path, file) in files {
+        let counts = CoverageCounts::new(file);
+        writeln!(lcov, "TN:").unwrap();
+        writeln!(lcov, "SF:{}", root_dir.join(path).display()).unwrap();
+        for (block, (line, hits)) in branches_by_line(file).enumerate() {
+            for (branch, branch_hits) in hits.iter().enumerate() {
+                // A branch which was never reached has no hit count, rather than one of zero.
+                let taken = if hits == [0, 0] { "-".to_string() } else { branch_hits.to_string() };
+                writeln!(lcov, "BRDA:{line},{block},{branch},{taken}").unwrap();
+            }
+        }
+        writeln!(lcov, "BRF:{}", counts.branches).unwrap();
+        writeln!(lcov, "BRH:{}", counts.branches_covered).unwrap();
+        for (line, coverage) in &file.lines {
+            writeln!(lcov, "DA:{line},{}", coverage.hits()).unwrap();
+        }
+        writeln!(lcov, "LF:{}", counts.lines).unwrap();
+        writeln!(lcov, "LH:{}", counts.lines_covered).unwrap();
+        writeln!(lcov, "end_of_record").unwrap();
+    }
+    lcov
+}
+
+/// Writes a report in the Cobertura XML format, with a class for each file.
+fn cobertura_report(root_dir: &Path, files: &[(PathBuf, &FileCoverage)], timestamp: u64) -> String {
+    let total = files.iter().map(|(_, file)| CoverageCounts::new(file)).sum::<CoverageCounts>();
+
+    let mut xml = String::from("<?xml version=\"1.0\" ?>\n");
+    writeln!(
+        xml,
+        "<!DOCTYPE coverage SYSTEM \"http://cobertura.sourceforge.net/xml/coverage-04.dtd\">"
+    )
+    .unwrap();
+    writeln!(
+        xml,
+        "<coverage line-rate=\"{:.4}\" branch-rate=\"{:.4}\" lines-covered=\"{}\" lines-valid=\"{}\" branches-covered=\"{}\" branches-valid=\"{}\" complexity=\"0\" version=\"0\" timestamp=\"{timestamp}\">",
+        rate(total.lines_covered, total.lines),
+        rate(total.branches_covered, total.branches),
+        total.lines_covered,
+        total.lines,
+        total.branches_covered,
+        total.branches,
+    )
+    .unwrap();
+    writeln!(xml, "  <sources>").unwrap();
+    writeln!(xml, "    <source>{}</source>", escape_xml(&root_dir.display().to_string())).unwrap();
+    writeln!(xml, "  </sources>").unwrap();
+    writeln!(xml, "  <packages>").unwrap();
+    writeln!(
+        xml,
+        "    <package name=\"{}\" line-rate=\"{:.4}\" branch-rate=\"{:.4}\" complexity=\"0\">",
+        escape_xml(&root_dir.file_name().unwrap_or_default().to_string_lossy()),
+        rate(total.lines_covered, total.lines),
+        rate(total.branches_covered, total.branches),
+    )
+    .unwrap();
+    writeln!(xml, "      <classes>").unwrap();
+
+    for (path, file) in files {
+        let counts = CoverageCounts::new(file);
+        let path = escape_xml(&path.display().to_string());
+        writeln!(
+            xml,
+            "        <class name=\"{path}\" filename=\"{path}\" line-rate=\"{:.4}\" branch-rate=\"{:.4}\" complexity=\"0\">",
+            rate(counts.lines_covered, counts.lines),
+            rate(counts.branches_covered, counts.branches),
+        )
+        .unwrap();
+        writeln!(xml, "          <methods/>").unwrap();
+        writeln!(xml, "          <lines>").unwrap();
+        for (line, coverage) in &file.lines {
+            let line_branches: Vec<[usize; 2]> = branches_by_line(file)
+                .filter(|(branch_line, _)| branch_line == line)
+                .map(|(_, hits)| hits)
+                .collect();
+            if line_branches.is_empty() {
+                writeln!(
+                    xml,
+                    "            <line number=\"{line}\" hits=\"{}\" branch=\"false\"/>",
+                    coverage.hits()
+                )
+                .unwrap();
+            } else {
+                let branches = line_branches.len() * 2;
+                let covered = line_branches.iter().flatten().filter(|hits| **hits > 0).count();
+                writeln!(
+                    xml,
+                    "            <line number=\"{line}\" hits=\"{}\" branch=\"true\" condition-coverage=\"{}% ({covered}/{branches})\"/>",
+                    coverage.hits(),
+                    covered * 100 / branches,
+                )
+                .unwrap();
+            }
+        }
+        writeln!(xml, "          </lines>").unwrap();
+        writeln!(xml, "        </class>").unwrap();
+    }
+
+    writeln!(xml, "      </classes>").unwrap();
+    writeln!(xml, "    </package>").unwrap();
+    writeln!(xml, "  </packages>").unwrap();
+    writeln!(xml, "</coverage>").unwrap();
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use nargo::coverage::{BranchCoverage, FileCoverage, LineCoverage};
+
+    use super::{cobertura_report, lcov_report};
+
+    fn file_coverage() -> FileCoverage {
+        let mut file = FileCoverage::default();
+        file.lines.insert(1, LineCoverage { constrained_hits: Some(1), unconstrained_hits: None });
+        file.lines.insert(2, LineCoverage { constrained_hits: None, unconstrained_hits: Some(4) });
+        file.lines.insert(3, LineCoverage { constrained_hits: None, unconstrained_hits: Some(0) });
+        file.branches.insert((2, 8), BranchCoverage { taken: 4, not_taken: 0 });
+        file
+    }
+
+    #[test]
+    fn writes_lcov_report() {
+        let file = file_coverage();
+        let files = vec![(PathBuf::from("src/main.nr"), &file)];
+
+        let expected = "TN:
+SF:/project/src/main.nr
+BRDA:2,0,0,4
+BRDA:2,0,1,0
+BRF:2
+BRH:1
+DA:1,1
+DA:2,4
+DA:3,0
+LF:3
+LH:2
+end_of_record
+";
+        assert_eq!(lcov_report(Path::new("/project"), &files), expected);
+    }
+
+    #[test]
+    fn writes_cobertura_report() {
+        let file = file_coverage();
+        let files = vec![(PathBuf::from("src/main.nr"), &file)];
+
+        let report = cobertura_report(Path::new("/project"), &files, 0);
+        assert!(report.contains("<coverage line-rate=\"0.6667\" branch-rate=\"0.5000\" lines-covered=\"2\" lines-valid=\"3\" branches-covered=\"1\" branches-valid=\"2\""));
+        assert!(report.contains("<class name=\"src/main.nr\" filename=\"src/main.nr\""));
+        assert!(report.contains("<line number=\"1\" hits=\"1\" branch=\"false\"/>"));
+        assert!(report.contains(
+            "<line number=\"2\" hits=\"4\" branch=\"true\" condition-coverage=\"50% (1/2)\"/>"
+        ));
+        assert!(report.contains("<line number=\"3\" hits=\"0\" branch=\"false\"/>"));
+    }
+}
@@ -3,11 +3,15 @@ use backend_interface::Backend;
 use clap::Args;
 use nargo::constants::PROVER_INPUT_FILE;
 use nargo::insert_all_files_for_workspace_into_file_manager;
+use nargo::parse_all;
 use nargo::workspace::Workspace;
-use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
+use nargo_toml::{
+    get_package_manifest, resolve_workspace_from_toml, NetworkMode, PackageSelection,
+};
 use noirc_abi::input_parser::Format;
 use noirc_driver::{
-    file_manager_with_stdlib, CompileOptions, CompiledProgram, NOIR_ARTIFACT_VERSION_STRING,
+    add_debug_source_to_file_manager, file_manager_with_stdlib, CompileOptions, CompiledProgram,
+    NOIR_ARTIFACT_VERSION_STRING,
 };
 use noirc_frontend::graph::CrateName;
 
@@ -22,6 +26,7 @@ use dap::types::Capabilities;
 use serde_json::Value;
 
 use super::compile_cmd::compile_bin_package;
+use super::debug_cmd::compile_test_for_debugging;
 use super::fs::inputs::read_inputs_from_file;
 use crate::errors::CliError;
 
@@ -32,7 +37,11 @@ pub(crate) struct DapCommand;
 
 struct LoadError(&'static str);
 
-fn find_workspace(project_folder: &str, package: Option<&str>) -> Option<Workspace> {
+fn find_workspace(
+    project_folder: &str,
+    package: Option<&str>,
+    network_mode: NetworkMode,
+) -> Option<Workspace> {
     let Ok(toml_path) = get_package_manifest(Path::new(project_folder)) else {
         eprintln!("ERROR: Failed to get package manifest");
         return None;
@@ -43,6 +52,7 @@ fn find_workspace(project_folder: &str, package: Option<&str>) -> Option<Workspa
         &toml_path,
         selection,
         Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        network_mode,
     ) {
         Ok(workspace) => Some(workspace),
         Err(err) => {
@@ -56,29 +66,49 @@ fn load_and_compile_project(
     backend: &Backend,
     project_folder: &str,
     package: Option<&str>,
+    test_name: Option<&str>,
     prover_name: &str,
+    network_mode: NetworkMode,
 ) -> Result<(CompiledProgram, WitnessMap), LoadError> {
-    let workspace =
-        find_workspace(project_folder, package).ok_or(LoadError("Cannot open workspace"))?;
+    let workspace = find_workspace(project_folder, package, network_mode)
+        .ok_or(LoadError("Cannot open workspace"))?;
 
     let expression_width =
         backend.get_backend_info().map_err(|_| LoadError("Failed to get backend info"))?;
-    let package = workspace
-        .into_iter()
-        .find(|p| p.is_binary())
-        .ok_or(LoadError("No matching binary packages found in workspace"))?;
 
     let mut workspace_file_manager = file_manager_with_stdlib(std::path::Path::new(""));
+    add_debug_source_to_file_manager(&mut workspace_file_manager);
     insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
-
-    let compiled_program = compile_bin_package(
-        &workspace_file_manager,
-        &workspace,
-        package,
-        &CompileOptions::default(),
-        expression_width,
-    )
-    .map_err(|_| LoadError("Failed to compile project"))?;
+    let parsed_files = parse_all(&workspace_file_manager);
+
+    let compile_options = CompileOptions { instrument_debug: true, ..CompileOptions::default() };
+    let (package, compiled_program) = match test_name {
+        Some(test_name) => compile_test_for_debugging(
+            &workspace_file_manager,
+            &parsed_files,
+            &workspace,
+            test_name,
+            &compile_options,
+            expression_width,
+        )
+        .map_err(|_| LoadError("Failed to compile test function"))?,
+        None => {
+            let package = workspace
+                .into_iter()
+                .find(|p| p.is_binary())
+                .ok_or(LoadError("No matching binary packages found in workspace"))?;
+            let compiled_program = compile_bin_package(
+                &workspace_file_manager,
+                &parsed_files,
+                &workspace,
+                package,
+                &compile_options,
+                expression_width,
+            )
+            .map_err(|_| LoadError("Failed to compile project"))?;
+            (package, compiled_program)
+        }
+    };
 
     let (inputs_map, _) =
         read_inputs_from_file(&package.root_dir, prover_name, Format::Toml, &compiled_program.abi)
@@ -94,6 +124,7 @@ fn load_and_compile_project(
 fn loop_uninitialized_dap<R: Read, W: Write>(
     mut server: Server<R, W>,
     backend: &Backend,
+    network_mode: NetworkMode,
 ) -> Result<(), ServerError> {
     loop {
         let req = match server.poll_request()? {
@@ -104,9 +135,13 @@ fn loop_uninitialized_dap<R: Read, W: Write>(
         match req.command {
             Command::Initialize(_) => {
                 let rsp = req.success(ResponseBody::Initialize(Capabilities {
+                    supports_conditional_breakpoints: Some(true),
+                    supports_hit_conditional_breakpoints: Some(true),
+                    supports_log_points: Some(true),
                     supports_disassemble_request: Some(true),
                     supports_instruction_breakpoints: Some(true),
                     supports_stepping_granularity: Some(true),
+                    supports_step_back: Some(true),
                     ..Default::default()
                 }));
                 server.respond(rsp)?;
@@ -124,6 +159,7 @@ fn loop_uninitialized_dap<R: Read, W: Write>(
 
                 let project_folder = project_folder.as_str();
                 let package = additional_data.get("package").and_then(|v| v.as_str());
+                let test_name = additional_data.get("testName").and_then(|v| v.as_str());
                 let prover_name = additional_data
                     .get("proverName")
                     .and_then(|v| v.as_str())
@@ -131,9 +167,17 @@ fn loop_uninitialized_dap<R: Read, W: Write>(
 
                 eprintln!("Project folder: {}", project_folder);
                 eprintln!("Package: {}", package.unwrap_or("(default)"));
+                eprintln!("Test name: {}", test_name.unwrap_or("(none)"));
                 eprintln!("Prover name: {}", prover_name);
 
-                match load_and_compile_project(backend, project_folder, package, prover_name) {
+                match load_and_compile_project(
+                    backend,
+                    project_folder,
+                    package,
+                    test_name,
+                    prover_name,
+                    network_mode,
+                ) {
                     Ok((compiled_program, initial_witness)) => {
                         server.respond(req.ack()?)?;
 
@@ -170,11 +214,11 @@ fn loop_uninitialized_dap<R: Read, W: Write>(
 pub(crate) fn run(
     backend: &Backend,
     _args: DapCommand,
-    _config: NargoConfig,
+    config: NargoConfig,
 ) -> Result<(), CliError> {
     let output = BufWriter::new(std::io::stdout());
     let input = BufReader::new(std::io::stdin());
     let server = Server::new(input, output);
 
-    loop_uninitialized_dap(server, backend).map_err(CliError::DapError)
+    loop_uninitialized_dap(server, backend, config.network_mode()).map_err(CliError::DapError)
 }
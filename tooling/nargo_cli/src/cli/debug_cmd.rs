@@ -1,22 +1,31 @@
 use std::path::PathBuf;
 
 use acvm::acir::native_types::WitnessMap;
+use acvm::ExpressionWidth;
 use bn254_blackbox_solver::Bn254BlackBoxSolver;
 use clap::Args;
 
+use fm::FileManager;
 use nargo::artifacts::debug::DebugArtifact;
 use nargo::constants::PROVER_INPUT_FILE;
-use nargo::insert_all_files_for_workspace_into_file_manager;
 use nargo::package::Package;
+use nargo::workspace::Workspace;
+use nargo::{
+    insert_all_files_for_workspace_into_file_manager, parse_all, prepare_package_with_parsed_files,
+};
 use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
 use noirc_abi::input_parser::{Format, InputValue};
 use noirc_abi::InputMap;
 use noirc_driver::{
-    file_manager_with_stdlib, CompileOptions, CompiledProgram, NOIR_ARTIFACT_VERSION_STRING,
+    add_debug_source_to_file_manager, compile_no_check, file_manager_with_stdlib,
+    link_to_debug_crate, CompileOptions, CompiledProgram, NOIR_ARTIFACT_VERSION_STRING,
 };
+use noirc_errors::FileDiagnostic;
 use noirc_frontend::graph::CrateName;
+use noirc_frontend::hir::{FunctionNameMatch, ParsedFiles};
 
-use super::compile_cmd::compile_bin_package;
+use super::check_cmd::check_crate_and_report_errors;
+use super::compile_cmd::{compile_bin_package, report_errors};
 use super::fs::{inputs::read_inputs_from_file, witness::save_witness_to_dir};
 use super::NargoConfig;
 use crate::backends::Backend;
@@ -36,6 +45,10 @@ pub(crate) struct DebugCommand {
     #[clap(long)]
     package: Option<CrateName>,
 
+    /// The fully qualified name of a test function to debug instead of `main`
+    #[clap(long)]
+    test_name: Option<String>,
+
     #[clap(flatten)]
     compile_options: CompileOptions,
 }
@@ -51,12 +64,36 @@ pub(crate) fn run(
         &toml_path,
         selection,
         Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        config.network_mode(),
     )?;
     let target_dir = &workspace.target_directory_path();
     let expression_width = backend.get_backend_info()?;
 
     let mut workspace_file_manager = file_manager_with_stdlib(std::path::Path::new(""));
+    add_debug_source_to_file_manager(&mut workspace_file_manager);
     insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
+    let parsed_files = parse_all(&workspace_file_manager);
+
+    // Source variables are instrumented so that their values can be inspected while debugging
+    let compile_options = CompileOptions { instrument_debug: true, ..args.compile_options };
+
+    if let Some(test_name) = &args.test_name {
+        let (package, compiled_program) = compile_test_for_debugging(
+            &workspace_file_manager,
+            &parsed_files,
+            &workspace,
+            test_name,
+            &compile_options,
+            expression_width,
+        )?;
+        return run_async(
+            package,
+            compiled_program,
+            &args.prover_name,
+            &args.witness_name,
+            target_dir,
+        );
+    }
 
     let Some(package) = workspace.into_iter().find(|p| p.is_binary()) else {
         println!(
@@ -67,15 +104,61 @@ pub(crate) fn run(
 
     let compiled_program = compile_bin_package(
         &workspace_file_manager,
+        &parsed_files,
         &workspace,
         package,
-        &args.compile_options,
+        &compile_options,
         expression_width,
     )?;
 
     run_async(package, compiled_program, &args.prover_name, &args.witness_name, target_dir)
 }
 
+/// Compiles the test function with the given fully qualified name for debugging, looking for it
+/// in each of the selected packages of the workspace.
+pub(crate) fn compile_test_for_debugging<'a>(
+    file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
+    workspace: &'a Workspace,
+    test_name: &str,
+    compile_options: &CompileOptions,
+    expression_width: ExpressionWidth,
+) -> Result<(&'a Package, CompiledProgram), CliError> {
+    for package in workspace {
+        let (mut context, crate_id) =
+            prepare_package_with_parsed_files(file_manager, parsed_files, package);
+        link_to_debug_crate(&mut context, crate_id);
+        check_crate_and_report_errors(
+            &mut context,
+            crate_id,
+            compile_options.deny_warnings,
+            compile_options.disable_macros,
+            compile_options.silence_warnings,
+        )?;
+
+        let pattern = FunctionNameMatch::Exact(test_name);
+        let test_functions = context.get_all_test_functions_in_crate_matching(&crate_id, pattern);
+        let Some((_, test_function)) = test_functions.into_iter().next() else {
+            continue;
+        };
+
+        // The test function is compiled as the entry point of a program, as `nargo test` does
+        let compilation_result =
+            compile_no_check(&context, compile_options, test_function.get_id(), None, false)
+                .map(|program| (program, vec![]))
+                .map_err(|error| vec![FileDiagnostic::from(error)]);
+        let program = report_errors(
+            compilation_result,
+            file_manager,
+            compile_options.deny_warnings,
+            compile_options.silence_warnings,
+        )?;
+        return Ok((package, nargo::ops::optimize_program(program, expression_width)));
+    }
+
+    Err(CliError::Generic(format!("Found no test function named '{test_name}'")))
+}
+
 fn run_async(
     package: &Package,
     program: CompiledProgram,
@@ -1,13 +1,18 @@
 use acvm::acir::native_types::WitnessMap;
 use bn254_blackbox_solver::Bn254BlackBoxSolver;
 use clap::Args;
+use fm::FileManager;
 
 use nargo::artifacts::debug::DebugArtifact;
 use nargo::constants::PROVER_INPUT_FILE;
+use nargo::coverage::{OpcodeCoverage, SourceCoverage};
 use nargo::errors::try_to_diagnose_runtime_error;
 use nargo::insert_all_files_for_workspace_into_file_manager;
-use nargo::ops::DefaultForeignCallExecutor;
+use nargo::ops::{DefaultForeignCallExecutor, ExecutionObserver};
 use nargo::package::Package;
+use nargo::parse_all;
+use nargo::profile::BrilligProfile;
+use nargo::workspace::Workspace;
 use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
 use noirc_abi::input_parser::{Format, InputValue};
 use noirc_abi::InputMap;
@@ -15,8 +20,10 @@ use noirc_driver::{
     file_manager_with_stdlib, CompileOptions, CompiledProgram, NOIR_ARTIFACT_VERSION_STRING,
 };
 use noirc_frontend::graph::CrateName;
+use prettytable::{row, table};
 
 use super::compile_cmd::compile_bin_package;
+use super::coverage::report_coverage;
 use super::fs::{inputs::read_inputs_from_file, witness::save_witness_to_dir};
 use super::NargoConfig;
 use crate::backends::Backend;
@@ -46,8 +53,21 @@ pub(crate) struct ExecuteCommand {
     /// JSON RPC url to solve oracle calls
     #[clap(long)]
     oracle_resolver: Option<String>,
+
+    /// Record which lines and branches the execution ran, writing lcov and Cobertura reports to
+    /// the target directory
+    #[clap(long)]
+    coverage: bool,
+
+    /// Count the Brillig opcodes run by unconstrained functions, printing how many ran, how many
+    /// foreign calls they made and their peak memory use for each source location
+    #[clap(long)]
+    profile_brillig: bool,
 }
 
+/// The number of source locations shown in a Brillig profile
+const BRILLIG_PROFILE_ROWS: usize = 20;
+
 pub(crate) fn run(
     backend: &Backend,
     args: ExecuteCommand,
@@ -61,28 +81,59 @@ pub(crate) fn run(
         &toml_path,
         selection,
         Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        config.network_mode(),
     )?;
     let target_dir = &workspace.target_directory_path();
 
     let mut workspace_file_manager = file_manager_with_stdlib(&workspace.root_dir);
     insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
+    let parsed_files = parse_all(&workspace_file_manager);
 
     let expression_width = backend.get_backend_info_or_default();
+    let mut coverage = SourceCoverage::default();
     for package in &workspace {
         let compiled_program = compile_bin_package(
             &workspace_file_manager,
+            &parsed_files,
             &workspace,
             package,
             &args.compile_options,
             expression_width,
         )?;
 
+        let mut opcode_coverage = args.coverage.then(OpcodeCoverage::default);
+        let mut brillig_profile = args.profile_brillig.then(BrilligProfile::default);
+        let mut observers: Vec<&mut dyn ExecutionObserver> = Vec::new();
+        if let Some(opcode_coverage) = &mut opcode_coverage {
+            observers.push(opcode_coverage);
+        }
+        if let Some(brillig_profile) = &mut brillig_profile {
+            observers.push(brillig_profile);
+        }
         let (return_value, solved_witness) = execute_program_and_decode(
-            compiled_program,
+            &compiled_program,
             package,
             &args.prover_name,
             args.oracle_resolver.as_deref(),
+            &mut observers,
         )?;
+        if let Some(opcode_coverage) = opcode_coverage {
+            coverage.add_execution(
+                &workspace_file_manager,
+                &compiled_program.circuit,
+                &compiled_program.debug,
+                &opcode_coverage,
+            );
+        }
+        if let Some(brillig_profile) = brillig_profile {
+            report_brillig_profile(
+                &workspace,
+                &workspace_file_manager,
+                package,
+                &compiled_program,
+                &brillig_profile,
+            );
+        }
 
         println!("[{}] Circuit witness successfully solved", package.name);
         if let Some(return_value) = return_value {
@@ -94,19 +145,25 @@ pub(crate) fn run(
             println!("[{}] Witness saved to {}", package.name, witness_path.display());
         }
     }
+
+    if args.coverage {
+        report_coverage(&workspace, &coverage);
+    }
     Ok(())
 }
 
 fn execute_program_and_decode(
-    program: CompiledProgram,
+    program: &CompiledProgram,
     package: &Package,
     prover_name: &str,
     foreign_call_resolver_url: Option<&str>,
+    observers: &mut [&mut dyn ExecutionObserver],
 ) -> Result<(Option<InputValue>, WitnessMap), CliError> {
     // Parse the initial witness values from Prover.toml
     let (inputs_map, _) =
         read_inputs_from_file(&package.root_dir, prover_name, Format::Toml, &program.abi)?;
-    let solved_witness = execute_program(&program, &inputs_map, foreign_call_resolver_url)?;
+    let solved_witness =
+        execute_program(program, &inputs_map, foreign_call_resolver_url, observers)?;
     let public_abi = program.abi.public_abi();
     let (_, return_value) = public_abi.decode(&solved_witness)?;
 
@@ -117,17 +174,32 @@ pub(crate) fn execute_program(
     compiled_program: &CompiledProgram,
     inputs_map: &InputMap,
     foreign_call_resolver_url: Option<&str>,
+    observers: &mut [&mut dyn ExecutionObserver],
 ) -> Result<WitnessMap, CliError> {
     let blackbox_solver = Bn254BlackBoxSolver::new();
 
     let initial_witness = compiled_program.abi.encode(inputs_map, None)?;
 
-    let solved_witness_err = nargo::ops::execute_circuit(
-        &compiled_program.circuit,
-        initial_witness,
-        &blackbox_solver,
-        &mut DefaultForeignCallExecutor::new(true, foreign_call_resolver_url),
-    );
+    let mut foreign_call_executor =
+        DefaultForeignCallExecutor::new(true, foreign_call_resolver_url);
+    // Stepping through every opcode is slower than solving the circuit, so it's only done when
+    // something is watching.
+    let solved_witness_err = if observers.is_empty() {
+        nargo::ops::execute_circuit(
+            &compiled_program.circuit,
+            initial_witness,
+            &blackbox_solver,
+            &mut foreign_call_executor,
+        )
+    } else {
+        nargo::ops::execute_circuit_with_observers(
+            &compiled_program.circuit,
+            initial_witness,
+            &blackbox_solver,
+            &mut foreign_call_executor,
+            observers,
+        )
+    };
     match solved_witness_err {
         Ok(solved_witness) => Ok(solved_witness),
         Err(err) => {
@@ -145,3 +217,43 @@ pub(crate) fn execute_program(
         }
     }
 }
+
+/// Prints the source locations whose Brillig opcodes ran most often.
+fn report_brillig_profile(
+    workspace: &Workspace,
+    file_manager: &FileManager,
+    package: &Package,
+    program: &CompiledProgram,
+    profile: &BrilligProfile,
+) {
+    let locations = profile.by_location(file_manager, &program.debug);
+
+    let mut summary = table!([
+        Fm->"Location",
+        Fm->"Executed Opcodes",
+        Fm->"Foreign Calls",
+        Fm->"Peak Memory"
+    ]);
+    for location in locations.iter().take(BRILLIG_PROFILE_ROWS) {
+        let path = location.file.strip_prefix(&workspace.root_dir).unwrap_or(&location.file);
+        summary.add_row(row![
+            Fm->format!("{}:{}:{}", path.display(), location.line, location.column),
+            Fc->location.stats.executions,
+            Fc->location.stats.foreign_calls,
+            Fc->location.stats.memory_high_water_mark,
+        ]);
+    }
+    let total = profile.total();
+    summary.add_row(row![
+        Fm->"Total",
+        Fc->total.executions,
+        Fc->total.foreign_calls,
+        Fc->total.memory_high_water_mark,
+    ]);
+
+    eprintln!("[{}] Brillig execution profile", package.name);
+    eprint!("{summary}");
+    if locations.len() > BRILLIG_PROFILE_ROWS {
+        eprintln!("... and {} more locations", locations.len() - BRILLIG_PROFILE_ROWS);
+    }
+}
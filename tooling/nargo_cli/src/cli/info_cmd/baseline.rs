@@ -0,0 +1,229 @@
+//! Baselines of circuit sizes, checked in alongside a project so that CI catches circuits which
+//! grow unexpectedly.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use prettytable::{row, table};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{CliError, FilesystemError};
+
+use super::super::fs::write_to_file;
+use super::InfoReport;
+
+/// The sizes of every program and contract function, keyed by name.
+///
+/// Circuits are kept in a sorted map rather than the nested lists of the JSON report, so that a
+/// change to the baseline only touches the lines of the circuits which changed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct Baseline {
+    circuits: BTreeMap<String, CircuitSize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct CircuitSize {
+    acir_opcodes: usize,
+    circuit_size: u32,
+}
+
+/// A circuit whose size differs from the baseline.
+#[derive(Debug, Clone, PartialEq)]
+struct SizeChange {
+    name: String,
+    /// `None` for circuits which aren't in the baseline yet
+    baseline: Option<CircuitSize>,
+    /// `None` for circuits in the baseline which weren't found anymore
+    current: Option<CircuitSize>,
+    /// Whether the circuit grew past the tolerance
+    regression: bool,
+}
+
+impl Baseline {
+    pub(super) fn new(report: &InfoReport) -> Self {
+        let mut circuits = BTreeMap::new();
+        for program in &report.programs {
+            let size = CircuitSize {
+                acir_opcodes: program.acir_opcodes,
+                circuit_size: program.circuit_size,
+            };
+            circuits.insert(program.name.clone(), size);
+        }
+        for contract in &report.contracts {
+            for function in &contract.functions {
+                let size = CircuitSize {
+                    acir_opcodes: function.acir_opcodes,
+                    circuit_size: function.circuit_size,
+                };
+                circuits.insert(format!("{}::{}", contract.name, function.name), size);
+            }
+        }
+        Baseline { circuits }
+    }
+
+    pub(super) fn read(path: &Path) -> Result<Self, FilesystemError> {
+        let contents = std::fs::read_to_string(path).map_err(|error| {
+            FilesystemError::BaselineNotValid(path.to_path_buf(), error.to_string())
+        })?;
+        serde_json::from_str(&contents).map_err(|error| {
+            FilesystemError::BaselineNotValid(path.to_path_buf(), error.to_string())
+        })
+    }
+
+    pub(super) fn write(&self, path: &Path) {
+        let mut json =
+            serde_json::to_string_pretty(self).expect("Baselines should serialize to JSON");
+        json.push('\n');
+        write_to_file(json.as_bytes(), path);
+    }
+
+    /// The circuits of `current` which differ from this baseline, along with the circuits of this
+    /// baseline which are missing from `current`, sorted by name. A circuit is a regression if
+    /// either of its sizes grew by more than `tolerance` percent.
+    fn changes(&self, current: &Baseline, tolerance: f64) -> Vec<SizeChange> {
+        let grew = |baseline: f64, current: f64| current > baseline * (1.0 + tolerance / 100.0);
+
+        let mut changes = Vec::new();
+        for (name, size) in &current.circuits {
+            let baseline = self.circuits.get(name).copied();
+            if baseline == Some(*size) {
+                continue;
+            }
+            let regression = baseline.map_or(false, |baseline| {
+                grew(baseline.acir_opcodes as f64, size.acir_opcodes as f64)
+                    || grew(baseline.circuit_size.into(), size.circuit_size.into())
+            });
+            let current = Some(*size);
+            changes.push(SizeChange { name: name.clone(), baseline, current, regression });
+        }
+        for (name, size) in &self.circuits {
+            if !current.circuits.contains_key(name) {
+                let baseline = Some(*size);
+                changes.push(SizeChange {
+                    name: name.clone(),
+                    baseline,
+                    current: None,
+                    regression: false,
+                });
+            }
+        }
+        changes.sort_by(|a, b| a.name.cmp(&b.name));
+        changes
+    }
+}
+
+/// Compares the `current` circuit sizes against the baseline at `path`, printing every circuit
+/// which changed and failing if any grew past the tolerance.
+pub(super) fn check_baseline(
+    path: &Path,
+    current: &Baseline,
+    tolerance: f64,
+) -> Result<(), CliError> {
+    let baseline = Baseline::read(path)?;
+    let changes = baseline.changes(current, tolerance);
+    if changes.is_empty() {
+        eprintln!("Circuit sizes match the baseline in {}", path.display());
+        return Ok(());
+    }
+
+    let mut table = table!([
+        Fm->"Circuit",
+        Fm->"ACIR Opcodes",
+        Fm->"Backend Circuit Size",
+        Fm->"Status"
+    ]);
+    for change in &changes {
+        let status = match (change.baseline, change.current, change.regression) {
+            (None, _, _) => "new",
+            (Some(_), None, _) => "removed",
+            (Some(_), Some(_), true) => "regression",
+            (Some(_), Some(_), false) => "ok",
+        };
+        let acir_opcodes = |size: Option<CircuitSize>| size.map(|size| size.acir_opcodes);
+        let circuit_size = |size: Option<CircuitSize>| size.map(|size| size.circuit_size as usize);
+        table.add_row(row![
+            Fm->change.name,
+            Fc->size_change(acir_opcodes(change.baseline), acir_opcodes(change.current)),
+            Fc->size_change(circuit_size(change.baseline), circuit_size(change.current)),
+            status,
+        ]);
+    }
+    eprint!("{table}");
+
+    let regressions = changes.iter().filter(|change| change.regression).count();
+    if regressions > 0 {
+        return Err(CliError::CircuitSizeRegression(regressions, path.to_path_buf()));
+    }
+    Ok(())
+}
+
+fn size_change(baseline: Option<usize>, current: Option<usize>) -> String {
+    match (baseline, current) {
+        (None, None) => String::new(),
+        (None, Some(current)) => current.to_string(),
+        (Some(baseline), None) => format!("{baseline} -> -"),
+        (Some(0), Some(current)) => format!("0 -> {current}"),
+        (Some(baseline), Some(current)) => {
+            let percentage = (current as f64 - baseline as f64) / baseline as f64 * 100.0;
+            format!("{baseline} -> {current} ({percentage:+.1}%)")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{size_change, Baseline, CircuitSize};
+
+    fn baseline(circuits: &[(&str, usize, u32)]) -> Baseline {
+        Baseline {
+            circuits: circuits
+                .iter()
+                .map(|(name, acir_opcodes, circuit_size)| {
+                    (
+                        name.to_string(),
+                        CircuitSize { acir_opcodes: *acir_opcodes, circuit_size: *circuit_size },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn flags_circuits_which_grew_past_the_tolerance() {
+        let old = baseline(&[
+            ("main", 100, 1000),
+            ("Token::transfer", 50, 500),
+            ("same", 1, 1),
+            ("Token::burn", 20, 200),
+        ]);
+        let new = baseline(&[
+            ("main", 104, 1040),
+            ("Token::transfer", 50, 510),
+            ("same", 1, 1),
+            ("Token::mint", 10, 100),
+        ]);
+
+        let changes = old.changes(&new, 3.0);
+        let regressions: Vec<(&str, bool)> =
+            changes.iter().map(|change| (change.name.as_str(), change.regression)).collect();
+        assert_eq!(
+            regressions,
+            vec![
+                ("Token::burn", false),
+                ("Token::mint", false),
+                ("Token::transfer", false),
+                ("main", true)
+            ]
+        );
+        assert!(changes[0].current.is_none());
+        assert!(changes[1].baseline.is_none());
+    }
+
+    #[test]
+    fn formats_size_changes() {
+        assert_eq!(size_change(Some(200), Some(210)), "200 -> 210 (+5.0%)");
+        assert_eq!(size_change(Some(200), Some(150)), "200 -> 150 (-25.0%)");
+        assert_eq!(size_change(None, Some(7)), "7");
+        assert_eq!(size_change(Some(7), None), "7 -> -");
+    }
+}
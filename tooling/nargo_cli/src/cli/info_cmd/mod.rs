@@ -1,19 +1,15 @@
-use std::collections::HashMap;
+use std::path::PathBuf;
 
 use acvm::ExpressionWidth;
 use backend_interface::BackendError;
 use clap::Args;
 use iter_extended::vecmap;
-use nargo::{
-    artifacts::debug::DebugArtifact, insert_all_files_for_workspace_into_file_manager,
-    package::Package,
-};
+use nargo::{insert_all_files_for_workspace_into_file_manager, package::Package, parse_all};
 use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
 use noirc_driver::{
     file_manager_with_stdlib, CompileOptions, CompiledContract, CompiledProgram,
     NOIR_ARTIFACT_VERSION_STRING,
 };
-use noirc_errors::{debug_info::OpCodesCount, Location};
 use noirc_frontend::graph::CrateName;
 use prettytable::{row, table, Row};
 use rayon::prelude::*;
@@ -24,11 +20,18 @@ use crate::errors::CliError;
 
 use super::{compile_cmd::compile_workspace, NargoConfig};
 
+mod baseline;
+
+use baseline::{check_baseline, Baseline};
+
 /// Provides detailed information on a circuit
 ///
 /// Current information provided:
 /// 1. The number of ACIR opcodes
 /// 2. Counts the final number gates in the circuit used by a backend
+///
+/// The sizes can be recorded to a baseline file with `--write-baseline`, and compared against it
+/// with `--check-baseline` to catch circuits which grow unexpectedly.
 #[derive(Debug, Clone, Args)]
 pub(crate) struct InfoCommand {
     /// The name of the package to detail
@@ -43,8 +46,18 @@ pub(crate) struct InfoCommand {
     #[clap(long, hide = true)]
     json: bool,
 
-    #[clap(long, hide = true)]
-    profile_info: bool,
+    /// Fail if any circuit grew past the sizes recorded in this baseline file
+    #[clap(long, value_name = "FILE", conflicts_with = "write_baseline")]
+    check_baseline: Option<PathBuf>,
+
+    /// Record the size of every circuit to this baseline file
+    #[clap(long, value_name = "FILE")]
+    write_baseline: Option<PathBuf>,
+
+    /// The percentage by which a circuit may grow past the baseline before `--check-baseline`
+    /// fails
+    #[clap(long, value_name = "PERCENT", default_value_t = 0.0, requires = "check_baseline")]
+    baseline_tolerance: f64,
 
     #[clap(flatten)]
     compile_options: CompileOptions,
@@ -63,10 +76,12 @@ pub(crate) fn run(
         &toml_path,
         selection,
         Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        config.network_mode(),
     )?;
 
     let mut workspace_file_manager = file_manager_with_stdlib(&workspace.root_dir);
     insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
+    let parsed_files = parse_all(&workspace_file_manager);
 
     let (binary_packages, contract_packages): (Vec<_>, Vec<_>) = workspace
         .into_iter()
@@ -77,6 +92,7 @@ pub(crate) fn run(
     let expression_width = backend.get_backend_info_or_default();
     let (compiled_programs, compiled_contracts) = compile_workspace(
         &workspace_file_manager,
+        &parsed_files,
         &workspace,
         &binary_packages,
         &contract_packages,
@@ -84,23 +100,6 @@ pub(crate) fn run(
         &args.compile_options,
     )?;
 
-    if args.profile_info {
-        for compiled_program in &compiled_programs {
-            let span_opcodes = compiled_program.debug.count_span_opcodes();
-            let debug_artifact: DebugArtifact = compiled_program.clone().into();
-            print_span_opcodes(span_opcodes, &debug_artifact);
-        }
-
-        for compiled_contract in &compiled_contracts {
-            let debug_artifact: DebugArtifact = compiled_contract.clone().into();
-            let functions = &compiled_contract.functions;
-            for contract_function in functions {
-                let span_opcodes = contract_function.debug.count_span_opcodes();
-                print_span_opcodes(span_opcodes, &debug_artifact);
-            }
-        }
-    }
-
     let program_info = binary_packages
         .into_par_iter()
         .zip(compiled_programs)
@@ -115,6 +114,7 @@ pub(crate) fn run(
         .collect::<Result<_, _>>()?;
 
     let info_report = InfoReport { programs: program_info, contracts: contract_info };
+    let circuit_sizes = Baseline::new(&info_report);
 
     if args.json {
         // Expose machine-readable JSON data.
@@ -148,56 +148,15 @@ pub(crate) fn run(
         }
     }
 
-    Ok(())
-}
-
-/// Provides profiling information on
-///
-/// Number of OpCodes in relation to Noir source file
-/// and line number information
-fn print_span_opcodes(
-    span_opcodes_map: HashMap<Location, OpCodesCount>,
-    debug_artifact: &DebugArtifact,
-) {
-    let mut pairs: Vec<(&Location, &OpCodesCount)> = span_opcodes_map.iter().collect();
-
-    pairs.sort_by(|a, b| {
-        a.1.acir_size.cmp(&b.1.acir_size).then_with(|| a.1.brillig_size.cmp(&b.1.brillig_size))
-    });
-
-    for (location, opcodes_count) in pairs {
-        let debug_file = debug_artifact.file_map.get(&location.file).unwrap();
-
-        let start_byte = byte_index(&debug_file.source, location.span.start() + 1);
-        let end_byte = byte_index(&debug_file.source, location.span.end() + 1);
-        let range = start_byte..end_byte;
-        let span_content = &debug_file.source[range];
-        let line = debug_artifact.location_line_index(*location).unwrap() + 1;
-        println!(
-            "Ln. {}: {} (ACIR:{}, Brillig:{} opcode|s) in file: {}",
-            line,
-            span_content,
-            opcodes_count.acir_size,
-            opcodes_count.brillig_size,
-            debug_file.path.to_str().unwrap()
-        );
+    if let Some(baseline_path) = &args.write_baseline {
+        circuit_sizes.write(baseline_path);
+        eprintln!("Circuit sizes written to {}", baseline_path.display());
     }
-}
-fn byte_index(string: &str, index: u32) -> usize {
-    let mut byte_index = 0;
-    let mut char_index = 0;
-
-    #[allow(clippy::explicit_counter_loop)]
-    for (byte_offset, _) in string.char_indices() {
-        if char_index == index {
-            return byte_index;
-        }
-
-        byte_index = byte_offset;
-        char_index += 1;
+    if let Some(baseline_path) = &args.check_baseline {
+        check_baseline(baseline_path, &circuit_sizes, args.baseline_tolerance)?;
     }
 
-    byte_index
+    Ok(())
 }
 
 #[derive(Debug, Default, Serialize)]
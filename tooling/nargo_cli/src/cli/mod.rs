@@ -1,6 +1,6 @@
 use clap::{Args, Parser, Subcommand};
 use const_format::formatcp;
-use nargo_toml::find_package_root;
+use nargo_toml::{find_package_root, NetworkMode};
 use noirc_driver::NOIR_ARTIFACT_VERSION_STRING;
 use std::path::PathBuf;
 
@@ -14,6 +14,7 @@ mod backend_cmd;
 mod check_cmd;
 mod codegen_verifier_cmd;
 mod compile_cmd;
+mod coverage;
 mod dap_cmd;
 mod debug_cmd;
 mod execute_cmd;
@@ -23,9 +24,14 @@ mod info_cmd;
 mod init_cmd;
 mod lsp_cmd;
 mod new_cmd;
+mod package_cmd;
+mod profile_cmd;
 mod prove_cmd;
 mod test_cmd;
+mod update_cmd;
+mod vendor_cmd;
 mod verify_cmd;
+mod watch;
 
 const GIT_HASH: &str = env!("GIT_COMMIT");
 const IS_DIRTY: &str = env!("GIT_DIRTY");
@@ -55,6 +61,20 @@ pub(crate) struct NargoConfig {
     // REMINDER: Also change this flag in the LSP test lens if renamed
     #[arg(long, hide = true, global = true, default_value = "./")]
     program_dir: PathBuf,
+
+    /// Never fetch git dependencies, only reading the sources vendored with `nargo vendor`
+    #[arg(long, global = true)]
+    offline: bool,
+}
+
+impl NargoConfig {
+    pub(crate) fn network_mode(&self) -> NetworkMode {
+        if self.offline {
+            NetworkMode::Offline
+        } else {
+            NetworkMode::Online
+        }
+    }
 }
 
 #[non_exhaustive]
@@ -77,6 +97,10 @@ enum NargoCommand {
     Verify(verify_cmd::VerifyCommand),
     Test(test_cmd::TestCommand),
     Info(info_cmd::InfoCommand),
+    Profile(profile_cmd::ProfileCommand),
+    Update(update_cmd::UpdateCommand),
+    Vendor(vendor_cmd::VendorCommand),
+    Package(package_cmd::PackageCommand),
     Lsp(lsp_cmd::LspCommand),
     #[command(hide = true)]
     Dap(dap_cmd::DapCommand),
@@ -117,11 +141,15 @@ pub(crate) fn start_cli() -> eyre::Result<()> {
         NargoCommand::Verify(args) => verify_cmd::run(&backend, args, config),
         NargoCommand::Test(args) => test_cmd::run(&backend, args, config),
         NargoCommand::Info(args) => info_cmd::run(&backend, args, config),
+        NargoCommand::Profile(args) => profile_cmd::run(&backend, args, config),
         NargoCommand::CodegenVerifier(args) => codegen_verifier_cmd::run(&backend, args, config),
         NargoCommand::Backend(args) => backend_cmd::run(args),
         NargoCommand::Lsp(args) => lsp_cmd::run(&backend, args, config),
         NargoCommand::Dap(args) => dap_cmd::run(&backend, args, config),
         NargoCommand::Fmt(args) => fmt_cmd::run(args, config),
+        NargoCommand::Update(args) => update_cmd::run(args, config),
+        NargoCommand::Vendor(args) => vendor_cmd::run(args, config),
+        NargoCommand::Package(args) => package_cmd::run(&backend, args, config),
     }?;
 
     Ok(())
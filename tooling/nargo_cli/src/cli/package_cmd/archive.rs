@@ -0,0 +1,77 @@
+//! Deterministic source archives: gzipped tarballs whose bytes only depend on the paths and
+//! contents of the files archived.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use flate2::{read::GzDecoder, Compression, GzBuilder};
+use sha2::{Digest, Sha256};
+
+/// The file within an archive which lists the checksum of every other file, in the format of
+/// `sha256sum` so that `sha256sum -c MANIFEST` checks them
+pub(super) const MANIFEST_FILE: &str = "MANIFEST";
+
+/// Archives `files`, keyed by their path with `/` separators, under the directory `prefix` along
+/// with a manifest of their checksums.
+///
+/// Entries are written in order of their paths, with fixed timestamps, owners and permissions.
+pub(super) fn write_archive(
+    prefix: &str,
+    files: &BTreeMap<String, Vec<u8>>,
+) -> std::io::Result<Vec<u8>> {
+    let manifest: String =
+        files.iter().map(|(path, contents)| format!("{}  {path}\n", sha256(contents))).collect();
+
+    let encoder = GzBuilder::new().mtime(0).write(Vec::new(), Compression::best());
+    let mut builder = tar::Builder::new(encoder);
+    let entries = files
+        .iter()
+        .map(|(path, contents)| (path.as_str(), contents.as_slice()))
+        .chain(std::iter::once((MANIFEST_FILE, manifest.as_bytes())));
+    for (path, contents) in entries {
+        let mut header = tar::Header::new_ustar();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_entry_type(tar::EntryType::Regular);
+        builder.append_data(&mut header, format!("{prefix}/{path}"), contents)?;
+    }
+    builder.into_inner()?.finish()
+}
+
+/// Unpacks `archive` into `destination`.
+pub(super) fn unpack_archive(archive: &[u8], destination: &Path) -> std::io::Result<()> {
+    tar::Archive::new(GzDecoder::new(archive)).unpack(destination)
+}
+
+pub(super) fn sha256(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{sha256, unpack_archive, write_archive, MANIFEST_FILE};
+
+    #[test]
+    fn archives_are_reproducible() {
+        let files = BTreeMap::from([
+            ("Nargo.toml".to_string(), b"[package]".to_vec()),
+            ("src/lib.nr".to_string(), b"fn foo() {}".to_vec()),
+        ]);
+        let archive = write_archive("example-0.1.0", &files).unwrap();
+        assert_eq!(write_archive("example-0.1.0", &files).unwrap(), archive);
+
+        let dir = tempfile::tempdir().unwrap();
+        unpack_archive(&archive, dir.path()).unwrap();
+        let package_dir = dir.path().join("example-0.1.0");
+        assert_eq!(std::fs::read(package_dir.join("src/lib.nr")).unwrap(), b"fn foo() {}");
+
+        let manifest = std::fs::read_to_string(package_dir.join(MANIFEST_FILE)).unwrap();
+        let expected_line = format!("{}  src/lib.nr", sha256(b"fn foo() {}"));
+        assert!(manifest.lines().any(|line| line == expected_line));
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+use acvm::ExpressionWidth;
+use clap::Args;
+use fm::FileManager;
+use nargo::{
+    insert_all_files_for_workspace_into_file_manager, package::Package, parse_all, prepare_package,
+    prepare_package_with_parsed_files,
+};
+use nargo_toml::{
+    get_package_manifest, packaged_manifest, resolve_workspace_from_toml, NetworkMode,
+    PackageSelection, LOCKFILE,
+};
+use noirc_driver::{file_manager_with_stdlib, CompileOptions, NOIR_ARTIFACT_VERSION_STRING};
+use noirc_frontend::graph::CrateName;
+
+use crate::backends::Backend;
+use crate::errors::{CliError, FilesystemError};
+
+use super::check_cmd::check_crate_and_report_errors;
+use super::compile_cmd::compile_workspace;
+use super::fs::{create_named_dir, write_to_file};
+use super::NargoConfig;
+
+mod archive;
+
+/// The directory within the target directory which archives are written to
+const PACKAGE_DIR: &str = "package";
+
+/// Bundle a package's sources into an archive which can be shared and audited
+///
+/// Writes `<name>-<version>.tar.gz` and its SHA-256 checksum to `target/package`, after checking
+/// that the package compiles from the archive alone.
+#[derive(Debug, Clone, Args)]
+pub(crate) struct PackageCommand {
+    /// The name of the package to package
+    #[clap(long, conflicts_with = "workspace")]
+    package: Option<CrateName>,
+
+    /// Package all packages in the workspace
+    #[clap(long, conflicts_with = "package")]
+    workspace: bool,
+
+    /// Don't check that the package compiles from its archive
+    #[clap(long)]
+    no_verify: bool,
+
+    #[clap(flatten)]
+    compile_options: CompileOptions,
+}
+
+pub(crate) fn run(
+    backend: &Backend,
+    args: PackageCommand,
+    config: NargoConfig,
+) -> Result<(), CliError> {
+    let toml_path = get_package_manifest(&config.program_dir)?;
+    let default_selection =
+        if args.workspace { PackageSelection::All } else { PackageSelection::DefaultOrAll };
+    let selection = args.package.map_or(default_selection, PackageSelection::Selected);
+    let workspace = resolve_workspace_from_toml(
+        &toml_path,
+        selection,
+        Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        config.network_mode(),
+    )?;
+    let expression_width = backend.get_backend_info_or_default();
+
+    let mut workspace_file_manager = file_manager_with_stdlib(&workspace.root_dir);
+    insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
+
+    // The workspace's lockfile pins the git dependencies of every package within it.
+    let lockfile_path = workspace.root_dir.join(LOCKFILE);
+    let lockfile = if lockfile_path.exists() {
+        let contents = std::fs::read(&lockfile_path)
+            .map_err(|error| FilesystemError::ArchiveFileNotRead(lockfile_path.clone(), error))?;
+        Some(contents)
+    } else {
+        None
+    };
+
+    let package_dir =
+        create_named_dir(&workspace.target_directory_path().join(PACKAGE_DIR), PACKAGE_DIR);
+    for package in &workspace {
+        let manifest = packaged_manifest(&workspace.root_dir, &package.root_dir)?;
+        let mut files = package_sources(&workspace_file_manager, package, &args.compile_options)?;
+        files.insert("Nargo.toml".to_string(), manifest.contents.into_bytes());
+        if let Some(lockfile) = &lockfile {
+            files.insert(LOCKFILE.to_string(), lockfile.clone());
+        }
+
+        let prefix = format!("{}-{}", manifest.name, manifest.version);
+        let archive_path = package_dir.join(format!("{prefix}.tar.gz"));
+        let archive = archive::write_archive(&prefix, &files)
+            .map_err(|error| FilesystemError::ArchiveNotWritten(archive_path.clone(), error))?;
+        let checksum = archive::sha256(&archive);
+        write_to_file(&archive, &archive_path);
+        write_to_file(
+            format!("{checksum}  {prefix}.tar.gz\n").as_bytes(),
+            &package_dir.join(format!("{prefix}.tar.gz.sha256")),
+        );
+
+        if !args.no_verify {
+            let unpacked_dir = package_dir.join(&prefix);
+            verify_archive(
+                &archive,
+                &package_dir,
+                &unpacked_dir,
+                config.network_mode(),
+                expression_width,
+                &args.compile_options,
+            )
+            .map_err(|error| {
+                CliError::Generic(format!(
+                    "[{}] Could not compile the package unpacked into {}: {error}",
+                    package.name,
+                    unpacked_dir.display()
+                ))
+            })?;
+        }
+
+        println!(
+            "[{}] Packaged {} files into {} (sha256 {checksum})",
+            package.name,
+            files.len(),
+            archive_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Returns the source files of `package` by their path within the package, which are found by
+/// following its module tree from its entry file.
+fn package_sources(
+    file_manager: &FileManager,
+    package: &Package,
+    compile_options: &CompileOptions,
+) -> Result<BTreeMap<String, Vec<u8>>, CliError> {
+    let (mut context, crate_id) = prepare_package(file_manager, package);
+    check_crate_and_report_errors(
+        &mut context,
+        crate_id,
+        compile_options.deny_warnings,
+        compile_options.disable_macros,
+        compile_options.silence_warnings,
+    )?;
+
+    let def_map = context.def_map(&crate_id).expect("the package's crate should have been checked");
+    let mut sources = BTreeMap::new();
+    for (_, module) in def_map.modules().iter() {
+        let path = file_manager.path(module.location.file);
+        let Ok(relative_path) = path.strip_prefix(&package.root_dir) else {
+            return Err(CliError::Generic(format!(
+                "[{}] Module {} is outside of the package, so it can't be packaged",
+                package.name,
+                path.display()
+            )));
+        };
+        let relative_path: Vec<_> = relative_path
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(name) => Some(name.to_string_lossy()),
+                _ => None,
+            })
+            .collect();
+        let source = file_manager.fetch_file(module.location.file);
+        sources.insert(relative_path.join("/"), source.as_bytes().to_vec());
+    }
+    Ok(sources)
+}
+
+/// Unpacks `archive` into `package_dir` and compiles the package within `unpacked_dir`, resolving
+/// only the dependencies declared by its packaged `Nargo.toml`.
+///
+/// Binary and contract packages are compiled in full, with their artifacts written to the
+/// unpacked package's own target directory. Libraries have no entry point, so they're checked.
+fn verify_archive(
+    archive: &[u8],
+    package_dir: &Path,
+    unpacked_dir: &Path,
+    network_mode: NetworkMode,
+    expression_width: ExpressionWidth,
+    compile_options: &CompileOptions,
+) -> Result<(), CliError> {
+    let unpack = || -> std::io::Result<()> {
+        if unpacked_dir.exists() {
+            std::fs::remove_dir_all(unpacked_dir)?;
+        }
+        archive::unpack_archive(archive, package_dir)
+    };
+    unpack()
+        .map_err(|error| FilesystemError::ArchiveNotWritten(unpacked_dir.to_path_buf(), error))?;
+
+    let workspace = resolve_workspace_from_toml(
+        &unpacked_dir.join("Nargo.toml"),
+        PackageSelection::All,
+        Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        network_mode,
+    )?;
+    let mut file_manager = file_manager_with_stdlib(&workspace.root_dir);
+    insert_all_files_for_workspace_into_file_manager(&workspace, &mut file_manager);
+    let parsed_files = parse_all(&file_manager);
+
+    let (libraries, packages): (Vec<_>, Vec<_>) =
+        workspace.into_iter().cloned().partition(|package| package.is_library());
+    let (binary_packages, contract_packages): (Vec<_>, Vec<_>) =
+        packages.into_iter().partition(|package| package.is_binary());
+    compile_workspace(
+        &file_manager,
+        &parsed_files,
+        &workspace,
+        &binary_packages,
+        &contract_packages,
+        expression_width,
+        compile_options,
+    )?;
+    for package in &libraries {
+        let (mut context, crate_id) =
+            prepare_package_with_parsed_files(&file_manager, &parsed_files, package);
+        check_crate_and_report_errors(
+            &mut context,
+            crate_id,
+            compile_options.deny_warnings,
+            compile_options.disable_macros,
+            compile_options.silence_warnings,
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Profiles as folded stacks, the format of Brendan Gregg's `flamegraph.pl`, and as flamegraphs.
+
+use std::fmt::Write;
+
+use crate::errors::CliError;
+
+use super::Profile;
+
+/// Writes a line of `frame;frame;frame weight` for each call stack, outermost frame first.
+/// Call stacks without any weight are left out.
+pub(super) fn folded_stacks(profile: &Profile) -> String {
+    let mut folded = String::new();
+    for (call_stack, weight) in &profile.samples {
+        if *weight == 0 {
+            continue;
+        }
+        let frames: Vec<String> =
+            call_stack.iter().map(|frame| frame.to_string().replace(';', ":")).collect();
+        writeln!(folded, "{} {weight}", frames.join(";")).unwrap();
+    }
+    folded
+}
+
+pub(super) fn flamegraph_svg(
+    folded_stacks: &str,
+    title: &str,
+    unit: &str,
+) -> Result<Vec<u8>, CliError> {
+    let mut options = inferno::flamegraph::Options::default();
+    options.title = title.to_string();
+    options.count_name = unit.to_string();
+
+    let mut svg = Vec::new();
+    inferno::flamegraph::from_lines(&mut options, folded_stacks.lines(), &mut svg)
+        .map_err(|error| CliError::Generic(format!("Could not write flamegraph: {error}")))?;
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Frame, Profile};
+    use super::folded_stacks;
+
+    #[test]
+    fn folds_call_stacks() {
+        let root = Frame { function: "my_package".to_string(), file: String::new(), line: 0 };
+        let main = Frame { function: "main".to_string(), file: "src/main.nr".to_string(), line: 3 };
+        let helper =
+            Frame { function: "helper".to_string(), file: "src/main.nr".to_string(), line: 9 };
+
+        let mut profile = Profile::new("acir_opcodes", "count");
+        profile.add(&[root.clone(), main.clone()], 1);
+        profile.add(&[root.clone(), main.clone(), helper.clone()], 2);
+        profile.add(&[root.clone(), main.clone(), helper], 3);
+        profile.add(&[root, main], 0);
+
+        assert_eq!(
+            folded_stacks(&profile),
+            "my_package;main (src/main.nr:3) 1\nmy_package;main (src/main.nr:3);helper (src/main.nr:9) 5\n"
+        );
+    }
+}
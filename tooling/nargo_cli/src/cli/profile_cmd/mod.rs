@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+
+use acvm::acir::circuit::{Circuit, OpcodeLocation};
+use clap::Args;
+use fm::{codespan_files::Files, FileManager};
+use nargo::insert_all_files_for_workspace_into_file_manager;
+use nargo::parse_all;
+use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
+use noirc_driver::{file_manager_with_stdlib, CompileOptions, NOIR_ARTIFACT_VERSION_STRING};
+use noirc_errors::{debug_info::DebugInfo, Location};
+use noirc_frontend::graph::CrateName;
+use rayon::prelude::*;
+
+use crate::backends::Backend;
+use crate::errors::CliError;
+
+use super::fs::{create_named_dir, write_to_file};
+use super::{compile_cmd::compile_workspace, NargoConfig};
+
+mod flamegraph;
+mod pprof;
+
+/// The directory within the target directory which profiles are written to
+const PROFILE_DIR: &str = "profile";
+
+/// Profiles the size of circuits, attributing each ACIR opcode and backend gate to the call stack
+/// of the source code it was generated from
+///
+/// Writes folded stacks, a flamegraph and a pprof profile for each circuit to `target/profile`.
+#[derive(Debug, Clone, Args)]
+pub(crate) struct ProfileCommand {
+    /// The name of the package to profile
+    #[clap(long, conflicts_with = "workspace")]
+    package: Option<CrateName>,
+
+    /// Profile all packages in the workspace
+    #[clap(long, conflicts_with = "package")]
+    workspace: bool,
+
+    /// Also profile the backend's gates (experimental)
+    #[clap(long)]
+    gates: bool,
+
+    #[clap(flatten)]
+    compile_options: CompileOptions,
+}
+
+pub(crate) fn run(
+    backend: &Backend,
+    args: ProfileCommand,
+    config: NargoConfig,
+) -> Result<(), CliError> {
+    let toml_path = get_package_manifest(&config.program_dir)?;
+    let default_selection =
+        if args.workspace { PackageSelection::All } else { PackageSelection::DefaultOrAll };
+    let selection = args.package.map_or(default_selection, PackageSelection::Selected);
+    let workspace = resolve_workspace_from_toml(
+        &toml_path,
+        selection,
+        Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        config.network_mode(),
+    )?;
+
+    let mut workspace_file_manager = file_manager_with_stdlib(&workspace.root_dir);
+    insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
+    let parsed_files = parse_all(&workspace_file_manager);
+
+    let (binary_packages, contract_packages): (Vec<_>, Vec<_>) = workspace
+        .into_iter()
+        .filter(|package| !package.is_library())
+        .cloned()
+        .partition(|package| package.is_binary());
+
+    let expression_width = backend.get_backend_info_or_default();
+    let (compiled_programs, compiled_contracts) = compile_workspace(
+        &workspace_file_manager,
+        &parsed_files,
+        &workspace,
+        &binary_packages,
+        &contract_packages,
+        expression_width,
+        &args.compile_options,
+    )?;
+
+    let mut circuits: Vec<(String, &Circuit, &DebugInfo)> = binary_packages
+        .iter()
+        .zip(&compiled_programs)
+        .map(|(package, program)| (package.name.to_string(), &program.circuit, &program.debug))
+        .collect();
+    for contract in &compiled_contracts {
+        for function in &contract.functions {
+            let name = format!("{}-{}", contract.name, function.name);
+            circuits.push((name, &function.bytecode, &function.debug));
+        }
+    }
+
+    let profile_dir =
+        create_named_dir(&workspace.target_directory_path().join(PROFILE_DIR), PROFILE_DIR);
+    for (name, circuit, debug) in circuits {
+        let root = Frame { function: name.clone(), file: String::new(), line: 0 };
+        let call_stacks =
+            opcode_call_stacks(&root, circuit, debug, &workspace_file_manager, &workspace.root_dir);
+
+        let mut opcodes_profile = Profile::new("acir_opcodes", "count");
+        for call_stack in &call_stacks {
+            opcodes_profile.add(call_stack, 1);
+        }
+        write_profile(&profile_dir, &name, &opcodes_profile)?;
+
+        if args.gates {
+            let gates_profile = profile_gates(backend, &root, circuit, &call_stacks)?;
+            write_profile(&profile_dir, &name, &gates_profile)?;
+        }
+    }
+    println!("Profiles written to {}", profile_dir.display());
+
+    Ok(())
+}
+
+/// A function call within the call stack of an opcode.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Frame {
+    function: String,
+    file: String,
+    line: usize,
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.file.is_empty() {
+            write!(f, "{}", self.function)
+        } else {
+            write!(f, "{} ({}:{})", self.function, self.file, self.line)
+        }
+    }
+}
+
+/// The total weight of the opcodes generated from each call stack, outermost frame first.
+struct Profile {
+    sample_type: &'static str,
+    unit: &'static str,
+    samples: BTreeMap<Vec<Frame>, u64>,
+}
+
+impl Profile {
+    fn new(sample_type: &'static str, unit: &'static str) -> Self {
+        Profile { sample_type, unit, samples: BTreeMap::new() }
+    }
+
+    fn add(&mut self, call_stack: &[Frame], weight: u64) {
+        *self.samples.entry(call_stack.to_vec()).or_default() += weight;
+    }
+}
+
+/// Returns the call stack of each of the circuit's ACIR opcodes, in order.
+///
+/// Calls are inlined into a circuit, so these are the calls which the opcodes were inlined from.
+fn opcode_call_stacks(
+    root: &Frame,
+    circuit: &Circuit,
+    debug: &DebugInfo,
+    file_manager: &FileManager,
+    root_dir: &Path,
+) -> Vec<Vec<Frame>> {
+    (0..circuit.opcodes.len())
+        .map(|index| {
+            let mut call_stack = vec![root.clone()];
+            if let Some(locations) = debug.locations.get(&OpcodeLocation::Acir(index)) {
+                // Every location but the innermost is a call site, into the function of the next
+                // frame
+                let mut function = debug.functions.first();
+                for location in locations {
+                    let name = function.map_or("unknown", |function| function.name.as_str());
+                    call_stack.extend(frame(name, location, file_manager, root_dir));
+                    function = debug.called_function(location);
+                }
+            }
+            call_stack
+        })
+        .collect()
+}
+
+fn frame(
+    function: &str,
+    location: &Location,
+    file_manager: &FileManager,
+    root_dir: &Path,
+) -> Option<Frame> {
+    let position =
+        file_manager.as_file_map().location(location.file, location.span.start() as usize).ok()?;
+    let path = file_manager.path(location.file);
+    Some(Frame {
+        function: function.to_string(),
+        file: path.strip_prefix(root_dir).unwrap_or(path).display().to_string(),
+        line: position.line_number,
+    })
+}
+
+/// Attributes the backend's gates to the call stacks of the opcodes they were generated for.
+///
+/// The backend only reports the size of whole circuits, so each run of consecutive opcodes with
+/// the same call stack is sized as a circuit of its own, less the gates of an empty circuit, which
+/// are credited to the circuit itself. Every opcode is sized exactly once, so this takes linear
+/// time in the size of the circuit, but gates which opcodes share, such as lookup tables, are
+/// credited to each run which needs them.
+fn profile_gates(
+    backend: &Backend,
+    root: &Frame,
+    circuit: &Circuit,
+    call_stacks: &[Vec<Frame>],
+) -> Result<Profile, CliError> {
+    // The start and end of each run of opcodes, and their call stack
+    let mut runs: Vec<(usize, usize, &[Frame])> = Vec::new();
+    for (index, call_stack) in call_stacks.iter().enumerate() {
+        match runs.last_mut() {
+            Some((_, end, run_call_stack)) if *run_call_stack == call_stack.as_slice() => {
+                *end = index + 1;
+            }
+            _ => runs.push((index, index + 1, call_stack)),
+        }
+    }
+
+    let sized_opcodes: Vec<(usize, usize)> =
+        std::iter::once((0, 0)).chain(runs.iter().map(|(start, end, _)| (*start, *end))).collect();
+    let sizes: Vec<u32> = sized_opcodes
+        .into_par_iter()
+        .map(|(start, end)| {
+            let run = Circuit {
+                opcodes: circuit.opcodes[start..end].to_vec(),
+                assert_messages: Vec::new(),
+                ..circuit.clone()
+            };
+            backend.get_exact_circuit_size(&run)
+        })
+        .collect::<Result<_, _>>()?;
+
+    let empty_size = sizes[0];
+    let mut profile = Profile::new("gates", "count");
+    profile.add(std::slice::from_ref(root), empty_size.into());
+    for ((_, _, call_stack), size) in runs.iter().zip(&sizes[1..]) {
+        profile.add(call_stack, size.saturating_sub(empty_size).into());
+    }
+    Ok(profile)
+}
+
+fn write_profile(profile_dir: &Path, name: &str, profile: &Profile) -> Result<(), CliError> {
+    let file_stem = format!("{name}.{}", profile.sample_type);
+
+    let folded_stacks = flamegraph::folded_stacks(profile);
+    write_to_file(folded_stacks.as_bytes(), &profile_dir.join(format!("{file_stem}.folded")));
+    if !folded_stacks.is_empty() {
+        let title = format!("{name} {}", profile.sample_type.replace('_', " "));
+        let svg = flamegraph::flamegraph_svg(&folded_stacks, &title, profile.unit)?;
+        write_to_file(&svg, &profile_dir.join(format!("{file_stem}.svg")));
+    }
+    write_to_file(&pprof::encode_profile(profile), &profile_dir.join(format!("{file_stem}.pb.gz")));
+    Ok(())
+}
@@ -0,0 +1,181 @@
+//! Profiles in the gzipped protobuf format of `pprof`, as described by
+//! <https://github.com/google/pprof/blob/main/proto/profile.proto>.
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use flate2::{write::GzEncoder, Compression};
+
+use super::{Frame, Profile};
+
+// Field numbers of the messages of `profile.proto`
+const PROFILE_SAMPLE_TYPE: u32 = 1;
+const PROFILE_SAMPLE: u32 = 2;
+const PROFILE_LOCATION: u32 = 4;
+const PROFILE_FUNCTION: u32 = 5;
+const PROFILE_STRING_TABLE: u32 = 6;
+const VALUE_TYPE_TYPE: u32 = 1;
+const VALUE_TYPE_UNIT: u32 = 2;
+const SAMPLE_LOCATION_ID: u32 = 1;
+const SAMPLE_VALUE: u32 = 2;
+const LOCATION_ID: u32 = 1;
+const LOCATION_LINE: u32 = 4;
+const LINE_FUNCTION_ID: u32 = 1;
+const LINE_LINE: u32 = 2;
+const FUNCTION_ID: u32 = 1;
+const FUNCTION_NAME: u32 = 2;
+const FUNCTION_SYSTEM_NAME: u32 = 3;
+const FUNCTION_FILENAME: u32 = 4;
+
+const WIRE_TYPE_VARINT: u32 = 0;
+const WIRE_TYPE_LENGTH_DELIMITED: u32 = 2;
+
+pub(super) fn encode_profile(profile: &Profile) -> Vec<u8> {
+    let mut builder = ProfileBuilder::default();
+    // The first string of the table must be empty.
+    builder.string_index("");
+
+    let mut message = Vec::new();
+    let mut sample_type = Vec::new();
+    write_varint_field(
+        &mut sample_type,
+        VALUE_TYPE_TYPE,
+        builder.string_index(profile.sample_type),
+    );
+    write_varint_field(&mut sample_type, VALUE_TYPE_UNIT, builder.string_index(profile.unit));
+    write_bytes_field(&mut message, PROFILE_SAMPLE_TYPE, &sample_type);
+
+    for (call_stack, weight) in &profile.samples {
+        // Samples list their locations from the innermost frame outwards.
+        let location_ids: Vec<u64> =
+            call_stack.iter().rev().map(|frame| builder.location_id(frame)).collect();
+        let mut sample = Vec::new();
+        write_packed_field(&mut sample, SAMPLE_LOCATION_ID, &location_ids);
+        write_packed_field(&mut sample, SAMPLE_VALUE, &[*weight]);
+        write_bytes_field(&mut message, PROFILE_SAMPLE, &sample);
+    }
+
+    for location in &builder.locations {
+        write_bytes_field(&mut message, PROFILE_LOCATION, location);
+    }
+    for function in &builder.functions {
+        write_bytes_field(&mut message, PROFILE_FUNCTION, function);
+    }
+    for string in &builder.strings {
+        write_bytes_field(&mut message, PROFILE_STRING_TABLE, string.as_bytes());
+    }
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&message).expect("Writing to memory should not fail");
+    encoder.finish().expect("Writing to memory should not fail")
+}
+
+/// Interns the strings, functions and locations which samples refer to by index or id.
+#[derive(Default)]
+struct ProfileBuilder {
+    strings: Vec<String>,
+    string_indices: HashMap<String, u64>,
+    /// Encoded `Function` messages, whose ids are their index plus one
+    functions: Vec<Vec<u8>>,
+    function_ids: HashMap<(String, String), u64>,
+    /// Encoded `Location` messages, whose ids are their index plus one
+    locations: Vec<Vec<u8>>,
+    location_ids: HashMap<Frame, u64>,
+}
+
+impl ProfileBuilder {
+    fn string_index(&mut self, string: &str) -> u64 {
+        if let Some(index) = self.string_indices.get(string) {
+            return *index;
+        }
+        let index = self.strings.len() as u64;
+        self.strings.push(string.to_string());
+        self.string_indices.insert(string.to_string(), index);
+        index
+    }
+
+    fn function_id(&mut self, frame: &Frame) -> u64 {
+        let key = (frame.function.clone(), frame.file.clone());
+        if let Some(id) = self.function_ids.get(&key) {
+            return *id;
+        }
+        let id = self.functions.len() as u64 + 1;
+        let name = self.string_index(&frame.function);
+        let file_name = self.string_index(&frame.file);
+
+        let mut function = Vec::new();
+        write_varint_field(&mut function, FUNCTION_ID, id);
+        write_varint_field(&mut function, FUNCTION_NAME, name);
+        write_varint_field(&mut function, FUNCTION_SYSTEM_NAME, name);
+        write_varint_field(&mut function, FUNCTION_FILENAME, file_name);
+        self.functions.push(function);
+        self.function_ids.insert(key, id);
+        id
+    }
+
+    fn location_id(&mut self, frame: &Frame) -> u64 {
+        if let Some(id) = self.location_ids.get(frame) {
+            return *id;
+        }
+        let id = self.locations.len() as u64 + 1;
+        let function_id = self.function_id(frame);
+
+        let mut line = Vec::new();
+        write_varint_field(&mut line, LINE_FUNCTION_ID, function_id);
+        write_varint_field(&mut line, LINE_LINE, frame.line as u64);
+        let mut location = Vec::new();
+        write_varint_field(&mut location, LOCATION_ID, id);
+        write_bytes_field(&mut location, LOCATION_LINE, &line);
+        self.locations.push(location);
+        self.location_ids.insert(frame.clone(), id);
+        id
+    }
+}
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn write_key(buffer: &mut Vec<u8>, field: u32, wire_type: u32) {
+    write_varint(buffer, u64::from((field << 3) | wire_type));
+}
+
+fn write_varint_field(buffer: &mut Vec<u8>, field: u32, value: u64) {
+    write_key(buffer, field, WIRE_TYPE_VARINT);
+    write_varint(buffer, value);
+}
+
+fn write_bytes_field(buffer: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    write_key(buffer, field, WIRE_TYPE_LENGTH_DELIMITED);
+    write_varint(buffer, bytes.len() as u64);
+    buffer.extend_from_slice(bytes);
+}
+
+fn write_packed_field(buffer: &mut Vec<u8>, field: u32, values: &[u64]) {
+    let mut packed = Vec::new();
+    for value in values {
+        write_varint(&mut packed, *value);
+    }
+    write_bytes_field(buffer, field, &packed);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_packed_field, write_varint};
+
+    #[test]
+    fn encodes_varints() {
+        let mut buffer = Vec::new();
+        write_varint(&mut buffer, 1);
+        write_varint(&mut buffer, 300);
+        assert_eq!(buffer, vec![0x01, 0xac, 0x02]);
+
+        let mut buffer = Vec::new();
+        write_packed_field(&mut buffer, 1, &[3, 270]);
+        assert_eq!(buffer, vec![0x0a, 0x03, 0x03, 0x8e, 0x02]);
+    }
+}
@@ -2,6 +2,7 @@ use clap::Args;
 use nargo::constants::{PROVER_INPUT_FILE, VERIFIER_INPUT_FILE};
 use nargo::insert_all_files_for_workspace_into_file_manager;
 use nargo::package::Package;
+use nargo::parse_all;
 use nargo::workspace::Workspace;
 use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
 use noirc_abi::input_parser::Format;
@@ -62,15 +63,18 @@ pub(crate) fn run(
         &toml_path,
         selection,
         Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        config.network_mode(),
     )?;
 
     let mut workspace_file_manager = file_manager_with_stdlib(&workspace.root_dir);
     insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
+    let parsed_files = parse_all(&workspace_file_manager);
 
     let expression_width = backend.get_backend_info()?;
     for package in &workspace {
         let program = compile_bin_package(
             &workspace_file_manager,
+            &parsed_files,
             &workspace,
             package,
             &args.compile_options,
@@ -108,7 +112,7 @@ pub(crate) fn prove_package(
         read_inputs_from_file(&package.root_dir, prover_name, Format::Toml, &compiled_program.abi)?;
 
     let solved_witness =
-        execute_program(&compiled_program, &inputs_map, foreign_call_resolver_url)?;
+        execute_program(&compiled_program, &inputs_map, foreign_call_resolver_url, &mut [])?;
 
     // Write public inputs into Verifier.toml
     let public_abi = compiled_program.abi.public_abi();
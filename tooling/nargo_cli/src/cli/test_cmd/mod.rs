@@ -0,0 +1,581 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use bn254_blackbox_solver::Bn254BlackBoxSolver;
+use clap::{Args, ValueEnum};
+use fm::FileManager;
+use nargo::{
+    coverage::SourceCoverage,
+    errors::TestCasesError,
+    insert_all_files_for_workspace_into_file_manager,
+    ops::{
+        compile_test, read_test_cases, run_compiled_test, test_cases_path, TestInputs, TestStatus,
+        DEFAULT_FUZZ_RUNS,
+    },
+    package::Package,
+    parse_all, prepare_package_with_parsed_files,
+    workspace::Workspace,
+};
+use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
+use noirc_driver::{
+    file_manager_with_stdlib, CompileOptions, CompiledProgram, NOIR_ARTIFACT_VERSION_STRING,
+};
+use noirc_frontend::{
+    graph::{CrateId, CrateName},
+    hir::{def_map::TestFunction, Context, FunctionNameMatch, ParsedFiles},
+};
+use rayon::prelude::*;
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+
+use crate::{backends::Backend, cli::check_cmd::check_crate_and_report_errors, errors::CliError};
+
+use super::coverage::report_coverage;
+use super::watch::watch_workspace;
+use super::NargoConfig;
+
+pub(super) mod report;
+
+/// Run the tests for this program
+#[derive(Debug, Clone, Args)]
+pub(crate) struct TestCommand {
+    /// If given, only tests with names containing this string will be run
+    test_name: Option<String>,
+
+    /// Display output of `println` statements
+    #[arg(long)]
+    show_output: bool,
+
+    /// Only run tests that match exactly
+    #[clap(long)]
+    exact: bool,
+
+    /// The name of the package to test
+    #[clap(long, conflicts_with = "workspace")]
+    package: Option<CrateName>,
+
+    /// Test all packages in the workspace
+    #[clap(long, conflicts_with = "package")]
+    workspace: bool,
+
+    #[clap(flatten)]
+    compile_options: CompileOptions,
+
+    /// JSON RPC url to solve oracle calls
+    #[clap(long)]
+    oracle_resolver: Option<String>,
+
+    /// Run the tests again whenever a source file or manifest of the workspace changes
+    #[clap(long)]
+    watch: bool,
+
+    /// Only run the tests within the given module, as a path from the crate root.
+    /// Can be given several times.
+    #[clap(long, value_name = "MODULE")]
+    include: Vec<String>,
+
+    /// Skip the tests within the given module, as a path from the crate root.
+    /// Can be given several times.
+    #[clap(long, value_name = "MODULE")]
+    exclude: Vec<String>,
+
+    /// List the tests which would be run, without running them
+    #[clap(long)]
+    list: bool,
+
+    /// How to report the test results
+    #[clap(long, value_enum, default_value_t = ReportFormat::Pretty)]
+    format: ReportFormat,
+
+    /// The number of threads to run tests on. Defaults to the number of CPUs.
+    #[clap(long)]
+    test_threads: Option<usize>,
+
+    /// The number of sets of random arguments to run each test function with parameters with
+    #[clap(long, default_value_t = DEFAULT_FUZZ_RUNS)]
+    fuzz_runs: usize,
+
+    /// The seed to generate the random arguments of test functions from, to reproduce a failure.
+    /// Defaults to a random seed.
+    #[clap(long)]
+    fuzz_seed: Option<u64>,
+
+    /// Record which lines and branches the tests ran, writing lcov and Cobertura reports to the
+    /// target directory
+    #[clap(long)]
+    coverage: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    /// Human-readable results, printed as each test finishes
+    Pretty,
+    /// A JSON document, printed once all tests have finished
+    Json,
+    /// A JUnit XML document, printed once all tests have finished
+    Junit,
+}
+
+pub(crate) fn run(
+    _backend: &Backend,
+    args: TestCommand,
+    config: NargoConfig,
+) -> Result<(), CliError> {
+    let toml_path = get_package_manifest(&config.program_dir)?;
+    let default_selection =
+        if args.workspace { PackageSelection::All } else { PackageSelection::DefaultOrAll };
+    let selection = args.package.clone().map_or(default_selection, PackageSelection::Selected);
+
+    if args.watch {
+        return watch_workspace(
+            &toml_path,
+            selection,
+            config.network_mode(),
+            |file_manager, parsed_files, workspace| {
+                test_workspace(file_manager, parsed_files, workspace, &args)
+            },
+        );
+    }
+
+    let workspace = resolve_workspace_from_toml(
+        &toml_path,
+        selection,
+        Some(NOIR_ARTIFACT_VERSION_STRING.to_string()),
+        config.network_mode(),
+    )?;
+
+    let mut workspace_file_manager = file_manager_with_stdlib(&workspace.root_dir);
+    insert_all_files_for_workspace_into_file_manager(&workspace, &mut workspace_file_manager);
+    let parsed_files = parse_all(&workspace_file_manager);
+
+    test_workspace(&workspace_file_manager, &parsed_files, &workspace, &args)
+}
+
+fn test_workspace(
+    file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
+    workspace: &Workspace,
+    args: &TestCommand,
+) -> Result<(), CliError> {
+    let pattern = match &args.test_name {
+        Some(name) => {
+            if args.exact {
+                FunctionNameMatch::Exact(name)
+            } else {
+                FunctionNameMatch::Contains(name)
+            }
+        }
+        None => FunctionNameMatch::Anything,
+    };
+    let filter = TestFilter { pattern, include: &args.include, exclude: &args.exclude };
+
+    if args.list {
+        for package in workspace {
+            let (_, _, tests) =
+                collect_tests(file_manager, parsed_files, package, &filter, &args.compile_options)?;
+            for (test_name, _) in tests {
+                println!("[{}] {test_name}", package.name);
+            }
+        }
+        return Ok(());
+    }
+
+    // All tests share a seed, so that a failure can be reproduced with a single `--fuzz-seed`
+    let fuzz_seed = args.fuzz_seed.unwrap_or_else(rand::random);
+    // Tests finish in any order, so each one is reported as a whole once it finishes
+    let output_lock = Mutex::new(());
+    let packages: Vec<&Package> = workspace.into_iter().collect();
+    // A package which fails to compile is recorded as such, so that the others are still tested
+    // and reported on.
+    let test_packages = || {
+        packages
+            .par_iter()
+            .map(|package| {
+                run_tests(
+                    file_manager,
+                    parsed_files,
+                    package,
+                    &filter,
+                    fuzz_seed,
+                    &output_lock,
+                    args,
+                )
+                .unwrap_or_else(|error| {
+                    let error = error.to_string();
+                    if !error.is_empty() {
+                        let _guard = output_lock.lock().expect("Failed to lock test output");
+                        eprintln!("{error}");
+                    }
+                    PackageTestResults::failed(package, error)
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+    let package_results = match args.test_threads {
+        Some(num_threads) => rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .build()
+            .map_err(|error| CliError::Generic(error.to_string()))?
+            .install(test_packages),
+        None => test_packages(),
+    };
+
+    match args.format {
+        ReportFormat::Pretty => (),
+        ReportFormat::Json => {
+            println!("{}", report::json_report(file_manager, &package_results));
+        }
+        ReportFormat::Junit => {
+            print!("{}", report::junit_report(file_manager, &package_results));
+        }
+    }
+
+    if args.coverage {
+        let mut coverage = SourceCoverage::default();
+        for package in &package_results {
+            coverage.merge(package.coverage.clone());
+        }
+        report_coverage(workspace, &coverage);
+    }
+
+    let any_failed = package_results.iter().any(|package| package.error.is_some())
+        || package_results.iter().flat_map(|package| &package.results).any(TestResult::failed);
+    if any_failed {
+        Err(CliError::Generic(String::new()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Selects the tests to run out of those of a package.
+struct TestFilter<'a> {
+    pattern: FunctionNameMatch<'a>,
+    include: &'a [String],
+    exclude: &'a [String],
+}
+
+impl TestFilter<'_> {
+    fn matches_module(&self, test_name: &str) -> bool {
+        let within = |module: &String| {
+            test_name.strip_prefix(module.as_str()).map_or(false, |rest| rest.starts_with("::"))
+        };
+        (self.include.is_empty() || self.include.iter().any(within))
+            && !self.exclude.iter().any(within)
+    }
+}
+
+/// A test which has been compiled, or whose compilation failed with the given outcome, ready
+/// to be run on any thread.
+struct CompiledTest {
+    name: String,
+    test_function: TestFunction,
+    program: Result<CompiledProgram, TestStatus>,
+    inputs: TestInputs,
+    compile_time: Duration,
+}
+
+/// The outcome of running a single test.
+struct TestResult {
+    name: String,
+    status: TestStatus,
+    duration: Duration,
+    /// What the test printed, if `--show-output` was given. It's kept with the result rather
+    /// than printed as the test runs, so that it isn't interleaved with the output of other
+    /// tests or with a machine-readable report.
+    output: String,
+}
+
+impl TestResult {
+    fn failed(&self) -> bool {
+        !matches!(self.status, TestStatus::Pass)
+    }
+}
+
+struct PackageTestResults {
+    package_name: String,
+    results: Vec<TestResult>,
+    /// The coverage of all of the package's tests, if it was collected
+    coverage: SourceCoverage,
+    /// Why none of the package's tests could be run, such as the package failing to compile
+    error: Option<String>,
+}
+
+impl PackageTestResults {
+    fn failed(package: &Package, error: String) -> Self {
+        PackageTestResults {
+            package_name: package.name.to_string(),
+            results: Vec::new(),
+            coverage: SourceCoverage::default(),
+            error: Some(error),
+        }
+    }
+}
+
+/// Checks the package, reporting any errors in it, and returns its tests which pass the filter
+/// by their fully qualified names.
+fn collect_tests<'a>(
+    file_manager: &'a FileManager,
+    parsed_files: &'a ParsedFiles,
+    package: &Package,
+    filter: &TestFilter,
+    compile_options: &CompileOptions,
+) -> Result<(Context<'a>, CrateId, Vec<(String, TestFunction)>), CliError> {
+    let (mut context, crate_id) =
+        prepare_package_with_parsed_files(file_manager, parsed_files, package);
+    check_crate_and_report_errors(
+        &mut context,
+        crate_id,
+        compile_options.deny_warnings,
+        compile_options.disable_macros,
+        compile_options.silence_warnings,
+    )?;
+
+    let tests: Vec<(String, TestFunction)> = context
+        .get_all_test_functions_in_crate_matching(&crate_id, filter.pattern)
+        .into_iter()
+        .filter(|(test_name, _)| filter.matches_module(test_name))
+        .collect();
+    if tests.is_empty() {
+        match &filter.pattern {
+            FunctionNameMatch::Exact(pattern) => {
+                return Err(CliError::Generic(format!(
+                    "[{}] Found 0 tests matching input '{pattern}'.",
+                    package.name
+                )))
+            }
+            FunctionNameMatch::Contains(pattern) => {
+                return Err(CliError::Generic(format!(
+                    "[{}] Found 0 tests containing '{pattern}'.",
+                    package.name
+                )))
+            }
+            // If we are running all tests in a crate, having none is not an error
+            FunctionNameMatch::Anything => {}
+        };
+    }
+    Ok((context, crate_id, tests))
+}
+
+fn run_tests(
+    file_manager: &FileManager,
+    parsed_files: &ParsedFiles,
+    package: &Package,
+    filter: &TestFilter,
+    fuzz_seed: u64,
+    output_lock: &Mutex<()>,
+    args: &TestCommand,
+) -> Result<PackageTestResults, CliError> {
+    let (context, crate_id, tests) =
+        collect_tests(file_manager, parsed_files, package, filter, &args.compile_options)?;
+    let mut test_cases = read_test_cases(&package.root_dir)?;
+    check_test_cases(&context, crate_id, package, &test_cases)?;
+    let count_all = tests.len();
+    let plural = if count_all == 1 { "" } else { "s" };
+    let pretty = args.format == ReportFormat::Pretty;
+    if pretty {
+        let _guard = output_lock.lock().expect("Failed to lock test output");
+        println!("[{}] Running {count_all} test function{plural}", package.name);
+    }
+
+    // The frontend's types can't be shared between threads, so the tests are compiled up front
+    // and only their executions are run in parallel
+    let tests: Vec<CompiledTest> = tests
+        .into_iter()
+        .map(|(name, test_function)| {
+            let start = Instant::now();
+            let program = compile_test(&context, &test_function, &args.compile_options);
+            let inputs = test_inputs(&mut test_cases, &name, fuzz_seed, args);
+            CompiledTest { name, test_function, program, inputs, compile_time: start.elapsed() }
+        })
+        .collect();
+
+    let (results, coverages): (Vec<TestResult>, Vec<Option<SourceCoverage>>) = tests
+        .into_par_iter()
+        .map_init(Bn254BlackBoxSolver::new, |blackbox_solver, test| {
+            let start = Instant::now();
+            let mut coverage = args.coverage.then(SourceCoverage::default);
+            let mut output = String::new();
+            let status = match test.program {
+                Ok(program) => run_compiled_test(
+                    &*blackbox_solver,
+                    file_manager,
+                    &program,
+                    &test.test_function,
+                    &test.inputs,
+                    coverage.as_mut(),
+                    args.show_output.then_some(&mut output),
+                    args.oracle_resolver.as_deref(),
+                ),
+                Err(status) => status,
+            };
+            let duration = test.compile_time + start.elapsed();
+            let result = TestResult { name: test.name, status, duration, output };
+            if pretty {
+                let _guard = output_lock.lock().expect("Failed to lock test output");
+                print_test_result(file_manager, package, &result, &args.compile_options);
+            }
+            (result, coverage)
+        })
+        .unzip();
+
+    if pretty {
+        let _guard = output_lock.lock().expect("Failed to lock test output");
+        print_package_summary(package, &results);
+    }
+    let mut coverage = SourceCoverage::default();
+    for test_coverage in coverages.into_iter().flatten() {
+        coverage.merge(test_coverage);
+    }
+    Ok(PackageTestResults {
+        package_name: package.name.to_string(),
+        results,
+        coverage,
+        error: None,
+    })
+}
+
+/// Checks that every test in the package's `Tests.toml` is one of its test functions, whether or
+/// not it's selected to run, so that misspelled or renamed tests aren't silently fuzzed instead.
+fn check_test_cases(
+    context: &Context,
+    crate_id: CrateId,
+    package: &Package,
+    test_cases: &BTreeMap<String, Vec<String>>,
+) -> Result<(), TestCasesError> {
+    if test_cases.is_empty() {
+        return Ok(());
+    }
+    let test_names: Vec<String> = context
+        .get_all_test_functions_in_crate_matching(&crate_id, FunctionNameMatch::Anything)
+        .into_iter()
+        .map(|(test_name, _)| test_name)
+        .collect();
+    match test_cases.keys().find(|name| !test_names.contains(name)) {
+        Some(name) => {
+            Err(TestCasesError::UnknownTest(test_cases_path(&package.root_dir), name.clone()))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Tests with fixed cases in the package's `Tests.toml` are run with those, all others with
+/// generated arguments.
+fn test_inputs(
+    test_cases: &mut BTreeMap<String, Vec<String>>,
+    test_name: &str,
+    fuzz_seed: u64,
+    args: &TestCommand,
+) -> TestInputs {
+    match test_cases.remove(test_name) {
+        Some(cases) => TestInputs::Cases(cases),
+        None => TestInputs::Generated { runs: args.fuzz_runs, seed: fuzz_seed },
+    }
+}
+
+fn print_test_result(
+    file_manager: &FileManager,
+    package: &Package,
+    result: &TestResult,
+    compile_options: &CompileOptions,
+) {
+    let writer = StandardStream::stderr(ColorChoice::Always);
+    let mut writer = writer.lock();
+
+    write!(writer, "[{}] Testing {}... ", package.name, result.name)
+        .expect("Failed to write to stderr");
+    match &result.status {
+        TestStatus::Pass { .. } => {
+            writer
+                .set_color(ColorSpec::new().set_fg(Some(Color::Green)))
+                .expect("Failed to set color");
+            writeln!(writer, "ok").expect("Failed to write to stderr");
+        }
+        TestStatus::Fail { message, error_diagnostic } => {
+            writer
+                .set_color(ColorSpec::new().set_fg(Some(Color::Red)))
+                .expect("Failed to set color");
+            writeln!(writer, "FAIL\n{message}\n").expect("Failed to write to stderr");
+            if let Some(diag) = error_diagnostic {
+                noirc_errors::reporter::report_all(
+                    file_manager.as_file_map(),
+                    std::slice::from_ref(diag),
+                    compile_options.deny_warnings,
+                    compile_options.silence_warnings,
+                );
+            }
+        }
+        TestStatus::CompileError(err) => {
+            noirc_errors::reporter::report_all(
+                file_manager.as_file_map(),
+                std::slice::from_ref(err),
+                compile_options.deny_warnings,
+                compile_options.silence_warnings,
+            );
+        }
+    }
+    writer.reset().expect("Failed to reset writer");
+    print!("{}", result.output);
+}
+
+fn print_package_summary(package: &Package, results: &[TestResult]) {
+    let writer = StandardStream::stderr(ColorChoice::Always);
+    let mut writer = writer.lock();
+
+    let count_all = results.len();
+    let count_failed = results.iter().filter(|result| result.failed()).count();
+    let plural = if count_all == 1 { "" } else { "s" };
+
+    write!(writer, "[{}] ", package.name).expect("Failed to write to stderr");
+
+    if count_failed == 0 {
+        writer.set_color(ColorSpec::new().set_fg(Some(Color::Green))).expect("Failed to set color");
+        write!(writer, "{count_all} test{plural} passed").expect("Failed to write to stderr");
+    } else {
+        let count_passed = count_all - count_failed;
+        let plural_failed = if count_failed == 1 { "" } else { "s" };
+        let plural_passed = if count_passed == 1 { "" } else { "s" };
+
+        if count_passed != 0 {
+            writer
+                .set_color(ColorSpec::new().set_fg(Some(Color::Green)))
+                .expect("Failed to set color");
+            write!(writer, "{count_passed} test{plural_passed} passed, ",)
+                .expect("Failed to write to stderr");
+        }
+
+        writer.set_color(ColorSpec::new().set_fg(Some(Color::Red))).expect("Failed to set color");
+        write!(writer, "{count_failed} test{plural_failed} failed")
+            .expect("Failed to write to stderr");
+    }
+    writer.reset().expect("Failed to reset writer");
+    writeln!(writer).expect("Failed to write to stderr");
+}
+
+#[cfg(test)]
+mod tests {
+    use noirc_frontend::hir::FunctionNameMatch;
+
+    use super::TestFilter;
+
+    #[test]
+    fn filters_tests_by_module() {
+        let include = vec!["foo".to_string()];
+        let exclude = vec!["foo::slow".to_string()];
+        let filter = TestFilter {
+            pattern: FunctionNameMatch::Anything,
+            include: &include,
+            exclude: &exclude,
+        };
+
+        assert!(filter.matches_module("foo::test_a"));
+        assert!(filter.matches_module("foo::bar::test_b"));
+        assert!(!filter.matches_module("foo::slow::test_c"));
+        assert!(!filter.matches_module("foobar::test_d"));
+        assert!(!filter.matches_module("test_e"));
+
+        let filter =
+            TestFilter { pattern: FunctionNameMatch::Anything, include: &[], exclude: &[] };
+        assert!(filter.matches_module("test_e"));
+    }
+}
@@ -0,0 +1,296 @@
+//! Machine-readable reports of test results, for CI systems to consume.
+
+use std::fmt::Write;
+
+use fm::{codespan_files::Files, FileManager};
+use nargo::ops::TestStatus;
+use noirc_errors::FileDiagnostic;
+use serde::Serialize;
+
+use super::{PackageTestResults, TestResult};
+
+#[derive(Debug, Serialize)]
+struct PackageReport {
+    name: String,
+    tests: Vec<TestReport>,
+}
+
+#[derive(Debug, Serialize)]
+struct TestReport {
+    name: String,
+    status: Status,
+    duration_secs: f64,
+    /// Why the test failed, if it did
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    /// Where the test failed, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<SourceLocation>,
+    /// What the test printed, if its output was shown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Pass,
+    Fail,
+    CompileError,
+}
+
+#[derive(Debug, Serialize)]
+struct SourceLocation {
+    file: String,
+    line: usize,
+    column: usize,
+}
+
+impl TestReport {
+    fn new(file_manager: &FileManager, result: &TestResult) -> Self {
+        let (status, message, diagnostic) = match &result.status {
+            TestStatus::Pass => (Status::Pass, None, None),
+            TestStatus::Fail { message, error_diagnostic } => {
+                (Status::Fail, Some(message.trim().to_string()), error_diagnostic.as_ref())
+            }
+            TestStatus::CompileError(diagnostic) => (
+                Status::CompileError,
+                Some(diagnostic.diagnostic.message.clone()),
+                Some(diagnostic),
+            ),
+        };
+        TestReport {
+            name: result.name.clone(),
+            status,
+            duration_secs: result.duration.as_secs_f64(),
+            message,
+            location: diagnostic.and_then(|diagnostic| source_location(file_manager, diagnostic)),
+            output: (!result.output.is_empty()).then(|| result.output.clone()),
+        }
+    }
+}
+
+impl TestReport {
+    /// An entry standing in for the tests of a package which couldn't be run at all.
+    fn package_error(error: &str) -> Self {
+        let message = if error.is_empty() { "The package failed to compile" } else { error };
+        TestReport {
+            name: PACKAGE_ERROR_NAME.to_string(),
+            status: Status::CompileError,
+            duration_secs: 0.0,
+            message: Some(message.to_string()),
+            location: None,
+            output: None,
+        }
+    }
+}
+
+/// The name of the entry reported for a package whose tests couldn't be run
+const PACKAGE_ERROR_NAME: &str = "(compilation)";
+
+/// The start of the first span the diagnostic points at.
+fn source_location(
+    file_manager: &FileManager,
+    diagnostic: &FileDiagnostic,
+) -> Option<SourceLocation> {
+    let label = diagnostic.diagnostic.secondaries.first()?;
+    let location = file_manager
+        .as_file_map()
+        .location(diagnostic.file_id, label.span.start() as usize)
+        .ok()?;
+    Some(SourceLocation {
+        file: file_manager.path(diagnostic.file_id).display().to_string(),
+        line: location.line_number,
+        column: location.column_number,
+    })
+}
+
+fn package_reports(
+    file_manager: &FileManager,
+    package_results: &[PackageTestResults],
+) -> Vec<PackageReport> {
+    package_results
+        .iter()
+        .map(|package| {
+            let mut tests: Vec<TestReport> = package
+                .results
+                .iter()
+                .map(|result| TestReport::new(file_manager, result))
+                .collect();
+            if let Some(error) = &package.error {
+                tests.push(TestReport::package_error(error));
+            }
+            PackageReport { name: package.package_name.clone(), tests }
+        })
+        .collect()
+}
+
+pub(super) fn json_report(
+    file_manager: &FileManager,
+    package_results: &[PackageTestResults],
+) -> String {
+    let packages = package_reports(file_manager, package_results);
+    serde_json::to_string_pretty(&serde_json::json!({ "packages": packages }))
+        .expect("Test reports should serialize to JSON")
+}
+
+pub(super) fn junit_report(
+    file_manager: &FileManager,
+    package_results: &[PackageTestResults],
+) -> String {
+    write_junit(&package_reports(file_manager, package_results))
+}
+
+fn write_junit(packages: &[PackageReport]) -> String {
+    let all_tests = packages.iter().flat_map(|package| &package.tests);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(xml, "<testsuites {}>", summary_attributes(all_tests)).unwrap();
+
+    for package in packages {
+        writeln!(
+            xml,
+            "  <testsuite name=\"{}\" {}>",
+            escape_xml(&package.name),
+            summary_attributes(package.tests.iter())
+        )
+        .unwrap();
+
+        for test in &package.tests {
+            let attributes = format!(
+                "name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&test.name),
+                escape_xml(&package.name),
+                test.duration_secs
+            );
+            let element = match test.status {
+                Status::Pass => None,
+                Status::Fail => Some("failure"),
+                Status::CompileError => Some("error"),
+            };
+            if element.is_none() && test.output.is_none() {
+                writeln!(xml, "    <testcase {attributes}/>").unwrap();
+                continue;
+            }
+
+            writeln!(xml, "    <testcase {attributes}>").unwrap();
+            if let Some(element) = element {
+                let message = test.message.as_deref().unwrap_or_default();
+                let mut details = message.to_string();
+                if let Some(location) = &test.location {
+                    write!(details, "\nat {}:{}:{}", location.file, location.line, location.column)
+                        .unwrap();
+                }
+                writeln!(
+                    xml,
+                    "      <{element} message=\"{}\">{}</{element}>",
+                    escape_xml(message),
+                    escape_xml(&details)
+                )
+                .unwrap();
+            }
+            if let Some(output) = &test.output {
+                writeln!(xml, "      <system-out>{}</system-out>", escape_xml(output)).unwrap();
+            }
+            writeln!(xml, "    </testcase>").unwrap();
+        }
+
+        writeln!(xml, "  </testsuite>").unwrap();
+    }
+    writeln!(xml, "</testsuites>").unwrap();
+    xml
+}
+
+fn summary_attributes<'a>(tests: impl Iterator<Item = &'a TestReport> + Clone) -> String {
+    let count = |status| tests.clone().filter(|test| test.status == status).count();
+    format!(
+        "tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\"",
+        tests.clone().count(),
+        count(Status::Fail),
+        count(Status::CompileError),
+        tests.clone().map(|test| test.duration_secs).sum::<f64>()
+    )
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_junit, PackageReport, SourceLocation, Status, TestReport};
+
+    #[test]
+    fn writes_junit_report() {
+        let packages = vec![PackageReport {
+            name: "my_package".to_string(),
+            tests: vec![
+                TestReport {
+                    name: "foo::test_pass".to_string(),
+                    status: Status::Pass,
+                    duration_secs: 0.25,
+                    message: None,
+                    location: None,
+                    output: None,
+                },
+                TestReport {
+                    name: "foo::test_fail".to_string(),
+                    status: Status::Fail,
+                    duration_secs: 0.5,
+                    message: Some("Failed assertion: 'x < y'".to_string()),
+                    location: Some(SourceLocation {
+                        file: "src/foo.nr".to_string(),
+                        line: 3,
+                        column: 12,
+                    }),
+                    output: Some("x = 3, y = 2\n".to_string()),
+                },
+            ],
+        }];
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="2" failures="1" errors="0" time="0.750">
+  <testsuite name="my_package" tests="2" failures="1" errors="0" time="0.750">
+    <testcase name="foo::test_pass" classname="my_package" time="0.250"/>
+    <testcase name="foo::test_fail" classname="my_package" time="0.500">
+      <failure message="Failed assertion: &apos;x &lt; y&apos;">Failed assertion: &apos;x &lt; y&apos;
+at src/foo.nr:3:12</failure>
+      <system-out>x = 3, y = 2
+</system-out>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+        assert_eq!(write_junit(&packages), expected);
+    }
+
+    #[test]
+    fn reports_packages_which_failed_to_compile() {
+        let packages = vec![PackageReport {
+            name: "broken".to_string(),
+            tests: vec![TestReport::package_error("Aborting due to 1 previous error")],
+        }];
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites tests="1" failures="0" errors="1" time="0.000">
+  <testsuite name="broken" tests="1" failures="0" errors="1" time="0.000">
+    <testcase name="(compilation)" classname="broken" time="0.000">
+      <error message="Aborting due to 1 previous error">Aborting due to 1 previous error</error>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+        assert_eq!(write_junit(&packages), expected);
+    }
+}
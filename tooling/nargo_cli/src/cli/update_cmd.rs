@@ -0,0 +1,21 @@
+use clap::Args;
+use nargo_toml::{get_package_manifest, update_workspace_from_toml};
+use noirc_driver::NOIR_ARTIFACT_VERSION_STRING;
+
+use crate::errors::CliError;
+
+use super::NargoConfig;
+
+/// Fetch the git dependencies of the workspace again and record the commits their tags now point
+/// to in Nargo.lock
+#[derive(Debug, Clone, Args)]
+pub(crate) struct UpdateCommand {}
+
+pub(crate) fn run(_args: UpdateCommand, config: NargoConfig) -> Result<(), CliError> {
+    let toml_path = get_package_manifest(&config.program_dir)?;
+    let workspace =
+        update_workspace_from_toml(&toml_path, Some(NOIR_ARTIFACT_VERSION_STRING.to_string()))?;
+
+    println!("Updated the lockfile of the workspace at {}", workspace.root_dir.display());
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use clap::Args;
+use nargo_toml::{get_package_manifest, vendor_workspace_from_toml};
+use noirc_driver::NOIR_ARTIFACT_VERSION_STRING;
+
+use crate::errors::CliError;
+
+use super::NargoConfig;
+
+/// Copy every git dependency of the workspace into its `vendor` directory, so that it can be built
+/// with `--offline`
+#[derive(Debug, Clone, Args)]
+pub(crate) struct VendorCommand {}
+
+pub(crate) fn run(_args: VendorCommand, config: NargoConfig) -> Result<(), CliError> {
+    if config.offline {
+        return Err(CliError::Generic(
+            "Dependencies can't be vendored while offline, as they may need to be fetched".into(),
+        ));
+    }
+
+    let toml_path = get_package_manifest(&config.program_dir)?;
+    let vendor_dir =
+        vendor_workspace_from_toml(&toml_path, Some(NOIR_ARTIFACT_VERSION_STRING.to_string()))?;
+
+    println!("Vendored the git dependencies of the workspace into {}", vendor_dir.display());
+    Ok(())
+}
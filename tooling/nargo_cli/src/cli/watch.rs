@@ -0,0 +1,269 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+use fm::FileManager;
+use nargo::package::{Dependency, Package};
+use nargo::workspace::Workspace;
+use nargo::{insert_all_files_for_workspace_into_file_manager, noir_file_ids};
+use nargo_toml::{resolve_workspace_from_toml, NetworkMode, PackageSelection};
+use noirc_driver::{file_manager_with_stdlib, NOIR_ARTIFACT_VERSION_STRING};
+use noirc_frontend::hir::ParsedFiles;
+use noirc_frontend::parser::ParserError;
+use noirc_frontend::{parse_program, ParsedModule};
+use notify_debouncer_full::notify::{EventKind, RecursiveMode, Watcher};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult};
+
+use crate::errors::CliError;
+
+/// How long to wait for changes to settle before running again, as editors and formatters often
+/// write several files in a row
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Runs `run` on the workspace, and then again every time a Noir source file or a manifest
+/// changes in the workspace or in its dependencies. This only returns if watching fails.
+///
+/// The workspace is resolved again before each run so that changes to the manifests are picked
+/// up. The standard library is only read once, and only the files which changed since the
+/// previous run are parsed again.
+pub(crate) fn watch_workspace(
+    toml_path: &Path,
+    selection: PackageSelection,
+    network_mode: NetworkMode,
+    mut run: impl FnMut(&FileManager, &ParsedFiles, &Workspace) -> Result<(), CliError>,
+) -> Result<(), CliError> {
+    let (sender, receiver) = channel();
+    let mut debouncer = new_debouncer(DEBOUNCE_TIMEOUT, None, sender)?;
+    let mut watched_dirs = BTreeSet::new();
+    let mut stdlib_file_manager = None;
+    let mut parse_cache = ParseCache::default();
+
+    loop {
+        let mut dirs_to_watch = BTreeSet::new();
+        // The manifest is watched even if it can't be resolved, so that fixing it starts a new run
+        dirs_to_watch.extend(toml_path.parent().map(Path::to_path_buf));
+
+        match resolve_workspace_from_toml(
+            toml_path,
+            selection.clone(),
+            Some(NOIR_ARTIFACT_VERSION_STRING.to_owned()),
+            network_mode,
+        ) {
+            Ok(workspace) => {
+                let stdlib_file_manager = stdlib_file_manager
+                    .get_or_insert_with(|| file_manager_with_stdlib(&workspace.root_dir));
+                let mut workspace_file_manager = stdlib_file_manager.clone();
+                insert_all_files_for_workspace_into_file_manager(
+                    &workspace,
+                    &mut workspace_file_manager,
+                );
+                let parsed_files = parse_cache.parse_all(&workspace_file_manager);
+
+                report_error(run(&workspace_file_manager, &parsed_files, &workspace));
+                dirs_to_watch.extend(workspace_dirs(&workspace));
+            }
+            Err(error) => report_error(Err(error.into())),
+        }
+
+        let dirs_to_watch = outermost_dirs(dirs_to_watch);
+        for dir in watched_dirs.difference(&dirs_to_watch) {
+            debouncer.watcher().unwatch(dir)?;
+            debouncer.cache().remove_root(dir);
+        }
+        for dir in dirs_to_watch.difference(&watched_dirs) {
+            debouncer.watcher().watch(dir, RecursiveMode::Recursive)?;
+            debouncer.cache().add_root(dir, RecursiveMode::Recursive);
+        }
+        watched_dirs = dirs_to_watch;
+
+        println!("Watching for changes...");
+        wait_for_changes(&receiver)?;
+        println!();
+    }
+}
+
+/// The files parsed for the previous run by their path, along with the source they were parsed
+/// from. File ids can't be used as keys as they change whenever files are added or removed.
+#[derive(Default)]
+struct ParseCache {
+    files: HashMap<PathBuf, (String, (ParsedModule, Vec<ParserError>))>,
+}
+
+impl ParseCache {
+    /// Parses the Noir source files in the file manager, reusing the results of the previous run
+    /// for the files whose source didn't change. Files which are no longer used are forgotten.
+    fn parse_all(&mut self, file_manager: &FileManager) -> ParsedFiles {
+        let mut files = HashMap::new();
+        let parsed_files = noir_file_ids(file_manager)
+            .map(|file_id| {
+                let path = file_manager.path(file_id).to_path_buf();
+                let source = file_manager.fetch_file(file_id);
+                let parsed_file = match self.files.remove(&path) {
+                    Some((cached_source, parsed_file)) if cached_source == source => parsed_file,
+                    _ => parse_program(source),
+                };
+                files.insert(path, (source.to_owned(), parsed_file.clone()));
+                (file_id, parsed_file)
+            })
+            .collect();
+        self.files = files;
+        parsed_files
+    }
+}
+
+fn report_error(result: Result<(), CliError>) {
+    match result {
+        // Compilation errors have already been reported by the time they are returned
+        Err(CliError::CompileError(_)) | Ok(()) => (),
+        Err(error) => {
+            let message = error.to_string();
+            if !message.is_empty() {
+                eprintln!("{message}");
+            }
+        }
+    }
+}
+
+/// The directories of the workspace's packages and of all the dependencies they use.
+fn workspace_dirs(workspace: &Workspace) -> BTreeSet<PathBuf> {
+    fn add_package_dirs(package: &Package, dirs: &mut BTreeSet<PathBuf>) {
+        if dirs.insert(package.root_dir.clone()) {
+            // Git dependencies are watched too, as their checkouts change when they are vendored
+            // or updated
+            for dependency in package.dependencies.values() {
+                match dependency {
+                    Dependency::Local { package } | Dependency::Remote { package } => {
+                        add_package_dirs(package, dirs);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut dirs = BTreeSet::from([workspace.root_dir.clone()]);
+    for package in &workspace.members {
+        add_package_dirs(package, &mut dirs);
+    }
+    dirs
+}
+
+/// Removes the directories within other directories of the set, as directories are watched
+/// recursively.
+fn outermost_dirs(dirs: BTreeSet<PathBuf>) -> BTreeSet<PathBuf> {
+    let mut outermost: BTreeSet<PathBuf> = BTreeSet::new();
+    // Ancestors are sorted before their descendants
+    for dir in dirs {
+        if !outermost.iter().any(|ancestor| dir.starts_with(ancestor)) {
+            outermost.insert(dir);
+        }
+    }
+    outermost
+}
+
+fn wait_for_changes(receiver: &Receiver<DebounceEventResult>) -> Result<(), CliError> {
+    for result in receiver {
+        let events = result.map_err(|mut errors| errors.remove(0))?;
+        let sources_changed = events.iter().any(|event| {
+            matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+                && event.paths.iter().any(|path| is_watched_file(path))
+        });
+        if sources_changed {
+            return Ok(());
+        }
+    }
+    Err(CliError::Generic("Stopped receiving file changes".to_string()))
+}
+
+fn is_watched_file(path: &Path) -> bool {
+    path.extension().map_or(false, |extension| extension == "nr")
+        || path.file_name().map_or(false, |name| name == "Nargo.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    use nargo_toml::{NetworkMode, PackageSelection};
+    use noirc_frontend::parser::ItemKind;
+
+    use super::{is_watched_file, outermost_dirs, watch_workspace};
+
+    #[test]
+    fn keeps_only_outermost_dirs() {
+        let dirs = BTreeSet::from([
+            PathBuf::from("/project/crates/b"),
+            PathBuf::from("/project"),
+            PathBuf::from("/project/crates/a"),
+            PathBuf::from("/lib"),
+            PathBuf::from("/library"),
+        ]);
+        let expected = BTreeSet::from([
+            PathBuf::from("/lib"),
+            PathBuf::from("/library"),
+            PathBuf::from("/project"),
+        ]);
+        assert_eq!(outermost_dirs(dirs), expected);
+    }
+
+    #[test]
+    fn watches_sources_and_manifests() {
+        assert!(is_watched_file(Path::new("/project/src/main.nr")));
+        assert!(is_watched_file(Path::new("/project/Nargo.toml")));
+        assert!(!is_watched_file(Path::new("/project/Prover.toml")));
+        assert!(!is_watched_file(Path::new("/project/target/main.json")));
+    }
+
+    #[test]
+    fn runs_again_with_the_changed_source() {
+        let project = tempfile::tempdir().unwrap();
+        let toml_path = project.path().join("Nargo.toml");
+        let main_path = project.path().join("src").join("main.nr");
+        std::fs::write(
+            &toml_path,
+            "[package]\nname = \"watched\"\ntype = \"bin\"\nauthors = [\"\"]\n",
+        )
+        .unwrap();
+        std::fs::create_dir(project.path().join("src")).unwrap();
+        std::fs::write(&main_path, "fn main() {}\n").unwrap();
+
+        // Each run reports the functions parsed from the package's entry file
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || {
+            watch_workspace(
+                &toml_path,
+                PackageSelection::All,
+                NetworkMode::Offline,
+                |file_manager, parsed_files, workspace| {
+                    let entry_path = workspace.members[0].entry_path.clone();
+                    let file_id = file_manager.name_to_id(entry_path).unwrap();
+                    let functions: Vec<String> = parsed_files[&file_id]
+                        .0
+                        .items
+                        .iter()
+                        .filter_map(|item| match &item.kind {
+                            ItemKind::Function(function) => Some(function.name().to_string()),
+                            _ => None,
+                        })
+                        .collect();
+                    // The receiver is gone once the test has finished
+                    let _ = sender.send(functions);
+                    Ok(())
+                },
+            )
+        });
+        let timeout = Duration::from_secs(10);
+        assert_eq!(receiver.recv_timeout(timeout).unwrap(), vec!["main"]);
+
+        // The directories may not be watched yet by the time the first run finishes, so the file
+        // is written again until the change is picked up
+        let changed_functions = (0..10).find_map(|_| {
+            std::fs::write(&main_path, "fn main() {}\n\nfn helper() {}\n").unwrap();
+            receiver.recv_timeout(Duration::from_secs(2)).ok()
+        });
+        assert_eq!(changed_functions, Some(vec!["main".to_string(), "helper".to_string()]));
+    }
+}
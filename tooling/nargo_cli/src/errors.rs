@@ -1,6 +1,9 @@
 use acvm::acir::native_types::WitnessMapError;
 use hex::FromHexError;
-use nargo::{errors::CompileError, NargoError};
+use nargo::{
+    errors::{CompileError, TestCasesError},
+    NargoError,
+};
 use nargo_toml::ManifestError;
 use noirc_abi::errors::{AbiError, InputParserError};
 use std::path::PathBuf;
@@ -16,6 +19,12 @@ pub(crate) enum FilesystemError {
         " Error: cannot find {0}.toml file.\n Expected location: {1:?} \n Please generate this file at the expected location."
     )]
     MissingTomlFile(String, PathBuf),
+    #[error("Error: could not read circuit size baseline {}: {1}", .0.display())]
+    BaselineNotValid(PathBuf, String),
+    #[error("Error: could not write package archive {}: {1}", .0.display())]
+    ArchiveNotWritten(PathBuf, std::io::Error),
+    #[error("Error: could not read {} into the package archive: {1}", .0.display())]
+    ArchiveFileNotRead(PathBuf, std::io::Error),
 
     /// Input parsing error
     #[error(transparent)]
@@ -42,6 +51,9 @@ pub(crate) enum CliError {
     #[error("Invalid package name {0}. Did you mean to use `--name`?")]
     InvalidPackageName(String),
 
+    #[error("{0} circuit(s) grew past the baseline in {}. If this is expected, update it with `--write-baseline`", .1.display())]
+    CircuitSizeRegression(usize, PathBuf),
+
     /// ABI encoding/decoding error
     #[error(transparent)]
     AbiError(#[from] AbiError),
@@ -56,6 +68,9 @@ pub(crate) enum CliError {
     #[error(transparent)]
     DapError(#[from] dap::errors::ServerError),
 
+    #[error("Failed to watch the workspace for changes: {0}")]
+    WatchError(#[from] notify_debouncer_full::notify::Error),
+
     /// Error from Nargo
     #[error(transparent)]
     NargoError(#[from] NargoError),
@@ -64,6 +79,10 @@ pub(crate) enum CliError {
     #[error(transparent)]
     ManifestError(#[from] ManifestError),
 
+    /// Error from a package's fixed test cases
+    #[error(transparent)]
+    TestCasesError(#[from] TestCasesError),
+
     /// Error from the compilation pipeline
     #[error(transparent)]
     CompileError(#[from] CompileError),
@@ -70,8 +70,64 @@ pub enum ManifestError {
     #[error(transparent)]
     SemverError(SemverError),
 
+    #[error(transparent)]
+    RegistryError(#[from] RegistryError),
+
+    #[error(
+        "`{field}` in {toml} must be `{{ workspace = true }}` to inherit it from the workspace"
+    )]
+    InvalidWorkspaceInheritance { toml: PathBuf, field: String },
+
+    #[error("`{field}` in {toml} is inherited from the workspace, but the package isn't a member of the workspace being resolved")]
+    NotAWorkspaceMember { toml: PathBuf, field: String },
+
+    #[error(
+        "`{field}` in {toml} is inherited from the workspace, but the workspace doesn't define it"
+    )]
+    MissingWorkspaceField { toml: PathBuf, field: String },
+
+    #[error("Workspace dependency `{name}` in {toml} cannot itself be inherited from a workspace")]
+    InvalidWorkspaceDependency { toml: PathBuf, name: String },
+
+    #[error("{toml} is missing {fields}, which a package needs to be packaged")]
+    MissingPackageMetadata { toml: PathBuf, fields: String },
+
+    #[error("Dependency `{name}` in {toml} is read from a local path, so the package can't be built from its archive")]
+    LocalDependencyInPackage { toml: PathBuf, name: String },
+
+    #[error("Dependency `{name}` in {toml} is resolved against a package index in a local directory, so the package can't be built from its archive")]
+    LocalRegistryInPackage { toml: PathBuf, name: String },
+
     #[error("Cyclic package dependency found when processing {cycle}")]
     CyclicDependency { cycle: String },
+
+    #[error("{} is badly formed, could not parse.\n\n {1}", .0.display())]
+    MalformedLockfile(PathBuf, toml::de::Error),
+
+    #[error("Could not write {}", .0.display())]
+    LockfileWriteFailed(PathBuf),
+
+    #[error("Cannot fetch dependency {git} at tag `{tag}` while offline, as it hasn't been vendored. Run `nargo vendor` while online to vendor it")]
+    OfflineDependency { git: String, tag: String },
+
+    #[error("{} is badly formed, could not parse.\n\n {1}", .0.display())]
+    MalformedVendorConfig(PathBuf, toml::de::Error),
+
+    #[error("Could not vendor dependencies into {}: {1}", .0.display())]
+    VendorFailed(PathBuf, std::io::Error),
+
+    #[error("{} already exists but wasn't created by `nargo vendor`. Move or remove it before vendoring dependencies", .0.display())]
+    UnmanagedVendorDirectory(PathBuf),
+
+    #[error("Dependency {git} at tag `{tag}` does not match Nargo.lock.\n Expected commit {locked_commit} with checksum {locked_checksum}\n but found commit {commit} with checksum {checksum}.\n If the dependency was meant to change, run `nargo update` to refresh Nargo.lock")]
+    LockfileMismatch {
+        git: String,
+        tag: String,
+        locked_commit: String,
+        locked_checksum: String,
+        commit: String,
+        checksum: String,
+    },
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -88,3 +144,28 @@ pub enum SemverError {
     #[error("Could not parse the package version for package {package_name} in Nargo.toml. Error: {error}")]
     CouldNotParsePackageVersion { package_name: String, error: String },
 }
+
+/// Errors resolving dependencies with a `version` requirement against a workspace's package index.
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum RegistryError {
+    #[error("Dependency `{name}` in {toml} has a version requirement, but the workspace has no `[registry]` to resolve it against")]
+    MissingRegistry { name: String, toml: PathBuf },
+
+    #[error("Could not parse the version requirement `{requirement}` of dependency `{name}` in {toml}. Error: {error}")]
+    InvalidVersionRequirement { name: String, requirement: String, toml: PathBuf, error: String },
+
+    #[error("Package `{name}` was not found in the package index at {index}")]
+    MissingPackage { name: String, index: PathBuf },
+
+    #[error("{} is badly formed, could not parse.\n\n {1}", .0.display())]
+    MalformedIndex(PathBuf, String),
+
+    #[error("No version of package `{name}` satisfies every requirement on it:{requirements}\n Available versions: {available}")]
+    VersionConflict { name: String, requirements: String, available: String },
+
+    #[error("Version {version} of package `{name}` in the package index has `version = \"{found}\"` in its Nargo.toml")]
+    IndexVersionMismatch { name: String, version: String, found: String },
+
+    #[error("Could not settle on a version of every registry package after {0} attempts")]
+    UnsettledVersions(usize),
+}
@@ -1,4 +1,11 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// A git repository checked out at the commit a tag points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GitCheckout {
+    pub(crate) path: PathBuf,
+    pub(crate) commit: String,
+}
 
 /// Creates a unique folder name for a GitHub repo
 /// by using its URL and tag
@@ -25,7 +32,10 @@ fn git_dep_location(base: &url::Url, tag: &str) -> PathBuf {
 /// github-rs looks promising, however it seems to require an API token
 ///
 /// One advantage of using "git clone" is that there is effectively no rate limit
-pub(crate) fn clone_git_repo(url: &str, tag: &str) -> Result<PathBuf, String> {
+///
+/// A repository which was already cloned is reused, unless `refetch` is set to clone it again in
+/// case its tag has moved since.
+pub(crate) fn clone_git_repo(url: &str, tag: &str, refetch: bool) -> Result<PathBuf, String> {
     use std::process::Command;
 
     let base = match url::Url::parse(url) {
@@ -35,7 +45,10 @@ pub(crate) fn clone_git_repo(url: &str, tag: &str) -> Result<PathBuf, String> {
 
     let loc = git_dep_location(&base, tag);
     if loc.exists() {
-        return Ok(loc);
+        if !refetch {
+            return Ok(loc);
+        }
+        std::fs::remove_dir_all(&loc).map_err(|err| err.to_string())?;
     }
 
     Command::new("git")
@@ -53,3 +66,24 @@ pub(crate) fn clone_git_repo(url: &str, tag: &str) -> Result<PathBuf, String> {
 
     Ok(loc)
 }
+
+/// Returns the hash of the commit checked out in the repository at `repo_path`.
+pub(crate) fn git_commit(repo_path: &Path) -> Result<String, String> {
+    use std::process::Command;
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .arg("rev-parse")
+        .arg("HEAD")
+        .output()
+        .map_err(|err| format!("git rev-parse command failed to start: {err}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Could not find the commit checked out in {}: {}",
+            repo_path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -4,25 +4,41 @@
 #![cfg_attr(not(test), warn(unused_crate_dependencies, unused_extern_crates))]
 
 use std::{
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet},
     path::{Component, Path, PathBuf},
 };
 
-use errors::SemverError;
+use ::semver::{Version, VersionReq};
+use errors::{RegistryError, SemverError};
 use fm::{NormalizePath, FILE_EXTENSION};
 use nargo::{
     package::{Dependency, Package, PackageType},
     workspace::Workspace,
 };
 use noirc_frontend::graph::CrateName;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 mod errors;
 mod git;
+mod lockfile;
+mod packaging;
+mod registry;
 mod semver;
+mod vendor;
 
 pub use errors::ManifestError;
-use git::clone_git_repo;
+use git::{clone_git_repo, git_commit, GitCheckout};
+pub use lockfile::LOCKFILE;
+use lockfile::{locked_versions, sync_lockfile, LockedDependency, LockedPackage, LockfileMode};
+pub use packaging::{packaged_manifest, PackagedManifest};
+use registry::{
+    read_index, select_version, version_conflict, IndexEntry, RegistryConfig, Requirement,
+};
+use vendor::{vendor_sources, VendoredSources};
+
+/// How many times the dependency graph is resolved again to settle on a version of each registry
+/// package before giving up
+const MAX_RESOLUTION_PASSES: usize = 16;
 
 /// Searches for a `Nargo.toml` file in the current directory and all parent directories.
 /// For example, if the current directory is `/workspace/package/src`, then this function
@@ -117,13 +133,15 @@ struct PackageConfig {
     package: PackageMetadata,
     #[serde(default)]
     dependencies: BTreeMap<String, DependencyConfig>,
+    /// The package index which dependencies with a `version` requirement are resolved against
+    registry: Option<RegistryConfig>,
 }
 
 impl PackageConfig {
     fn resolve_to_package(
         &self,
         root_dir: &Path,
-        processed: &mut Vec<String>,
+        resolver: &mut Resolver,
     ) -> Result<Package, ManifestError> {
         let name: CrateName = if let Some(name) = &self.package.name {
             name.parse().map_err(|_| ManifestError::InvalidPackageName {
@@ -135,14 +153,16 @@ impl PackageConfig {
         };
 
         let mut dependencies: BTreeMap<CrateName, Dependency> = BTreeMap::new();
-        for (name, dep_config) in self.dependencies.iter() {
-            let name = name.parse().map_err(|_| ManifestError::InvalidDependencyName {
-                toml: root_dir.join("Nargo.toml"),
-                name: name.into(),
-            })?;
-            let resolved_dep = dep_config.resolve_to_dependency(root_dir, processed)?;
+        for (dep_name, dep_config) in self.dependencies.iter() {
+            let crate_name =
+                dep_name.parse().map_err(|_| ManifestError::InvalidDependencyName {
+                    toml: root_dir.join("Nargo.toml"),
+                    name: dep_name.into(),
+                })?;
+            let resolved_dep =
+                dep_config.resolve_to_dependency(dep_name, &name, root_dir, resolver)?;
 
-            dependencies.insert(name, resolved_dep);
+            dependencies.insert(crate_name, resolved_dep);
         }
 
         let package_type = match self.package.package_type.as_deref() {
@@ -189,6 +209,9 @@ impl PackageConfig {
             }
         };
 
+        // Fields which aren't used while resolving are still inherited, to report any mistakes.
+        let inherited = self.package.inherited(root_dir, resolver)?;
+
         // If there is a package version, ensure that it is semver compatible
         if let Some(version) = &self.package.version {
             semver::parse_semver_compatible_version(version).map_err(|err| {
@@ -201,7 +224,7 @@ impl PackageConfig {
 
         Ok(Package {
             version: self.package.version.clone(),
-            compiler_required_version: self.package.compiler_version.clone(),
+            compiler_required_version: inherited.compiler_version,
             root_dir: root_dir.to_path_buf(),
             entry_path,
             package_type,
@@ -224,9 +247,20 @@ enum Config {
     Workspace {
         #[serde(alias = "workspace")]
         workspace_config: WorkspaceConfig,
+        /// The package index which the members' `version` requirements are resolved against
+        registry: Option<RegistryConfig>,
     },
 }
 
+impl Config {
+    fn registry(&self) -> Option<&RegistryConfig> {
+        match self {
+            Config::Package { package_config } => package_config.registry.as_ref(),
+            Config::Workspace { registry, .. } => registry.as_ref(),
+        }
+    }
+}
+
 impl TryFrom<String> for Config {
     type Error = toml::de::Error;
 
@@ -244,6 +278,7 @@ impl TryFrom<&str> for Config {
 }
 
 /// Tracks the root_dir of a `Nargo.toml` and the contents inside the file.
+#[derive(Clone)]
 struct NargoToml {
     root_dir: PathBuf,
     config: Config,
@@ -256,9 +291,64 @@ struct WorkspaceConfig {
     members: Vec<PathBuf>,
     /// Specifies the default crate to interact with in the context (similarly to how we have nargo as the default crate in this repository).
     default_member: Option<PathBuf>,
+    /// Dependencies which members can inherit with `name = { workspace = true }`.
+    /// Their paths are relative to the workspace.
+    #[serde(default)]
+    dependencies: BTreeMap<String, DependencyConfig>,
+    /// Package metadata which members can inherit with `field = { workspace = true }`.
+    #[serde(default)]
+    package: InheritableMetadata,
+}
+
+impl WorkspaceConfig {
+    /// Whether the package at `pkg_root` is a member of this workspace, whose root is `root_dir`.
+    fn has_member(&self, root_dir: &Path, pkg_root: &Path) -> bool {
+        let pkg_root = pkg_root.normalize();
+        self.members.iter().any(|member| root_dir.join(member).normalize() == pkg_root)
+    }
+}
+
+/// The package metadata which members of a workspace can inherit from it.
+#[derive(Default, Debug, Deserialize, Clone)]
+struct InheritableMetadata {
+    authors: Option<Vec<String>>,
+    compiler_version: Option<String>,
+    license: Option<String>,
+}
+
+/// A field of a member's package metadata, which it either defines itself or inherits from its
+/// workspace.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+enum MaybeWorkspace<T> {
+    Defined(T),
+    Workspace { workspace: bool },
+}
+
+impl<T: Clone> MaybeWorkspace<T> {
+    fn resolve(
+        &self,
+        field: &str,
+        pkg_root: &Path,
+        resolver: &Resolver,
+        inherited: impl FnOnce(&InheritableMetadata) -> Option<&T>,
+    ) -> Result<T, ManifestError> {
+        match self {
+            MaybeWorkspace::Defined(value) => Ok(value.clone()),
+            MaybeWorkspace::Workspace { workspace } => {
+                let workspace_config =
+                    resolver.inherit_from_workspace(field, pkg_root, *workspace)?;
+                inherited(&workspace_config.package).cloned().ok_or_else(|| {
+                    ManifestError::MissingWorkspaceField {
+                        toml: pkg_root.join("Nargo.toml"),
+                        field: field.to_string(),
+                    }
+                })
+            }
+        }
+    }
 }
 
-#[allow(dead_code)]
 #[derive(Default, Debug, Deserialize, Clone)]
 struct PackageMetadata {
     name: Option<String>,
@@ -267,34 +357,69 @@ struct PackageMetadata {
     package_type: Option<String>,
     entry: Option<PathBuf>,
     description: Option<String>,
-    authors: Option<Vec<String>>,
+    authors: Option<MaybeWorkspace<Vec<String>>>,
     // If no compiler version is supplied, the latest is used
     // For now, we state that all packages must be compiled under the same
     // compiler version.
     // We also state that ACIR and the compiler will upgrade in lockstep.
     // so you will not need to supply an ACIR and compiler version
-    compiler_version: Option<String>,
-    license: Option<String>,
+    compiler_version: Option<MaybeWorkspace<String>>,
+    license: Option<MaybeWorkspace<String>>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+impl PackageMetadata {
+    /// The fields which the package at `root_dir` either defines or inherits from its workspace.
+    fn inherited(
+        &self,
+        root_dir: &Path,
+        resolver: &Resolver,
+    ) -> Result<InheritableMetadata, ManifestError> {
+        let authors = self.authors.as_ref().map(|authors| {
+            authors
+                .resolve("package.authors", root_dir, resolver, |package| package.authors.as_ref())
+        });
+        let compiler_version = self.compiler_version.as_ref().map(|compiler_version| {
+            compiler_version.resolve("package.compiler_version", root_dir, resolver, |package| {
+                package.compiler_version.as_ref()
+            })
+        });
+        let license = self.license.as_ref().map(|license| {
+            license
+                .resolve("package.license", root_dir, resolver, |package| package.license.as_ref())
+        });
+        Ok(InheritableMetadata {
+            authors: authors.transpose()?,
+            compiler_version: compiler_version.transpose()?,
+            license: license.transpose()?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(untagged)]
 /// Enum representing the different types of ways to
-/// supply a source for the dependency
+/// supply a source for the dependency, or a version
+/// requirement on a package from the workspace's registry,
+/// or to inherit the dependency from the workspace
 enum DependencyConfig {
     Github { git: String, tag: String, directory: Option<String> },
     Path { path: String },
+    Registry { version: String },
+    Workspace { workspace: bool },
 }
 
 impl DependencyConfig {
+    /// Resolves the dependency `name` of package `required_by`, whose root is `pkg_root`.
     fn resolve_to_dependency(
         &self,
+        name: &str,
+        required_by: &CrateName,
         pkg_root: &Path,
-        processed: &mut Vec<String>,
+        resolver: &mut Resolver,
     ) -> Result<Dependency, ManifestError> {
         let dep = match self {
             Self::Github { git, tag, directory } => {
-                let dir_path = clone_git_repo(git, tag).map_err(ManifestError::GitError)?;
+                let dir_path = resolver.checkout_git_repo(git, tag)?;
                 let project_path = if let Some(directory) = directory {
                     let internal_path = dir_path.join(directory).normalize();
                     if !internal_path.starts_with(&dir_path) {
@@ -307,16 +432,46 @@ impl DependencyConfig {
                 } else {
                     dir_path
                 };
+                resolver.lock_git_dependency(git, tag, directory.as_deref(), &project_path)?;
                 let toml_path = project_path.join("Nargo.toml");
-                let package = resolve_package_from_toml(&toml_path, processed)?;
+                let package = resolve_package_from_toml(&toml_path, resolver)?;
                 Dependency::Remote { package }
             }
             Self::Path { path } => {
                 let dir_path = pkg_root.join(path);
                 let toml_path = dir_path.join("Nargo.toml");
-                let package = resolve_package_from_toml(&toml_path, processed)?;
+                let package = resolve_package_from_toml(&toml_path, resolver)?;
                 Dependency::Local { package }
             }
+            Self::Registry { version } => {
+                let toml_path = pkg_root.join("Nargo.toml");
+                let (index_root, entry) =
+                    resolver.registry_dependency(name, version, required_by, &toml_path)?;
+                // The sources of the index's packages are relative to the index.
+                let dep =
+                    entry.source.resolve_to_dependency(name, required_by, &index_root, resolver)?;
+                let (Dependency::Local { package } | Dependency::Remote { package }) = &dep;
+                let found = package.version.as_deref().unwrap_or_default();
+                if Version::parse(found).ok().as_ref() != Some(&entry.version) {
+                    return Err(RegistryError::IndexVersionMismatch {
+                        name: name.to_string(),
+                        version: entry.version.to_string(),
+                        found: found.to_string(),
+                    }
+                    .into());
+                }
+                dep
+            }
+            Self::Workspace { workspace } => {
+                let (workspace_root, dep_config) =
+                    resolver.workspace_dependency(name, pkg_root, *workspace)?;
+                return dep_config.resolve_to_dependency(
+                    name,
+                    required_by,
+                    &workspace_root,
+                    resolver,
+                );
+            }
         };
 
         // Cannot depend on a binary
@@ -329,14 +484,338 @@ impl DependencyConfig {
     }
 }
 
+/// Whether git dependencies may be fetched from the network while resolving a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkMode {
+    /// Git dependencies which weren't vendored are cloned into `~/nargo`.
+    Online,
+    /// Every git dependency must have been vendored with `nargo vendor`, so that nothing is
+    /// fetched from the network or read from `~/nargo`.
+    Offline,
+}
+
+/// State shared while resolving the packages of a workspace and all of their dependencies.
+#[derive(Debug)]
+struct Resolver {
+    lockfile_mode: LockfileMode,
+    network_mode: NetworkMode,
+    /// The sources vendored into the workspace, which replace the repositories they were copied from
+    vendored: Option<VendoredSources>,
+    /// The manifests of the packages currently being resolved, to detect cyclic dependencies
+    processed: Vec<String>,
+    /// Every git repository checked out so far, by URL and tag
+    checkouts: BTreeMap<(String, String), GitCheckout>,
+    /// The git repositories checked out during the current pass, see [Resolver::begin_pass]
+    used_checkouts: BTreeSet<(String, String)>,
+    /// Every git dependency resolved so far, by source
+    git_dependencies: BTreeMap<(String, String, Option<String>), LockedDependency>,
+    /// The package index of the workspace being resolved
+    registry: Option<RegistryConfig>,
+    /// The directory which the path of a local package index is relative to
+    registry_root: PathBuf,
+    /// Whether the git package index was fetched again while resolving, see
+    /// [Resolver::refresh_registry]
+    registry_refreshed: bool,
+    workspace_root: PathBuf,
+    /// The `[workspace]` table of the workspace being resolved, which its members inherit from
+    workspace_config: Option<WorkspaceConfig>,
+    /// The versions listed in the index for each registry package read so far, by name
+    index_entries: BTreeMap<String, Vec<IndexEntry>>,
+    /// The version picked for each registry package, by name
+    versions: BTreeMap<String, Version>,
+    /// The requirements on each registry package found during the current pass, by name
+    requirements: BTreeMap<String, Vec<Requirement>>,
+    /// Whether every registry dependency resolved during the current pass could use the version
+    /// picked for its package
+    settled: bool,
+}
+
+impl Resolver {
+    fn new(
+        lockfile_mode: LockfileMode,
+        network_mode: NetworkMode,
+        vendored: Option<VendoredSources>,
+    ) -> Self {
+        Resolver {
+            lockfile_mode,
+            network_mode,
+            vendored,
+            processed: Vec::new(),
+            checkouts: BTreeMap::new(),
+            used_checkouts: BTreeSet::new(),
+            git_dependencies: BTreeMap::new(),
+            registry: None,
+            registry_root: PathBuf::new(),
+            registry_refreshed: false,
+            workspace_root: PathBuf::new(),
+            workspace_config: None,
+            index_entries: BTreeMap::new(),
+            versions: BTreeMap::new(),
+            requirements: BTreeMap::new(),
+            settled: true,
+        }
+    }
+
+    /// Resolves dependencies against the registry of the workspace of `nargo_toml`, and lets its
+    /// members inherit from its `[workspace]` table.
+    ///
+    /// If `nargo_toml` is a package, its members inherit from the closest workspace in a parent
+    /// directory which lists it as a member instead, as when nargo is run in a member's directory.
+    fn set_workspace(&mut self, nargo_toml: &NargoToml) -> Result<(), ManifestError> {
+        // Only the registry of the workspace itself is used, and not those of its dependencies.
+        self.registry = nargo_toml.config.registry().cloned();
+        self.registry_root = nargo_toml.root_dir.clone();
+        self.workspace_root = nargo_toml.root_dir.clone();
+        match &nargo_toml.config {
+            Config::Workspace { workspace_config, .. } => {
+                self.workspace_config = Some(workspace_config.clone());
+            }
+            Config::Package { .. } => {
+                if let Some(workspace) = find_parent_workspace(&nargo_toml.root_dir)? {
+                    // The package's own registry takes precedence over the workspace's.
+                    let package_registry = self
+                        .registry
+                        .take()
+                        .map(|registry| (registry, std::mem::take(&mut self.registry_root)));
+                    self.set_workspace(&workspace)?;
+                    if let Some((registry, registry_root)) = package_registry {
+                        self.registry = Some(registry);
+                        self.registry_root = registry_root;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Forgets the dependencies found while resolving the dependency graph, before resolving it
+    /// again with the versions picked for registry packages since. Checkouts and indexes are kept.
+    fn begin_pass(&mut self) {
+        self.used_checkouts.clear();
+        self.git_dependencies.clear();
+        self.requirements.clear();
+        self.settled = true;
+    }
+
+    fn checkout_git_repo(&mut self, git: &str, tag: &str) -> Result<PathBuf, ManifestError> {
+        let key = (git.to_string(), tag.to_string());
+        self.used_checkouts.insert(key.clone());
+        if let Some(checkout) = self.checkouts.get(&key) {
+            return Ok(checkout.path.clone());
+        }
+
+        let vendored = self.vendored.as_ref().and_then(|vendored| vendored.checkout(git, tag));
+        let checkout = match (vendored, self.network_mode) {
+            (Some(checkout), _) => checkout,
+            (None, NetworkMode::Offline) => {
+                return Err(ManifestError::OfflineDependency { git: key.0, tag: key.1 });
+            }
+            (None, NetworkMode::Online) => {
+                // Updating clones each repository again, in case its tag has moved.
+                let refetch = self.lockfile_mode == LockfileMode::Update;
+                let path = clone_git_repo(git, tag, refetch).map_err(ManifestError::GitError)?;
+                let commit = git_commit(&path).map_err(ManifestError::GitError)?;
+                GitCheckout { path, commit }
+            }
+        };
+        let path = checkout.path.clone();
+        self.checkouts.insert(key, checkout);
+        Ok(path)
+    }
+
+    fn lock_git_dependency(
+        &mut self,
+        git: &str,
+        tag: &str,
+        directory: Option<&str>,
+        package_path: &Path,
+    ) -> Result<(), ManifestError> {
+        if self.lockfile_mode == LockfileMode::Ignore {
+            return Ok(());
+        }
+        let source = (git.to_string(), tag.to_string(), directory.map(str::to_string));
+        if !self.git_dependencies.contains_key(&source) {
+            let checkout = &self.checkouts[&(source.0.clone(), source.1.clone())];
+            let locked = LockedDependency::new(git, tag, directory, checkout, package_path)?;
+            self.git_dependencies.insert(source, locked);
+        }
+        Ok(())
+    }
+
+    /// The `[workspace]` table which the package at `pkg_root` inherits `field` from, as long as
+    /// the package is a member of the workspace being resolved.
+    fn inherit_from_workspace(
+        &self,
+        field: &str,
+        pkg_root: &Path,
+        workspace: bool,
+    ) -> Result<&WorkspaceConfig, ManifestError> {
+        let toml = pkg_root.join("Nargo.toml");
+        if !workspace {
+            return Err(ManifestError::InvalidWorkspaceInheritance { toml, field: field.into() });
+        }
+        let workspace_config = self
+            .workspace_config
+            .as_ref()
+            .filter(|workspace_config| workspace_config.has_member(&self.workspace_root, pkg_root));
+        workspace_config.ok_or(ManifestError::NotAWorkspaceMember { toml, field: field.into() })
+    }
+
+    /// The dependency `name` of the workspace, which the package at `pkg_root` inherits, along
+    /// with the root of the workspace which its path is relative to.
+    fn workspace_dependency(
+        &self,
+        name: &str,
+        pkg_root: &Path,
+        workspace: bool,
+    ) -> Result<(PathBuf, DependencyConfig), ManifestError> {
+        let field = format!("dependencies.{name}");
+        let workspace_config = self.inherit_from_workspace(&field, pkg_root, workspace)?;
+        match workspace_config.dependencies.get(name) {
+            Some(DependencyConfig::Workspace { .. }) => {
+                Err(ManifestError::InvalidWorkspaceDependency {
+                    toml: self.workspace_root.join("Nargo.toml"),
+                    name: name.into(),
+                })
+            }
+            Some(dep_config) => Ok((self.workspace_root.clone(), dep_config.clone())),
+            None => Err(ManifestError::MissingWorkspaceField {
+                toml: pkg_root.join("Nargo.toml"),
+                field,
+            }),
+        }
+    }
+
+    /// Picks the version of registry package `name` which `required_by` depends on, preferring
+    /// the version already picked for it if that satisfies `requirement`. Returns the root of the
+    /// index along with the version's entry.
+    fn registry_dependency(
+        &mut self,
+        name: &str,
+        requirement: &str,
+        required_by: &CrateName,
+        toml_path: &Path,
+    ) -> Result<(PathBuf, IndexEntry), ManifestError> {
+        let parsed_requirement = VersionReq::parse(requirement).map_err(|error| {
+            RegistryError::InvalidVersionRequirement {
+                name: name.to_string(),
+                requirement: requirement.to_string(),
+                toml: toml_path.to_path_buf(),
+                error: error.to_string(),
+            }
+        })?;
+        let requirement =
+            Requirement { requirement: parsed_requirement, required_by: required_by.to_string() };
+        // A package or version which is missing from the index may have been published since
+        // the index was checked out, in which case it is found once the index is fetched again.
+        let (index_root, version) = loop {
+            let index_root = match self.registry.clone() {
+                Some(RegistryConfig::Git { git, branch }) => {
+                    self.checkout_git_repo(&git, &branch)?
+                }
+                Some(RegistryConfig::Path { path }) => self.registry_root.join(path),
+                None => {
+                    let toml = toml_path.to_path_buf();
+                    let name = name.to_string();
+                    return Err(RegistryError::MissingRegistry { name, toml }.into());
+                }
+            };
+            if !self.index_entries.contains_key(name) {
+                match read_index(&index_root, name) {
+                    Ok(entries) => {
+                        self.index_entries.insert(name.to_string(), entries);
+                    }
+                    Err(_) if self.refresh_registry()? => continue,
+                    Err(error) => return Err(error),
+                }
+            }
+
+            let picked = self.versions.get(name);
+            let entries = &self.index_entries[name];
+            let version = select_version(entries, std::slice::from_ref(&requirement), picked);
+            match version {
+                Some(version) => break (index_root, version),
+                None if self.refresh_registry()? => continue,
+                None => {
+                    let requirements = self.requirements.entry(name.to_string()).or_default();
+                    requirements.push(requirement);
+                    let entries = &self.index_entries[name];
+                    return Err(version_conflict(name, entries, requirements).into());
+                }
+            }
+        };
+        self.requirements.entry(name.to_string()).or_default().push(requirement);
+        let picked = self.versions.get(name);
+        let entries = &self.index_entries[name];
+
+        match picked {
+            // The first package to require `name` picks its version for the rest of the pass.
+            None => {
+                self.versions.insert(name.to_string(), version.clone());
+            }
+            // Another pass is needed to settle on a version which every package accepts.
+            Some(picked) if picked != &version => self.settled = false,
+            Some(_) => (),
+        }
+        let entry = entries.iter().find(|entry| entry.version == version).cloned();
+        Ok((index_root, entry.expect("selected version should be in the index")))
+    }
+
+    /// Fetches the git package index again, in case it was checked out before the package or
+    /// version being resolved was published. Returns whether it was fetched, which happens at most
+    /// once per resolution, and never offline, for a vendored index or when every repository was
+    /// just fetched again by `nargo update`.
+    fn refresh_registry(&mut self) -> Result<bool, ManifestError> {
+        let Some(RegistryConfig::Git { git, branch }) = self.registry.clone() else {
+            return Ok(false);
+        };
+        let vendored = self.vendored.as_ref().and_then(|vendored| vendored.checkout(&git, &branch));
+        if self.registry_refreshed
+            || vendored.is_some()
+            || self.network_mode == NetworkMode::Offline
+            || self.lockfile_mode == LockfileMode::Update
+        {
+            return Ok(false);
+        }
+        self.registry_refreshed = true;
+
+        let path = clone_git_repo(&git, &branch, true).map_err(ManifestError::GitError)?;
+        let commit = git_commit(&path).map_err(ManifestError::GitError)?;
+        // The versions read from the index so far are read again, so that every package is
+        // resolved against the same state of the index.
+        for (name, entries) in self.index_entries.iter_mut() {
+            *entries = read_index(&path, name)?;
+        }
+        self.checkouts.insert((git, branch), GitCheckout { path, commit });
+        Ok(true)
+    }
+
+    /// Picks a version of each registry package required during the last pass which satisfies
+    /// all of its requirements, and returns whether every package already resolved to it.
+    fn settle_versions(&mut self) -> Result<bool, ManifestError> {
+        let mut settled = self.settled;
+        let mut versions = BTreeMap::new();
+        for (name, requirements) in &self.requirements {
+            let entries = &self.index_entries[name];
+            let picked = self.versions.get(name);
+            let version = select_version(entries, requirements, picked)
+                .ok_or_else(|| version_conflict(name, entries, requirements))?;
+            settled &= picked == Some(&version);
+            versions.insert(name.clone(), version);
+        }
+        self.versions = versions;
+        Ok(settled)
+    }
+}
+
 fn toml_to_workspace(
     nargo_toml: NargoToml,
     package_selection: PackageSelection,
+    resolver: &mut Resolver,
 ) -> Result<Workspace, ManifestError> {
-    let mut resolved = Vec::new();
     let workspace = match nargo_toml.config {
         Config::Package { package_config } => {
-            let member = package_config.resolve_to_package(&nargo_toml.root_dir, &mut resolved)?;
+            let member = package_config.resolve_to_package(&nargo_toml.root_dir, resolver)?;
             match &package_selection {
                 PackageSelection::Selected(selected_name) if selected_name != &member.name => {
                     return Err(ManifestError::MissingSelectedPackage(member.name))
@@ -348,13 +827,13 @@ fn toml_to_workspace(
                 },
             }
         }
-        Config::Workspace { workspace_config } => {
+        Config::Workspace { workspace_config, .. } => {
             let mut members = Vec::new();
             let mut selected_package_index = None;
             for (index, member_path) in workspace_config.members.into_iter().enumerate() {
                 let package_root_dir = nargo_toml.root_dir.join(&member_path);
                 let package_toml_path = package_root_dir.join("Nargo.toml");
-                let member = resolve_package_from_toml(&package_toml_path, &mut resolved)?;
+                let member = resolve_package_from_toml(&package_toml_path, resolver)?;
 
                 match &package_selection {
                     PackageSelection::Selected(selected_name) => {
@@ -410,17 +889,35 @@ fn read_toml(toml_path: &Path) -> Result<NargoToml, ManifestError> {
     Ok(nargo_toml)
 }
 
+/// Searches the parent directories of the package at `pkg_root` for the closest workspace, and
+/// returns it if the package is one of its members.
+fn find_parent_workspace(pkg_root: &Path) -> Result<Option<NargoToml>, ManifestError> {
+    let pkg_root = pkg_root.normalize();
+    for path in pkg_root.ancestors().skip(1) {
+        let Ok(toml_path) = get_package_manifest(path) else {
+            continue;
+        };
+        let nargo_toml = read_toml(&toml_path)?;
+        if let Config::Workspace { workspace_config, .. } = &nargo_toml.config {
+            let is_member = workspace_config.has_member(&nargo_toml.root_dir, &pkg_root);
+            return Ok(is_member.then_some(nargo_toml));
+        }
+    }
+    Ok(None)
+}
+
 /// Resolves a Nargo.toml file into a `Package` struct as defined by our `nargo` core.
 fn resolve_package_from_toml(
     toml_path: &Path,
-    processed: &mut Vec<String>,
+    resolver: &mut Resolver,
 ) -> Result<Package, ManifestError> {
     // Checks for cyclic dependencies
+    let processed = &mut resolver.processed;
     let str_path = toml_path.to_str().expect("ICE - path is empty");
     if processed.contains(&str_path.to_string()) {
         let mut cycle = false;
         let mut message = String::new();
-        for toml in processed {
+        for toml in processed.iter() {
             cycle = cycle || toml == str_path;
             if cycle {
                 message += &format!("{} referencing ", toml);
@@ -438,19 +935,20 @@ fn resolve_package_from_toml(
 
     let result = match nargo_toml.config {
         Config::Package { package_config } => {
-            package_config.resolve_to_package(&nargo_toml.root_dir, processed)
+            package_config.resolve_to_package(&nargo_toml.root_dir, resolver)
         }
         Config::Workspace { .. } => {
             Err(ManifestError::UnexpectedWorkspace(toml_path.to_path_buf()))
         }
     };
+    let processed = &mut resolver.processed;
     let pos =
         processed.iter().position(|toml| toml == str_path).expect("added package must be here");
     processed.remove(pos);
     result
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PackageSelection {
     Selected(CrateName),
     DefaultOrAll,
@@ -458,13 +956,117 @@ pub enum PackageSelection {
 }
 
 /// Resolves a Nargo.toml file into a `Workspace` struct as defined by our `nargo` core.
+///
+/// Git dependencies are read from the workspace's vendor directory if they were vendored, and are
+/// otherwise cloned unless `network_mode` is offline. They are checked against the workspace's
+/// `Nargo.lock`, and any which aren't locked yet are added to it.
 pub fn resolve_workspace_from_toml(
     toml_path: &Path,
     package_selection: PackageSelection,
     current_compiler_version: Option<String>,
+    network_mode: NetworkMode,
+) -> Result<Workspace, ManifestError> {
+    let nargo_toml = read_toml(toml_path)?;
+    let vendored = VendoredSources::read(&nargo_toml.root_dir)?;
+    let mut resolver = Resolver::new(LockfileMode::Check, network_mode, vendored);
+    resolve_workspace(nargo_toml, package_selection, current_compiler_version, &mut resolver)
+}
+
+/// Resolves a Nargo.toml file like [resolve_workspace_from_toml], but without checksumming git
+/// dependencies or checking and writing the workspace's `Nargo.lock`, for tools such as the
+/// language server which resolve the workspace again on every change.
+pub fn resolve_workspace_without_lockfile(
+    toml_path: &Path,
+    package_selection: PackageSelection,
+    current_compiler_version: Option<String>,
+    network_mode: NetworkMode,
+) -> Result<Workspace, ManifestError> {
+    let nargo_toml = read_toml(toml_path)?;
+    let vendored = VendoredSources::read(&nargo_toml.root_dir)?;
+    let mut resolver = Resolver::new(LockfileMode::Ignore, network_mode, vendored);
+    resolve_workspace(nargo_toml, package_selection, current_compiler_version, &mut resolver)
+}
+
+/// Resolves a Nargo.toml file like [resolve_workspace_from_toml], but clones every git dependency
+/// again and replaces the workspace's `Nargo.lock` with the commits their tags now point to. The
+/// highest versions of registry packages which satisfy their requirements replace the locked ones.
+///
+/// If the workspace's dependencies were vendored, they are vendored again from the new clones.
+pub fn update_workspace_from_toml(
+    toml_path: &Path,
+    current_compiler_version: Option<String>,
 ) -> Result<Workspace, ManifestError> {
     let nargo_toml = read_toml(toml_path)?;
-    let workspace = toml_to_workspace(nargo_toml, package_selection)?;
+    let was_vendored = VendoredSources::read(&nargo_toml.root_dir)?.is_some();
+    let mut resolver = Resolver::new(LockfileMode::Update, NetworkMode::Online, None);
+    let workspace = resolve_workspace(
+        nargo_toml,
+        PackageSelection::All,
+        current_compiler_version,
+        &mut resolver,
+    )?;
+    if was_vendored {
+        vendor_sources(&workspace.root_dir, &resolver.checkouts)?;
+    }
+    Ok(workspace)
+}
+
+/// Copies every git dependency of the workspace into its vendor directory, from where they are
+/// read instead of their repositories from then on. Returns the vendor directory.
+pub fn vendor_workspace_from_toml(
+    toml_path: &Path,
+    current_compiler_version: Option<String>,
+) -> Result<PathBuf, ManifestError> {
+    let nargo_toml = read_toml(toml_path)?;
+    // The vendor directory is replaced, so any sources vendored before are ignored.
+    let mut resolver = Resolver::new(LockfileMode::Check, NetworkMode::Online, None);
+    let workspace = resolve_workspace(
+        nargo_toml,
+        PackageSelection::All,
+        current_compiler_version,
+        &mut resolver,
+    )?;
+    vendor_sources(&workspace.root_dir, &resolver.checkouts)
+}
+
+/// Resolves the workspace of `nargo_toml`, as many times as it takes for every package which
+/// depends on a registry package to resolve to the same version of it.
+fn resolve_workspace(
+    nargo_toml: NargoToml,
+    package_selection: PackageSelection,
+    current_compiler_version: Option<String>,
+    resolver: &mut Resolver,
+) -> Result<Workspace, ManifestError> {
+    resolver.set_workspace(&nargo_toml)?;
+    if resolver.lockfile_mode != LockfileMode::Update {
+        resolver.versions = locked_versions(&nargo_toml.root_dir)?;
+    }
+
+    let mut passes = 0;
+    let workspace = loop {
+        resolver.begin_pass();
+        let workspace = toml_to_workspace(nargo_toml.clone(), package_selection.clone(), resolver)?;
+        if resolver.settle_versions()? {
+            break workspace;
+        }
+        passes += 1;
+        if passes == MAX_RESOLUTION_PASSES {
+            return Err(RegistryError::UnsettledVersions(passes).into());
+        }
+    };
+    // Drop repositories which were only checked out for versions which weren't picked in the end.
+    let used_checkouts = &resolver.used_checkouts;
+    resolver.checkouts.retain(|key, _| used_checkouts.contains(key));
+
+    let git_dependencies = resolver.git_dependencies.values().cloned().collect();
+    let packages = resolver
+        .versions
+        .iter()
+        .map(|(name, version)| LockedPackage { name: name.clone(), version: version.to_string() })
+        .collect();
+    if resolver.lockfile_mode != LockfileMode::Ignore {
+        sync_lockfile(&workspace.root_dir, git_dependencies, packages, resolver.lockfile_mode)?;
+    }
     if let Some(current_compiler_version) = current_compiler_version {
         semver::semver_check_workspace(&workspace, current_compiler_version)?;
     }
@@ -525,3 +1127,170 @@ fn parse_workspace_default_member_toml() {
     assert!(Config::try_from(String::from(src)).is_ok());
     assert!(Config::try_from(src).is_ok());
 }
+
+#[test]
+fn parse_workspace_inheritance_toml() {
+    let src = r#"
+        [workspace]
+        members = ["a", "b"]
+
+        [workspace.package]
+        authors = ["kev", "foo"]
+        compiler_version = "*"
+
+        [workspace.dependencies]
+        rand = { tag = "next", git = "https://github.com/rust-lang-nursery/rand"}
+        hello = {path = "./noir_driver"}
+    "#;
+    assert!(Config::try_from(src).is_ok());
+
+    let src = r#"
+        [package]
+        name = "test"
+        authors.workspace = true
+        compiler_version = { workspace = true }
+
+        [dependencies]
+        rand = { workspace = true }
+    "#;
+    assert!(Config::try_from(src).is_ok());
+}
+
+#[test]
+fn members_inherit_from_workspace() {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |path: &str, contents: &str| {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    };
+    write(
+        "Nargo.toml",
+        r#"
+        [workspace]
+        members = ["crates/a"]
+
+        [workspace.package]
+        compiler_version = ">=0.1.0"
+
+        [workspace.dependencies]
+        lib_b = { path = "crates/b" }
+        "#,
+    );
+    write(
+        "crates/a/Nargo.toml",
+        r#"
+        [package]
+        name = "a"
+        type = "bin"
+        compiler_version.workspace = true
+
+        [dependencies]
+        lib_b = { workspace = true }
+        "#,
+    );
+    write("crates/a/src/main.nr", "fn main() {}");
+    write(
+        "crates/b/Nargo.toml",
+        r#"
+        [package]
+        name = "lib_b"
+        type = "lib"
+        compiler_version.workspace = true
+        "#,
+    );
+    write("crates/b/src/lib.nr", "");
+
+    // `lib_b` isn't a member, so it can't inherit from the workspace.
+    let toml_path = dir.path().join("Nargo.toml");
+    let result =
+        resolve_workspace_from_toml(&toml_path, PackageSelection::All, None, NetworkMode::Offline);
+    assert!(matches!(result, Err(ManifestError::NotAWorkspaceMember { .. })));
+
+    write(
+        "Nargo.toml",
+        r#"
+        [workspace]
+        members = ["crates/a", "crates/b"]
+
+        [workspace.package]
+        compiler_version = ">=0.1.0"
+
+        [workspace.dependencies]
+        lib_b = { path = "crates/b" }
+        "#,
+    );
+    let workspace =
+        resolve_workspace_from_toml(&toml_path, PackageSelection::All, None, NetworkMode::Offline)
+            .unwrap();
+    let member = &workspace.members[0];
+    assert_eq!(member.compiler_required_version.as_deref(), Some(">=0.1.0"));
+    assert_eq!(member.dependencies.len(), 1);
+
+    // A member still inherits from its workspace when resolved from its own directory.
+    let toml_path = dir.path().join("crates/a/Nargo.toml");
+    let workspace =
+        resolve_workspace_from_toml(&toml_path, PackageSelection::All, None, NetworkMode::Offline)
+            .unwrap();
+    let member = &workspace.members[0];
+    assert_eq!(member.compiler_required_version.as_deref(), Some(">=0.1.0"));
+    assert_eq!(member.dependencies.len(), 1);
+}
+
+#[test]
+fn members_reached_by_relative_paths_inherit_from_workspace() {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |path: &str, contents: &str| {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    };
+    write(
+        "Nargo.toml",
+        r#"
+        [workspace]
+        members = ["a", "b"]
+
+        [workspace.package]
+        compiler_version = ">=0.1.0"
+        "#,
+    );
+    write(
+        "a/Nargo.toml",
+        r#"
+        [package]
+        name = "a"
+        type = "bin"
+
+        [dependencies]
+        lib_b = { path = "../b" }
+        "#,
+    );
+    write("a/src/main.nr", "fn main() {}");
+    write(
+        "b/Nargo.toml",
+        r#"
+        [package]
+        name = "lib_b"
+        type = "lib"
+        compiler_version.workspace = true
+        "#,
+    );
+    write("b/src/lib.nr", "");
+
+    for toml_path in ["Nargo.toml", "a/Nargo.toml", "a/../a/Nargo.toml"] {
+        let toml_path = dir.path().join(toml_path);
+        let workspace = resolve_workspace_from_toml(
+            &toml_path,
+            PackageSelection::All,
+            None,
+            NetworkMode::Offline,
+        )
+        .unwrap();
+        let dependency = workspace.members[0].dependencies.values().next().unwrap();
+        let Dependency::Local { package } = dependency else {
+            panic!("expected a local dependency");
+        };
+        assert_eq!(package.compiler_required_version.as_deref(), Some(">=0.1.0"));
+    }
+}
@@ -0,0 +1,366 @@
+//! `Nargo.lock` pins every git dependency of a workspace to the commit its tag resolved to and a
+//! checksum of its sources, so that a tag which is moved after the fact can't silently change the
+//! circuits built from it. It also records the version picked for each package from the registry.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::git::GitCheckout;
+use crate::ManifestError;
+
+pub const LOCKFILE: &str = "Nargo.lock";
+
+const LOCKFILE_VERSION: u32 = 1;
+
+const LOCKFILE_HEADER: &str =
+    "# This file is generated by Nargo and is not intended for manual editing.
+# Run `nargo update` to fetch the dependencies again and refresh it.
+";
+
+/// Directories of a dependency which aren't part of its sources
+pub(crate) const IGNORED_DIRECTORIES: [&str; 2] = [".git", "target"];
+
+/// The file within the `.git` directory of a checkout which caches the checksums of the packages
+/// in it, along with the metadata of the files they were computed from
+const CHECKSUM_CACHE: &str = "nargo-checksums.toml";
+
+/// A checksum cached in [`CHECKSUM_CACHE`], which is only reused while the files it was computed
+/// from still have the same [`fingerprint`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedChecksum {
+    fingerprint: String,
+    checksum: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Lockfile {
+    version: u32,
+    #[serde(default, rename = "dependency")]
+    dependencies: Vec<LockedDependency>,
+    #[serde(default, rename = "package")]
+    packages: Vec<LockedPackage>,
+}
+
+/// A git dependency as it was resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LockedDependency {
+    pub(crate) git: String,
+    pub(crate) tag: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) directory: Option<String>,
+    /// The commit which the tag pointed to
+    pub(crate) commit: String,
+    /// The SHA-256 checksum of the dependency's sources, see [`checksum`]
+    pub(crate) checksum: String,
+}
+
+/// The version picked for a package from the registry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LockedPackage {
+    pub(crate) name: String,
+    pub(crate) version: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LockfileMode {
+    /// Fail if a git dependency no longer matches the lockfile. Dependencies which aren't locked
+    /// yet are added to it.
+    Check,
+    /// Replace the lockfile with the dependencies as they were just resolved.
+    Update,
+    /// Neither check nor write the lockfile, and don't checksum git dependencies. Versions of
+    /// registry packages are still read from it.
+    Ignore,
+}
+
+impl LockedDependency {
+    /// Locks a dependency on the package at `package_path`, within `checkout`.
+    pub(crate) fn new(
+        git: &str,
+        tag: &str,
+        directory: Option<&str>,
+        checkout: &GitCheckout,
+        package_path: &Path,
+    ) -> Result<Self, ManifestError> {
+        Ok(LockedDependency {
+            git: git.to_string(),
+            tag: tag.to_string(),
+            directory: directory.map(str::to_string),
+            commit: checkout.commit.clone(),
+            checksum: cached_checksum(checkout, package_path)
+                .map_err(|_| ManifestError::ReadFailed(package_path.to_path_buf()))?,
+        })
+    }
+
+    /// Where the dependency comes from, which identifies it within the lockfile.
+    pub(crate) fn source(&self) -> (&str, &str, Option<&str>) {
+        (&self.git, &self.tag, self.directory.as_deref())
+    }
+}
+
+/// Checks the git `dependencies` of the workspace at `root_dir` against its lockfile, then
+/// writes the lockfile if they or the versions of its registry `packages` changed.
+///
+/// Workspaces without any git or registry dependencies don't get a lockfile unless they already
+/// have one.
+pub(crate) fn sync_lockfile(
+    root_dir: &Path,
+    dependencies: Vec<LockedDependency>,
+    packages: Vec<LockedPackage>,
+    mode: LockfileMode,
+) -> Result<(), ManifestError> {
+    let lockfile_path = root_dir.join(LOCKFILE);
+    let existing = read_lockfile(&lockfile_path)?;
+
+    if let (LockfileMode::Check, Some(existing)) = (mode, &existing) {
+        for dependency in &dependencies {
+            let locked =
+                existing.dependencies.iter().find(|locked| locked.source() == dependency.source());
+            if let Some(locked) = locked {
+                if locked != dependency {
+                    return Err(ManifestError::LockfileMismatch {
+                        git: dependency.git.clone(),
+                        tag: dependency.tag.clone(),
+                        locked_commit: locked.commit.clone(),
+                        locked_checksum: locked.checksum.clone(),
+                        commit: dependency.commit.clone(),
+                        checksum: dependency.checksum.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    let lockfile = Lockfile { version: LOCKFILE_VERSION, dependencies, packages };
+    if existing.as_ref() == Some(&lockfile)
+        || (existing.is_none() && lockfile.dependencies.is_empty() && lockfile.packages.is_empty())
+    {
+        return Ok(());
+    }
+    let contents = toml::to_string(&lockfile).expect("Lockfiles should serialize to TOML");
+    std::fs::write(&lockfile_path, format!("{LOCKFILE_HEADER}\n{contents}"))
+        .map_err(|_| ManifestError::LockfileWriteFailed(lockfile_path))
+}
+
+/// The versions of registry packages locked for the workspace at `root_dir`, by name.
+///
+/// A version which can't be parsed isn't locked, so that a new version is picked for its package.
+pub(crate) fn locked_versions(root_dir: &Path) -> Result<BTreeMap<String, Version>, ManifestError> {
+    let Some(lockfile) = read_lockfile(&root_dir.join(LOCKFILE))? else {
+        return Ok(BTreeMap::new());
+    };
+    Ok(lockfile
+        .packages
+        .into_iter()
+        .filter_map(|package| Some((package.name, Version::parse(&package.version).ok()?)))
+        .collect())
+}
+
+fn read_lockfile(lockfile_path: &Path) -> Result<Option<Lockfile>, ManifestError> {
+    if !lockfile_path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(lockfile_path)
+        .map_err(|_| ManifestError::ReadFailed(lockfile_path.to_path_buf()))?;
+    let lockfile = toml::from_str(&contents)
+        .map_err(|error| ManifestError::MalformedLockfile(lockfile_path.to_path_buf(), error))?;
+    Ok(Some(lockfile))
+}
+
+/// Hashes the path and contents of every file within `package_path`, in order of their paths.
+///
+/// Paths are hashed relative to the package with `/` separators, so that the checksum is the same
+/// wherever and on whichever platform the dependency was downloaded.
+pub(crate) fn checksum(package_path: &Path) -> std::io::Result<String> {
+    checksum_files(package_files(package_path)?)
+}
+
+/// Every file within `package_path` by its relative path, in order of their paths.
+fn package_files(package_path: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    collect_files(package_path, String::new(), &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn checksum_files(files: Vec<(String, PathBuf)>) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    for (relative_path, path) in files {
+        let contents = std::fs::read(path)?;
+        hasher.update(relative_path.as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    Ok(format!("sha256:{}", hex::encode(hasher.finalize())))
+}
+
+/// Hashes the path, size and modification time of every file in `files`, which changes whenever
+/// any of them is added, removed or written to.
+fn fingerprint(files: &[(String, PathBuf)]) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    for (relative_path, path) in files {
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos());
+        hasher.update(relative_path.as_bytes());
+        hasher.update([0]);
+        hasher.update(metadata.len().to_le_bytes());
+        hasher.update(modified.to_le_bytes());
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// The [`checksum`] of the package at `package_path` within `checkout`, which is cached in the
+/// checkout's `.git` directory by commit.
+///
+/// A cached checksum is only reused while the package's files have the same paths, sizes and
+/// modification times as when it was computed, so that edits made to the checkout afterwards are
+/// still caught. Checkouts without a `.git` directory, such as vendored ones, aren't cached.
+fn cached_checksum(checkout: &GitCheckout, package_path: &Path) -> std::io::Result<String> {
+    let files = package_files(package_path)?;
+    let git_dir = checkout.path.join(".git");
+    if !git_dir.is_dir() {
+        return checksum_files(files);
+    }
+    let directory = package_path.strip_prefix(&checkout.path).unwrap_or(package_path);
+    let key = format!("{}:{}", checkout.commit, directory.to_string_lossy());
+    let fingerprint = fingerprint(&files)?;
+
+    let cache_path = git_dir.join(CHECKSUM_CACHE);
+    let mut cache: BTreeMap<String, CachedChecksum> = std::fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default();
+    if let Some(cached) = cache.get(&key) {
+        if cached.fingerprint == fingerprint {
+            return Ok(cached.checksum.clone());
+        }
+    }
+
+    let computed = checksum_files(files)?;
+    cache.insert(key, CachedChecksum { fingerprint, checksum: computed.clone() });
+    // The cache only saves time, so the checksum is still used if it can't be written
+    if let Ok(contents) = toml::to_string(&cache) {
+        let _ = std::fs::write(&cache_path, contents);
+    }
+    Ok(computed)
+}
+
+fn collect_files(
+    dir: &Path,
+    relative_dir: String,
+    files: &mut Vec<(String, PathBuf)>,
+) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative_path =
+            if relative_dir.is_empty() { name.clone() } else { format!("{relative_dir}/{name}") };
+        if entry.file_type()?.is_dir() {
+            if !IGNORED_DIRECTORIES.contains(&name.as_str()) {
+                collect_files(&entry.path(), relative_path, files)?;
+            }
+        } else {
+            files.push((relative_path, entry.path()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cached_checksum, checksum, locked_versions, sync_lockfile, LockedDependency, LockedPackage,
+        LockfileMode, LOCKFILE,
+    };
+    use crate::{git::GitCheckout, ManifestError};
+
+    fn dependency(commit: &str) -> LockedDependency {
+        LockedDependency {
+            git: "https://github.com/noir-lang/example".to_string(),
+            tag: "v0.1.0".to_string(),
+            directory: None,
+            commit: commit.to_string(),
+            checksum: "sha256:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn checksum_covers_sources_but_not_build_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.nr"), "fn foo() {}").unwrap();
+        let original = checksum(dir.path()).unwrap();
+
+        std::fs::create_dir_all(dir.path().join("target")).unwrap();
+        std::fs::write(dir.path().join("target/foo.json"), "{}").unwrap();
+        assert_eq!(checksum(dir.path()).unwrap(), original);
+
+        std::fs::write(dir.path().join("src/lib.nr"), "fn bar() {}").unwrap();
+        assert_ne!(checksum(dir.path()).unwrap(), original);
+    }
+
+    #[test]
+    fn fails_when_a_cached_checkout_was_edited() {
+        let checkout_dir = tempfile::tempdir().unwrap();
+        let workspace_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(checkout_dir.path().join(".git")).unwrap();
+        std::fs::create_dir_all(checkout_dir.path().join("src")).unwrap();
+        std::fs::write(checkout_dir.path().join("src/lib.nr"), "fn foo() {}").unwrap();
+        let checkout =
+            GitCheckout { path: checkout_dir.path().to_path_buf(), commit: "aaaa".to_string() };
+        let sync = || {
+            let git = "https://github.com/noir-lang/example";
+            let dependency =
+                LockedDependency::new(git, "v0.1.0", None, &checkout, checkout_dir.path())?;
+            sync_lockfile(workspace_dir.path(), vec![dependency], vec![], LockfileMode::Check)
+        };
+        sync().unwrap();
+        sync().unwrap();
+        assert_eq!(
+            cached_checksum(&checkout, checkout_dir.path()).unwrap(),
+            checksum(checkout_dir.path()).unwrap()
+        );
+
+        std::fs::write(checkout_dir.path().join("src/lib.nr"), "fn foo() { 1 }").unwrap();
+        assert!(matches!(sync(), Err(ManifestError::LockfileMismatch { .. })));
+    }
+
+    #[test]
+    fn fails_when_a_dependency_moved_unless_updating() {
+        let dir = tempfile::tempdir().unwrap();
+        let sync = |commit, mode| sync_lockfile(dir.path(), vec![dependency(commit)], vec![], mode);
+        sync("aaaa", LockfileMode::Check).unwrap();
+        assert!(dir.path().join(LOCKFILE).exists());
+
+        let result = sync("bbbb", LockfileMode::Check);
+        assert!(matches!(result, Err(ManifestError::LockfileMismatch { .. })));
+
+        sync("bbbb", LockfileMode::Update).unwrap();
+        sync("bbbb", LockfileMode::Check).unwrap();
+    }
+
+    #[test]
+    fn no_lockfile_without_git_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        sync_lockfile(dir.path(), Vec::new(), Vec::new(), LockfileMode::Check).unwrap();
+        assert!(!dir.path().join(LOCKFILE).exists());
+    }
+
+    #[test]
+    fn locks_registry_package_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = LockedPackage { name: "example".to_string(), version: "0.3.1".to_string() };
+        sync_lockfile(dir.path(), Vec::new(), vec![package], LockfileMode::Check).unwrap();
+
+        let versions = locked_versions(dir.path()).unwrap();
+        assert_eq!(versions["example"].to_string(), "0.3.1");
+    }
+}
@@ -0,0 +1,249 @@
+//! The `Nargo.toml` of a package as it's written into the archive made by `nargo package`, which
+//! must build the package on its own, away from its workspace.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::errors::RegistryError;
+use crate::lockfile::LockfileMode;
+use crate::registry::RegistryConfig;
+use crate::{read_toml, Config, DependencyConfig, ManifestError, NetworkMode, Resolver};
+
+const PACKAGED_MANIFEST_HEADER: &str =
+    "# This file is generated by `nargo package` from the package's original Nargo.toml.
+# Fields and dependencies inherited from its workspace are written out in full.
+";
+
+/// A package's `Nargo.toml`, rewritten to be packaged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackagedManifest {
+    pub name: String,
+    pub version: String,
+    pub contents: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PackagedConfig {
+    package: PackagedMetadata,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    dependencies: BTreeMap<String, DependencyConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    registry: Option<RegistryConfig>,
+}
+
+#[derive(Debug, Serialize)]
+struct PackagedMetadata {
+    name: String,
+    version: String,
+    #[serde(rename = "type")]
+    package_type: Option<String>,
+    entry: Option<PathBuf>,
+    description: String,
+    authors: Option<Vec<String>>,
+    compiler_version: Option<String>,
+    license: String,
+}
+
+/// Checks that the package at `package_root`, within the workspace at `workspace_root`, has the
+/// metadata needed to package it, and rewrites its `Nargo.toml` so that it can be built without
+/// its workspace.
+///
+/// Dependencies on local paths can't be packaged, as their sources would be missing. The
+/// workspace's registry is written into the manifest if any dependencies are resolved against it.
+pub fn packaged_manifest(
+    workspace_root: &Path,
+    package_root: &Path,
+) -> Result<PackagedManifest, ManifestError> {
+    let workspace_toml = read_toml(&workspace_root.join("Nargo.toml"))?;
+    let toml_path = package_root.join("Nargo.toml");
+    let package_toml = read_toml(&toml_path)?;
+    let Config::Package { package_config } = package_toml.config else {
+        return Err(ManifestError::UnexpectedWorkspace(toml_path));
+    };
+    let root_dir = &package_toml.root_dir;
+
+    // Nothing is fetched, as the resolver is only used to inherit from the workspace.
+    let mut resolver = Resolver::new(LockfileMode::Check, NetworkMode::Offline, None);
+    resolver.set_workspace(&workspace_toml)?;
+
+    let metadata = &package_config.package;
+    let inherited = metadata.inherited(root_dir, &resolver)?;
+    let (Some(name), Some(version), Some(description), Some(license)) = (
+        metadata.name.clone(),
+        metadata.version.clone(),
+        metadata.description.clone(),
+        inherited.license.clone(),
+    ) else {
+        let fields = [
+            ("name", metadata.name.is_none()),
+            ("version", metadata.version.is_none()),
+            ("description", metadata.description.is_none()),
+            ("license", inherited.license.is_none()),
+        ];
+        let missing: Vec<String> = fields
+            .iter()
+            .filter(|(_, missing)| *missing)
+            .map(|(field, _)| format!("`{field}`"))
+            .collect();
+        let fields = missing.join(", ");
+        return Err(ManifestError::MissingPackageMetadata { toml: toml_path, fields });
+    };
+
+    let mut dependencies = BTreeMap::new();
+    let mut registry = None;
+    for (dep_name, dep_config) in &package_config.dependencies {
+        let dep_config = match dep_config {
+            DependencyConfig::Workspace { workspace } => {
+                resolver.workspace_dependency(dep_name, root_dir, *workspace)?.1
+            }
+            dep_config => dep_config.clone(),
+        };
+        match (&dep_config, &resolver.registry) {
+            (DependencyConfig::Github { .. }, _) => (),
+            (
+                DependencyConfig::Registry { .. },
+                Some(git_registry @ RegistryConfig::Git { .. }),
+            ) => {
+                registry = Some(git_registry.clone());
+            }
+            (DependencyConfig::Registry { .. }, Some(RegistryConfig::Path { .. })) => {
+                return Err(ManifestError::LocalRegistryInPackage {
+                    toml: toml_path,
+                    name: dep_name.clone(),
+                })
+            }
+            (DependencyConfig::Registry { .. }, None) => {
+                let name = dep_name.clone();
+                return Err(RegistryError::MissingRegistry { name, toml: toml_path }.into());
+            }
+            (DependencyConfig::Path { .. } | DependencyConfig::Workspace { .. }, _) => {
+                return Err(ManifestError::LocalDependencyInPackage {
+                    toml: toml_path,
+                    name: dep_name.clone(),
+                })
+            }
+        }
+        dependencies.insert(dep_name.clone(), dep_config);
+    }
+
+    let config = PackagedConfig {
+        package: PackagedMetadata {
+            name: name.clone(),
+            version: version.clone(),
+            package_type: metadata.package_type.clone(),
+            entry: metadata.entry.clone(),
+            description,
+            authors: inherited.authors,
+            compiler_version: inherited.compiler_version,
+            license,
+        },
+        dependencies,
+        registry,
+    };
+    let contents = toml::to_string(&config).expect("Packaged manifests should serialize to TOML");
+    Ok(PackagedManifest {
+        name,
+        version,
+        contents: format!("{PACKAGED_MANIFEST_HEADER}\n{contents}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::packaged_manifest;
+    use crate::{Config, ManifestError};
+
+    #[test]
+    fn packaged_manifest_inherits_from_the_workspace() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, contents: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        };
+        write(
+            "Nargo.toml",
+            r#"
+            [workspace]
+            members = ["crates/a"]
+
+            [workspace.package]
+            license = "MIT"
+
+            [workspace.dependencies]
+            rand = { tag = "v0.1.0", git = "https://github.com/noir-lang/rand" }
+            "#,
+        );
+        write(
+            "crates/a/Nargo.toml",
+            r#"
+            [package]
+            name = "a"
+            type = "lib"
+            version = "0.1.0"
+            license.workspace = true
+
+            [dependencies]
+            rand = { workspace = true }
+            "#,
+        );
+
+        let package_root = dir.path().join("crates/a");
+        let result = packaged_manifest(dir.path(), &package_root);
+        assert!(matches!(
+            result,
+            Err(ManifestError::MissingPackageMetadata { fields, .. }) if fields == "`description`"
+        ));
+
+        write(
+            "crates/a/Nargo.toml",
+            r#"
+            [package]
+            name = "a"
+            type = "lib"
+            version = "0.1.0"
+            description = "An example"
+            license.workspace = true
+
+            [dependencies]
+            rand = { workspace = true }
+            "#,
+        );
+        let manifest = packaged_manifest(dir.path(), &package_root).unwrap();
+        assert_eq!(manifest.version, "0.1.0");
+        assert!(manifest.contents.contains(r#"license = "MIT""#));
+        assert!(manifest.contents.contains("https://github.com/noir-lang/rand"));
+        assert!(Config::try_from(manifest.contents.as_str()).is_ok());
+    }
+
+    #[test]
+    fn rejects_dependencies_on_a_local_registry() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("Nargo.toml"),
+            r#"
+            [package]
+            name = "a"
+            type = "lib"
+            version = "0.1.0"
+            description = "An example"
+            license = "MIT"
+
+            [dependencies]
+            rand = { version = "^0.1" }
+
+            [registry]
+            path = "../index"
+            "#,
+        )
+        .unwrap();
+
+        let result = packaged_manifest(dir.path(), dir.path());
+        assert!(matches!(
+            result,
+            Err(ManifestError::LocalRegistryInPackage { name, .. }) if name == "rand"
+        ));
+    }
+}
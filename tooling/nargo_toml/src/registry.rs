@@ -0,0 +1,262 @@
+//! Package indexes, which dependencies with a `version` requirement are resolved against.
+//!
+//! An index is a directory, either local or in a git repository, with a `<package name>.toml` file
+//! for each package listing where the source of each of its versions can be found:
+//!
+//! ```toml
+//! [[version]]
+//! version = "0.3.1"
+//! git = "https://github.com/noir-lang/example"
+//! tag = "v0.3.1"
+//!
+//! [[version]]
+//! version = "0.4.0"
+//! path = "example/0.4.0" # relative to the index
+//! ```
+//!
+//! Every package of a workspace's dependency graph which requires the same registry package
+//! shares a single version of it, the highest one which satisfies all of their requirements.
+
+use std::path::Path;
+
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::RegistryError;
+use crate::{DependencyConfig, ManifestError};
+
+/// Where a workspace's package index is, as given by the `[registry]` table of its `Nargo.toml`.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub(crate) enum RegistryConfig {
+    /// An index in a git repository, kept up to date with `nargo update` and fetched again when
+    /// it is missing a package or version being resolved
+    Git { git: String, branch: String },
+    /// An index in a local directory, relative to the workspace
+    Path { path: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexFile {
+    #[serde(default, rename = "version")]
+    versions: Vec<RawIndexEntry>,
+}
+
+/// A version as written in an index. Its source is spelled out field by field, rather than as a
+/// [DependencyConfig], so that sources which only make sense in a package's manifest, such as
+/// `workspace = true`, are rejected.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawIndexEntry {
+    version: String,
+    git: Option<String>,
+    tag: Option<String>,
+    directory: Option<String>,
+    path: Option<String>,
+}
+
+impl RawIndexEntry {
+    fn source(self) -> Result<DependencyConfig, String> {
+        match self {
+            RawIndexEntry { git: Some(git), tag: Some(tag), directory, path: None, .. } => {
+                Ok(DependencyConfig::Github { git, tag, directory })
+            }
+            RawIndexEntry { git: None, tag: None, directory: None, path: Some(path), .. } => {
+                Ok(DependencyConfig::Path { path })
+            }
+            RawIndexEntry { version, .. } => Err(format!(
+                "version `{version}` must have either a `git` source with a `tag` or a `path`"
+            )),
+        }
+    }
+}
+
+/// A version of a package in an index, and where its source is.
+#[derive(Debug, Clone)]
+pub(crate) struct IndexEntry {
+    pub(crate) version: Version,
+    pub(crate) source: DependencyConfig,
+}
+
+/// A version requirement on a registry package, and the package which made it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Requirement {
+    pub(crate) requirement: VersionReq,
+    pub(crate) required_by: String,
+}
+
+/// Reads every version of `name` listed in the index at `index_root`, highest version first.
+pub(crate) fn read_index(index_root: &Path, name: &str) -> Result<Vec<IndexEntry>, ManifestError> {
+    let index_path = index_root.join(name).with_extension("toml");
+    if !index_path.exists() {
+        let index = index_root.to_path_buf();
+        return Err(RegistryError::MissingPackage { name: name.to_string(), index }.into());
+    }
+    let malformed = |error: String| RegistryError::MalformedIndex(index_path.clone(), error);
+
+    let contents = std::fs::read_to_string(&index_path)
+        .map_err(|_| ManifestError::ReadFailed(index_path.clone()))?;
+    let index: IndexFile =
+        toml::from_str(&contents).map_err(|error| malformed(error.to_string()))?;
+
+    let mut entries = Vec::new();
+    for entry in index.versions {
+        let version = Version::parse(&entry.version)
+            .map_err(|error| malformed(format!("invalid version `{}`: {error}", entry.version)))?;
+        let source = entry.source().map_err(malformed)?;
+        entries.push(IndexEntry { version, source });
+    }
+    entries.sort_by(|a, b| b.version.cmp(&a.version));
+    Ok(entries)
+}
+
+/// Picks the version of a package which satisfies all of its `requirements`.
+///
+/// The `preferred` version, such as the one recorded in the lockfile, is kept as long as it still
+/// satisfies them, so that new versions in the index don't change a build by themselves. Otherwise
+/// the highest version which satisfies them is picked.
+pub(crate) fn select_version(
+    entries: &[IndexEntry],
+    requirements: &[Requirement],
+    preferred: Option<&Version>,
+) -> Option<Version> {
+    let satisfies_all = |version: &Version| {
+        requirements.iter().all(|requirement| requirement.requirement.matches(version))
+    };
+    let available = |version: &Version| entries.iter().any(|entry| &entry.version == version);
+
+    if let Some(preferred) = preferred {
+        if available(preferred) && satisfies_all(preferred) {
+            return Some(preferred.clone());
+        }
+    }
+    entries.iter().map(|entry| &entry.version).find(|version| satisfies_all(version)).cloned()
+}
+
+/// Describes why no version of package `name` could be picked.
+pub(crate) fn version_conflict(
+    name: &str,
+    entries: &[IndexEntry],
+    requirements: &[Requirement],
+) -> RegistryError {
+    let requirements = requirements
+        .iter()
+        .map(|requirement| {
+            format!("\n  {} (required by {})", requirement.requirement, requirement.required_by)
+        })
+        .collect();
+    RegistryError::VersionConflict {
+        name: name.to_string(),
+        requirements,
+        available: available_versions(entries),
+    }
+}
+
+fn available_versions(entries: &[IndexEntry]) -> String {
+    if entries.is_empty() {
+        return "none".to_string();
+    }
+    let versions: Vec<String> = entries.iter().map(|entry| entry.version.to_string()).collect();
+    versions.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use semver::{Version, VersionReq};
+
+    use super::{read_index, select_version, IndexEntry, Requirement};
+    use crate::DependencyConfig;
+
+    fn entries(versions: &[&str]) -> Vec<IndexEntry> {
+        versions
+            .iter()
+            .map(|version| IndexEntry {
+                version: Version::parse(version).unwrap(),
+                source: DependencyConfig::Path { path: version.to_string() },
+            })
+            .collect()
+    }
+
+    fn requirement(requirement: &str, required_by: &str) -> Requirement {
+        Requirement {
+            requirement: VersionReq::parse(requirement).unwrap(),
+            required_by: required_by.to_string(),
+        }
+    }
+
+    #[test]
+    fn unifies_compatible_requirements_on_the_highest_version() {
+        let entries = entries(&["0.4.0", "0.3.2", "0.3.1", "0.3.0"]);
+        let requirements = [requirement("^0.3", "a"), requirement(">=0.3.1, <0.3.2", "b")];
+        assert_eq!(
+            select_version(&entries, &requirements, None),
+            Some(Version::parse("0.3.1").unwrap())
+        );
+        assert_eq!(
+            select_version(&entries, &requirements[..1], None),
+            Some(Version::parse("0.3.2").unwrap())
+        );
+    }
+
+    #[test]
+    fn keeps_the_preferred_version_while_it_satisfies_the_requirements() {
+        let entries = entries(&["0.3.2", "0.3.1"]);
+        let preferred = Version::parse("0.3.1").unwrap();
+        assert_eq!(
+            select_version(&entries, &[requirement("^0.3", "a")], Some(&preferred)),
+            Some(preferred.clone())
+        );
+        assert_eq!(
+            select_version(&entries, &[requirement("^0.3.2", "a")], Some(&preferred)),
+            Some(Version::parse("0.3.2").unwrap())
+        );
+    }
+
+    #[test]
+    fn finds_no_version_for_conflicting_requirements() {
+        let entries = entries(&["0.4.0", "0.3.2"]);
+        let requirements = [requirement("^0.3", "a"), requirement("^0.4", "b")];
+        assert_eq!(select_version(&entries, &requirements, None), None);
+    }
+
+    #[test]
+    fn reads_index_sorted_by_version() {
+        let index = tempfile::tempdir().unwrap();
+        let contents = r#"
+            [[version]]
+            version = "0.3.0"
+            path = "example/0.3.0"
+
+            [[version]]
+            version = "0.10.0"
+            git = "https://github.com/noir-lang/example"
+            tag = "v0.10.0"
+        "#;
+        std::fs::write(index.path().join("example.toml"), contents).unwrap();
+
+        let entries = read_index(index.path(), "example").unwrap();
+        let versions: Vec<String> = entries.iter().map(|entry| entry.version.to_string()).collect();
+        assert_eq!(versions, vec!["0.10.0", "0.3.0"]);
+        assert!(matches!(entries[0].source, DependencyConfig::Github { .. }));
+        assert!(read_index(index.path(), "missing").is_err());
+    }
+
+    #[test]
+    fn rejects_index_entries_without_a_git_or_path_source() {
+        let index = tempfile::tempdir().unwrap();
+        let sources = [
+            "workspace = true",
+            r#"path = "example/0.3.0"
+            workspace = true"#,
+            r#"git = "https://github.com/noir-lang/example""#,
+            r#"git = "https://github.com/noir-lang/example"
+            tag = "v0.3.0"
+            path = "example/0.3.0""#,
+        ];
+        for source in sources {
+            let contents = format!("[[version]]\nversion = \"0.3.0\"\n{source}\n");
+            std::fs::write(index.path().join("example.toml"), contents).unwrap();
+            assert!(read_index(index.path(), "example").is_err(), "accepted `{source}`");
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Copies of a workspace's git dependencies, made by `nargo vendor`, which replace their
+//! repositories when resolving the workspace so that it can be built without network access.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::git::GitCheckout;
+use crate::lockfile::IGNORED_DIRECTORIES;
+use crate::ManifestError;
+
+pub(crate) const VENDOR_DIR: &str = "vendor";
+
+/// The file within the vendor directory which records where each repository was copied to
+const VENDOR_CONFIG: &str = "sources.toml";
+
+const VENDOR_CONFIG_HEADER: &str =
+    "# This file is generated by `nargo vendor` and is not intended for manual editing.
+# The git dependencies listed here are read from the vendor directory instead of being fetched.
+";
+
+/// The git repositories which were vendored into a workspace.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct VendoredSources {
+    #[serde(skip)]
+    vendor_dir: PathBuf,
+    #[serde(default, rename = "source")]
+    sources: Vec<VendoredSource>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct VendoredSource {
+    git: String,
+    tag: String,
+    /// The commit which was copied
+    commit: String,
+    /// Where the repository was copied to, relative to the vendor directory
+    path: String,
+}
+
+impl VendoredSources {
+    /// Reads the sources vendored into the workspace at `root_dir`, if it has any.
+    pub(crate) fn read(root_dir: &Path) -> Result<Option<Self>, ManifestError> {
+        let vendor_dir = root_dir.join(VENDOR_DIR);
+        let config_path = vendor_dir.join(VENDOR_CONFIG);
+        if !config_path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&config_path)
+            .map_err(|_| ManifestError::ReadFailed(config_path.clone()))?;
+        let sources: VendoredSources = toml::from_str(&contents)
+            .map_err(|error| ManifestError::MalformedVendorConfig(config_path, error))?;
+        Ok(Some(VendoredSources { vendor_dir, ..sources }))
+    }
+
+    /// The vendored copy of the repository at `git` as of `tag`.
+    pub(crate) fn checkout(&self, git: &str, tag: &str) -> Option<GitCheckout> {
+        let source = self.sources.iter().find(|source| source.git == git && source.tag == tag)?;
+        Some(GitCheckout {
+            path: self.vendor_dir.join(&source.path),
+            commit: source.commit.clone(),
+        })
+    }
+}
+
+/// Replaces the vendor directory of the workspace at `root_dir` with copies of the `checkouts`,
+/// keyed by their URL and tag, and returns the vendor directory.
+///
+/// An existing vendor directory is only replaced if it was created by `nargo vendor`.
+pub(crate) fn vendor_sources(
+    root_dir: &Path,
+    checkouts: &BTreeMap<(String, String), GitCheckout>,
+) -> Result<PathBuf, ManifestError> {
+    let vendor_dir = root_dir.join(VENDOR_DIR);
+    let vendor_error = |error| ManifestError::VendorFailed(vendor_dir.clone(), error);
+
+    if vendor_dir.exists() {
+        if !vendor_dir.join(VENDOR_CONFIG).is_file() {
+            return Err(ManifestError::UnmanagedVendorDirectory(vendor_dir));
+        }
+        std::fs::remove_dir_all(&vendor_dir).map_err(vendor_error)?;
+    }
+    let mut sources = Vec::new();
+    for ((git, tag), checkout) in checkouts {
+        let path = vendor_path(git, tag);
+        copy_sources(&checkout.path, &vendor_dir.join(&path)).map_err(vendor_error)?;
+        sources.push(VendoredSource {
+            git: git.clone(),
+            tag: tag.clone(),
+            commit: checkout.commit.clone(),
+            path,
+        });
+    }
+
+    let config = VendoredSources { vendor_dir: vendor_dir.clone(), sources };
+    let contents = toml::to_string(&config).expect("Vendored sources should serialize to TOML");
+    std::fs::create_dir_all(&vendor_dir).map_err(vendor_error)?;
+    std::fs::write(vendor_dir.join(VENDOR_CONFIG), format!("{VENDOR_CONFIG_HEADER}\n{contents}"))
+        .map_err(vendor_error)?;
+    Ok(vendor_dir)
+}
+
+/// Where to copy the repository at `git` as of `tag`, such as `github.com/noir-lang/example/v0.1.0`.
+fn vendor_path(git: &str, tag: &str) -> String {
+    let mut components = match url::Url::parse(git) {
+        Ok(url) => {
+            let mut components = vec![url.host_str().unwrap_or_default().to_string()];
+            components.extend(url.path().trim_end_matches(".git").split('/').map(str::to_string));
+            components
+        }
+        Err(_) => vec![git.to_string()],
+    };
+    components.push(tag.to_string());
+
+    let components: Vec<String> = components
+        .iter()
+        .filter(|component| !component.is_empty())
+        .map(|component| sanitize_path_component(component))
+        .collect();
+    components.join("/")
+}
+
+/// Replaces any characters which could make `component` escape the vendor directory.
+fn sanitize_path_component(component: &str) -> String {
+    let component: String = component
+        .chars()
+        .map(|char| if char.is_ascii_alphanumeric() || "-_.".contains(char) { char } else { '_' })
+        .collect();
+    if component.chars().all(|char| char == '.') {
+        component.replace('.', "_")
+    } else {
+        component
+    }
+}
+
+fn copy_sources(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if entry.file_type()?.is_dir() {
+            if !IGNORED_DIRECTORIES.iter().any(|ignored| name == *ignored) {
+                copy_sources(&entry.path(), &to.join(&name))?;
+            }
+        } else {
+            std::fs::copy(entry.path(), to.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{vendor_path, vendor_sources, VendoredSources};
+    use crate::git::GitCheckout;
+    use crate::ManifestError;
+
+    #[test]
+    fn vendor_paths_stay_within_the_vendor_directory() {
+        assert_eq!(
+            vendor_path("https://github.com/noir-lang/example.git", "v0.1.0"),
+            "github.com/noir-lang/example/v0.1.0"
+        );
+        assert_eq!(
+            vendor_path("https://github.com/noir-lang/example", "../.."),
+            "github.com/noir-lang/example/.._.."
+        );
+        assert_eq!(
+            vendor_path("https://github.com/noir-lang/example", ".."),
+            "github.com/noir-lang/example/__"
+        );
+    }
+
+    #[test]
+    fn vendored_sources_replace_their_repositories() {
+        let repo = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(repo.path().join(".git")).unwrap();
+        std::fs::write(repo.path().join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+        std::fs::write(repo.path().join("Nargo.toml"), "[package]").unwrap();
+
+        let workspace = tempfile::tempdir().unwrap();
+        let git = "https://github.com/noir-lang/example".to_string();
+        let tag = "v0.1.0".to_string();
+        let checkouts = BTreeMap::from([(
+            (git.clone(), tag.clone()),
+            GitCheckout { path: repo.path().to_path_buf(), commit: "abcd".to_string() },
+        )]);
+        vendor_sources(workspace.path(), &checkouts).unwrap();
+
+        let vendored = VendoredSources::read(workspace.path()).unwrap().unwrap();
+        let checkout = vendored.checkout(&git, &tag).unwrap();
+        assert_eq!(checkout.commit, "abcd");
+        assert!(checkout.path.starts_with(workspace.path().join("vendor")));
+        assert!(checkout.path.join("Nargo.toml").exists());
+        assert!(!checkout.path.join(".git").exists());
+        assert!(vendored.checkout(&git, "v0.2.0").is_none());
+    }
+
+    #[test]
+    fn keeps_vendor_directories_not_created_by_nargo() {
+        let workspace = tempfile::tempdir().unwrap();
+        let user_file = workspace.path().join("vendor/notes.txt");
+        std::fs::create_dir_all(user_file.parent().unwrap()).unwrap();
+        std::fs::write(&user_file, "keep me").unwrap();
+
+        let result = vendor_sources(workspace.path(), &BTreeMap::new());
+        assert!(matches!(result, Err(ManifestError::UnmanagedVendorDirectory(_))));
+        assert!(user_file.exists());
+
+        // Once nargo owns the directory, vendoring again replaces it.
+        std::fs::remove_file(&user_file).unwrap();
+        std::fs::remove_dir(user_file.parent().unwrap()).unwrap();
+        vendor_sources(workspace.path(), &BTreeMap::new()).unwrap();
+        vendor_sources(workspace.path(), &BTreeMap::new()).unwrap();
+        assert!(VendoredSources::read(workspace.path()).unwrap().is_some());
+    }
+}